@@ -0,0 +1,206 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::error::ContractError;
+use crate::state::{
+    read_claimed, read_config, read_latest_stage, read_merkle_root, store_claimed, store_config,
+    store_latest_stage, store_merkle_root, Config,
+};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::airdrop::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
+    MerkleRootResponse, QueryMsg,
+};
+use sha2::Digest;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
+        },
+    )?;
+
+    store_latest_stage(deps.storage, 0)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::RegisterMerkleRoot { merkle_root } => {
+            register_merkle_root(deps, info, merkle_root)
+        }
+        ExecuteMsg::Claim {
+            stage,
+            amount,
+            proof,
+        } => claim(deps, info, stage, amount, proof),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+pub fn register_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    merkle_root: String,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    decode_hash(&merkle_root)?;
+
+    let stage = read_latest_stage(deps.storage)?
+        .checked_add(1)
+        .ok_or(ContractError::StageLimitReached {})?;
+    store_merkle_root(deps.storage, stage, merkle_root.clone())?;
+    store_latest_stage(deps.storage, stage)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_merkle_root"),
+        attr("stage", stage.to_string()),
+        attr("merkle_root", merkle_root),
+    ]))
+}
+
+/// Verify the merkle proof of the `address:amount` leaf
+/// against the root of the stage and send the airdrop
+/// Executor: airdrop recipient
+pub fn claim(
+    deps: DepsMut,
+    info: MessageInfo,
+    stage: u8,
+    amount: Uint256,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let merkle_root: String = read_merkle_root(deps.storage, stage)?;
+
+    let user_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_claimed(deps.storage, &user_raw, stage)? {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+
+    let leaf = format!("{}:{}", info.sender, amount);
+    let mut hash: [u8; 32] = sha2::Sha256::digest(leaf.as_bytes()).into();
+    for p in proof.iter() {
+        let proof_hash = decode_hash(p)?;
+
+        // sibling hashes are concatenated in ascending order
+        let (first, second) = if hash < proof_hash {
+            (hash, proof_hash)
+        } else {
+            (proof_hash, hash)
+        };
+        hash = sha2::Sha256::digest(&[first, second].concat()).into();
+    }
+
+    if hash != decode_hash(&merkle_root)? {
+        return Err(ContractError::VerificationFailed {});
+    }
+
+    store_claimed(deps.storage, &user_raw, stage)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "claim"),
+            attr("stage", stage.to_string()),
+            attr("address", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+fn decode_hash(hash: &str) -> Result<[u8; 32], ContractError> {
+    let mut buf: [u8; 32] = [0; 32];
+    hex::decode_to_slice(hash, &mut buf)
+        .map_err(|_| ContractError::InvalidHash(hash.to_string()))?;
+
+    Ok(buf)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, stage)?),
+        QueryMsg::LatestStage {} => to_binary(&query_latest_stage(deps)?),
+        QueryMsg::IsClaimed { stage, address } => {
+            to_binary(&query_is_claimed(deps, stage, address)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        anchor_token: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+    })
+}
+
+fn query_merkle_root(deps: Deps, stage: u8) -> StdResult<MerkleRootResponse> {
+    Ok(MerkleRootResponse {
+        stage,
+        merkle_root: read_merkle_root(deps.storage, stage)?,
+    })
+}
+
+fn query_latest_stage(deps: Deps) -> StdResult<LatestStageResponse> {
+    Ok(LatestStageResponse {
+        latest_stage: read_latest_stage(deps.storage)?,
+    })
+}
+
+fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsClaimedResponse> {
+    let user_raw = deps.api.addr_canonicalize(&address)?;
+    Ok(IsClaimedResponse {
+        is_claimed: read_claimed(deps.storage, &user_raw, stage)?,
+    })
+}
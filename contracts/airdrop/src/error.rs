@@ -0,0 +1,23 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid hex encoded hash: {0}")]
+    InvalidHash(String),
+
+    #[error("Airdrop of this stage is already claimed")]
+    AlreadyClaimed {},
+
+    #[error("Maximum number of airdrop stages reached")]
+    StageLimitReached {},
+
+    #[error("Merkle proof verification failed")]
+    VerificationFailed {},
+}
@@ -0,0 +1,314 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use crate::state::store_latest_stage;
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, SubMsg, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::airdrop::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
+    MerkleRootResponse, QueryMsg,
+};
+
+// merkle tree of `addr000{i}:{i + 1}000000` leaves for i in 0..4
+const MERKLE_ROOT: &str = "b59069a0553eb83133861c5f463a8a0031ccec247614443db957979dd773716b";
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+    }
+}
+
+fn claim_msg() -> ExecuteMsg {
+    ExecuteMsg::Claim {
+        stage: 1u8,
+        amount: Uint256::from(1_000_000u64),
+        proof: vec![
+            "38875e4a5eb1753073b263b90d0094270e361411441c53190750588948bdf700".to_string(),
+            "6df52fa40562a36d8f899cde438db6a91778b98dbdb11cb6efe55262cb6953f3".to_string(),
+        ],
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            anchor_token: "anchor0000".to_string(),
+        }
+    );
+
+    let res: LatestStageResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap()).unwrap();
+    assert_eq!(res.latest_stage, 0u8);
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "owner0001".to_string());
+}
+
+#[test]
+fn register_merkle_root() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: MERKLE_ROOT.to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: "b59069a0".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidHash(hash)) => assert_eq!(hash, "b59069a0"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: MERKLE_ROOT.to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr("merkle_root", MERKLE_ROOT),
+        ]
+    );
+
+    let res: LatestStageResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap()).unwrap();
+    assert_eq!(res.latest_stage, 1u8);
+
+    let res: MerkleRootResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoot { stage: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        MerkleRootResponse {
+            stage: 1u8,
+            merkle_root: MERKLE_ROOT.to_string(),
+        }
+    );
+
+    // stages are stored as a single byte
+    store_latest_stage(deps.as_mut().storage, u8::MAX).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: MERKLE_ROOT.to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::StageLimitReached {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn claim() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: MERKLE_ROOT.to_string(),
+        },
+    )
+    .unwrap();
+
+    // the proof does not match another sender
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        claim_msg(),
+    );
+    match res {
+        Err(ContractError::VerificationFailed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // nor another amount
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Claim {
+            stage: 1u8,
+            amount: Uint256::from(2_000_000u64),
+            proof: vec![
+                "38875e4a5eb1753073b263b90d0094270e361411441c53190750588948bdf700".to_string(),
+                "6df52fa40562a36d8f899cde438db6a91778b98dbdb11cb6efe55262cb6953f3".to_string(),
+            ],
+        },
+    );
+    match res {
+        Err(ContractError::VerificationFailed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        claim_msg(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1_000_000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("stage", "1"),
+            attr("address", "addr0000"),
+            attr("amount", "1000000"),
+        ]
+    );
+
+    let res: IsClaimedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsClaimed {
+                stage: 1,
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.is_claimed);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        claim_msg(),
+    );
+    match res {
+        Err(ContractError::AlreadyClaimed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the other leaves can still be claimed
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        ExecuteMsg::Claim {
+            stage: 1u8,
+            amount: Uint256::from(3_000_000u64),
+            proof: vec![
+                "5ca429dc1076427502210ac22ad6bc3add96458070755640461a6e8d33d6f793".to_string(),
+                "7e163576a742b195222ce06be2d8c06ddaa30b0d10320db71623c9f330ac5872".to_string(),
+            ],
+        },
+    )
+    .unwrap();
+
+    let res: IsClaimedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsClaimed {
+                stage: 1,
+                address: "addr0001".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(!res.is_claimed);
+}
@@ -0,0 +1,229 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::error::ContractError;
+use crate::state::{read_config, store_config, Config};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Api, Binary, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply,
+    Response, StdResult, SubMsg, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::collector::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use terraswap::asset::{Asset, AssetInfo, PairInfo};
+use terraswap::pair::ExecuteMsg as PairExecuteMsg;
+use terraswap::querier::{query_balance, query_pair_info, query_token_balance};
+
+const SWEEP_REPLY_ID: u64 = 1;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            terraswap_factory: deps.api.addr_canonicalize(&msg.terraswap_factory)?,
+            anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
+            reward_weights: canonicalize_reward_weights(deps.api, msg.reward_weights)?,
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig {
+            owner,
+            reward_weights,
+        } => update_config(deps, info, owner, reward_weights),
+        ExecuteMsg::Sweep { denom } => sweep(deps, env, denom),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        SWEEP_REPLY_ID => distribute(deps, env),
+        _ => Err(ContractError::InvalidReplyId {}),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    reward_weights: Option<Vec<(String, Decimal256)>>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(reward_weights) = reward_weights {
+        config.reward_weights = canonicalize_reward_weights(deps.api, reward_weights)?;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+/// Swap the whole balance of the given denom to the protocol token;
+/// the swapped tokens are distributed once the swap succeeded
+/// Executor: anyone
+pub fn sweep(deps: DepsMut, env: Env, denom: String) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let amount = query_balance(&deps.querier, env.contract.address, denom.clone())?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToSweep(denom));
+    }
+
+    let pair_info: PairInfo = query_pair_info(
+        &deps.querier,
+        deps.api.addr_humanize(&config.terraswap_factory)?,
+        &[
+            AssetInfo::NativeToken {
+                denom: denom.clone(),
+            },
+            AssetInfo::Token {
+                contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            },
+        ],
+    )?;
+
+    // the tax is paid on top of the offered amount
+    let swap_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.clone(),
+        },
+        amount,
+    };
+    let swap_coin = swap_asset.deduct_tax(&deps.querier)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_info.contract_addr,
+                funds: vec![swap_coin.clone()],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken { denom },
+                        amount: swap_coin.amount,
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                })?,
+            }),
+            SWEEP_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "sweep"),
+            attr("collected_rewards", swap_coin.to_string()),
+        ]))
+}
+
+/// Split the protocol token balance between the reward recipients;
+/// the last recipient takes the rounding remainder
+fn distribute(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?;
+
+    let amount: Uint256 =
+        query_token_balance(&deps.querier, anchor_token.clone(), env.contract.address)?.into();
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut distributed_amount = Uint256::zero();
+    for (i, (recipient, weight)) in config.reward_weights.iter().enumerate() {
+        let recipient_amount = if i + 1 == config.reward_weights.len() {
+            amount - distributed_amount
+        } else {
+            amount * *weight
+        };
+
+        if recipient_amount.is_zero() {
+            continue;
+        }
+
+        distributed_amount += recipient_amount;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: anchor_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deps.api.addr_humanize(recipient)?.to_string(),
+                amount: recipient_amount.into(),
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "distribute"),
+        attr("distributed_amount", distributed_amount),
+    ]))
+}
+
+fn canonicalize_reward_weights(
+    api: &dyn Api,
+    reward_weights: Vec<(String, Decimal256)>,
+) -> Result<Vec<(CanonicalAddr, Decimal256)>, ContractError> {
+    let mut total_weight = Decimal256::zero();
+    for (_, weight) in reward_weights.iter() {
+        if weight.is_zero() {
+            return Err(ContractError::InvalidRewardWeights {});
+        }
+
+        total_weight += *weight;
+    }
+
+    if total_weight != Decimal256::one() {
+        return Err(ContractError::InvalidRewardWeights {});
+    }
+
+    reward_weights
+        .into_iter()
+        .map(|(recipient, weight)| Ok((api.addr_canonicalize(&recipient)?, weight)))
+        .collect()
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        terraswap_factory: deps
+            .api
+            .addr_humanize(&config.terraswap_factory)?
+            .to_string(),
+        anchor_token: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+        reward_weights: config
+            .reward_weights
+            .iter()
+            .map(|(recipient, weight)| {
+                Ok((deps.api.addr_humanize(recipient)?.to_string(), *weight))
+            })
+            .collect::<StdResult<Vec<(String, Decimal256)>>>()?,
+    })
+}
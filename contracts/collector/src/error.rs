@@ -0,0 +1,20 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Reward weights must be positive and sum to one")]
+    InvalidRewardWeights {},
+
+    #[error("No {0} balance to sweep")]
+    NothingToSweep(String),
+
+    #[error("Invalid reply ID")]
+    InvalidReplyId {},
+}
@@ -0,0 +1,273 @@
+use crate::contract::{execute, instantiate, query, reply};
+use crate::error::ContractError;
+use crate::testing::mock_querier::mock_dependencies;
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Coin, ContractResult, CosmosMsg, Decimal, Reply, SubMsg,
+    SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::collector::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use terraswap::asset::{Asset, AssetInfo};
+use terraswap::pair::ExecuteMsg as PairExecuteMsg;
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        terraswap_factory: "terraswapfactory".to_string(),
+        anchor_token: "tokenANC".to_string(),
+        reward_weights: vec![
+            ("gov0000".to_string(), Decimal256::percent(30)),
+            ("distributor0000".to_string(), Decimal256::percent(70)),
+        ],
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, init_msg()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            terraswap_factory: "terraswapfactory".to_string(),
+            anchor_token: "tokenANC".to_string(),
+            reward_weights: vec![
+                ("gov0000".to_string(), Decimal256::percent(30)),
+                ("distributor0000".to_string(), Decimal256::percent(70)),
+            ],
+        }
+    );
+
+    // weights must sum to one
+    let mut msg = init_msg();
+    msg.reward_weights[1].1 = Decimal256::percent(60);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::InvalidRewardWeights {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        reward_weights: Some(vec![("gov0000".to_string(), Decimal256::one())]),
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: None,
+            reward_weights: Some(vec![
+                ("gov0000".to_string(), Decimal256::one()),
+                ("distributor0000".to_string(), Decimal256::zero()),
+            ]),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidRewardWeights {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "update_config")]);
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "owner0001".to_string());
+    assert_eq!(
+        config.reward_weights,
+        vec![("gov0000".to_string(), Decimal256::one())]
+    );
+}
+
+#[test]
+fn sweep() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100_000_000u128),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1_000_000u128))],
+    );
+    deps.querier
+        .with_terraswap_pairs(&[(&"uusdtokenANC".to_string(), &"pairANC".to_string())]);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sweep {
+            denom: "ukrw".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::NothingToSweep(denom)) => assert_eq!(denom, "ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // anyone can sweep
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Sweep {
+            denom: "uusd".to_string(),
+        },
+    )
+    .unwrap();
+
+    // the tax on the offered amount is kept back, rounded up
+    let swap_amount = Uint128::from(99_009_900u128);
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pairANC".to_string(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: swap_amount,
+                }],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        amount: swap_amount,
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                })
+                .unwrap(),
+            }),
+            1
+        )]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "sweep"),
+            attr("collected_rewards", "99009900uusd"),
+        ]
+    );
+}
+
+#[test]
+fn distribute() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"tokenANC".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_001u128),
+        )],
+    )]);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let swap_reply = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), swap_reply).unwrap();
+
+    // the last recipient takes the rounding remainder
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "tokenANC".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "gov0000".to_string(),
+                    amount: Uint128::from(300_000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "tokenANC".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "distributor0000".to_string(),
+                    amount: Uint128::from(700_001u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute"),
+            attr("distributed_amount", "1000001"),
+        ]
+    );
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 2,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidReplyId {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -1,8 +1,5 @@
 use crate::error::ContractError;
-use crate::state::{
-    read_borrower_info, read_borrowers, read_config, remove_borrower_info, store_borrower_info,
-    BorrowerInfo, Config,
-};
+use crate::state::{read_config, Config};
 
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{
@@ -11,6 +8,9 @@ use cosmwasm_std::{
 };
 use cw20::Cw20ExecuteMsg;
 use moneymarket::custody::{BorrowerResponse, BorrowersResponse};
+use moneymarket::custody_state::{
+    read_borrower_info, read_borrowers, remove_borrower_info, store_borrower_info, BorrowerInfo,
+};
 use moneymarket::liquidation::Cw20HookMsg as LiquidationCw20HookMsg;
 use terra_cosmwasm::TerraMsgWrapper;
 
@@ -25,8 +25,7 @@ pub fn deposit_collateral(
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // increase borrower collateral
-    borrower_info.balance += amount;
-    borrower_info.spendable += amount;
+    borrower_info.deposit(amount);
 
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
 
@@ -50,17 +49,8 @@ pub fn withdraw_collateral(
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
-    // Check spendable balance
-    let amount = amount.unwrap_or(borrower_info.spendable);
-    if borrower_info.spendable < amount {
-        return Err(ContractError::WithdrawAmountExceedsSpendable(
-            borrower_info.spendable.into(),
-        ));
-    }
-
-    // decrease borrower collateral
-    borrower_info.balance = borrower_info.balance - amount;
-    borrower_info.spendable = borrower_info.spendable - amount;
+    // decrease borrower collateral, up to the spendable balance
+    let amount = borrower_info.withdraw(amount)?;
 
     if borrower_info.balance == Uint256::zero() {
         remove_borrower_info(deps.storage, &borrower_raw);
@@ -103,13 +93,7 @@ pub fn lock_collateral(
 
     let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
-    if amount > borrower_info.spendable {
-        return Err(ContractError::LockAmountExceedsSpendable(
-            borrower_info.spendable.into(),
-        ));
-    }
-
-    borrower_info.spendable = borrower_info.spendable - amount;
+    borrower_info.lock(amount)?;
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
     Ok(Response::new().add_attributes(vec![
         attr("action", "lock_collateral"),
@@ -134,14 +118,7 @@ pub fn unlock_collateral(
 
     let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
-    let locked_amount = borrower_info.balance - borrower_info.spendable;
-    if amount > locked_amount {
-        return Err(ContractError::UnlockAmountExceedsLocked(
-            locked_amount.into(),
-        ));
-    }
-
-    borrower_info.spendable += amount;
+    borrower_info.unlock(amount)?;
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -165,14 +142,7 @@ pub fn liquidate_collateral(
 
     let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
-    let locked_amount = borrower_info.balance - borrower_info.spendable;
-    if amount > locked_amount {
-        return Err(ContractError::LiquidationAmountExceedsLocked(
-            locked_amount.into(),
-        ));
-    }
-
-    borrower_info.balance = borrower_info.balance - amount;
+    borrower_info.liquidate(amount)?;
     store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
 
     Ok(Response::new()
@@ -1,7 +1,7 @@
 use cosmwasm_bignumber::Uint256;
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryRequest,
-    ReplyOn, Response, StdResult, SubMsg, Uint128, WasmMsg, WasmQuery,
+    attr, to_binary, Addr, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryRequest, ReplyOn,
+    Response, StdResult, SubMsg, Uint128, WasmMsg, WasmQuery,
 };
 
 use crate::contract::{CLAIM_REWARDS_OPERATION, SWAP_TO_STABLE_OPERATION};
@@ -9,6 +9,7 @@ use crate::error::ContractError;
 use crate::external::handle::{RewardContractExecuteMsg, RewardContractQueryMsg};
 use crate::state::{read_config, BETHAccruedRewardsResponse, Config};
 
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
 use moneymarket::querier::{deduct_tax, query_all_balances, query_balance};
 use terra_cosmwasm::{create_swap_msg, TerraMsgWrapper};
 
@@ -72,15 +73,16 @@ pub fn distribute_hook(
     )?;
     let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = vec![];
     if !reward_amount.is_zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: overseer_contract.to_string(),
-            amount: vec![deduct_tax(
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer_contract.to_string(),
+            funds: vec![deduct_tax(
                 deps.as_ref(),
                 Coin {
                     denom: config.stable_denom,
                     amount: reward_amount.into(),
                 },
             )?],
+            msg: to_binary(&OverseerExecuteMsg::FundReserve {})?,
         }));
     }
 
@@ -91,7 +93,8 @@ pub fn distribute_hook(
 }
 
 /// Swap all coins to stable_denom
-/// and execute `swap_hook`
+/// and execute `swap_hook`; when rewards are already
+/// paid in stable_denom they are distributed right away
 /// Executor: itself
 pub fn swap_to_stable_denom(
     deps: DepsMut,
@@ -99,17 +102,19 @@ pub fn swap_to_stable_denom(
 ) -> Result<Response<TerraMsgWrapper>, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
-    let contract_addr = env.contract.address;
-    let balances: Vec<Coin> = query_all_balances(deps.as_ref(), contract_addr)?;
+    let balances: Vec<Coin> = query_all_balances(deps.as_ref(), env.contract.address.clone())?;
     let mut messages: Vec<SubMsg<TerraMsgWrapper>> = balances
         .iter()
         .filter(|x| x.denom != config.stable_denom)
         .map(|coin: &Coin| SubMsg::new(create_swap_msg(coin.clone(), config.stable_denom.clone())))
         .collect();
 
-    if let Some(last) = messages.last_mut() {
-        last.id = SWAP_TO_STABLE_OPERATION;
-        last.reply_on = ReplyOn::Success;
+    match messages.last_mut() {
+        Some(last) => {
+            last.id = SWAP_TO_STABLE_OPERATION;
+            last.reply_on = ReplyOn::Success;
+        }
+        None => return distribute_hook(deps, env),
     }
 
     Ok(Response::new().add_submessages(messages))
@@ -1,4 +1,5 @@
 use cosmwasm_std::{OverflowError, StdError};
+use moneymarket::custody_state::CollateralError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -30,3 +31,22 @@ pub enum ContractError {
     #[error("Withdraw amount cannot exceed the user's spendable amount: {0}")]
     WithdrawAmountExceedsSpendable(u128),
 }
+
+impl From<CollateralError> for ContractError {
+    fn from(err: CollateralError) -> Self {
+        match err {
+            CollateralError::LiquidationAmountExceedsLocked(locked) => {
+                ContractError::LiquidationAmountExceedsLocked(locked.into())
+            }
+            CollateralError::LockAmountExceedsSpendable(spendable) => {
+                ContractError::LockAmountExceedsSpendable(spendable.into())
+            }
+            CollateralError::UnlockAmountExceedsLocked(locked) => {
+                ContractError::UnlockAmountExceedsLocked(locked.into())
+            }
+            CollateralError::WithdrawAmountExceedsSpendable(spendable) => {
+                ContractError::WithdrawAmountExceedsSpendable(spendable.into())
+            }
+        }
+    }
+}
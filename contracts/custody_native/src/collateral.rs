@@ -0,0 +1,215 @@
+use crate::error::ContractError;
+use crate::state::{read_config, Config};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, MessageInfo,
+    Response, StdResult, WasmMsg,
+};
+use moneymarket::custody::{BorrowerResponse, BorrowersResponse};
+use moneymarket::custody_state::{
+    read_borrower_info, read_borrowers, remove_borrower_info, store_borrower_info, BorrowerInfo,
+};
+use moneymarket::liquidation::ExecuteMsg as LiquidationExecuteMsg;
+use moneymarket::querier::deduct_tax;
+
+/// Deposit the collateral coins sent with the message
+/// Executor: borrower
+pub fn deposit_collateral(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.collateral_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    // Cannot deposit zero amount
+    if amount.is_zero() {
+        return Err(ContractError::ZeroDeposit(config.collateral_denom));
+    }
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // increase borrower collateral
+    borrower_info.deposit(amount);
+
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_collateral"),
+        attr("borrower", borrower.as_str()),
+        attr("amount", amount.to_string()),
+    ]))
+}
+
+/// Withdraw spendable collateral or a specified amount of collateral;
+/// the transfer tax is paid out of the withdrawn coins
+/// Executor: borrower
+pub fn withdraw_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // decrease borrower collateral, up to the spendable balance
+    let amount = borrower_info.withdraw(amount)?;
+
+    if borrower_info.balance == Uint256::zero() {
+        remove_borrower_info(deps.storage, &borrower_raw);
+    } else {
+        store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: borrower.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.collateral_denom,
+                    amount: amount.into(),
+                },
+            )?],
+        }))
+        .add_attributes(vec![
+            attr("action", "withdraw_collateral"),
+            attr("borrower", borrower.as_str()),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+/// Decrease spendable collateral to lock
+/// specified amount of collateral coins
+/// Executor: overseer
+pub fn lock_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    borrower_info.lock(amount)?;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "lock_collateral"),
+        attr("borrower", borrower),
+        attr("amount", amount),
+    ]))
+}
+
+/// Increase spendable collateral to unlock
+/// specified amount of collateral coins
+/// Executor: overseer
+pub fn unlock_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    borrower_info.unlock(amount)?;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unlock_collateral"),
+        attr("borrower", borrower),
+        attr("amount", amount),
+    ]))
+}
+
+/// Send the liquidated coins, net of the transfer tax,
+/// to be sold to the liquidator's bid
+/// Executor: overseer
+pub fn liquidate_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    liquidator: Addr,
+    borrower: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw: CanonicalAddr = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    borrower_info.liquidate(amount)?;
+    store_borrower_info(deps.storage, &borrower_raw, &borrower_info)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.liquidation_contract)?
+                .to_string(),
+            funds: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.collateral_denom,
+                    amount: amount.into(),
+                },
+            )?],
+            msg: to_binary(&LiquidationExecuteMsg::ExecuteBid {
+                liquidator: liquidator.to_string(),
+                fee_address: Some(
+                    deps.api
+                        .addr_humanize(&config.overseer_contract)?
+                        .to_string(),
+                ),
+                repay_address: Some(deps.api.addr_humanize(&config.market_contract)?.to_string()),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "liquidate_collateral"),
+            attr("liquidator", liquidator),
+            attr("borrower", borrower),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn query_borrower(deps: Deps, borrower: Addr) -> StdResult<BorrowerResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    Ok(BorrowerResponse {
+        borrower: borrower.to_string(),
+        balance: borrower_info.balance,
+        spendable: borrower_info.spendable,
+    })
+}
+
+pub fn query_borrowers(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BorrowersResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let borrowers = read_borrowers(deps, start_after, limit)?;
+    Ok(BorrowersResponse { borrowers })
+}
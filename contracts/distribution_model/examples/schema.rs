@@ -4,7 +4,8 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::distribution_model::{
-    AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AncEmissionRateResponse, ConfigResponse, EmissionRateHistoryResponse, EmissionRateResponse,
+    ExecuteMsg, InstantiateMsg, QueryMsg,
 };
 
 fn main() {
@@ -18,4 +19,6 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(AncEmissionRateResponse), &out_dir);
+    export_schema(&schema_for!(EmissionRateResponse), &out_dir);
+    export_schema(&schema_for!(EmissionRateHistoryResponse), &out_dir);
 }
@@ -1,14 +1,20 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
 
 use crate::error::ContractError;
-use crate::state::{read_config, store_config, Config};
+use crate::state::{
+    read_config, read_emission_rate, read_emission_rate_history, store_config, store_emission_rate,
+    Config, EmissionRate,
+};
 
 use cosmwasm_bignumber::Decimal256;
 use moneymarket::common::optional_addr_validate;
 use moneymarket::distribution_model::{
-    AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AncEmissionRateResponse, ConfigResponse, EmissionRateHistoryResponse, EmissionRateResponse,
+    ExecuteMsg, InstantiateMsg, QueryMsg,
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -26,6 +32,7 @@ pub fn instantiate(
             emission_floor: msg.emission_floor,
             increment_multiplier: msg.increment_multiplier,
             decrement_multiplier: msg.decrement_multiplier,
+            overseer_contract: None,
         },
     )?;
 
@@ -35,7 +42,7 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
@@ -46,6 +53,7 @@ pub fn execute(
             emission_floor,
             increment_multiplier,
             decrement_multiplier,
+            overseer_contract,
         } => {
             let api = deps.api;
             update_config(
@@ -56,11 +64,25 @@ pub fn execute(
                 emission_floor,
                 increment_multiplier,
                 decrement_multiplier,
+                optional_addr_validate(api, overseer_contract)?,
             )
         }
+        ExecuteMsg::UpdateEmissionRate {
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+        } => update_emission_rate(
+            deps,
+            env,
+            info,
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+        ),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -69,6 +91,7 @@ pub fn update_config(
     emission_floor: Option<Decimal256>,
     increment_multiplier: Option<Decimal256>,
     decrement_multiplier: Option<Decimal256>,
+    overseer_contract: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -95,10 +118,53 @@ pub fn update_config(
         config.decrement_multiplier = decrement_multiplier;
     }
 
+    if let Some(overseer_contract) = overseer_contract {
+        config.overseer_contract = Some(deps.api.addr_canonicalize(overseer_contract.as_str())?);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::default())
 }
 
+/// Adjust the stored emission rate once per epoch
+/// Executor: overseer
+pub fn update_emission_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    deposit_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if Some(deps.api.addr_canonicalize(info.sender.as_str())?) != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let current = read_emission_rate(deps.storage, &config);
+    let emission_rate = compute_emission_rate(
+        &config,
+        deposit_rate,
+        target_deposit_rate,
+        threshold_deposit_rate,
+        current.emission_rate,
+    );
+
+    store_emission_rate(
+        deps.storage,
+        &EmissionRate {
+            emission_rate,
+            last_updated: env.block.height,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_emission_rate"),
+        attr("deposit_rate", deposit_rate.to_string()),
+        attr("emission_rate", emission_rate.to_string()),
+    ]))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -115,6 +181,10 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             threshold_deposit_rate,
             current_emission_rate,
         )?),
+        QueryMsg::EmissionRate {} => to_binary(&query_emission_rate(deps)?),
+        QueryMsg::EmissionRateHistory { start_after, limit } => {
+            to_binary(&query_emission_rate_history(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -126,6 +196,11 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         emission_floor: state.emission_floor,
         increment_multiplier: state.increment_multiplier,
         decrement_multiplier: state.decrement_multiplier,
+        overseer_contract: state
+            .overseer_contract
+            .map(|addr| deps.api.addr_humanize(&addr))
+            .transpose()?
+            .map(|addr| addr.to_string()),
     };
 
     Ok(resp)
@@ -140,6 +215,44 @@ fn query_anc_emission_rate(
 ) -> StdResult<AncEmissionRateResponse> {
     let config: Config = read_config(deps.storage)?;
 
+    Ok(AncEmissionRateResponse {
+        emission_rate: compute_emission_rate(
+            &config,
+            deposit_rate,
+            target_deposit_rate,
+            threshold_deposit_rate,
+            current_emission_rate,
+        ),
+    })
+}
+
+fn query_emission_rate(deps: Deps) -> StdResult<EmissionRateResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let emission_rate = read_emission_rate(deps.storage, &config);
+
+    Ok(EmissionRateResponse {
+        emission_rate: emission_rate.emission_rate,
+        last_updated: emission_rate.last_updated,
+    })
+}
+
+fn query_emission_rate_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EmissionRateHistoryResponse> {
+    Ok(EmissionRateHistoryResponse {
+        history: read_emission_rate_history(deps.storage, start_after, limit)?,
+    })
+}
+
+fn compute_emission_rate(
+    config: &Config,
+    deposit_rate: Decimal256,
+    target_deposit_rate: Decimal256,
+    threshold_deposit_rate: Decimal256,
+    current_emission_rate: Decimal256,
+) -> Decimal256 {
     let half_dec = Decimal256::one() + Decimal256::one();
     let mid_rate = (threshold_deposit_rate + target_deposit_rate) / half_dec;
     let high_trigger = (mid_rate + target_deposit_rate) / half_dec;
@@ -153,13 +266,11 @@ fn query_anc_emission_rate(
         current_emission_rate
     };
 
-    let emission_rate = if emission_rate > config.emission_cap {
+    if emission_rate > config.emission_cap {
         config.emission_cap
     } else if emission_rate < config.emission_floor {
         config.emission_floor
     } else {
         emission_rate
-    };
-
-    Ok(AncEmissionRateResponse { emission_rate })
+    }
 }
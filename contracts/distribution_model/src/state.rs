@@ -2,10 +2,13 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read};
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::distribution_model::EmissionRateResponse;
 
 static KEY_CONFIG: &[u8] = b"config";
+static KEY_EMISSION_RATE: &[u8] = b"emission_rate";
+static PREFIX_EMISSION_RATE_HISTORY: &[u8] = b"emission_rate_history";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -14,6 +17,13 @@ pub struct Config {
     pub emission_floor: Decimal256,
     pub increment_multiplier: Decimal256,
     pub decrement_multiplier: Decimal256,
+    pub overseer_contract: Option<CanonicalAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionRate {
+    pub emission_rate: Decimal256,
+    pub last_updated: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -23,3 +33,57 @@ pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()>
 pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
+
+/// Store the current emission rate and record it in the history
+pub fn store_emission_rate(
+    storage: &mut dyn Storage,
+    emission_rate: &EmissionRate,
+) -> StdResult<()> {
+    singleton(storage, KEY_EMISSION_RATE).save(emission_rate)?;
+
+    let mut history_bucket: Bucket<Decimal256> = Bucket::new(storage, PREFIX_EMISSION_RATE_HISTORY);
+    history_bucket.save(
+        &emission_rate.last_updated.to_be_bytes(),
+        &emission_rate.emission_rate,
+    )
+}
+
+/// The emission rate starts at the floor until the first update
+pub fn read_emission_rate(storage: &dyn Storage, config: &Config) -> EmissionRate {
+    match singleton_read(storage, KEY_EMISSION_RATE).load() {
+        Ok(v) => v,
+        _ => EmissionRate {
+            emission_rate: config.emission_floor,
+            last_updated: 0,
+        },
+    }
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_emission_rate_history(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EmissionRateResponse>> {
+    let history_bucket: ReadonlyBucket<Decimal256> =
+        ReadonlyBucket::new(storage, PREFIX_EMISSION_RATE_HISTORY);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|height| (height + 1).to_be_bytes().to_vec());
+
+    history_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, emission_rate) = item?;
+            let mut height = [0u8; 8];
+            height.copy_from_slice(&k);
+            Ok(EmissionRateResponse {
+                emission_rate,
+                last_updated: u64::from_be_bytes(height),
+            })
+        })
+        .collect()
+}
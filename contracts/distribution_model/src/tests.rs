@@ -1,10 +1,11 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::from_binary;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary};
 use moneymarket::distribution_model::{
-    AncEmissionRateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    AncEmissionRateResponse, ConfigResponse, EmissionRateHistoryResponse, EmissionRateResponse,
+    ExecuteMsg, InstantiateMsg, QueryMsg,
 };
 
 #[test]
@@ -58,6 +59,7 @@ fn update_config() {
         emission_floor: None,
         increment_multiplier: None,
         decrement_multiplier: None,
+        overseer_contract: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -80,6 +82,7 @@ fn update_config() {
         emission_floor: Some(Decimal256::from_uint256(10u64)),
         increment_multiplier: Some(Decimal256::percent(110)),
         decrement_multiplier: Some(Decimal256::percent(90)),
+        overseer_contract: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -161,3 +164,135 @@ fn proper_emission_rate() {
     let value: AncEmissionRateResponse = from_binary(&res).unwrap();
     assert_eq!("10", &value.emission_rate.to_string());
 }
+
+#[test]
+fn update_emission_rate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        emission_cap: Decimal256::from_uint256(100u64),
+        emission_floor: Decimal256::from_uint256(10u64),
+        increment_multiplier: Decimal256::percent(110),
+        decrement_multiplier: Decimal256::percent(90),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // starts at the floor
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionRate {}).unwrap();
+    let value: EmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionRateResponse {
+            emission_rate: Decimal256::from_uint256(10u64),
+            last_updated: 0,
+        }
+    );
+
+    // deposit rate below the low trigger
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        deposit_rate: Decimal256::percent(5),
+        target_deposit_rate: Decimal256::percent(10),
+        threshold_deposit_rate: Decimal256::percent(5),
+    };
+
+    // no overseer registered yet
+    let info = mock_info("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let update_config = ExecuteMsg::UpdateConfig {
+        owner: None,
+        emission_cap: None,
+        emission_floor: None,
+        increment_multiplier: None,
+        decrement_multiplier: None,
+        overseer_contract: Some("overseer".to_string()),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        update_config,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env();
+    let first_height = env.block.height;
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_emission_rate"),
+            attr("deposit_rate", "0.05"),
+            attr("emission_rate", "11"),
+        ]
+    );
+
+    // the stored rate keeps growing with each epoch
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::EmissionRate {}).unwrap();
+    let value: EmissionRateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        EmissionRateResponse {
+            emission_rate: Decimal256::from_uint256(121u64) / Decimal256::from_uint256(10u64),
+            last_updated: env.block.height,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::EmissionRateHistory {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: EmissionRateHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.history,
+        vec![
+            EmissionRateResponse {
+                emission_rate: Decimal256::from_uint256(11u64),
+                last_updated: first_height,
+            },
+            EmissionRateResponse {
+                emission_rate: Decimal256::from_uint256(121u64) / Decimal256::from_uint256(10u64),
+                last_updated: env.block.height,
+            },
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::EmissionRateHistory {
+            start_after: Some(first_height),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let value: EmissionRateHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(value.history.len(), 1);
+    assert_eq!(value.history[0].last_updated, env.block.height);
+}
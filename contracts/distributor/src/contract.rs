@@ -0,0 +1,252 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::error::ContractError;
+use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Binary, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::distributor::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let whitelist = msg
+        .whitelist
+        .iter()
+        .map(|addr| deps.api.addr_canonicalize(addr))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
+            whitelist,
+            epoch_period: msg.epoch_period,
+            max_spend_per_epoch: msg.max_spend_per_epoch,
+            min_spend_amount: msg.min_spend_amount,
+        },
+    )?;
+
+    store_state(
+        deps.storage,
+        &State {
+            epoch_start_height: env.block.height,
+            epoch_spent_amount: Uint256::zero(),
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig {
+            owner,
+            epoch_period,
+            max_spend_per_epoch,
+            min_spend_amount,
+        } => update_config(
+            deps,
+            info,
+            owner,
+            epoch_period,
+            max_spend_per_epoch,
+            min_spend_amount,
+        ),
+        ExecuteMsg::AddDistributor { distributor } => add_distributor(deps, info, distributor),
+        ExecuteMsg::RemoveDistributor { distributor } => {
+            remove_distributor(deps, info, distributor)
+        }
+        ExecuteMsg::Spend { recipient, amount } => spend(deps, env, info, recipient, amount),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    epoch_period: Option<u64>,
+    max_spend_per_epoch: Option<Uint256>,
+    min_spend_amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    if let Some(epoch_period) = epoch_period {
+        config.epoch_period = epoch_period;
+    }
+
+    if let Some(max_spend_per_epoch) = max_spend_per_epoch {
+        config.max_spend_per_epoch = max_spend_per_epoch;
+    }
+
+    if let Some(min_spend_amount) = min_spend_amount {
+        config.min_spend_amount = min_spend_amount;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+pub fn add_distributor(
+    deps: DepsMut,
+    info: MessageInfo,
+    distributor: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let distributor_raw = deps.api.addr_canonicalize(&distributor)?;
+    if config.whitelist.contains(&distributor_raw) {
+        return Err(ContractError::DistributorAlreadyRegistered {});
+    }
+
+    config.whitelist.push(distributor_raw);
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_distributor"),
+        attr("distributor", distributor),
+    ]))
+}
+
+pub fn remove_distributor(
+    deps: DepsMut,
+    info: MessageInfo,
+    distributor: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let distributor_raw = deps.api.addr_canonicalize(&distributor)?;
+    if !config.whitelist.contains(&distributor_raw) {
+        return Err(ContractError::DistributorNotRegistered {});
+    }
+
+    config.whitelist.retain(|addr| *addr != distributor_raw);
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_distributor"),
+        attr("distributor", distributor),
+    ]))
+}
+
+/// Send protocol tokens to the recipient; all distributors
+/// share the spend limit of the current epoch
+/// Executor: whitelisted contract
+pub fn spend(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !config.whitelist.contains(&sender_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if amount < config.min_spend_amount {
+        return Err(ContractError::SpendAmountTooSmall(
+            config.min_spend_amount.into(),
+        ));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    state.roll_epoch(&config, env.block.height);
+
+    let remaining_amount = if config.max_spend_per_epoch > state.epoch_spent_amount {
+        config.max_spend_per_epoch - state.epoch_spent_amount
+    } else {
+        Uint256::zero()
+    };
+    if amount > remaining_amount {
+        return Err(ContractError::EpochSpendLimitExceeded(
+            remaining_amount.into(),
+        ));
+    }
+
+    state.epoch_spent_amount += amount;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.clone(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "spend"),
+            attr("distributor", info.sender),
+            attr("recipient", recipient),
+            attr("amount", amount),
+        ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps, env)?),
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        anchor_token: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+        whitelist: config
+            .whitelist
+            .iter()
+            .map(|addr| Ok(deps.api.addr_humanize(addr)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+        epoch_period: config.epoch_period,
+        max_spend_per_epoch: config.max_spend_per_epoch,
+        min_spend_amount: config.min_spend_amount,
+    })
+}
+
+fn query_state(deps: Deps, env: Env) -> StdResult<StateResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    state.roll_epoch(&config, env.block.height);
+
+    Ok(StateResponse {
+        epoch_start_height: state.epoch_start_height,
+        epoch_spent_amount: state.epoch_spent_amount,
+    })
+}
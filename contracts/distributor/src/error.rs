@@ -0,0 +1,23 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Distributor is already registered")]
+    DistributorAlreadyRegistered {},
+
+    #[error("Distributor is not registered")]
+    DistributorNotRegistered {},
+
+    #[error("Spend amount cannot be less than the minimum spend amount: {0}")]
+    SpendAmountTooSmall(u128),
+
+    #[error("Spend amount exceeds the epoch spend limit; Remaining limit: {0}")]
+    EpochSpendLimitExceeded(u128),
+}
@@ -0,0 +1,335 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, Env, SubMsg, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::distributor::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+        whitelist: vec!["market0000".to_string(), "overseer0000".to_string()],
+        epoch_period: 100u64,
+        max_spend_per_epoch: Uint256::from(1_000_000u64),
+        min_spend_amount: Uint256::from(100u64),
+    }
+}
+
+fn env_at(height: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height = height;
+    env
+}
+
+fn spend_msg(amount: u64) -> ExecuteMsg {
+    ExecuteMsg::Spend {
+        recipient: "addr0000".to_string(),
+        amount: Uint256::from(amount),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            anchor_token: "anchor0000".to_string(),
+            whitelist: vec!["market0000".to_string(), "overseer0000".to_string()],
+            epoch_period: 100u64,
+            max_spend_per_epoch: Uint256::from(1_000_000u64),
+            min_spend_amount: Uint256::from(100u64),
+        }
+    );
+
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
+    assert_eq!(
+        state,
+        StateResponse {
+            epoch_start_height: mock_env().block.height,
+            epoch_spent_amount: Uint256::zero(),
+        }
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        epoch_period: None,
+        max_spend_per_epoch: Some(Uint256::from(2_000_000u64)),
+        min_spend_amount: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "update_config")]);
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "owner0001".to_string());
+    assert_eq!(config.max_spend_per_epoch, Uint256::from(2_000_000u64));
+    assert_eq!(config.epoch_period, 100u64);
+}
+
+#[test]
+fn add_remove_distributor() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::AddDistributor {
+            distributor: "addr0000".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::AddDistributor {
+            distributor: "market0000".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::DistributorAlreadyRegistered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::AddDistributor {
+            distributor: "staking0000".to_string(),
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RemoveDistributor {
+            distributor: "market0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "remove_distributor"),
+            attr("distributor", "market0000"),
+        ]
+    );
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config.whitelist,
+        vec!["overseer0000".to_string(), "staking0000".to_string()]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RemoveDistributor {
+            distributor: "market0000".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::DistributorNotRegistered {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // removed distributors cannot spend anymore
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("market0000", &[]),
+        spend_msg(1_000),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn spend() {
+    let mut deps = mock_dependencies(&[]);
+    let start_height = mock_env().block.height;
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        spend_msg(1_000),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("market0000", &[]),
+        spend_msg(99),
+    );
+    match res {
+        Err(ContractError::SpendAmountTooSmall(amount)) => assert_eq!(amount, 100u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("market0000", &[]),
+        spend_msg(600_000),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(600_000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "spend"),
+            attr("distributor", "market0000"),
+            attr("recipient", "addr0000"),
+            attr("amount", "600000"),
+        ]
+    );
+
+    // distributors share the epoch limit
+    let res = execute(
+        deps.as_mut(),
+        env_at(start_height + 99),
+        mock_info("overseer0000", &[]),
+        spend_msg(400_001),
+    );
+    match res {
+        Err(ContractError::EpochSpendLimitExceeded(remaining)) => {
+            assert_eq!(remaining, 400_000u128)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(
+        deps.as_mut(),
+        env_at(start_height + 99),
+        mock_info("overseer0000", &[]),
+        spend_msg(400_000),
+    )
+    .unwrap();
+
+    // the limit resets with the next epoch
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(start_height + 100),
+            QueryMsg::State {},
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        state,
+        StateResponse {
+            epoch_start_height: start_height + 100,
+            epoch_spent_amount: Uint256::zero(),
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        env_at(start_height + 100),
+        mock_info("market0000", &[]),
+        spend_msg(1_000_000),
+    )
+    .unwrap();
+
+    let state: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(start_height + 150),
+            QueryMsg::State {},
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        state,
+        StateResponse {
+            epoch_start_height: start_height + 100,
+            epoch_spent_amount: Uint256::from(1_000_000u64),
+        }
+    );
+}
@@ -0,0 +1,234 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::error::ContractError;
+use crate::poll::{
+    cast_vote, create_poll, end_poll, execute_poll, query_poll, query_polls, query_voters,
+};
+use crate::staking::{query_staker, stake_voting_tokens, withdraw_voting_tokens};
+use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult,
+};
+use cw20::Cw20ReceiveMsg;
+use moneymarket::common::optional_addr_validate;
+use moneymarket::gov::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    validate_quorum(msg.quorum)?;
+    validate_threshold(msg.threshold)?;
+
+    store_config(
+        deps.storage,
+        &Config {
+            governance_token: deps.api.addr_canonicalize(&msg.governance_token)?,
+            quorum: msg.quorum,
+            threshold: msg.threshold,
+            voting_period: msg.voting_period,
+            timelock_period: msg.timelock_period,
+            proposal_deposit: msg.proposal_deposit,
+        },
+    )?;
+
+    store_state(
+        deps.storage,
+        &State {
+            poll_count: 0,
+            total_staked: Uint256::zero(),
+            total_deposit: Uint256::zero(),
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig {
+            quorum,
+            threshold,
+            voting_period,
+            timelock_period,
+            proposal_deposit,
+        } => update_config(
+            deps,
+            env,
+            info,
+            quorum,
+            threshold,
+            voting_period,
+            timelock_period,
+            proposal_deposit,
+        ),
+        ExecuteMsg::CastVote {
+            poll_id,
+            vote,
+            amount,
+        } => cast_vote(deps, env, info, poll_id, vote, amount),
+        ExecuteMsg::WithdrawVotingTokens { amount } => withdraw_voting_tokens(deps, info, amount),
+        ExecuteMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
+        ExecuteMsg::ExecutePoll { poll_id } => execute_poll(deps, env, poll_id),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // only the governance token can be staked or deposited
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.governance_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::StakeVotingTokens {}) => {
+            stake_voting_tokens(deps, sender, cw20_msg.amount.into())
+        }
+        Ok(Cw20HookMsg::CreatePoll {
+            title,
+            description,
+            execute_msgs,
+        }) => create_poll(
+            deps,
+            env,
+            sender,
+            cw20_msg.amount.into(),
+            title,
+            description,
+            execute_msgs,
+        ),
+        _ => Err(ContractError::MissingHook {}),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    quorum: Option<Decimal256>,
+    threshold: Option<Decimal256>,
+    voting_period: Option<u64>,
+    timelock_period: Option<u64>,
+    proposal_deposit: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    // config changes go through polls like any other parameter change
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config: Config = read_config(deps.storage)?;
+
+    if let Some(quorum) = quorum {
+        validate_quorum(quorum)?;
+        config.quorum = quorum;
+    }
+
+    if let Some(threshold) = threshold {
+        validate_threshold(threshold)?;
+        config.threshold = threshold;
+    }
+
+    if let Some(voting_period) = voting_period {
+        config.voting_period = voting_period;
+    }
+
+    if let Some(timelock_period) = timelock_period {
+        config.timelock_period = timelock_period;
+    }
+
+    if let Some(proposal_deposit) = proposal_deposit {
+        config.proposal_deposit = proposal_deposit;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+fn validate_quorum(quorum: Decimal256) -> StdResult<()> {
+    if quorum > Decimal256::one() {
+        return Err(StdError::generic_err("quorum must be 0 to 1"));
+    }
+
+    Ok(())
+}
+
+fn validate_threshold(threshold: Decimal256) -> StdResult<()> {
+    if threshold > Decimal256::one() {
+        return Err(StdError::generic_err("threshold must be 0 to 1"));
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::Staker { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&query_staker(deps, address)?)
+        }
+        QueryMsg::Poll { poll_id } => to_binary(&query_poll(deps, poll_id)?),
+        QueryMsg::Polls {
+            filter,
+            start_after,
+            limit,
+        } => to_binary(&query_polls(deps, filter, start_after, limit)?),
+        QueryMsg::Voters {
+            poll_id,
+            start_after,
+            limit,
+        } => {
+            let start_after = optional_addr_validate(deps.api, start_after)?;
+            to_binary(&query_voters(deps, poll_id, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        governance_token: deps
+            .api
+            .addr_humanize(&config.governance_token)?
+            .to_string(),
+        quorum: config.quorum,
+        threshold: config.threshold,
+        voting_period: config.voting_period,
+        timelock_period: config.timelock_period,
+        proposal_deposit: config.proposal_deposit,
+    })
+}
+
+fn query_state(deps: Deps) -> StdResult<StateResponse> {
+    let state: State = read_state(deps.storage)?;
+    Ok(StateResponse {
+        poll_count: state.poll_count,
+        total_staked: state.total_staked,
+        total_deposit: state.total_deposit,
+    })
+}
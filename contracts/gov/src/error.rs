@@ -0,0 +1,59 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid request: \"stake voting tokens\" or \"create poll\" message not included in request")]
+    MissingHook {},
+
+    #[error("Must deposit more than {0} token")]
+    InsufficientProposalDeposit(u128),
+
+    #[error("Title must be between 4 and 64 characters")]
+    InvalidTitle {},
+
+    #[error("Description must be between 4 and 1024 characters")]
+    InvalidDescription {},
+
+    #[error("Poll does not exist")]
+    PollNotFound {},
+
+    #[error("Poll is not in progress")]
+    PollNotInProgress {},
+
+    #[error("Voting period has not expired")]
+    PollVotingPeriod {},
+
+    #[error("Voting period has expired")]
+    PollVotingPeriodExpired {},
+
+    #[error("User has already voted")]
+    AlreadyVoted {},
+
+    #[error("User does not have enough staked tokens")]
+    InsufficientStaked {},
+
+    #[error("Nothing staked")]
+    NothingStaked {},
+
+    #[error("User is trying to withdraw too many tokens")]
+    InvalidWithdrawAmount {},
+
+    #[error("Poll is not in passed status")]
+    PollNotPassed {},
+
+    #[error("Timelock period has not expired")]
+    TimelockNotExpired {},
+
+    #[error("Amount must be greater than zero")]
+    ZeroAmount {},
+}
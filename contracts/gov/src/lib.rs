@@ -0,0 +1,8 @@
+pub mod contract;
+pub mod error;
+mod poll;
+mod staking;
+pub mod state;
+
+#[cfg(test)]
+mod testing;
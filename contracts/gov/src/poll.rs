@@ -0,0 +1,320 @@
+use crate::error::ContractError;
+use crate::staking::release_locked_balance;
+use crate::state::{
+    read_config, read_poll, read_poll_voter, read_poll_voters, read_polls, read_staker, read_state,
+    store_poll, store_poll_voter, store_staker, store_state, Config, ExecuteData, Poll, Staker,
+    State,
+};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::gov::{
+    PollExecuteMsg, PollResponse, PollStatus, PollsResponse, VoteOption, VoterInfo, VotersResponse,
+};
+
+const MIN_TITLE_LENGTH: usize = 4;
+const MAX_TITLE_LENGTH: usize = 64;
+const MIN_DESC_LENGTH: usize = 4;
+const MAX_DESC_LENGTH: usize = 1024;
+
+/// Create a poll with the sent tokens as its deposit
+/// Executor: governance token contract
+pub fn create_poll(
+    deps: DepsMut,
+    env: Env,
+    creator: Addr,
+    deposit_amount: Uint256,
+    title: String,
+    description: String,
+    execute_msgs: Option<Vec<PollExecuteMsg>>,
+) -> Result<Response, ContractError> {
+    if title.len() < MIN_TITLE_LENGTH || title.len() > MAX_TITLE_LENGTH {
+        return Err(ContractError::InvalidTitle {});
+    }
+
+    if description.len() < MIN_DESC_LENGTH || description.len() > MAX_DESC_LENGTH {
+        return Err(ContractError::InvalidDescription {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    if deposit_amount < config.proposal_deposit {
+        return Err(ContractError::InsufficientProposalDeposit(
+            config.proposal_deposit.into(),
+        ));
+    }
+
+    let execute_data = match execute_msgs {
+        Some(execute_msgs) => {
+            let mut execute_data = execute_msgs
+                .into_iter()
+                .map(|msg| {
+                    Ok(ExecuteData {
+                        order: msg.order,
+                        contract: deps.api.addr_canonicalize(&msg.contract)?,
+                        msg: msg.msg,
+                    })
+                })
+                .collect::<StdResult<Vec<ExecuteData>>>()?;
+            execute_data.sort_by_key(|data| data.order);
+            Some(execute_data)
+        }
+        None => None,
+    };
+
+    let mut state: State = read_state(deps.storage)?;
+    state.poll_count += 1;
+    state.total_deposit += deposit_amount;
+
+    let poll = Poll {
+        id: state.poll_count,
+        creator: deps.api.addr_canonicalize(creator.as_str())?,
+        status: PollStatus::InProgress,
+        yes_votes: Uint256::zero(),
+        no_votes: Uint256::zero(),
+        end_height: env.block.height + config.voting_period,
+        title,
+        description,
+        execute_data,
+        deposit_amount,
+        total_balance_at_end_poll: None,
+    };
+
+    store_poll(deps.storage, &poll, None)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "create_poll"),
+        attr("creator", creator),
+        attr("poll_id", poll.id.to_string()),
+        attr("end_height", poll.end_height.to_string()),
+    ]))
+}
+
+/// Vote on an in-progress poll with staked tokens
+/// Executor: staker
+pub fn cast_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    let mut poll: Poll =
+        read_poll(deps.storage, poll_id).map_err(|_| ContractError::PollNotFound {})?;
+    if poll.status != PollStatus::InProgress {
+        return Err(ContractError::PollNotInProgress {});
+    }
+
+    if env.block.height > poll.end_height {
+        return Err(ContractError::PollVotingPeriodExpired {});
+    }
+
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_poll_voter(deps.storage, poll_id, &sender_raw).is_some() {
+        return Err(ContractError::AlreadyVoted {});
+    }
+
+    let mut staker: Staker = read_staker(deps.storage, &sender_raw);
+    if amount.is_zero() || staker.balance < amount {
+        return Err(ContractError::InsufficientStaked {});
+    }
+
+    match vote {
+        VoteOption::Yes => poll.yes_votes += amount,
+        VoteOption::No => poll.no_votes += amount,
+    }
+
+    let voter_info = VoterInfo {
+        vote: vote.clone(),
+        balance: amount,
+    };
+
+    // the voted amount stays locked until the poll ends
+    release_locked_balance(deps.storage, &mut staker)?;
+    staker.locked_balance.push((poll_id, voter_info.clone()));
+
+    store_staker(deps.storage, &sender_raw, &staker)?;
+    store_poll_voter(deps.storage, poll_id, &sender_raw, &voter_info)?;
+    store_poll(deps.storage, &poll, None)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cast_vote"),
+        attr("poll_id", poll_id.to_string()),
+        attr("amount", amount),
+        attr("voter", info.sender),
+        attr("vote_option", vote.to_string()),
+    ]))
+}
+
+/// Tally an in-progress poll once its voting period is over.
+/// The deposit is returned to the creator when the poll
+/// reached quorum, otherwise it stays with the contract
+/// Executor: anyone
+pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
+    let mut poll: Poll =
+        read_poll(deps.storage, poll_id).map_err(|_| ContractError::PollNotFound {})?;
+    if poll.status != PollStatus::InProgress {
+        return Err(ContractError::PollNotInProgress {});
+    }
+
+    if env.block.height <= poll.end_height {
+        return Err(ContractError::PollVotingPeriod {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let tallied_weight = poll.yes_votes + poll.no_votes;
+    let quorum = if state.total_staked.is_zero() {
+        Decimal256::zero()
+    } else {
+        Decimal256::from_ratio(tallied_weight, state.total_staked)
+    };
+
+    let mut passed = false;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !tallied_weight.is_zero() && quorum >= config.quorum {
+        passed = Decimal256::from_ratio(poll.yes_votes, tallied_weight) > config.threshold;
+
+        if !poll.deposit_amount.is_zero() {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps
+                    .api
+                    .addr_humanize(&config.governance_token)?
+                    .to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: deps.api.addr_humanize(&poll.creator)?.to_string(),
+                    amount: poll.deposit_amount.into(),
+                })?,
+            }));
+        }
+    }
+
+    state.total_deposit = state.total_deposit - poll.deposit_amount;
+    store_state(deps.storage, &state)?;
+
+    poll.status = if passed {
+        PollStatus::Passed
+    } else {
+        PollStatus::Rejected
+    };
+    poll.total_balance_at_end_poll = Some(state.total_staked);
+    store_poll(deps.storage, &poll, Some(&PollStatus::InProgress))?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "end_poll"),
+        attr("poll_id", poll_id.to_string()),
+        attr("quorum", quorum.to_string()),
+        attr("tallied_weight", tallied_weight),
+        attr("passed", passed.to_string()),
+    ]))
+}
+
+/// Execute the messages of a passed poll once
+/// its timelock period is over
+/// Executor: anyone
+pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
+    let mut poll: Poll =
+        read_poll(deps.storage, poll_id).map_err(|_| ContractError::PollNotFound {})?;
+    if poll.status != PollStatus::Passed {
+        return Err(ContractError::PollNotPassed {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    if env.block.height <= poll.end_height + config.timelock_period {
+        return Err(ContractError::TimelockNotExpired {});
+    }
+
+    let messages = poll
+        .execute_data
+        .iter()
+        .flatten()
+        .map(|data| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&data.contract)?.to_string(),
+                funds: vec![],
+                msg: data.msg.clone(),
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    poll.status = PollStatus::Executed;
+    store_poll(deps.storage, &poll, Some(&PollStatus::Passed))?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "execute_poll"),
+        attr("poll_id", poll_id.to_string()),
+    ]))
+}
+
+pub fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
+    let poll: Poll = read_poll(deps.storage, poll_id)?;
+    poll_to_response(deps, poll)
+}
+
+pub fn query_polls(
+    deps: Deps,
+    filter: Option<PollStatus>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PollsResponse> {
+    let polls = read_polls(deps.storage, filter, start_after, limit)?
+        .into_iter()
+        .map(|poll| poll_to_response(deps, poll))
+        .collect::<StdResult<Vec<PollResponse>>>()?;
+
+    Ok(PollsResponse { polls })
+}
+
+pub fn query_voters(
+    deps: Deps,
+    poll_id: u64,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<VotersResponse> {
+    let start_after = match start_after {
+        Some(addr) => Some(deps.api.addr_canonicalize(addr.as_str())?),
+        None => None,
+    };
+
+    Ok(VotersResponse {
+        voters: read_poll_voters(deps, poll_id, start_after, limit)?,
+    })
+}
+
+fn poll_to_response(deps: Deps, poll: Poll) -> StdResult<PollResponse> {
+    let execute_msgs = match poll.execute_data {
+        Some(execute_data) => Some(
+            execute_data
+                .into_iter()
+                .map(|data| {
+                    Ok(PollExecuteMsg {
+                        order: data.order,
+                        contract: deps.api.addr_humanize(&data.contract)?.to_string(),
+                        msg: data.msg,
+                    })
+                })
+                .collect::<StdResult<Vec<PollExecuteMsg>>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(PollResponse {
+        id: poll.id,
+        creator: deps.api.addr_humanize(&poll.creator)?.to_string(),
+        status: poll.status,
+        end_height: poll.end_height,
+        title: poll.title,
+        description: poll.description,
+        deposit_amount: poll.deposit_amount,
+        execute_msgs,
+        yes_votes: poll.yes_votes,
+        no_votes: poll.no_votes,
+        total_balance_at_end_poll: poll.total_balance_at_end_poll,
+    })
+}
@@ -0,0 +1,127 @@
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_poll, read_staker, read_state, remove_staker, store_staker, store_state,
+    Config, Staker, State,
+};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{
+    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, MessageInfo, Response, StdResult, Storage,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::gov::{PollStatus, StakerResponse};
+
+/// Stake governance tokens to gain voting power
+/// Executor: governance token contract
+pub fn stake_voting_tokens(
+    deps: DepsMut,
+    sender: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let sender_raw = deps.api.addr_canonicalize(sender.as_str())?;
+    let mut staker: Staker = read_staker(deps.storage, &sender_raw);
+    let mut state: State = read_state(deps.storage)?;
+
+    staker.balance += amount;
+    state.total_staked += amount;
+
+    store_staker(deps.storage, &sender_raw, &staker)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "staking"),
+        attr("sender", sender),
+        attr("amount", amount),
+    ]))
+}
+
+/// Withdraw staked tokens that are not locked in
+/// polls still in progress
+/// Executor: staker
+pub fn withdraw_voting_tokens(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut staker: Staker = read_staker(deps.storage, &sender_raw);
+    if staker.balance.is_zero() {
+        return Err(ContractError::NothingStaked {});
+    }
+
+    // votes on finished polls no longer lock any tokens
+    release_locked_balance(deps.storage, &mut staker)?;
+
+    let locked_amount = staker
+        .locked_balance
+        .iter()
+        .map(|(_, voter_info)| voter_info.balance)
+        .max()
+        .unwrap_or_else(Uint256::zero);
+    let withdrawable = staker.balance - locked_amount;
+
+    let amount = amount.unwrap_or(withdrawable);
+    if amount.is_zero() || amount > withdrawable {
+        return Err(ContractError::InvalidWithdrawAmount {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    state.total_staked = state.total_staked - amount;
+    staker.balance = staker.balance - amount;
+
+    if staker.balance.is_zero() {
+        remove_staker(deps.storage, &sender_raw);
+    } else {
+        store_staker(deps.storage, &sender_raw, &staker)?;
+    }
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.governance_token)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "withdraw"),
+            attr("recipient", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+/// Drop the votes on polls that are no longer in progress
+pub fn release_locked_balance(storage: &dyn Storage, staker: &mut Staker) -> StdResult<()> {
+    let mut locked_balance = vec![];
+    for (poll_id, voter_info) in staker.locked_balance.drain(..) {
+        if read_poll(storage, poll_id)?.status == PollStatus::InProgress {
+            locked_balance.push((poll_id, voter_info));
+        }
+    }
+
+    staker.locked_balance = locked_balance;
+    Ok(())
+}
+
+pub fn query_staker(deps: Deps, address: Addr) -> StdResult<StakerResponse> {
+    let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+    let mut staker: Staker = read_staker(deps.storage, &address_raw);
+    release_locked_balance(deps.storage, &mut staker)?;
+
+    Ok(StakerResponse {
+        balance: staker.balance,
+        locked_balance: staker.locked_balance,
+    })
+}
@@ -0,0 +1,220 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{Binary, CanonicalAddr, Deps, Order, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::gov::{PollStatus, VoterInfo, VotersResponseElem};
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_STATE: &[u8] = b"state";
+
+static PREFIX_POLL: &[u8] = b"poll";
+static PREFIX_POLL_INDEXER: &[u8] = b"poll_indexer";
+static PREFIX_POLL_VOTER: &[u8] = b"poll_voter";
+static PREFIX_STAKER: &[u8] = b"staker";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub governance_token: CanonicalAddr,
+    pub quorum: Decimal256,
+    pub threshold: Decimal256,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub proposal_deposit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub poll_count: u64,
+    pub total_staked: Uint256,
+    pub total_deposit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Staker {
+    pub balance: Uint256,
+    pub locked_balance: Vec<(u64, VoterInfo)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecuteData {
+    pub order: u64,
+    pub contract: CanonicalAddr,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Poll {
+    pub id: u64,
+    pub creator: CanonicalAddr,
+    pub status: PollStatus,
+    pub yes_votes: Uint256,
+    pub no_votes: Uint256,
+    pub end_height: u64,
+    pub title: String,
+    pub description: String,
+    pub execute_data: Option<Vec<ExecuteData>>,
+    pub deposit_amount: Uint256,
+    pub total_balance_at_end_poll: Option<Uint256>,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_state(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
+    singleton(storage, KEY_STATE).save(state)
+}
+
+pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
+    singleton_read(storage, KEY_STATE).load()
+}
+
+pub fn store_staker(
+    storage: &mut dyn Storage,
+    staker_raw: &CanonicalAddr,
+    staker: &Staker,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_STAKER).save(staker_raw.as_slice(), staker)
+}
+
+pub fn read_staker(storage: &dyn Storage, staker_raw: &CanonicalAddr) -> Staker {
+    ReadonlyBucket::new(storage, PREFIX_STAKER)
+        .load(staker_raw.as_slice())
+        .unwrap_or_default()
+}
+
+pub fn remove_staker(storage: &mut dyn Storage, staker_raw: &CanonicalAddr) {
+    let mut staker_bucket: Bucket<Staker> = Bucket::new(storage, PREFIX_STAKER);
+    staker_bucket.remove(staker_raw.as_slice())
+}
+
+/// Store the poll and keep the status index in sync
+pub fn store_poll(
+    storage: &mut dyn Storage,
+    poll: &Poll,
+    prev_status: Option<&PollStatus>,
+) -> StdResult<()> {
+    if let Some(prev_status) = prev_status {
+        let mut indexer: Bucket<bool> = Bucket::multilevel(
+            storage,
+            &[PREFIX_POLL_INDEXER, prev_status.to_string().as_bytes()],
+        );
+        indexer.remove(&poll.id.to_be_bytes());
+    }
+
+    let mut indexer: Bucket<bool> = Bucket::multilevel(
+        storage,
+        &[PREFIX_POLL_INDEXER, poll.status.to_string().as_bytes()],
+    );
+    indexer.save(&poll.id.to_be_bytes(), &true)?;
+
+    Bucket::new(storage, PREFIX_POLL).save(&poll.id.to_be_bytes(), poll)
+}
+
+pub fn read_poll(storage: &dyn Storage, poll_id: u64) -> StdResult<Poll> {
+    ReadonlyBucket::new(storage, PREFIX_POLL).load(&poll_id.to_be_bytes())
+}
+
+pub fn store_poll_voter(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+    voter_raw: &CanonicalAddr,
+    voter_info: &VoterInfo,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
+        .save(voter_raw.as_slice(), voter_info)
+}
+
+pub fn read_poll_voter(
+    storage: &dyn Storage,
+    poll_id: u64,
+    voter_raw: &CanonicalAddr,
+) -> Option<VoterInfo> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()])
+        .may_load(voter_raw.as_slice())
+        .unwrap_or(None)
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_polls(
+    storage: &dyn Storage,
+    filter: Option<PollStatus>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Poll>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| (id + 1).to_be_bytes().to_vec());
+
+    match filter {
+        Some(status) => {
+            let indexer: ReadonlyBucket<bool> = ReadonlyBucket::multilevel(
+                storage,
+                &[PREFIX_POLL_INDEXER, status.to_string().as_bytes()],
+            );
+            indexer
+                .range(start.as_deref(), None, Order::Ascending)
+                .take(limit)
+                .map(|elem| {
+                    let (k, _) = elem?;
+                    let mut id = [0u8; 8];
+                    id.copy_from_slice(&k);
+                    read_poll(storage, u64::from_be_bytes(id))
+                })
+                .collect()
+        }
+        None => {
+            let polls: ReadonlyBucket<Poll> = ReadonlyBucket::new(storage, PREFIX_POLL);
+            polls
+                .range(start.as_deref(), None, Order::Ascending)
+                .take(limit)
+                .map(|elem| {
+                    let (_, poll) = elem?;
+                    Ok(poll)
+                })
+                .collect()
+        }
+    }
+}
+
+pub fn read_poll_voters(
+    deps: Deps,
+    poll_id: u64,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<VotersResponseElem>> {
+    let voters: ReadonlyBucket<VoterInfo> =
+        ReadonlyBucket::multilevel(deps.storage, &[PREFIX_POLL_VOTER, &poll_id.to_be_bytes()]);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    voters
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, voter_info) = elem?;
+            Ok(VotersResponseElem {
+                voter: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                vote: voter_info.vote,
+                balance: voter_info.balance,
+            })
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
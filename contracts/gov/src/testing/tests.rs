@@ -0,0 +1,687 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, CosmosMsg, Env, OwnedDeps, StdError, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use moneymarket::gov::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
+    PollStatus, PollsResponse, QueryMsg, StakerResponse, StateResponse, VoteOption, VoterInfo,
+    VotersResponse, VotersResponseElem,
+};
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
+
+const VOTING_TOKEN: &str = "voting_token";
+const VOTING_PERIOD: u64 = 100;
+const TIMELOCK_PERIOD: u64 = 50;
+const PROPOSAL_DEPOSIT: u128 = 1000;
+
+type MockDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        governance_token: VOTING_TOKEN.to_string(),
+        quorum: Decimal256::percent(30),
+        threshold: Decimal256::percent(50),
+        voting_period: VOTING_PERIOD,
+        timelock_period: TIMELOCK_PERIOD,
+        proposal_deposit: Uint256::from(PROPOSAL_DEPOSIT),
+    }
+}
+
+fn setup() -> MockDeps {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    deps
+}
+
+fn env_at(height: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height = height;
+    env
+}
+
+fn receive(
+    deps: &mut MockDeps,
+    env: Env,
+    sender: &str,
+    amount: u128,
+    msg: Cw20HookMsg,
+) -> Result<cosmwasm_std::Response, ContractError> {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(&msg).unwrap(),
+    });
+    execute(deps.as_mut(), env, mock_info(VOTING_TOKEN, &[]), msg)
+}
+
+fn stake(deps: &mut MockDeps, sender: &str, amount: u128) {
+    receive(
+        deps,
+        mock_env(),
+        sender,
+        amount,
+        Cw20HookMsg::StakeVotingTokens {},
+    )
+    .unwrap();
+}
+
+fn create_poll_msg(execute_msgs: Option<Vec<PollExecuteMsg>>) -> Cw20HookMsg {
+    Cw20HookMsg::CreatePoll {
+        title: "lower max ltv".to_string(),
+        description: "lower the bLuna max ltv to 40%".to_string(),
+        execute_msgs,
+    }
+}
+
+fn whitelist_msg() -> PollExecuteMsg {
+    PollExecuteMsg {
+        order: 1u64,
+        contract: "overseer".to_string(),
+        msg: to_binary(&OverseerExecuteMsg::UpdateWhitelist {
+            collateral_token: "bluna".to_string(),
+            custody_contract: None,
+            max_ltv: Some(Decimal256::percent(40)),
+            max_collateral: None,
+            unlock_delay: None,
+            unlock_delay_threshold: None,
+        })
+        .unwrap(),
+    }
+}
+
+fn vote(
+    deps: &mut MockDeps,
+    env: Env,
+    voter: &str,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: u128,
+) -> Result<cosmwasm_std::Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(voter, &[]),
+        ExecuteMsg::CastVote {
+            poll_id,
+            vote,
+            amount: Uint256::from(amount),
+        },
+    )
+}
+
+fn query_poll(deps: &MockDeps, poll_id: u64) -> PollResponse {
+    from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id }).unwrap()).unwrap()
+}
+
+#[test]
+fn proper_initialization() {
+    let deps = setup();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            governance_token: VOTING_TOKEN.to_string(),
+            quorum: Decimal256::percent(30),
+            threshold: Decimal256::percent(50),
+            voting_period: VOTING_PERIOD,
+            timelock_period: TIMELOCK_PERIOD,
+            proposal_deposit: Uint256::from(PROPOSAL_DEPOSIT),
+        }
+    );
+
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
+    assert_eq!(
+        state,
+        StateResponse {
+            poll_count: 0,
+            total_staked: Uint256::zero(),
+            total_deposit: Uint256::zero(),
+        }
+    );
+
+    // quorum above one is rejected
+    let mut deps = mock_dependencies(&[]);
+    let mut msg = init_msg();
+    msg.quorum = Decimal256::percent(101);
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "quorum must be 0 to 1"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn update_config() {
+    let mut deps = setup();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        quorum: None,
+        threshold: Some(Decimal256::percent(60)),
+        voting_period: Some(200u64),
+        timelock_period: None,
+        proposal_deposit: None,
+    };
+
+    // only the contract itself, through a poll, can update the config
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "update_config")]);
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.quorum, Decimal256::percent(30));
+    assert_eq!(config.threshold, Decimal256::percent(60));
+    assert_eq!(config.voting_period, 200u64);
+}
+
+#[test]
+fn create_poll() {
+    let mut deps = setup();
+
+    // only the governance token is accepted
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("other_token", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(PROPOSAL_DEPOSIT),
+            msg: to_binary(&create_poll_msg(None)).unwrap(),
+        }),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = receive(
+        &mut deps,
+        mock_env(),
+        "addr0000",
+        PROPOSAL_DEPOSIT - 1,
+        create_poll_msg(None),
+    );
+    match res {
+        Err(ContractError::InsufficientProposalDeposit(amount)) => {
+            assert_eq!(amount, PROPOSAL_DEPOSIT)
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = receive(
+        &mut deps,
+        mock_env(),
+        "addr0000",
+        PROPOSAL_DEPOSIT,
+        Cw20HookMsg::CreatePoll {
+            title: "abc".to_string(),
+            description: "lower the bLuna max ltv to 40%".to_string(),
+            execute_msgs: None,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidTitle {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let env = mock_env();
+    let res = receive(
+        &mut deps,
+        env.clone(),
+        "addr0000",
+        PROPOSAL_DEPOSIT,
+        create_poll_msg(Some(vec![whitelist_msg()])),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_poll"),
+            attr("creator", "addr0000"),
+            attr("poll_id", "1"),
+            attr("end_height", (env.block.height + VOTING_PERIOD).to_string()),
+        ]
+    );
+
+    assert_eq!(
+        query_poll(&deps, 1u64),
+        PollResponse {
+            id: 1u64,
+            creator: "addr0000".to_string(),
+            status: PollStatus::InProgress,
+            end_height: env.block.height + VOTING_PERIOD,
+            title: "lower max ltv".to_string(),
+            description: "lower the bLuna max ltv to 40%".to_string(),
+            deposit_amount: Uint256::from(PROPOSAL_DEPOSIT),
+            execute_msgs: Some(vec![whitelist_msg()]),
+            yes_votes: Uint256::zero(),
+            no_votes: Uint256::zero(),
+            total_balance_at_end_poll: None,
+        }
+    );
+
+    let polls: PollsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Polls {
+                filter: Some(PollStatus::InProgress),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(polls.polls.len(), 1);
+
+    let polls: PollsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Polls {
+                filter: Some(PollStatus::Passed),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(polls.polls, vec![]);
+}
+
+#[test]
+fn pass_and_execute_poll() {
+    let mut deps = setup();
+    stake(&mut deps, "voter0000", 600);
+    stake(&mut deps, "voter0001", 400);
+
+    let env = mock_env();
+    let poll_end_height = env.block.height + VOTING_PERIOD;
+    receive(
+        &mut deps,
+        env.clone(),
+        "creator",
+        PROPOSAL_DEPOSIT,
+        create_poll_msg(Some(vec![whitelist_msg()])),
+    )
+    .unwrap();
+
+    let res = vote(
+        &mut deps,
+        env.clone(),
+        "voter0000",
+        1u64,
+        VoteOption::Yes,
+        600,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "600"),
+            attr("voter", "voter0000"),
+            attr("vote_option", "yes"),
+        ]
+    );
+    vote(
+        &mut deps,
+        env.clone(),
+        "voter0001",
+        1u64,
+        VoteOption::No,
+        100,
+    )
+    .unwrap();
+
+    let res = vote(
+        &mut deps,
+        env.clone(),
+        "voter0001",
+        1u64,
+        VoteOption::No,
+        100,
+    );
+    match res {
+        Err(ContractError::AlreadyVoted {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let voters: VotersResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Voters {
+                poll_id: 1u64,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        voters.voters,
+        vec![
+            VotersResponseElem {
+                voter: "voter0000".to_string(),
+                vote: VoteOption::Yes,
+                balance: Uint256::from(600u64),
+            },
+            VotersResponseElem {
+                voter: "voter0001".to_string(),
+                vote: VoteOption::No,
+                balance: Uint256::from(100u64),
+            },
+        ]
+    );
+
+    // the poll cannot end while voting is open
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::EndPoll { poll_id: 1u64 },
+    );
+    match res {
+        Err(ContractError::PollVotingPeriod {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = vote(
+        &mut deps,
+        env_at(poll_end_height + 1),
+        "voter0001",
+        1u64,
+        VoteOption::No,
+        100,
+    );
+    match res {
+        Err(ContractError::PollVotingPeriodExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // 700 of 1000 voted and 600 of 700 voted yes;
+    // quorum is reached so the deposit goes back to the creator
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height + 1),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::EndPoll { poll_id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "creator".to_string(),
+                amount: Uint128::from(PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("quorum", "0.7"),
+            attr("tallied_weight", "700"),
+            attr("passed", "true"),
+        ]
+    );
+
+    let poll = query_poll(&deps, 1u64);
+    assert_eq!(poll.status, PollStatus::Passed);
+    assert_eq!(poll.total_balance_at_end_poll, Some(Uint256::from(1000u64)));
+
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
+    assert_eq!(state.total_deposit, Uint256::zero());
+
+    // the passed poll waits for the timelock period
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height + TIMELOCK_PERIOD),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1u64 },
+    );
+    match res {
+        Err(ContractError::TimelockNotExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height + TIMELOCK_PERIOD + 1),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "overseer".to_string(),
+            funds: vec![],
+            msg: whitelist_msg().msg,
+        }))]
+    );
+    assert_eq!(query_poll(&deps, 1u64).status, PollStatus::Executed);
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height + TIMELOCK_PERIOD + 1),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1u64 },
+    );
+    match res {
+        Err(ContractError::PollNotPassed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn reject_poll_without_quorum() {
+    let mut deps = setup();
+    stake(&mut deps, "voter0000", 200);
+    stake(&mut deps, "voter0001", 800);
+
+    let env = mock_env();
+    let poll_end_height = env.block.height + VOTING_PERIOD;
+    receive(
+        &mut deps,
+        env.clone(),
+        "creator",
+        PROPOSAL_DEPOSIT,
+        create_poll_msg(Some(vec![whitelist_msg()])),
+    )
+    .unwrap();
+    vote(&mut deps, env, "voter0000", 1u64, VoteOption::Yes, 200).unwrap();
+
+    // 20% of the staked tokens voted, below the 30% quorum;
+    // the deposit stays with the contract
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height + 1),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::EndPoll { poll_id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("quorum", "0.2"),
+            attr("tallied_weight", "200"),
+            attr("passed", "false"),
+        ]
+    );
+    assert_eq!(query_poll(&deps, 1u64).status, PollStatus::Rejected);
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(poll_end_height + TIMELOCK_PERIOD + 1),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::ExecutePoll { poll_id: 1u64 },
+    );
+    match res {
+        Err(ContractError::PollNotPassed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn withdraw_voting_tokens() {
+    let mut deps = setup();
+    stake(&mut deps, "voter0000", 1000);
+
+    let env = mock_env();
+    let poll_end_height = env.block.height + VOTING_PERIOD;
+    receive(
+        &mut deps,
+        env.clone(),
+        "creator",
+        PROPOSAL_DEPOSIT,
+        create_poll_msg(None),
+    )
+    .unwrap();
+    vote(&mut deps, env, "voter0000", 1u64, VoteOption::Yes, 700).unwrap();
+
+    let staker: StakerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Staker {
+                address: "voter0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        staker,
+        StakerResponse {
+            balance: Uint256::from(1000u64),
+            locked_balance: vec![(
+                1u64,
+                VoterInfo {
+                    vote: VoteOption::Yes,
+                    balance: Uint256::from(700u64),
+                }
+            )],
+        }
+    );
+
+    // the voted tokens are locked while the poll is in progress
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("voter0000", &[]),
+        ExecuteMsg::WithdrawVotingTokens {
+            amount: Some(Uint256::from(301u64)),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidWithdrawAmount {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("voter0000", &[]),
+        ExecuteMsg::WithdrawVotingTokens { amount: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "voter0000".to_string(),
+                amount: Uint128::from(300u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // once the poll ends the rest can be withdrawn
+    execute(
+        deps.as_mut(),
+        env_at(poll_end_height + 1),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::EndPoll { poll_id: 1u64 },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("voter0000", &[]),
+        ExecuteMsg::WithdrawVotingTokens { amount: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("recipient", "voter0000"),
+            attr("amount", "700"),
+        ]
+    );
+
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
+    assert_eq!(state.total_staked, Uint256::zero());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("voter0000", &[]),
+        ExecuteMsg::WithdrawVotingTokens { amount: None },
+    );
+    match res {
+        Err(ContractError::NothingStaked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -17,13 +17,19 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
+    if let Some(optimal_utilization) = msg.optimal_utilization {
+        assert_optimal_utilization(optimal_utilization)?;
+    }
+
     store_config(
         deps.storage,
         &Config {
             owner: deps.api.addr_canonicalize(&msg.owner)?,
             base_rate: msg.base_rate,
             interest_multiplier: msg.interest_multiplier,
+            optimal_utilization: msg.optimal_utilization,
+            slope_2: msg.slope_2,
         },
     )?;
 
@@ -42,6 +48,8 @@ pub fn execute(
             owner,
             base_rate,
             interest_multiplier,
+            optimal_utilization,
+            slope_2,
         } => {
             let api = deps.api;
             update_config(
@@ -50,6 +58,8 @@ pub fn execute(
                 optional_addr_validate(api, owner)?,
                 base_rate,
                 interest_multiplier,
+                optimal_utilization,
+                slope_2,
             )
         }
     }
@@ -61,6 +71,8 @@ pub fn update_config(
     owner: Option<Addr>,
     base_rate: Option<Decimal256>,
     interest_multiplier: Option<Decimal256>,
+    optimal_utilization: Option<Decimal256>,
+    slope_2: Option<Decimal256>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -79,10 +91,27 @@ pub fn update_config(
         config.interest_multiplier = interest_multiplier;
     }
 
+    if let Some(optimal_utilization) = optimal_utilization {
+        assert_optimal_utilization(optimal_utilization)?;
+        config.optimal_utilization = Some(optimal_utilization);
+    }
+
+    if let Some(slope_2) = slope_2 {
+        config.slope_2 = Some(slope_2);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::default())
 }
 
+fn assert_optimal_utilization(optimal_utilization: Decimal256) -> Result<(), ContractError> {
+    if optimal_utilization.is_zero() || optimal_utilization > Decimal256::one() {
+        return Err(ContractError::InvalidOptimalUtilization {});
+    }
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -91,6 +120,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             market_balance,
             total_liabilities,
             total_reserves,
+            ..
         } => to_binary(&query_borrow_rate(
             deps,
             market_balance,
@@ -106,6 +136,8 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         base_rate: state.base_rate,
         interest_multiplier: state.interest_multiplier,
+        optimal_utilization: state.optimal_utilization,
+        slope_2: state.slope_2,
     };
 
     Ok(resp)
@@ -130,6 +162,20 @@ fn query_borrow_rate(
     };
 
     Ok(BorrowRateResponse {
-        rate: utilization_ratio * config.interest_multiplier + config.base_rate,
+        rate: compute_borrow_rate(&config, utilization_ratio),
     })
 }
+
+/// Two-slope rate curve; the rate grows by `interest_multiplier`
+/// up to the optimal utilization and by `slope_2` beyond it
+fn compute_borrow_rate(config: &Config, utilization_ratio: Decimal256) -> Decimal256 {
+    match config.optimal_utilization {
+        Some(optimal_utilization) if utilization_ratio > optimal_utilization => {
+            let slope_2 = config.slope_2.unwrap_or(config.interest_multiplier);
+            config.base_rate
+                + optimal_utilization * config.interest_multiplier
+                + (utilization_ratio - optimal_utilization) * slope_2
+        }
+        _ => utilization_ratio * config.interest_multiplier + config.base_rate,
+    }
+}
@@ -11,4 +11,7 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Optimal utilization must be greater than 0 and at most 1")]
+    InvalidOptimalUtilization {},
 }
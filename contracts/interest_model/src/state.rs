@@ -12,6 +12,8 @@ pub struct Config {
     pub owner: CanonicalAddr,
     pub base_rate: Decimal256,
     pub interest_multiplier: Decimal256,
+    pub optimal_utilization: Option<Decimal256>,
+    pub slope_2: Option<Decimal256>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -15,6 +15,8 @@ fn proper_initialization() {
         owner: "owner0000".to_string(),
         base_rate: Decimal256::percent(10),
         interest_multiplier: Decimal256::percent(10),
+        optimal_utilization: None,
+        slope_2: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -34,6 +36,8 @@ fn proper_initialization() {
         market_balance: Uint256::from(1000000u128),
         total_liabilities: Decimal256::from_uint256(500000u128),
         total_reserves: Decimal256::from_uint256(100000u128),
+        stable_denom: None,
+        utilization: None,
     };
     let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
     let value: BorrowRateResponse = from_binary(&res).unwrap();
@@ -45,6 +49,8 @@ fn proper_initialization() {
         market_balance: Uint256::zero(),
         total_liabilities: Decimal256::zero(),
         total_reserves: Decimal256::zero(),
+        stable_denom: None,
+        utilization: None,
     };
     let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
     let value: BorrowRateResponse = from_binary(&res).unwrap();
@@ -59,6 +65,8 @@ fn update_config() {
         owner: "owner0000".to_string(),
         base_rate: Decimal256::percent(10),
         interest_multiplier: Decimal256::percent(10),
+        optimal_utilization: None,
+        slope_2: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -70,6 +78,8 @@ fn update_config() {
         owner: Some("owner0001".to_string()),
         base_rate: None,
         interest_multiplier: None,
+        optimal_utilization: None,
+        slope_2: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -88,6 +98,8 @@ fn update_config() {
         owner: None,
         base_rate: Some(Decimal256::percent(1)),
         interest_multiplier: Some(Decimal256::percent(1)),
+        optimal_utilization: None,
+        slope_2: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -96,3 +108,80 @@ fn update_config() {
         _ => panic!("Must return unauthorized error"),
     }
 }
+
+#[test]
+fn kinked_borrow_rate() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_rate: Decimal256::percent(2),
+        interest_multiplier: Decimal256::percent(10),
+        optimal_utilization: Some(Decimal256::percent(101)),
+        slope_2: Some(Decimal256::percent(100)),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InvalidOptimalUtilization {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = InstantiateMsg {
+        optimal_utilization: Some(Decimal256::percent(80)),
+        ..msg
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let borrow_rate = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, total_liabilities: u128| {
+        let query_msg = QueryMsg::BorrowRate {
+            market_balance: Uint256::from(1000000u128 - total_liabilities),
+            total_liabilities: Decimal256::from_uint256(total_liabilities),
+            total_reserves: Decimal256::zero(),
+            stable_denom: None,
+            utilization: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+        let value: BorrowRateResponse = from_binary(&res).unwrap();
+        value.rate
+    };
+
+    // below the kink; rate = 2% + 50% * 10%
+    assert_eq!("0.07", &borrow_rate(&deps, 500000u128).to_string());
+    // at the kink; rate = 2% + 80% * 10%
+    assert_eq!("0.1", &borrow_rate(&deps, 800000u128).to_string());
+    // above the kink; rate = 2% + 80% * 10% + 10% * 100%
+    assert_eq!("0.2", &borrow_rate(&deps, 900000u128).to_string());
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        base_rate: None,
+        interest_multiplier: None,
+        optimal_utilization: Some(Decimal256::zero()),
+        slope_2: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidOptimalUtilization {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        base_rate: None,
+        interest_multiplier: None,
+        optimal_utilization: Some(Decimal256::percent(50)),
+        slope_2: Some(Decimal256::percent(50)),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let value: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Decimal256::percent(50)), value.optimal_utilization);
+    assert_eq!(Some(Decimal256::percent(50)), value.slope_2);
+
+    // rate = 2% + 50% * 10% + 40% * 50%
+    assert_eq!("0.27", &borrow_rate(&deps, 900000u128).to_string());
+}
@@ -107,6 +107,9 @@ pub fn retract_bid(
         ]))
 }
 
+/// Sell the collateral to the liquidator's bid; native collaterals
+/// are sent net of the transfer tax
+#[allow(clippy::too_many_arguments)]
 pub fn execute_bid(
     deps: DepsMut,
     env: Env,
@@ -115,22 +118,30 @@ pub fn execute_bid(
     fee_address: Addr,
     collateral_token: Addr,
     amount: Uint256,
+    native: bool,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
     let bidder_raw = deps.api.addr_canonicalize(liquidator.as_str())?;
     let bid: Bid = read_bid(deps.storage, &bidder_raw, &collateral_token_raw)?;
 
+    // stale prices are accepted only when configured, the same
+    // way the overseer starts liquidations during feeder outages
+    let time_constraints = if config.liquidate_on_stale_price {
+        None
+    } else {
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        })
+    };
     let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
     let price: PriceResponse = query_price(
         deps.as_ref(),
         oracle_contract,
         collateral_token.to_string(),
         config.stable_denom.clone(),
-        Some(TimeConstraints {
-            block_time: env.block.time.seconds(),
-            valid_timeframe: config.price_timeframe,
-        }),
+        time_constraints,
     )?;
 
     let collateral_value = amount * price.rate;
@@ -160,7 +171,18 @@ pub fn execute_bid(
     let bid_fee = required_stable * config.bid_fee;
     let repay_amount = required_stable - bid_fee;
 
-    let mut messages: Vec<CosmosMsg> = vec![
+    let collateral_msg = if native {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: liquidator.to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: collateral_token.to_string(),
+                    amount: amount.into(),
+                },
+            )?],
+        })
+    } else {
         CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: collateral_token.to_string(),
             funds: vec![],
@@ -168,7 +190,11 @@ pub fn execute_bid(
                 recipient: liquidator.to_string(),
                 amount: amount.into(),
             })?,
-        }),
+        })
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![
+        collateral_msg,
         CosmosMsg::Bank(BankMsg::Send {
             to_address: repay_address.to_string(),
             amount: vec![deduct_tax(
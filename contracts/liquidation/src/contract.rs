@@ -9,7 +9,8 @@ use crate::state::{read_config, store_config, Config};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    attr, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult,
 };
 use cw20::Cw20ReceiveMsg;
 use moneymarket::common::optional_addr_validate;
@@ -37,6 +38,9 @@ pub fn instantiate(
             max_premium_rate: msg.max_premium_rate,
             liquidation_threshold: msg.liquidation_threshold,
             price_timeframe: msg.price_timeframe,
+            close_factor: None,
+            full_liquidation_ratio: None,
+            liquidate_on_stale_price: false,
         },
     )?;
 
@@ -61,6 +65,9 @@ pub fn execute(
             max_premium_rate,
             liquidation_threshold,
             price_timeframe,
+            close_factor,
+            full_liquidation_ratio,
+            liquidate_on_stale_price,
         } => {
             let api = deps.api;
             update_config(
@@ -74,6 +81,9 @@ pub fn execute(
                 max_premium_rate,
                 liquidation_threshold,
                 price_timeframe,
+                close_factor,
+                full_liquidation_ratio,
+                liquidate_on_stale_price,
             )
         }
         ExecuteMsg::SubmitBid {
@@ -95,6 +105,11 @@ pub fn execute(
             let api = deps.api;
             retract_bid(deps, info, api.addr_validate(&collateral_token)?, amount)
         }
+        ExecuteMsg::ExecuteBid {
+            liquidator,
+            fee_address,
+            repay_address,
+        } => receive_native(deps, env, info, liquidator, fee_address, repay_address),
     }
 }
 
@@ -125,12 +140,48 @@ pub fn receive_cw20(
                 api.addr_validate(&fee_address)?,
                 api.addr_validate(&collateral_token)?,
                 cw20_msg.amount.into(),
+                false,
             )
         }
         _ => Err(ContractError::MissingExecuteBidHook {}),
     }
 }
 
+/// Native collaterals are identified by their denom
+/// the same way CW20 collaterals are by their token address
+pub fn receive_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    liquidator: String,
+    fee_address: Option<String>,
+    repay_address: Option<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if info.funds.len() != 1
+        || info.funds[0].amount.is_zero()
+        || info.funds[0].denom == config.stable_denom
+    {
+        return Err(ContractError::InvalidNativeCollateral {});
+    }
+
+    let collateral = &info.funds[0];
+    let repay_address = repay_address.unwrap_or_else(|| info.sender.to_string());
+    let fee_address = fee_address.unwrap_or_else(|| info.sender.to_string());
+
+    let api = deps.api;
+    execute_bid(
+        deps,
+        env,
+        api.addr_validate(&liquidator)?,
+        api.addr_validate(&repay_address)?,
+        api.addr_validate(&fee_address)?,
+        api.addr_validate(&collateral.denom)?,
+        collateral.amount.into(),
+        true,
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
@@ -143,6 +194,9 @@ pub fn update_config(
     max_premium_rate: Option<Decimal256>,
     liquidation_threshold: Option<Uint256>,
     price_timeframe: Option<u64>,
+    close_factor: Option<Decimal256>,
+    full_liquidation_ratio: Option<Decimal256>,
+    liquidate_on_stale_price: Option<bool>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -181,8 +235,20 @@ pub fn update_config(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(close_factor) = close_factor {
+        config.close_factor = Some(close_factor);
+    }
+
+    if let Some(full_liquidation_ratio) = full_liquidation_ratio {
+        config.full_liquidation_ratio = Some(full_liquidation_ratio);
+    }
+
+    if let Some(liquidate_on_stale_price) = liquidate_on_stale_price {
+        config.liquidate_on_stale_price = liquidate_on_stale_price;
+    }
+
     store_config(deps.storage, &config)?;
-    Ok(Response::default())
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -251,6 +317,9 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         max_premium_rate: config.max_premium_rate,
         liquidation_threshold: config.liquidation_threshold,
         price_timeframe: config.price_timeframe,
+        close_factor: config.close_factor,
+        full_liquidation_ratio: config.full_liquidation_ratio,
+        liquidate_on_stale_price: config.liquidate_on_stale_price,
     };
 
     Ok(resp)
@@ -269,6 +338,7 @@ fn query_liquidation_amount(
     if borrow_amount <= borrow_limit {
         return Ok(LiquidationAmountResponse {
             collaterals: vec![],
+            max_repay_amount: Uint256::zero(),
         });
     }
 
@@ -289,7 +359,10 @@ fn query_liquidation_amount(
     // else force liquidate all collaterals
     let expected_repay_amount = collaterals_value * fee_deductor;
     if expected_repay_amount <= borrow_amount {
-        return Ok(LiquidationAmountResponse { collaterals });
+        return Ok(LiquidationAmountResponse {
+            collaterals,
+            max_repay_amount: expected_repay_amount,
+        });
     }
 
     // When collaterals_value is smaller than liquidation_threshold,
@@ -298,13 +371,27 @@ fn query_liquidation_amount(
     let liquidation_ratio = if collaterals_value < config.liquidation_threshold {
         Decimal256::from_uint256(borrow_amount) / Decimal256::from_uint256(expected_repay_amount)
     } else {
-        Decimal256::from_uint256(borrow_amount - safe_borrow_amount)
-            / Decimal256::from_uint256(expected_repay_amount - safe_borrow_amount)
+        let liquidation_ratio = Decimal256::from_uint256(borrow_amount - safe_borrow_amount)
+            / Decimal256::from_uint256(expected_repay_amount - safe_borrow_amount);
+
+        // Mildly underwater loans repay at most close_factor of
+        // the loan per liquidation
+        match config.close_factor {
+            Some(close_factor) if !is_severely_underwater(&config, borrow_amount, borrow_limit) => {
+                std::cmp::min(
+                    liquidation_ratio,
+                    Decimal256::from_uint256(borrow_amount * close_factor)
+                        / Decimal256::from_uint256(expected_repay_amount),
+                )
+            }
+            _ => liquidation_ratio,
+        }
     };
 
     // Cap the liquidation_ratio to 1
     let liquidation_ratio = std::cmp::min(Decimal256::one(), liquidation_ratio);
     Ok(LiquidationAmountResponse {
+        max_repay_amount: expected_repay_amount * liquidation_ratio,
         collaterals: collaterals
             .iter()
             .zip(collateral_prices.iter())
@@ -319,3 +406,10 @@ fn query_liquidation_amount(
             .collect::<TokensHuman>(),
     })
 }
+
+fn is_severely_underwater(config: &Config, borrow_amount: Uint256, borrow_limit: Uint256) -> bool {
+    match config.full_liquidation_ratio {
+        Some(full_liquidation_ratio) => borrow_amount >= borrow_limit * full_liquidation_ratio,
+        None => false,
+    }
+}
@@ -24,6 +24,9 @@ pub enum ContractError {
     #[error("Invalid request: \"execute bid\" message not included in request")]
     MissingExecuteBidHook {},
 
+    #[error("Exactly one native collateral coin must be sent")]
+    InvalidNativeCollateral {},
+
     #[error("No bids with the specified information exist")]
     NoBidExists {},
 
@@ -22,6 +22,9 @@ pub struct Config {
     pub max_premium_rate: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
+    pub close_factor: Option<Decimal256>,
+    pub full_liquidation_ratio: Option<Decimal256>,
+    pub liquidate_on_stale_price: bool,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
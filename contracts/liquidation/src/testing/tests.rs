@@ -5,7 +5,7 @@ use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, SubMsg, Uint128, WasmMsg,
+    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use moneymarket::liquidation::{
@@ -48,6 +48,9 @@ fn proper_initialization() {
             max_premium_rate: Decimal256::percent(5),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
+            close_factor: None,
+            full_liquidation_ratio: None,
+            liquidate_on_stale_price: false,
         }
     );
 }
@@ -81,6 +84,9 @@ fn update_config() {
         max_premium_rate: None,
         liquidation_threshold: None,
         price_timeframe: None,
+        close_factor: None,
+        full_liquidation_ratio: None,
+        liquidate_on_stale_price: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -100,6 +106,9 @@ fn update_config() {
             max_premium_rate: Decimal256::percent(5),
             liquidation_threshold: Uint256::from(100000000u64),
             price_timeframe: 60u64,
+            close_factor: None,
+            full_liquidation_ratio: None,
+            liquidate_on_stale_price: false,
         }
     );
 
@@ -114,6 +123,9 @@ fn update_config() {
         max_premium_rate: Some(Decimal256::percent(7)),
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(120u64),
+        close_factor: None,
+        full_liquidation_ratio: None,
+        liquidate_on_stale_price: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -133,6 +145,9 @@ fn update_config() {
             max_premium_rate: Decimal256::percent(7),
             liquidation_threshold: Uint256::from(150000000u64),
             price_timeframe: 120u64,
+            close_factor: None,
+            full_liquidation_ratio: None,
+            liquidate_on_stale_price: false,
         }
     );
 
@@ -147,6 +162,9 @@ fn update_config() {
         max_premium_rate: Some(Decimal256::percent(7)),
         liquidation_threshold: Some(Uint256::from(150000000u64)),
         price_timeframe: Some(100u64),
+        close_factor: None,
+        full_liquidation_ratio: None,
+        liquidate_on_stale_price: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -416,7 +434,7 @@ fn execute_bid() {
         })
         .unwrap(),
     });
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     assert_eq!(
         res.messages,
         vec![
@@ -445,6 +463,155 @@ fn execute_bid() {
             })),
         ]
     );
+
+    // stale prices are rejected unless configured
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(61u64);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(10000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "addr0000".to_string(),
+            fee_address: None,
+            repay_address: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Price is too old")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let update_msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        max_premium_rate: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        close_factor: None,
+        full_liquidation_ratio: None,
+        liquidate_on_stale_price: Some(true),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        update_msg,
+    )
+    .unwrap();
+    execute(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn execute_bid_native() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+            (&"uluna".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(10),
+        bid_fee: Decimal256::percent(1),
+        max_premium_rate: Decimal256::percent(5),
+        liquidation_threshold: Uint256::from(100000000u64),
+        price_timeframe: 60u64,
+    };
+
+    let env = mock_env();
+    deps.querier.with_oracle_price(&[(
+        &("uluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::percent(50),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::SubmitBid {
+        collateral_token: "uluna".to_string(),
+        premium_rate: Decimal256::percent(1),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ExecuteBid {
+        liquidator: "addr0000".to_string(),
+        fee_address: Some("fee0000".to_string()),
+        repay_address: Some("repay0000".to_string()),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "custody0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        ),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::InvalidNativeCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // required_stable 495,000
+    // bid_fee         4,950
+    // repay_amount    490,050
+    let info = mock_info(
+        "custody0000",
+        &[Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(990099u128), // 1000000 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "repay0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(485198u128), // 490050 / (1 + tax_rate)
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "fee0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(4900u128), // 4950 / (1 + tax_rate)
+                }]
+            })),
+        ]
+    );
 }
 
 #[test]
@@ -484,6 +651,7 @@ fn query_liquidation_amount() {
         res,
         LiquidationAmountResponse {
             collaterals: vec![("token0000".to_string(), Uint256::from(1000000u64))],
+            max_repay_amount: Uint256::from(93109u64),
         }
     );
 
@@ -500,6 +668,7 @@ fn query_liquidation_amount() {
         res,
         LiquidationAmountResponse {
             collaterals: vec![],
+            max_repay_amount: Uint256::zero(),
         }
     );
 
@@ -530,6 +699,102 @@ fn query_liquidation_amount() {
                 ("token0001".to_string(), Uint256::from(716002u64)),
                 ("token0002".to_string(), Uint256::from(1074004u64)),
             ],
+            max_repay_amount: Uint256::from(999999u64),
+        }
+    );
+}
+
+#[test]
+fn query_liquidation_amount_close_factor() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        oracle_contract: "oracle0000".to_string(),
+        stable_denom: "uusd".to_string(),
+        safe_ratio: Decimal256::percent(80),
+        bid_fee: Decimal256::zero(),
+        max_premium_rate: Decimal256::zero(),
+        liquidation_threshold: Uint256::zero(),
+        price_timeframe: 60u64,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_msg = QueryMsg::LiquidationAmount {
+        borrow_amount: Uint256::from(950u64),
+        borrow_limit: Uint256::from(900u64),
+        collaterals: vec![("token0000".to_string(), Uint256::from(1000u64))],
+        collateral_prices: vec![Decimal256::one()],
+    };
+
+    // liquidation_ratio = (950 - 720) / (1000 - 720) = 0.821428571428571428
+    let res = query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(821u64))],
+            max_repay_amount: Uint256::from(821u64),
+        }
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        max_premium_rate: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        close_factor: Some(Decimal256::percent(50)),
+        full_liquidation_ratio: Some(Decimal256::percent(110)),
+        liquidate_on_stale_price: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // at most half of the loan is repaid
+    // liquidation_ratio = 950 * 0.5 / 1000
+    let res = query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(475u64))],
+            max_repay_amount: Uint256::from(475u64),
+        }
+    );
+
+    // 950 >= 900 * 1.05, the loan is severely underwater
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        stable_denom: None,
+        safe_ratio: None,
+        bid_fee: None,
+        max_premium_rate: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        close_factor: None,
+        full_liquidation_ratio: Some(Decimal256::percent(105)),
+        liquidate_on_stale_price: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let res: LiquidationAmountResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        res,
+        LiquidationAmountResponse {
+            collaterals: vec![("token0000".to_string(), Uint256::from(821u64))],
+            max_repay_amount: Uint256::from(821u64),
         }
     );
 }
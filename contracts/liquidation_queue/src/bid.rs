@@ -319,16 +319,23 @@ pub fn execute_liquidation(
         ));
     }
 
+    // stale prices are accepted only when configured, the same
+    // way the overseer starts liquidations during feeder outages
+    let time_constraints = if config.liquidate_on_stale_price {
+        None
+    } else {
+        Some(TimeConstraints {
+            block_time: env.block.time.seconds(),
+            valid_timeframe: config.price_timeframe,
+        })
+    };
     let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
     let price: PriceResponse = query_price(
         deps.as_ref(),
         oracle_contract,
         collateral_token.to_string(),
         config.stable_denom.clone(),
-        Some(TimeConstraints {
-            block_time: env.block.time.seconds(),
-            valid_timeframe: config.price_timeframe,
-        }),
+        time_constraints,
     )?;
 
     let mut remaining_collateral_to_liquidate = amount;
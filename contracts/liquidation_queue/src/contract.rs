@@ -14,7 +14,8 @@ use crate::state::{
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    attr, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult,
 };
 use cw20::Cw20ReceiveMsg;
 use moneymarket::liquidation_queue::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
@@ -41,6 +42,7 @@ pub fn instantiate(
             price_timeframe: msg.price_timeframe,
             waiting_period: msg.waiting_period,
             overseer: deps.api.addr_canonicalize(&msg.overseer)?,
+            liquidate_on_stale_price: false,
         },
     )?;
 
@@ -61,6 +63,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             price_timeframe,
             waiting_period,
             overseer,
+            liquidate_on_stale_price,
         } => update_config(
             deps,
             info,
@@ -73,6 +76,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             price_timeframe,
             waiting_period,
             overseer,
+            liquidate_on_stale_price,
         ),
         ExecuteMsg::WhitelistCollateral {
             collateral_token,
@@ -152,6 +156,7 @@ pub fn update_config(
     price_timeframe: Option<u64>,
     waiting_period: Option<u64>,
     overseer: Option<String>,
+    liquidate_on_stale_price: Option<bool>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
@@ -196,8 +201,12 @@ pub fn update_config(
         config.overseer = deps.api.addr_canonicalize(&overseer)?;
     }
 
+    if let Some(liquidate_on_stale_price) = liquidate_on_stale_price {
+        config.liquidate_on_stale_price = liquidate_on_stale_price;
+    }
+
     store_config(deps.storage, &config)?;
-    Ok(Response::new())
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
 pub fn whitelist_collateral(
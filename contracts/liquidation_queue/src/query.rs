@@ -26,6 +26,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         price_timeframe: config.price_timeframe,
         waiting_period: config.waiting_period,
         overseer: deps.api.addr_humanize(&config.overseer)?.to_string(),
+        liquidate_on_stale_price: config.liquidate_on_stale_price,
     };
 
     Ok(resp)
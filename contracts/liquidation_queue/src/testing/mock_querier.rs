@@ -2,7 +2,7 @@ use moneymarket::overseer::{WhitelistResponse, WhitelistResponseElem};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_bignumber::Decimal256;
+use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
@@ -203,6 +203,10 @@ impl WasmMockQuerier {
                                     max_ltv: *v,
                                     custody_contract: "custody0000".to_string(),
                                     collateral_token: "token0000".to_string(),
+                                    max_collateral: None,
+                                    unlock_delay: None,
+                                    unlock_delay_threshold: None,
+                                    total_collateral: Uint256::zero(),
                                 }],
                             })))
                         }
@@ -51,6 +51,7 @@ fn proper_initialization() {
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            liquidate_on_stale_price: false,
         }
     );
 }
@@ -87,6 +88,7 @@ fn update_config() {
         price_timeframe: None,
         waiting_period: None,
         overseer: None,
+        liquidate_on_stale_price: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -108,6 +110,7 @@ fn update_config() {
             price_timeframe: 60u64,
             waiting_period: 60u64,
             overseer: "overseer0000".to_string(),
+            liquidate_on_stale_price: false,
         }
     );
 
@@ -123,6 +126,7 @@ fn update_config() {
         price_timeframe: Some(120u64),
         waiting_period: Some(100u64),
         overseer: Some("overseer0001".to_string()),
+        liquidate_on_stale_price: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -144,6 +148,7 @@ fn update_config() {
             price_timeframe: 120u64,
             waiting_period: 100u64,
             overseer: "overseer0001".to_string(),
+            liquidate_on_stale_price: false,
         }
     );
 
@@ -159,6 +164,7 @@ fn update_config() {
         price_timeframe: Some(100u64),
         waiting_period: Some(100u64),
         overseer: Some("overseer0001".to_string()),
+        liquidate_on_stale_price: None,
     };
 
     let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
@@ -712,6 +718,43 @@ fn execute_bid() {
         ]
     );
 
+    // stale prices are rejected unless configured
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(100001u64);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "custody0000".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBid {
+            liquidator: "liquidator0000".to_string(),
+            fee_address: None,
+            repay_address: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(res, StdError::generic_err("Price is too old"));
+
+    let update_msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        oracle_contract: None,
+        safe_ratio: None,
+        bid_fee: None,
+        liquidator_fee: None,
+        liquidation_threshold: None,
+        price_timeframe: None,
+        waiting_period: None,
+        overseer: None,
+        liquidate_on_stale_price: Some(true),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        update_msg,
+    )
+    .unwrap();
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "custody0000".to_string(),
         amount: Uint128::from(2020206u128),
@@ -4,8 +4,14 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use moneymarket::market::{
-    BorrowerInfoResponse, BorrowerInfosResponse, ConfigResponse, Cw20HookMsg, EpochStateResponse,
-    ExecuteMsg, InstantiateMsg, QueryMsg,
+    BadDebtResponse, BadDebtsResponse, BorrowerHealthResponse, BorrowerIndexResponse,
+    BorrowerInfoResponse, BorrowerInfosResponse, BorrowerPositionResponse, ConfigResponse,
+    Cw20HookMsg, DepositValueResponse, EpochStateResponse, ExecuteMsg, InstantiateMsg,
+    InterestIndexAtResponse, IsWhitelistedResponse, LoanAmountAtResponse, MarketStateResponse,
+    MarketsResponse, MigrateMsg, PendingOwnerResponse, QueryMsg, RequiredBorrowLimitResponse,
+    ReservesFundedResponse, SimulateDepositResponse, SimulateRedeemResponse, StakerInfoResponse,
+    StakerInfosResponse, StakingStateResponse, StateResponse, TotalDepositsResponse,
+    UtilizationResponse, WiredContractsResponse,
 };
 use moneymarket_market::state::State;
 
@@ -19,9 +25,33 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(EpochStateResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfoResponse), &out_dir);
     export_schema(&schema_for!(BorrowerInfosResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerIndexResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerHealthResponse), &out_dir);
+    export_schema(&schema_for!(BorrowerPositionResponse), &out_dir);
+    export_schema(&schema_for!(RequiredBorrowLimitResponse), &out_dir);
+    export_schema(&schema_for!(BadDebtResponse), &out_dir);
+    export_schema(&schema_for!(BadDebtsResponse), &out_dir);
+    export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(MarketStateResponse), &out_dir);
+    export_schema(&schema_for!(UtilizationResponse), &out_dir);
+    export_schema(&schema_for!(ReservesFundedResponse), &out_dir);
+    export_schema(&schema_for!(SimulateDepositResponse), &out_dir);
+    export_schema(&schema_for!(SimulateRedeemResponse), &out_dir);
+    export_schema(&schema_for!(DepositValueResponse), &out_dir);
+    export_schema(&schema_for!(TotalDepositsResponse), &out_dir);
+    export_schema(&schema_for!(InterestIndexAtResponse), &out_dir);
+    export_schema(&schema_for!(LoanAmountAtResponse), &out_dir);
+    export_schema(&schema_for!(StakingStateResponse), &out_dir);
+    export_schema(&schema_for!(StakerInfoResponse), &out_dir);
+    export_schema(&schema_for!(StakerInfosResponse), &out_dir);
+    export_schema(&schema_for!(IsWhitelistedResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(WiredContractsResponse), &out_dir);
+    export_schema(&schema_for!(MarketsResponse), &out_dir);
 }
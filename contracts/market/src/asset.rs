@@ -0,0 +1,100 @@
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, StdResult, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+
+use crate::state::Config;
+
+use moneymarket::querier::{query_balance, query_token_balance, TaxCache};
+
+/// Balance of the market's base asset held by `account`
+pub fn query_stable_balance(deps: Deps, config: &Config, account: Addr) -> StdResult<Uint256> {
+    match &config.stable_token {
+        Some(stable_token) => {
+            query_token_balance(deps, deps.api.addr_humanize(stable_token)?, account)
+        }
+        None => query_balance(deps, account, config.stable_denom.to_string()),
+    }
+}
+
+/// Amount received by the recipient of a base asset transfer;
+/// only native transfers are taxed
+pub fn stable_amount_after_tax(deps: Deps, config: &Config, amount: Uint256) -> StdResult<Uint256> {
+    amount_after_tax(deps, config, amount, &mut TaxCache::default())
+}
+
+fn amount_after_tax(
+    deps: Deps,
+    config: &Config,
+    amount: Uint256,
+    tax_cache: &mut TaxCache,
+) -> StdResult<Uint256> {
+    if config.stable_token.is_some() {
+        return Ok(amount);
+    }
+
+    Ok(tax_cache
+        .deduct_tax(
+            deps,
+            Coin {
+                denom: config.stable_denom.to_string(),
+                amount: amount.into(),
+            },
+        )?
+        .amount
+        .into())
+}
+
+/// Send `amount` of the base asset to `recipient`, net of tax
+pub fn stable_transfer_msg(
+    deps: Deps,
+    config: &Config,
+    recipient: String,
+    amount: Uint256,
+) -> StdResult<CosmosMsg> {
+    let amount = stable_amount_after_tax(deps, config, amount)?;
+    stable_transfer_exact_msg(deps, config, recipient, amount)
+}
+
+/// Send the base asset to each recipient, net of tax,
+/// querying the tax parameters once for all transfers
+pub fn stable_transfer_msgs(
+    deps: Deps,
+    config: &Config,
+    transfers: Vec<(String, Uint256)>,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut tax_cache = TaxCache::default();
+    transfers
+        .into_iter()
+        .map(|(recipient, amount)| {
+            let amount = amount_after_tax(deps, config, amount, &mut tax_cache)?;
+            stable_transfer_exact_msg(deps, config, recipient, amount)
+        })
+        .collect()
+}
+
+/// Send exactly `amount` of the base asset to `recipient`,
+/// the market paying any tax on top of it
+pub fn stable_transfer_exact_msg(
+    deps: Deps,
+    config: &Config,
+    recipient: String,
+    amount: Uint256,
+) -> StdResult<CosmosMsg> {
+    Ok(match &config.stable_token {
+        Some(stable_token) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(stable_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient,
+                amount: amount.into(),
+            })?,
+        }),
+        None => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient,
+            amount: vec![Coin {
+                denom: config.stable_denom.to_string(),
+                amount: amount.into(),
+            }],
+        }),
+    })
+}
@@ -1,20 +1,34 @@
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, WasmMsg,
+    attr, to_binary, Addr, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo,
+    Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
+use cw20::Cw20ExecuteMsg;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::market::{BorrowerInfoResponse, BorrowerInfosResponse};
-use moneymarket::overseer::BorrowLimitResponse;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::market::{
+    BadDebtResponse, BadDebtsResponse, BorrowerHealthResponse, BorrowerIndexResponse,
+    BorrowerInfoResponse, BorrowerInfosResponse, BorrowerPositionResponse, EscrowResponse,
+    ExecuteMsg, InterestIndexAtResponse, LoanAmountAtResponse, RequiredBorrowLimitResponse,
+};
+use moneymarket::math::{checked_add_liability, checked_mul_liability, checked_sub_liability};
+use moneymarket::overseer::{
+    BorrowLimitResponse, CollateralsResponse, ExecuteMsg as OverseerExecuteMsg,
+};
+use moneymarket::querier::{query_borrow_limit, query_collaterals, query_supply};
+use moneymarket::tokens::TokensHuman;
 
-use crate::deposit::compute_exchange_rate_raw;
+use crate::asset::{query_stable_balance, stable_transfer_msg};
+use crate::contract::self_msg;
+use crate::deposit::{compute_deposit, compute_exchange_rate, compute_exchange_rate_raw};
 use crate::error::ContractError;
-use crate::querier::{query_borrow_limit, query_borrow_rate, query_target_deposit_rate};
+use crate::querier::{query_borrow_rate, query_target_deposit_rate};
 use crate::state::{
-    read_borrower_info, read_borrower_infos, read_config, read_state, store_borrower_info,
-    store_state, BorrowerInfo, Config, State,
+    read_bad_debt, read_bad_debts, read_borrower_info, read_borrower_infos,
+    read_borrower_infos_by_amount, read_config, read_escrow, read_interest_index_checkpoint,
+    read_is_borrow_whitelisted, read_loan_checkpoint, read_state, store_bad_debt,
+    store_borrower_info, store_escrow, store_interest_index_checkpoint, store_loan_checkpoint,
+    store_state, BorrowerInfo, Config, LoanCheckpoint, State,
 };
 
 pub fn borrow_stable(
@@ -30,10 +44,35 @@ pub fn borrow_stable(
 
     let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    if config.borrow_whitelist_enabled && !read_is_borrow_whitelisted(deps.storage, &borrower_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Funds sent back to the market or its peers would inflate
+    // the balance while the debt still stands against the borrower
+    if let Some(to) = &to {
+        let to_raw = deps.api.addr_canonicalize(to.as_str())?;
+        if *to == env.contract.address
+            || to_raw == config.overseer_contract
+            || to_raw == config.aterra_contract
+        {
+            return Err(ContractError::InvalidBorrowRecipient {});
+        }
+    }
+
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
+    assert_market_state(deps.as_ref(), &config, &state)?;
+
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
@@ -41,11 +80,13 @@ pub fn borrow_stable(
     compute_borrower_reward(&state, &mut liability);
 
     let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let escrow_value = compute_escrow_value(deps.as_ref(), &config, &state, &borrower_raw)?;
     let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
         deps.as_ref(),
         overseer,
         borrower.clone(),
         Some(env.block.time.seconds()),
+        Some(escrow_value),
     )?;
 
     if borrow_limit_res.borrow_limit < borrow_amount + liability.loan_amount {
@@ -54,38 +95,305 @@ pub fn borrow_stable(
         ));
     }
 
-    let current_balance = query_balance(
-        deps.as_ref(),
-        env.contract.address,
-        config.stable_denom.to_string(),
-    )?;
+    if borrow_amount + liability.loan_amount < config.min_borrow_amount {
+        return Err(ContractError::BorrowBelowMinimum(
+            config.min_borrow_amount.into(),
+        ));
+    }
+
+    // Risk caps set by the owner, on top of the collateral based limit
+    if let Some(per_account_borrow_cap) = config.per_account_borrow_cap {
+        if per_account_borrow_cap < borrow_amount + liability.loan_amount {
+            return Err(ContractError::AccountBorrowCapExceeded(
+                per_account_borrow_cap.into(),
+            ));
+        }
+    }
+
+    if let Some(max_borrow_amount) = config.max_borrow_amount {
+        if Decimal256::from_uint256(max_borrow_amount)
+            < state.total_liabilities + Decimal256::from_uint256(borrow_amount)
+        {
+            return Err(ContractError::GlobalBorrowCapExceeded(
+                max_borrow_amount.into(),
+            ));
+        }
+    }
+
+    let current_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
 
     // Assert borrow amount
     assert_max_borrow_factor(&config, &state, current_balance, borrow_amount)?;
 
     liability.loan_amount += borrow_amount;
-    state.total_liabilities += Decimal256::from_uint256(borrow_amount);
+    state.total_liabilities = checked_add_liability(
+        state.total_liabilities,
+        Decimal256::from_uint256(borrow_amount),
+    )?;
     store_state(deps.storage, &state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    checkpoint_liability(
+        deps.storage,
+        env.block.height,
+        &borrower_raw,
+        &state,
+        &liability,
+    )?;
 
     Ok(Response::new()
-        .add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: to.unwrap_or_else(|| borrower.clone()).to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: borrow_amount.into(),
-                },
-            )?],
-        }))
+        .add_message(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            to.unwrap_or_else(|| borrower.clone()).to_string(),
+            borrow_amount,
+        )?)
         .add_attributes(vec![
             attr("action", "borrow_stable"),
             attr("borrower", borrower),
             attr("borrow_amount", borrow_amount),
+            attr("remaining_loan", liability.loan_amount),
+        ]))
+}
+
+/// Lock `collaterals` in the overseer and borrow against them
+/// once the lock has been applied
+pub fn borrow_stable_with_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collaterals: TokensHuman,
+    borrow_amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps
+                    .api
+                    .addr_humanize(&config.overseer_contract)?
+                    .to_string(),
+                funds: vec![],
+                msg: to_binary(&OverseerExecuteMsg::LockCollateralFor {
+                    borrower: info.sender.to_string(),
+                    collaterals,
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: self_msg(
+                    deps.storage,
+                    ExecuteMsg::BorrowStableFor {
+                        borrower: info.sender.to_string(),
+                        borrow_amount,
+                    },
+                )?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "borrow_stable_with_collateral"),
+            attr("borrower", info.sender),
+        ]))
+}
+
+/// Deposit the sent stable asset, keep the minted aterra in escrow
+/// as collateral of the sender and borrow against it once minted
+pub fn deposit_and_borrow(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrow_amount: Uint256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let (deposit_amount, mint_amount, exchange_rate) =
+        compute_deposit(deps.branch(), &env, &config, &info)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let escrow_amount = read_escrow(deps.storage, &borrower_raw) + mint_amount;
+    store_escrow(deps.storage, &borrower_raw, &escrow_amount)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: env.contract.address.to_string(),
+                    amount: mint_amount.into(),
+                })?,
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: self_msg(
+                    deps.storage,
+                    ExecuteMsg::BorrowStableFor {
+                        borrower: borrower.to_string(),
+                        borrow_amount,
+                    },
+                )?,
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "deposit_and_borrow"),
+            attr("borrower", borrower),
+            attr("deposit_amount", deposit_amount),
+            attr("mint_amount", mint_amount),
+            attr("exchange_rate", exchange_rate.to_string()),
+            attr("escrow_amount", escrow_amount),
         ]))
 }
 
+/// Release escrowed aterra to the borrower; the borrow limit
+/// left without it must still cover the loan
+pub fn withdraw_escrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let escrow_amount = read_escrow(deps.storage, &borrower_raw);
+    if escrow_amount.is_zero() {
+        return Err(ContractError::NoEscrow {});
+    }
+
+    let amount = amount.unwrap_or(escrow_amount);
+    if amount > escrow_amount {
+        return Err(ContractError::InsufficientEscrowAmount(
+            escrow_amount.into(),
+        ));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
+    compute_borrower_interest(&state, &mut liability);
+
+    if !liability.loan_amount.is_zero() {
+        // The borrow limit is checked against the escrow left after the withdrawal
+        let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
+        let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+            deps.as_ref(),
+            deps.api.addr_humanize(&config.overseer_contract)?,
+            borrower.clone(),
+            Some(env.block.time.seconds()),
+            Some((escrow_amount - amount) * exchange_rate),
+        )?;
+
+        let borrow_limit = borrow_limit_res.borrow_limit;
+        if borrow_limit < liability.loan_amount {
+            return Err(ContractError::EscrowWithdrawTooLarge(borrow_limit.into()));
+        }
+    }
+
+    store_escrow(deps.storage, &borrower_raw, &(escrow_amount - amount))?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: borrower.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "withdraw_escrow"),
+            attr("borrower", borrower),
+            attr("amount", amount),
+        ]))
+}
+
+/// Settle the loan of an underwater borrower with their escrowed
+/// aterra first; whatever is not needed goes back to the borrower
+pub fn repay_stable_from_escrow(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if config.overseer_contract != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let escrow_amount = read_escrow(deps.storage, &borrower_raw);
+    if escrow_amount.is_zero() {
+        return Err(ContractError::NoEscrow {});
+    }
+
+    store_escrow(deps.storage, &borrower_raw, &Uint256::zero())?;
+
+    Ok(
+        repay_from_collateral(deps, env, borrower, escrow_amount.into(), true)?
+            .add_attribute("escrow_amount", escrow_amount),
+    )
+}
+
+pub fn query_escrow(deps: Deps, env: Env, borrower: Addr) -> StdResult<EscrowResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, &env, &config, &mut state, env.block.height, None)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+
+    Ok(EscrowResponse {
+        borrower: borrower.to_string(),
+        escrow_amount: read_escrow(deps.storage, &borrower_raw),
+        escrow_value: compute_escrow_value(deps, &config, &state, &borrower_raw)?,
+    })
+}
+
+/// Stable value of the borrower's escrowed aterra, handed to the overseer
+/// with borrow limit queries so it does not query the market back
+fn compute_escrow_value(
+    deps: Deps,
+    config: &Config,
+    state: &State,
+    borrower_raw: &CanonicalAddr,
+) -> StdResult<Uint256> {
+    let escrow_amount = read_escrow(deps.storage, borrower_raw);
+    if escrow_amount.is_zero() {
+        return Ok(Uint256::zero());
+    }
+
+    Ok(escrow_amount * compute_exchange_rate(deps, config, state, None)?)
+}
+
+pub fn borrow_stable_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    borrow_amount: Uint256,
+) -> Result<Response, ContractError> {
+    // only this contract can execute this message
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let info = MessageInfo {
+        sender: borrower,
+        funds: vec![],
+    };
+
+    borrow_stable(deps, env, info, borrow_amount, None)
+}
+
 pub fn repay_stable_from_liquidation(
     deps: DepsMut,
     env: Env,
@@ -98,91 +406,514 @@ pub fn repay_stable_from_liquidation(
         return Err(ContractError::Unauthorized {});
     }
 
-    let cur_balance: Uint256 = query_balance(
-        deps.as_ref(),
-        env.contract.address.clone(),
-        config.stable_denom.to_string(),
-    )?;
+    let cur_balance: Uint256 =
+        query_stable_balance(deps.as_ref(), &config, env.contract.address.clone())?;
+
+    // The balance delta is the amount actually received, net of
+    // any tax charged on the transfer from the liquidation contract
+    let received_amount = if cur_balance > prev_balance {
+        cur_balance - prev_balance
+    } else {
+        Uint256::zero()
+    };
 
     // override env
     let mut info = info;
 
-    info.sender = borrower;
+    info.sender = borrower.clone();
     info.funds = vec![Coin {
         denom: config.stable_denom,
-        amount: (cur_balance - prev_balance).into(),
+        amount: received_amount.into(),
     }];
 
-    repay_stable(deps, env, info)
+    repay_liability(deps, env, info, borrower, "repay_stable", true)
+}
+
+/// Liquidate the collaterals of an underwater borrower; the overseer
+/// settles the loan with the proceeds through RepayStableFromLiquidation
+pub fn execute_liquidation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    max_repay: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    let mut liability: BorrowerInfo = read_borrower_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
+    compute_borrower_interest(&state, &mut liability);
+
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let escrow_value = compute_escrow_value(
+        deps.as_ref(),
+        &config,
+        &state,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )?;
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps.as_ref(),
+        overseer.clone(),
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+        Some(escrow_value),
+    )?;
+
+    if borrow_limit_res.borrow_limit >= liability.loan_amount {
+        return Err(ContractError::CannotLiquidateSafeLoan {});
+    }
+
+    let mut attributes = vec![
+        attr("action", "execute_liquidation"),
+        attr("borrower", borrower.as_str()),
+        attr("liquidator", info.sender.as_str()),
+        attr("loan_amount", liability.loan_amount),
+        attr("borrow_limit", borrow_limit_res.borrow_limit),
+    ];
+    if let Some(max_repay) = max_repay {
+        attributes.push(attr("max_repay", max_repay));
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: overseer.to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::LiquidateCollateralFor {
+                liquidator: info.sender.to_string(),
+                borrower: borrower.to_string(),
+                max_repay,
+            })?,
+        }))
+        .add_attributes(attributes))
+}
+
+/// Write off the whole liability of a borrower whose collaterals are
+/// exhausted, drawing the loss from the reserves before the depositors
+pub fn write_off_bad_debt(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && sender_raw != config.overseer_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let collaterals_res: CollateralsResponse = query_collaterals(
+        deps.as_ref(),
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        borrower.clone(),
+    )?;
+    if collaterals_res.collaterals.iter().any(|c| !c.1.is_zero()) {
+        return Err(ContractError::CannotWriteOffCollateralizedLoan {});
+    }
+
+    // The escrow has to repay the loan through a liquidation first
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    if !read_escrow(deps.storage, &borrower_raw).is_zero() {
+        return Err(ContractError::CannotWriteOffEscrowedLoan {});
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
+    compute_borrower_interest(&state, &mut liability);
+
+    compute_reward(&mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    let written_off_amount = liability.loan_amount;
+    if written_off_amount.is_zero() {
+        return Err(ContractError::NoBadDebt {});
+    }
+
+    let reserves_used = std::cmp::min(state.total_reserves * Uint256::one(), written_off_amount);
+    state.total_reserves = state.total_reserves - Decimal256::from_uint256(reserves_used);
+    state.total_liabilities = checked_sub_liability(
+        state.total_liabilities,
+        Decimal256::from_uint256(written_off_amount),
+    )?;
+    liability.loan_amount = Uint256::zero();
+
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    checkpoint_liability(
+        deps.storage,
+        env.block.height,
+        &borrower_raw,
+        &state,
+        &liability,
+    )?;
+    store_state(deps.storage, &state)?;
+
+    let total_written_off = read_bad_debt(deps.storage, &borrower_raw) + written_off_amount;
+    store_bad_debt(deps.storage, &borrower_raw, &total_written_off)?;
+
+    Ok(Response::new()
+        .add_event(Event::new("bad_debt").add_attributes(vec![
+            attr("borrower", borrower.as_str()),
+            attr("written_off_amount", written_off_amount),
+            attr("reserves_used", reserves_used),
+            attr("socialized_amount", written_off_amount - reserves_used),
+        ]))
+        .add_attributes(vec![
+            attr("action", "write_off_bad_debt"),
+            attr("borrower", borrower),
+        ]))
+}
+
+pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let borrower = info.sender.clone();
+    repay_liability(deps, env, info, borrower, "repay_stable", false)
+}
+
+/// Repay the liability of `borrower` with the funds of the sender,
+/// any excess is refunded to the sender
+pub fn repay_stable_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    let payer = info.sender.clone();
+    Ok(
+        repay_liability(deps, env, info, borrower, "repay_stable_for", false)?
+            .add_attribute("payer", payer),
+    )
+}
+
+/// Liquidations may leave a loan below the dust threshold behind;
+/// rejecting them would keep the position from being liquidated
+fn repay_liability(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+    action: &str,
+    liquidation: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // Check stable denom deposit
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    // Cannot deposit zero amount
+    if amount.is_zero() {
+        return Err(ContractError::ZeroRepay(config.stable_denom));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        Some(amount),
+    )?;
+    let stored_loan_amount = liability.loan_amount;
+    compute_borrower_interest(&state, &mut liability);
+    let interest_accrued = liability.loan_amount - stored_loan_amount;
+
+    // Compute ANC reward
+    compute_reward(&mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    // Liquidation proceeds are still refunded to the borrower
+    if liability.loan_amount.is_zero() && !liquidation {
+        return Err(ContractError::NoLoan {});
+    }
+
+    let repay_amount: Uint256;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if liability.loan_amount < amount {
+        repay_amount = liability.loan_amount;
+        liability.loan_amount = Uint256::zero();
+
+        // Payback left repay amount to sender, net of tax
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            info.sender.to_string(),
+            amount - repay_amount,
+        )?);
+    } else {
+        repay_amount = amount;
+        liability.loan_amount = liability.loan_amount - repay_amount;
+        if !liquidation {
+            assert_no_dust_loan(&config, liability.loan_amount)?;
+        }
+    }
+
+    // Repayment goes to the newly accrued interest first
+    let interest_paid = if repay_amount < interest_accrued {
+        repay_amount
+    } else {
+        interest_accrued
+    };
+    let principal_paid = repay_amount - interest_paid;
+
+    state.total_liabilities = checked_sub_liability(
+        state.total_liabilities,
+        Decimal256::from_uint256(repay_amount),
+    )?;
+
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    checkpoint_liability(
+        deps.storage,
+        env.block.height,
+        &borrower_raw,
+        &state,
+        &liability,
+    )?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", action),
+        attr("borrower", borrower),
+        attr("repay_amount", repay_amount),
+        attr("interest_paid", interest_paid),
+        attr("principal_paid", principal_paid),
+        attr("remaining_loan", liability.loan_amount),
+    ]))
+}
+
+/// Repay the whole loan, including interest accrued up to the
+/// current block, and refund whatever exceeds it
+pub fn close_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // Check stable denom deposit
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    // Cannot deposit zero amount
+    if amount.is_zero() {
+        return Err(ContractError::ZeroRepay(config.stable_denom));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+
+    let borrower = info.sender;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    // Compute interest
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        Some(amount),
+    )?;
+    compute_borrower_interest(&state, &mut liability);
+
+    // Compute ANC reward
+    compute_reward(&mut state, env.block.height);
+    compute_borrower_reward(&state, &mut liability);
+
+    if liability.loan_amount.is_zero() {
+        return Err(ContractError::NoLoan {});
+    }
+
+    if amount < liability.loan_amount {
+        return Err(ContractError::InsufficientRepayAmount(
+            (liability.loan_amount - amount).into(),
+        ));
+    }
+
+    let repay_amount = liability.loan_amount;
+    liability.loan_amount = Uint256::zero();
+    state.total_liabilities = checked_sub_liability(
+        state.total_liabilities,
+        Decimal256::from_uint256(repay_amount),
+    )?;
+
+    // Payback left repay amount to sender, net of tax
+    let messages: Vec<CosmosMsg> = if amount > repay_amount {
+        vec![stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            borrower.to_string(),
+            amount - repay_amount,
+        )?]
+    } else {
+        vec![]
+    };
+
+    store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    checkpoint_liability(
+        deps.storage,
+        env.block.height,
+        &borrower_raw,
+        &state,
+        &liability,
+    )?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "close_position"),
+        attr("borrower", borrower),
+        attr("repay_amount", repay_amount),
+        attr("remaining_loan", liability.loan_amount),
+    ]))
 }
 
-pub fn repay_stable(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+/// Repay the loan with aterra sent by the borrower, valued at the
+/// current exchange rate. Aterra exceeding the loan is sent back;
+/// liquidations may leave a dust loan behind
+pub fn repay_from_collateral(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
+    aterra_amount: Uint128,
+    liquidation: bool,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
-    // Check stable denom deposit
-    let amount: Uint256 = info
-        .funds
-        .iter()
-        .find(|c| c.denom == config.stable_denom)
-        .map(|c| Uint256::from(c.amount))
-        .unwrap_or_else(Uint256::zero);
-
-    // Cannot deposit zero amount
-    if amount.is_zero() {
-        return Err(ContractError::ZeroRepay(config.stable_denom));
-    }
-
     let mut state: State = read_state(deps.storage)?;
 
-    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
     compute_interest(
         deps.as_ref(),
+        &env,
         &config,
         &mut state,
         env.block.height,
-        Some(amount),
+        None,
     )?;
+    let stored_loan_amount = liability.loan_amount;
     compute_borrower_interest(&state, &mut liability);
+    let interest_accrued = liability.loan_amount - stored_loan_amount;
 
     // Compute ANC reward
     compute_reward(&mut state, env.block.height);
     compute_borrower_reward(&state, &mut liability);
 
+    if liability.loan_amount.is_zero() {
+        return Err(ContractError::ZeroRepay(config.stable_denom));
+    }
+
+    // Load anchor token exchange rate with updated state,
+    // the same way redeem_stable values the burned aterra
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
+    let aterra_amount = Uint256::from(aterra_amount);
+    let aterra_value = aterra_amount * exchange_rate;
+
     let repay_amount: Uint256;
-    let mut messages: Vec<CosmosMsg> = vec![];
-    if liability.loan_amount < amount {
-        repay_amount = liability.loan_amount;
-        liability.loan_amount = Uint256::zero();
+    let burn_amount: Uint256;
+    if liability.loan_amount < aterra_value {
+        // Round up the aterra burned for the loan, so the
+        // rounding never goes against the depositors
+        let loan_in_aterra = Decimal256::from_uint256(liability.loan_amount) / exchange_rate;
+        let mut loan_in_aterra_floor = loan_in_aterra * Uint256::one();
+        if Decimal256::from_uint256(loan_in_aterra_floor) < loan_in_aterra {
+            loan_in_aterra_floor += Uint256::one();
+        }
 
-        // Payback left repay amount to sender
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: borrower.to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: (amount - repay_amount).into(),
-                },
-            )?],
-        }));
+        repay_amount = liability.loan_amount;
+        burn_amount = std::cmp::min(loan_in_aterra_floor, aterra_amount);
     } else {
-        repay_amount = amount;
-        liability.loan_amount = liability.loan_amount - repay_amount;
+        repay_amount = aterra_value;
+        burn_amount = aterra_amount;
+    }
+
+    liability.loan_amount = liability.loan_amount - repay_amount;
+    if !liquidation {
+        assert_no_dust_loan(&config, liability.loan_amount)?;
     }
 
-    state.total_liabilities = state.total_liabilities - Decimal256::from_uint256(repay_amount);
+    // Repayment goes to the newly accrued interest first
+    let interest_paid = std::cmp::min(repay_amount, interest_accrued);
+    let principal_paid = repay_amount - interest_paid;
+
+    state.total_liabilities = checked_sub_liability(
+        state.total_liabilities,
+        Decimal256::from_uint256(repay_amount),
+    )?;
+    state.prev_aterra_supply = state.prev_aterra_supply - burn_amount;
 
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    checkpoint_liability(
+        deps.storage,
+        env.block.height,
+        &borrower_raw,
+        &state,
+        &liability,
+    )?;
     store_state(deps.storage, &state)?;
 
+    let aterra_contract = deps.api.addr_humanize(&config.aterra_contract)?;
+    let mut messages: Vec<CosmosMsg> = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: aterra_contract.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: burn_amount.into(),
+        })?,
+    })];
+
+    // Payback left aterra to sender
+    if aterra_amount > burn_amount {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: aterra_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: borrower.to_string(),
+                amount: (aterra_amount - burn_amount).into(),
+            })?,
+        }));
+    }
+
     Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "repay_stable"),
+        attr("action", "repay_from_collateral"),
         attr("borrower", borrower),
+        attr("burn_amount", burn_amount),
         attr("repay_amount", repay_amount),
+        attr("interest_paid", interest_paid),
+        attr("principal_paid", principal_paid),
+        attr("remaining_loan", liability.loan_amount),
     ]))
 }
 
@@ -200,7 +931,14 @@ pub fn claim_rewards(
     let mut liability: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
 
     // Compute interest
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
     compute_borrower_interest(&state, &mut liability);
 
     // Compute ANC reward
@@ -212,6 +950,13 @@ pub fn claim_rewards(
 
     store_state(deps.storage, &state)?;
     store_borrower_info(deps.storage, &borrower_raw, &liability)?;
+    checkpoint_liability(
+        deps.storage,
+        env.block.height,
+        &borrower_raw,
+        &state,
+        &liability,
+    )?;
 
     let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
         vec![CosmosMsg::Wasm(WasmMsg::Execute {
@@ -241,27 +986,62 @@ pub fn claim_rewards(
 
 /// Compute interest and update state
 /// total liabilities and total reserves
+/// Record the liability of `borrower` and the interest index it was
+/// computed with, for the historical index and loan amount queries
+fn checkpoint_liability(
+    storage: &mut dyn Storage,
+    height: u64,
+    borrower: &CanonicalAddr,
+    state: &State,
+    liability: &BorrowerInfo,
+) -> StdResult<()> {
+    // The index only moves once per block, so one checkpoint per height is enough
+    let index_checkpoint = read_interest_index_checkpoint(storage, height)?;
+    if index_checkpoint.map(|(checkpoint_height, _)| checkpoint_height) != Some(height) {
+        store_interest_index_checkpoint(storage, height, &state.global_interest_index)?;
+    }
+    store_loan_checkpoint(
+        storage,
+        borrower,
+        height,
+        &LoanCheckpoint {
+            loan_amount: liability.loan_amount,
+            interest_index: liability.interest_index,
+        },
+    )
+}
+
+/// Loans too small to be worth liquidating cannot be left behind
+fn assert_no_dust_loan(config: &Config, loan_amount: Uint256) -> Result<(), ContractError> {
+    if !loan_amount.is_zero() && loan_amount < config.dust_threshold {
+        return Err(ContractError::DustLoan(config.dust_threshold.into()));
+    }
+
+    Ok(())
+}
+
 pub fn compute_interest(
     deps: Deps,
+    env: &Env,
     config: &Config,
     state: &mut State,
     block_height: u64,
     deposit_amount: Option<Uint256>,
 ) -> StdResult<()> {
-    if state.last_interest_updated >= block_height {
+    let accrual_clock = accrual_clock(config, env, block_height);
+    if state.last_interest_updated >= accrual_clock {
         return Ok(());
     }
 
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance: Uint256 = query_balance(
-        deps,
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - deposit_amount.unwrap_or_else(Uint256::zero);
+    let balance: Uint256 =
+        query_stable_balance(deps, config, deps.api.addr_humanize(&config.contract_addr)?)?
+            - deposit_amount.unwrap_or_else(Uint256::zero);
 
     let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
         deps,
         deps.api.addr_humanize(&config.interest_model)?,
+        &config.stable_denom,
         balance,
         state.total_liabilities,
         state.total_reserves,
@@ -272,14 +1052,31 @@ pub fn compute_interest(
 
     compute_interest_raw(
         state,
-        block_height,
+        accrual_clock,
         balance,
         aterra_supply,
-        borrow_rate_res.rate,
+        std::cmp::max(borrow_rate_res.rate, config.min_borrow_rate),
         target_deposit_rate,
-    );
+        config.max_accrual_blocks,
+        config.block_time,
+    )
+}
 
-    Ok(())
+/// Reading of the clock interest accrues on at the given block height;
+/// the height itself, or the block time when the market accrues on
+/// block time, projected with the nominal block time for other heights
+pub fn accrual_clock(config: &Config, env: &Env, block_height: u64) -> u64 {
+    match config.block_time {
+        Some(block_time) => {
+            let block_seconds = env.block.time.seconds();
+            if block_height >= env.block.height {
+                block_seconds + (block_height - env.block.height) * block_time
+            } else {
+                block_seconds.saturating_sub((env.block.height - block_height) * block_time)
+            }
+        }
+        None => block_height,
+    }
 }
 
 // CONTRACT: to use this function as state update purpose,
@@ -287,6 +1084,10 @@ pub fn compute_interest(
 // * state.prev_aterra_supply
 // * state.prev_exchange_rate
 // * state.last_interest_updated
+//
+// `block_height` is read from the accrual clock; with `block_time` set it
+// is a block time, and the elapsed seconds are counted in nominal blocks
+#[allow(clippy::too_many_arguments)]
 pub fn compute_interest_raw(
     state: &mut State,
     block_height: u64,
@@ -294,19 +1095,33 @@ pub fn compute_interest_raw(
     aterra_supply: Uint256,
     borrow_rate: Decimal256,
     target_deposit_rate: Decimal256,
-) {
+    max_accrual_blocks: Option<u64>,
+    block_time: Option<u64>,
+) -> StdResult<()> {
     if state.last_interest_updated >= block_height {
-        return;
+        return Ok(());
     }
 
-    let passed_blocks = Decimal256::from_uint256(block_height - state.last_interest_updated);
+    let block_time = block_time.unwrap_or(1);
+
+    // The remaining blocks are accrued by the following computations
+    let block_height = match max_accrual_blocks {
+        Some(max_accrual_blocks) => std::cmp::min(
+            block_height,
+            state.last_interest_updated + max_accrual_blocks * block_time,
+        ),
+        None => block_height,
+    };
+
+    let passed_blocks =
+        Decimal256::from_ratio(block_height - state.last_interest_updated, block_time);
 
     let interest_factor = passed_blocks * borrow_rate;
-    let interest_accrued = state.total_liabilities * interest_factor;
+    let interest_accrued = checked_mul_liability(state.total_liabilities, interest_factor)?;
 
     state.global_interest_index =
         state.global_interest_index * (Decimal256::one() + interest_factor);
-    state.total_liabilities += interest_accrued;
+    state.total_liabilities = checked_add_liability(state.total_liabilities, interest_accrued)?;
 
     let mut exchange_rate = compute_exchange_rate_raw(state, aterra_supply, balance);
     let effective_deposit_rate = exchange_rate / state.prev_exchange_rate;
@@ -328,15 +1143,30 @@ pub fn compute_interest_raw(
     state.prev_aterra_supply = aterra_supply;
     state.prev_exchange_rate = exchange_rate;
     state.last_interest_updated = block_height;
+
+    Ok(())
 }
 
 /// Compute new interest and apply to liability
 pub(crate) fn compute_borrower_interest(state: &State, liability: &mut BorrowerInfo) {
-    liability.loan_amount =
-        liability.loan_amount * state.global_interest_index / liability.interest_index;
+    liability.loan_amount = compute_loan(
+        liability.loan_amount,
+        liability.interest_index,
+        state.global_interest_index,
+    );
     liability.interest_index = state.global_interest_index;
 }
 
+/// Scale a loan by the index ratio in a single step, rounding down once;
+/// flooring the intermediate product can shrink the loan
+fn compute_loan(
+    loan_amount: Uint256,
+    interest_index: Decimal256,
+    global_interest_index: Decimal256,
+) -> Uint256 {
+    Uint256(loan_amount.0 * global_interest_index.0 / interest_index.0)
+}
+
 /// Compute distributed reward and update global index
 pub fn compute_reward(state: &mut State, block_height: u64) {
     if state.last_reward_updated >= block_height {
@@ -382,7 +1212,7 @@ pub fn query_borrower_info(
     let config: Config = read_config(deps.storage)?;
     let mut state: State = read_state(deps.storage)?;
 
-    compute_interest(deps, &config, &mut state, block_height, None)?;
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
     compute_borrower_interest(&state, &mut borrower_info);
 
     compute_reward(&mut state, block_height);
@@ -397,10 +1227,224 @@ pub fn query_borrower_info(
     })
 }
 
+pub fn query_borrower_index(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+) -> StdResult<BorrowerIndexResponse> {
+    let borrower_info: BorrowerInfo = read_borrower_info(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    );
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, &env, &config, &mut state, env.block.height, None)?;
+
+    Ok(BorrowerIndexResponse {
+        borrower: borrower.to_string(),
+        stored_index: borrower_info.interest_index,
+        global_index: state.global_interest_index,
+        block_height: env.block.height,
+    })
+}
+
+pub fn query_interest_index_at(deps: Deps, height: u64) -> StdResult<InterestIndexAtResponse> {
+    let (checkpoint_height, global_interest_index) =
+        read_interest_index_checkpoint(deps.storage, height)?
+            .ok_or_else(|| StdError::not_found("interest index checkpoint"))?;
+
+    Ok(InterestIndexAtResponse {
+        height,
+        checkpoint_height,
+        global_interest_index,
+    })
+}
+
+pub fn query_loan_amount_at(
+    deps: Deps,
+    borrower: Addr,
+    height: u64,
+) -> StdResult<LoanAmountAtResponse> {
+    let (mut checkpoint_height, loan_checkpoint) = read_loan_checkpoint(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+        height,
+    )?
+    .ok_or_else(|| StdError::not_found("loan checkpoint"))?;
+
+    // Accrue interest up to a later index checkpoint, if any
+    let mut loan_amount = loan_checkpoint.loan_amount;
+    if let Some((index_height, global_interest_index)) =
+        read_interest_index_checkpoint(deps.storage, height)?
+    {
+        if index_height > checkpoint_height {
+            loan_amount = compute_loan(
+                loan_amount,
+                loan_checkpoint.interest_index,
+                global_interest_index,
+            );
+            checkpoint_height = index_height;
+        }
+    }
+
+    Ok(LoanAmountAtResponse {
+        borrower: borrower.to_string(),
+        height,
+        checkpoint_height,
+        loan_amount,
+    })
+}
+
+pub fn query_required_borrow_limit(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    desired_borrow: Uint256,
+    block_height: u64,
+) -> StdResult<RequiredBorrowLimitResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+    compute_borrower_interest(&state, &mut borrower_info);
+
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+        Some(compute_escrow_value(deps, &config, &state, &borrower_raw)?),
+    )?;
+
+    let required_limit = borrower_info.loan_amount + desired_borrow;
+    let current_limit = borrow_limit_res.borrow_limit;
+    let shortfall = if required_limit > current_limit {
+        required_limit - current_limit
+    } else {
+        Uint256::zero()
+    };
+
+    Ok(RequiredBorrowLimitResponse {
+        borrower: borrower.to_string(),
+        required_limit,
+        current_limit,
+        shortfall,
+    })
+}
+
+pub fn query_borrower_position(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    block_height: Option<u64>,
+) -> StdResult<BorrowerPositionResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    let block_height = if let Some(block_height) = block_height {
+        block_height
+    } else {
+        env.block.height
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let principal = borrower_info.loan_amount;
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+    compute_borrower_interest(&state, &mut borrower_info);
+
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps,
+        deps.api.addr_humanize(&config.overseer_contract)?,
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+        Some(compute_escrow_value(deps, &config, &state, &borrower_raw)?),
+    )?;
+
+    let borrow_limit = borrow_limit_res.borrow_limit;
+    let loan_amount = borrower_info.loan_amount;
+    let borrowable = if borrow_limit > loan_amount {
+        borrow_limit - loan_amount
+    } else {
+        Uint256::zero()
+    };
+
+    Ok(BorrowerPositionResponse {
+        borrower: borrower.to_string(),
+        loan_amount,
+        principal,
+        interest_index: borrower_info.interest_index,
+        borrow_limit,
+        borrowable,
+        is_over_limit: loan_amount > borrow_limit,
+    })
+}
+
+pub fn query_borrower_health(
+    deps: Deps,
+    env: Env,
+    borrower: Addr,
+    block_height: Option<u64>,
+) -> StdResult<BorrowerHealthResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut borrower_info: BorrowerInfo = read_borrower_info(deps.storage, &borrower_raw);
+
+    let block_height = if let Some(block_height) = block_height {
+        block_height
+    } else {
+        env.block.height
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+    compute_borrower_interest(&state, &mut borrower_info);
+
+    let overseer = deps.api.addr_humanize(&config.overseer_contract)?;
+    let borrow_limit_res: BorrowLimitResponse = query_borrow_limit(
+        deps,
+        overseer.clone(),
+        borrower.clone(),
+        Some(env.block.time.seconds()),
+        Some(compute_escrow_value(deps, &config, &state, &borrower_raw)?),
+    )?;
+    let collaterals_res: CollateralsResponse = query_collaterals(deps, overseer, borrower.clone())?;
+
+    let borrow_limit = borrow_limit_res.borrow_limit;
+    let loan_amount = borrower_info.loan_amount;
+    let ltv = if borrow_limit.is_zero() {
+        None
+    } else {
+        Some(Decimal256::from_ratio(loan_amount.0, borrow_limit.0))
+    };
+    let health_factor = if loan_amount.is_zero() {
+        None
+    } else {
+        Some(Decimal256::from_ratio(borrow_limit.0, loan_amount.0))
+    };
+
+    Ok(BorrowerHealthResponse {
+        borrower: borrower.to_string(),
+        loan_amount,
+        borrow_limit,
+        collaterals: collaterals_res.collaterals,
+        ltv,
+        health_factor,
+    })
+}
+
 pub fn query_borrower_infos(
     deps: Deps,
+    env: Env,
     start_after: Option<Addr>,
     limit: Option<u32>,
+    block_height: Option<u64>,
 ) -> StdResult<BorrowerInfosResponse> {
     let start_after = if let Some(start_after) = start_after {
         Some(deps.api.addr_canonicalize(start_after.as_str())?)
@@ -409,7 +1453,107 @@ pub fn query_borrower_infos(
     };
 
     let borrower_infos: Vec<BorrowerInfoResponse> = read_borrower_infos(deps, start_after, limit)?;
-    Ok(BorrowerInfosResponse { borrower_infos })
+    Ok(BorrowerInfosResponse {
+        borrower_infos: accrue_borrower_infos(deps, env, block_height, borrower_infos)?,
+    })
+}
+
+pub fn query_bad_debts(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<BadDebtsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let bad_debts: Vec<BadDebtResponse> = read_bad_debts(deps, start_after, limit)?;
+    Ok(BadDebtsResponse { bad_debts })
+}
+
+pub fn query_liabilities_by_amount(
+    deps: Deps,
+    env: Env,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    descending: Option<bool>,
+    block_height: Option<u64>,
+) -> StdResult<BorrowerInfosResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let borrower_infos: Vec<BorrowerInfoResponse> =
+        read_borrower_infos_by_amount(deps, start_after, limit, descending.unwrap_or(false))?;
+    Ok(BorrowerInfosResponse {
+        borrower_infos: accrue_borrower_infos(deps, env, block_height, borrower_infos)?,
+    })
+}
+
+/// Apply interest and rewards up to `block_height` to stored liabilities,
+/// the same way a single borrower info query does
+fn accrue_borrower_infos(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+    borrower_infos: Vec<BorrowerInfoResponse>,
+) -> StdResult<Vec<BorrowerInfoResponse>> {
+    if borrower_infos.is_empty() {
+        return Ok(borrower_infos);
+    }
+
+    let block_height = block_height.unwrap_or(env.block.height);
+
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+    compute_reward(&mut state, block_height);
+
+    Ok(borrower_infos
+        .into_iter()
+        .map(|borrower_info| {
+            let mut liability = BorrowerInfo {
+                interest_index: borrower_info.interest_index,
+                reward_index: borrower_info.reward_index,
+                loan_amount: borrower_info.loan_amount,
+                pending_rewards: borrower_info.pending_rewards,
+            };
+            compute_borrower_interest(&state, &mut liability);
+            compute_borrower_reward(&state, &mut liability);
+
+            BorrowerInfoResponse {
+                borrower: borrower_info.borrower,
+                interest_index: liability.interest_index,
+                reward_index: liability.reward_index,
+                loan_amount: liability.loan_amount,
+                pending_rewards: liability.pending_rewards,
+            }
+        })
+        .collect())
+}
+
+/// Outstanding liabilities without any aterra in circulation can only
+/// come from a corrupted state or an incomplete migration
+pub(crate) fn assert_market_state(
+    deps: Deps,
+    config: &Config,
+    state: &State,
+) -> Result<(), ContractError> {
+    if state.total_liabilities.is_zero() {
+        return Ok(());
+    }
+
+    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
+    if aterra_supply.is_zero() {
+        return Err(ContractError::InconsistentMarketState {});
+    }
+
+    Ok(())
 }
 
 fn assert_max_borrow_factor(
@@ -422,9 +1566,10 @@ fn assert_max_borrow_factor(
     let borrow_amount = Decimal256::from_uint256(borrow_amount);
 
     // Assert max borrow factor
-    if state.total_liabilities + borrow_amount
-        > (current_balance + state.total_liabilities - state.total_reserves)
-            * config.max_borrow_factor
+    let total_deposits =
+        checked_add_liability(current_balance, state.total_liabilities)? - state.total_reserves;
+    if checked_add_liability(state.total_liabilities, borrow_amount)?
+        > checked_mul_liability(total_deposits, config.max_borrow_factor)?
     {
         return Err(ContractError::MaxBorrowFactorReached(
             config.stable_denom.clone(),
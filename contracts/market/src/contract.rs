@@ -1,38 +1,69 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
+use crate::asset::{query_stable_balance, stable_transfer_msgs};
 use crate::borrow::{
-    borrow_stable, claim_rewards, compute_interest, compute_interest_raw, compute_reward,
-    query_borrower_info, query_borrower_infos, repay_stable, repay_stable_from_liquidation,
+    accrual_clock, borrow_stable, borrow_stable_for, borrow_stable_with_collateral, claim_rewards,
+    close_position, compute_interest, compute_interest_raw, compute_reward, deposit_and_borrow,
+    execute_liquidation, query_bad_debts, query_borrower_health, query_borrower_index,
+    query_borrower_info, query_borrower_infos, query_borrower_position, query_escrow,
+    query_interest_index_at, query_liabilities_by_amount, query_loan_amount_at,
+    query_required_borrow_limit, repay_from_collateral, repay_stable, repay_stable_for,
+    repay_stable_from_escrow, repay_stable_from_liquidation, withdraw_escrow, write_off_bad_debt,
+};
+use crate::deposit::{
+    compute_exchange_rate_raw, deposit_stable, query_simulate_deposit, query_simulate_redeem,
+    redeem_stable,
 };
-use crate::deposit::{compute_exchange_rate_raw, deposit_stable, redeem_stable};
 use crate::error::ContractError;
-use crate::querier::{query_anc_emission_rate, query_borrow_rate, query_target_deposit_rate};
+use crate::flash_loan::{assert_flash_loan_repaid, flash_loan};
+use crate::querier::{
+    query_anc_emission_rate, query_borrow_rate, query_target_deposit_rate, query_token_info,
+};
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_config, read_state, store_config, store_state, Config, State};
+use crate::staking::{
+    bond, claim_staking_rewards, query_staker_info, query_staker_infos, query_staking_state,
+    unbond, update_staking_emission_rate,
+};
+use crate::state::{
+    is_market, market_storage, market_storage_read, pop_pending_market, push_pending_market,
+    read_config, read_contract_version, read_flash_loan, read_is_borrow_whitelisted,
+    read_legacy_config, read_market_denom, read_market_token, read_markets,
+    read_ownership_proposal, read_reserves_funded, read_state, reindex_borrower_infos,
+    remove_borrow_whitelist_elem, remove_ownership_proposal, store_borrow_whitelist_elem,
+    store_config, store_contract_version, store_interest_index_checkpoint, store_market,
+    store_market_denom, store_market_token, store_ownership_proposal, store_reserves_funded,
+    store_state, Config, ContractVersion, LegacyConfig, OwnershipProposal, State,
+};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Deps,
-    DepsMut, Env, MessageInfo, Reply, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Binary, CanonicalAddr, Coin, CosmosMsg, Deps, DepsMut, Env,
+    Event, MessageInfo, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse};
 
 use moneymarket::common::optional_addr_validate;
 use moneymarket::interest_model::BorrowRateResponse;
 use moneymarket::market::{
-    ConfigResponse, Cw20HookMsg, EpochStateResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    ConfigResponse, Cw20HookMsg, DepositValueResponse, EpochStateResponse, ExecuteMsg,
+    InstantiateMsg, IsWhitelistedResponse, MarketOperation, MarketStateResponse, MarketsResponse,
+    MarketsResponseElem, MigrateMsg, PendingOwnerResponse, QueryMsg, ReservesFundedResponse,
+    StateResponse, TotalDepositsResponse, UtilizationResponse, WiredContractsResponse,
 };
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::querier::{query_supply, query_token_balance};
 use protobuf::Message;
 use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 pub const INITIAL_DEPOSIT_AMOUNT: u128 = 1000000;
 
+// version info for migration
+pub const CONTRACT_NAME: &str = "crates.io:moneymarket-market";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
@@ -44,13 +75,28 @@ pub fn instantiate(
         .map(|c| c.amount)
         .unwrap_or_else(Uint128::zero);
 
-    if initial_deposit != Uint128::from(INITIAL_DEPOSIT_AMOUNT) {
+    // Token markets cannot receive tokens before they exist,
+    // so they start without the initial deposit
+    let stable_token = msg
+        .stable_token
+        .map(|stable_token| deps.api.addr_canonicalize(&stable_token))
+        .transpose()?;
+    let initial_deposit_amount = if stable_token.is_some() {
+        Uint128::zero()
+    } else {
+        Uint128::from(INITIAL_DEPOSIT_AMOUNT)
+    };
+
+    if initial_deposit != initial_deposit_amount {
         return Err(ContractError::InitialFundsNotDeposited(
             INITIAL_DEPOSIT_AMOUNT,
             msg.stable_denom,
         ));
     }
 
+    let (aterra_name, aterra_symbol) =
+        aterra_token_info(&msg.stable_denom, msg.aterra_name, msg.aterra_symbol);
+
     store_config(
         deps.storage,
         &Config {
@@ -63,7 +109,33 @@ pub fn instantiate(
             collector_contract: CanonicalAddr::from(vec![]),
             distributor_contract: CanonicalAddr::from(vec![]),
             stable_denom: msg.stable_denom.clone(),
+            stable_token,
             max_borrow_factor: msg.max_borrow_factor,
+            max_accrual_blocks: None,
+            min_borrow_rate: Decimal256::zero(),
+            insurance_fund: None,
+            insurance_split: Decimal256::zero(),
+            flash_loan_fee: Decimal256::zero(),
+            max_borrow_amount: None,
+            per_account_borrow_cap: None,
+            guardian: None,
+            paused_operations: vec![],
+            block_time: None,
+            min_borrow_amount: Uint256::zero(),
+            dust_threshold: Uint256::zero(),
+            borrow_whitelist_enabled: false,
+            aterra_name: aterra_name.clone(),
+            aterra_symbol: aterra_symbol.clone(),
+            aterra_code_id: msg.aterra_code_id,
+            market_id: msg.market_id,
+        },
+    )?;
+
+    store_contract_version(
+        deps.storage,
+        &ContractVersion {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
         },
     )?;
 
@@ -82,33 +154,179 @@ pub fn instantiate(
         },
     )?;
 
-    Ok(
-        Response::new().add_submessages(vec![SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Instantiate {
-                admin: None,
-                code_id: msg.aterra_code_id,
-                funds: vec![],
-                label: "".to_string(),
-                msg: to_binary(&TokenInstantiateMsg {
-                    name: format!("Anchor Terra {}", msg.stable_denom[1..].to_uppercase()),
-                    symbol: format!(
-                        "a{}T",
-                        msg.stable_denom[1..(msg.stable_denom.len() - 1)].to_uppercase()
-                    ),
-                    decimals: 6u8,
-                    initial_balances: vec![Cw20Coin {
+    let mut submsgs = vec![aterra_instantiate_msg(
+        &env,
+        msg.aterra_code_id,
+        aterra_name,
+        aterra_symbol,
+        msg.stable_decimals.unwrap_or(6u8),
+        initial_deposit,
+        1,
+    )?];
+    for denom in msg.additional_denoms.unwrap_or_default() {
+        submsgs.push(create_market(
+            deps.branch(),
+            &env,
+            &info,
+            denom,
+            msg.aterra_code_id,
+            msg.anc_emission_rate,
+        )?);
+    }
+
+    Ok(Response::new().add_submessages(submsgs))
+}
+
+/// aterra token info is derived from the stable denom unless given
+fn aterra_token_info(
+    stable_denom: &str,
+    aterra_name: Option<String>,
+    aterra_symbol: Option<String>,
+) -> (String, String) {
+    let aterra_name =
+        aterra_name.unwrap_or_else(|| format!("Anchor Terra {}", stable_denom[1..].to_uppercase()));
+    let aterra_symbol = aterra_symbol.unwrap_or_else(|| {
+        format!(
+            "a{}T",
+            stable_denom[1..(stable_denom.len() - 1)].to_uppercase()
+        )
+    });
+
+    (aterra_name, aterra_symbol)
+}
+
+/// Instantiate an aterra token minted by this contract, holding
+/// the initial deposit; its address is registered on `reply_id`
+fn aterra_instantiate_msg(
+    env: &Env,
+    code_id: u64,
+    name: String,
+    symbol: String,
+    decimals: u8,
+    initial_deposit: Uint128,
+    reply_id: u64,
+) -> StdResult<SubMsg> {
+    Ok(SubMsg::reply_on_success(
+        CosmosMsg::Wasm(WasmMsg::Instantiate {
+            admin: None,
+            code_id,
+            funds: vec![],
+            label: "".to_string(),
+            msg: to_binary(&TokenInstantiateMsg {
+                name,
+                symbol,
+                decimals,
+                initial_balances: if initial_deposit.is_zero() {
+                    vec![]
+                } else {
+                    vec![Cw20Coin {
                         address: env.contract.address.to_string(),
-                        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-                    }],
-                    mint: Some(MinterResponse {
-                        minter: env.contract.address.to_string(),
-                        cap: None,
-                    }),
-                })?,
-            }),
-            1,
-        )]),
-    )
+                        amount: initial_deposit,
+                    }]
+                },
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+        }),
+        reply_id,
+    ))
+}
+
+/// Set up the storage of an additional market of the native `denom`,
+/// sharing the wired contracts of the primary market but its overseer.
+/// Returns the instantiation of its aterra token
+fn create_market(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    denom: String,
+    aterra_code_id: u64,
+    anc_emission_rate: Decimal256,
+) -> Result<SubMsg, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if denom == config.stable_denom || is_market(deps.storage, &denom) {
+        return Err(ContractError::MarketAlreadyExists(denom));
+    }
+
+    let initial_deposit = info
+        .funds
+        .iter()
+        .find(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .unwrap_or_else(Uint128::zero);
+    if initial_deposit != Uint128::from(INITIAL_DEPOSIT_AMOUNT) {
+        return Err(ContractError::InitialFundsNotDeposited(
+            INITIAL_DEPOSIT_AMOUNT,
+            denom,
+        ));
+    }
+
+    store_market(deps.storage, &denom)?;
+    push_pending_market(deps.storage, &denom)?;
+
+    let (aterra_name, aterra_symbol) = aterra_token_info(&denom, None, None);
+    let mut storage = market_storage(deps.storage, &denom);
+    store_market_denom(&mut storage, &denom)?;
+    store_config(
+        &mut storage,
+        &Config {
+            contract_addr: config.contract_addr,
+            owner_addr: config.owner_addr,
+            aterra_contract: CanonicalAddr::from(vec![]),
+            overseer_contract: CanonicalAddr::from(vec![]),
+            interest_model: config.interest_model,
+            distribution_model: config.distribution_model,
+            collector_contract: config.collector_contract,
+            distributor_contract: config.distributor_contract,
+            stable_denom: denom.clone(),
+            stable_token: None,
+            max_borrow_factor: config.max_borrow_factor,
+            max_accrual_blocks: None,
+            min_borrow_rate: Decimal256::zero(),
+            insurance_fund: None,
+            insurance_split: Decimal256::zero(),
+            flash_loan_fee: Decimal256::zero(),
+            max_borrow_amount: None,
+            per_account_borrow_cap: None,
+            guardian: None,
+            paused_operations: vec![],
+            block_time: None,
+            min_borrow_amount: Uint256::zero(),
+            dust_threshold: Uint256::zero(),
+            borrow_whitelist_enabled: false,
+            aterra_name: aterra_name.clone(),
+            aterra_symbol: aterra_symbol.clone(),
+            aterra_code_id,
+            market_id: None,
+        },
+    )?;
+
+    store_state(
+        &mut storage,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate,
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )?;
+
+    Ok(aterra_instantiate_msg(
+        env,
+        aterra_code_id,
+        aterra_name,
+        aterra_symbol,
+        6u8,
+        initial_deposit,
+        2,
+    )?)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -118,7 +336,24 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    match msg {
+    // hooks of the aterra of an additional market run on that market
+    if let ExecuteMsg::Receive(_) = msg {
+        let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+        if let Some(denom) = read_market_token(deps.storage, &sender_raw)? {
+            return execute_market(deps, env, info, denom, msg);
+        }
+    }
+
+    let config: Config = read_config(deps.storage)?;
+
+    // Nothing else may run against the lent out balance
+    if read_flash_loan(deps.storage)?.is_some()
+        && !matches!(msg, ExecuteMsg::AssertFlashLoanRepaid {})
+    {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
+    let res = match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::RegisterContracts {
             overseer_contract,
@@ -130,6 +365,7 @@ pub fn execute(
             let api = deps.api;
             register_contracts(
                 deps,
+                env,
                 api.addr_validate(&overseer_contract)?,
                 api.addr_validate(&interest_model)?,
                 api.addr_validate(&distribution_model)?,
@@ -138,20 +374,78 @@ pub fn execute(
             )
         }
         ExecuteMsg::UpdateConfig {
-            owner_addr,
             interest_model,
             distribution_model,
             max_borrow_factor,
+            max_accrual_blocks,
+            min_borrow_rate,
+            insurance_fund,
+            remove_insurance_fund,
+            insurance_split,
+            flash_loan_fee,
+            max_borrow_amount,
+            per_account_borrow_cap,
+            guardian,
+            remove_guardian,
+            block_time,
+            min_borrow_amount,
+            dust_threshold,
+            overseer_contract,
+            force,
         } => {
             let api = deps.api;
             update_config(
                 deps,
                 env,
                 info,
-                optional_addr_validate(api, owner_addr)?,
                 optional_addr_validate(api, interest_model)?,
                 optional_addr_validate(api, distribution_model)?,
                 max_borrow_factor,
+                max_accrual_blocks,
+                min_borrow_rate,
+                optional_addr_validate(api, insurance_fund)?,
+                remove_insurance_fund.unwrap_or(false),
+                insurance_split,
+                flash_loan_fee,
+                max_borrow_amount,
+                per_account_borrow_cap,
+                optional_addr_validate(api, guardian)?,
+                remove_guardian.unwrap_or(false),
+                block_time,
+                min_borrow_amount,
+                dust_threshold,
+                optional_addr_validate(api, overseer_contract)?,
+                force.unwrap_or(false),
+            )
+        }
+        ExecuteMsg::ProposeNewOwner { owner, expires_at } => {
+            let api = deps.api;
+            propose_new_owner(deps, env, info, api.addr_validate(&owner)?, expires_at)
+        }
+        ExecuteMsg::ClaimOwnership {} => claim_ownership(deps, env, info),
+        ExecuteMsg::UpdateEmissionRate { emission_rate } => {
+            update_emission_rate(deps, env, info, emission_rate)
+        }
+        ExecuteMsg::Pause { operations } => pause(deps, info, operations),
+        ExecuteMsg::Unpause { operations } => unpause(deps, info, operations),
+        ExecuteMsg::UpdateAterraCodeId { code_id } => update_aterra_code_id(deps, info, code_id),
+        ExecuteMsg::UpdateBorrowWhitelist {
+            enabled,
+            add,
+            remove,
+        } => {
+            let api = deps.api;
+            update_borrow_whitelist(
+                deps,
+                info,
+                enabled,
+                add.iter()
+                    .map(|addr| api.addr_validate(addr))
+                    .collect::<StdResult<Vec<Addr>>>()?,
+                remove
+                    .iter()
+                    .map(|addr| api.addr_validate(addr))
+                    .collect::<StdResult<Vec<Addr>>>()?,
             )
         }
         ExecuteMsg::ExecuteEpochOperations {
@@ -168,8 +462,37 @@ pub fn execute(
             threshold_deposit_rate,
             distributed_interest,
         ),
-        ExecuteMsg::DepositStable {} => deposit_stable(deps, env, info),
-        ExecuteMsg::BorrowStable { borrow_amount, to } => {
+        ExecuteMsg::WithdrawReserves { amount, recipient } => {
+            let api = deps.api;
+            withdraw_reserves(
+                deps,
+                env,
+                info,
+                amount,
+                optional_addr_validate(api, recipient)?,
+            )
+        }
+        ExecuteMsg::WriteOffBadDebt { borrower } => {
+            let api = deps.api;
+            write_off_bad_debt(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::DepositStable {
+            recipient,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_native_stable(&config)?;
+            assert_not_paused(&config, MarketOperation::Deposit)?;
+            let api = deps.api;
+            deposit_stable(deps, env, info, optional_addr_validate(api, recipient)?)
+        }
+        ExecuteMsg::BorrowStable {
+            borrow_amount,
+            to,
+            deadline,
+        } => {
+            assert_deadline(&env, deadline)?;
+            assert_not_paused(&config, MarketOperation::Borrow)?;
             let api = deps.api;
             borrow_stable(
                 deps,
@@ -179,11 +502,66 @@ pub fn execute(
                 optional_addr_validate(api, to)?,
             )
         }
-        ExecuteMsg::RepayStable {} => repay_stable(deps, env, info),
+        ExecuteMsg::BorrowStableWithCollateral {
+            collaterals,
+            borrow_amount,
+        } => {
+            assert_not_paused(&config, MarketOperation::Borrow)?;
+            borrow_stable_with_collateral(deps, env, info, collaterals, borrow_amount)
+        }
+        ExecuteMsg::DepositAndBorrow { borrow_amount } => {
+            assert_native_stable(&config)?;
+            assert_not_paused(&config, MarketOperation::Deposit)?;
+            assert_not_paused(&config, MarketOperation::Borrow)?;
+            deposit_and_borrow(deps, env, info, borrow_amount)
+        }
+        ExecuteMsg::WithdrawEscrow { amount } => withdraw_escrow(deps, env, info, amount),
+        ExecuteMsg::BorrowStableFor {
+            borrower,
+            borrow_amount,
+        } => {
+            let api = deps.api;
+            borrow_stable_for(
+                deps,
+                env,
+                info,
+                api.addr_validate(&borrower)?,
+                borrow_amount,
+            )
+        }
+        ExecuteMsg::RepayStable { deadline } => {
+            assert_deadline(&env, deadline)?;
+            assert_native_stable(&config)?;
+            assert_not_paused(&config, MarketOperation::Repay)?;
+            repay_stable(deps, env, info)
+        }
+        ExecuteMsg::RepayStableFor { borrower, deadline } => {
+            assert_deadline(&env, deadline)?;
+            assert_native_stable(&config)?;
+            assert_not_paused(&config, MarketOperation::Repay)?;
+            let api = deps.api;
+            repay_stable_for(deps, env, info, api.addr_validate(&borrower)?)
+        }
+        ExecuteMsg::ExecuteLiquidation {
+            borrower,
+            max_repay,
+        } => {
+            assert_not_paused(&config, MarketOperation::Liquidate)?;
+            let api = deps.api;
+            execute_liquidation(deps, env, info, api.addr_validate(&borrower)?, max_repay)
+        }
+        ExecuteMsg::ClosePosition {} => {
+            assert_native_stable(&config)?;
+            assert_not_paused(&config, MarketOperation::Repay)?;
+            close_position(deps, env, info)
+        }
         ExecuteMsg::RepayStableFromLiquidation {
             borrower,
             prev_balance,
         } => {
+            // Liquidations are paused on their own, so pausing repays
+            // in an emergency does not let bad debt build up
+            assert_not_paused(&config, MarketOperation::Liquidate)?;
             let api = deps.api;
             repay_stable_from_liquidation(
                 deps,
@@ -193,35 +571,248 @@ pub fn execute(
                 prev_balance,
             )
         }
+        ExecuteMsg::RepayStableFromEscrow { borrower } => {
+            assert_not_paused(&config, MarketOperation::Liquidate)?;
+            let api = deps.api;
+            repay_stable_from_escrow(deps, env, info, api.addr_validate(&borrower)?)
+        }
         ExecuteMsg::ClaimRewards { to } => {
             let api = deps.api;
             claim_rewards(deps, env, info, optional_addr_validate(api, to)?)
         }
-    }
+        ExecuteMsg::Unbond { amount } => unbond(deps, env, info, amount),
+        ExecuteMsg::ClaimStakingRewards { to } => {
+            let api = deps.api;
+            claim_staking_rewards(deps, env, info, optional_addr_validate(api, to)?)
+        }
+        ExecuteMsg::FundReserves {} => {
+            assert_native_stable(&config)?;
+            fund_reserves(deps, env, info)
+        }
+        ExecuteMsg::UpdateStakingEmissionRate { emission_rate } => {
+            update_staking_emission_rate(deps, env, info, emission_rate)
+        }
+        ExecuteMsg::FlashLoan {
+            amount,
+            msg,
+            recipient,
+        } => {
+            assert_not_paused(&config, MarketOperation::Borrow)?;
+            let api = deps.api;
+            flash_loan(deps, env, amount, msg, api.addr_validate(&recipient)?)
+        }
+        ExecuteMsg::AssertFlashLoanRepaid {} => assert_flash_loan_repaid(deps, env, info),
+        ExecuteMsg::AddMarket {
+            denom,
+            aterra_code_id,
+        } => add_market(deps, env, info, denom, aterra_code_id),
+        ExecuteMsg::Market { denom, msg } => execute_market(deps, env, info, denom, *msg),
+    }?;
+
+    Ok(match config.market_id {
+        Some(market_id) => res.add_attribute("market_id", market_id),
+        None => res,
+    })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
-        1 => {
-            // get new token's contract address
-            let res: MsgInstantiateContractResponse = Message::parse_from_bytes(
-                msg.result.unwrap().data.unwrap().as_slice(),
-            )
-            .map_err(|_| {
-                ContractError::Std(StdError::parse_err(
-                    "MsgInstantiateContractResponse",
-                    "failed to parse data",
-                ))
-            })?;
-            let token_addr = Addr::unchecked(res.get_contract_address());
+        1 => register_aterra(deps, instantiated_address(msg)?),
+        2 => {
+            // aterra of an additional market, replied in the order they were added
+            let token_addr = instantiated_address(msg)?;
+            let denom = pop_pending_market(deps.storage)?;
+            let token_addr_raw = deps.api.addr_canonicalize(token_addr.as_str())?;
+            store_market_token(deps.storage, &token_addr_raw, &denom)?;
 
-            register_aterra(deps, token_addr)
+            let res = register_aterra(
+                DepsMut {
+                    storage: &mut market_storage(deps.storage, &denom),
+                    api: deps.api,
+                    querier: deps.querier,
+                },
+                token_addr,
+            )?;
+            Ok(res.add_attribute("market", denom))
         }
         _ => Err(ContractError::InvalidReplyId {}),
     }
 }
 
+fn instantiated_address(msg: Reply) -> Result<Addr, ContractError> {
+    // get new token's contract address
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(msg.result.unwrap().data.unwrap().as_slice()).map_err(|_| {
+            ContractError::Std(StdError::parse_err(
+                "MsgInstantiateContractResponse",
+                "failed to parse data",
+            ))
+        })?;
+
+    Ok(Addr::unchecked(res.get_contract_address()))
+}
+
+/// Execute `msg` on the market of `denom`
+fn execute_market(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if read_market_denom(deps.storage)?.is_some() {
+        return Err(ContractError::NotPrimaryMarket {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    if denom == config.stable_denom {
+        return execute(deps, env, info, msg);
+    }
+
+    if !is_market(deps.storage, &denom) {
+        return Err(ContractError::MarketNotFound(denom));
+    }
+
+    let res = execute(
+        DepsMut {
+            storage: &mut market_storage(deps.storage, &denom),
+            api: deps.api,
+            querier: deps.querier,
+        },
+        env,
+        info,
+        msg,
+    )?;
+    Ok(res.add_attribute("market", denom))
+}
+
+pub fn add_market(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: String,
+    aterra_code_id: u64,
+) -> Result<Response, ContractError> {
+    if read_market_denom(deps.storage)?.is_some() {
+        return Err(ContractError::NotPrimaryMarket {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the new market starts from the current emission rate
+    let anc_emission_rate = read_state(deps.storage)?.anc_emission_rate;
+    let aterra_instantiate = create_market(
+        deps,
+        &env,
+        &info,
+        denom.clone(),
+        aterra_code_id,
+        anc_emission_rate,
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(aterra_instantiate)
+        .add_attributes(vec![attr("action", "add_market"), attr("denom", denom)]))
+}
+
+/// Encode a message this market executes on itself, addressed
+/// to its own market when it is an additional one
+pub fn self_msg(storage: &dyn Storage, msg: ExecuteMsg) -> StdResult<Binary> {
+    match read_market_denom(storage)? {
+        Some(denom) => to_binary(&ExecuteMsg::Market {
+            denom,
+            msg: Box::new(msg),
+        }),
+        None => to_binary(&msg),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let prev_version = match read_contract_version(deps.storage)? {
+        Some(prev_version) => {
+            if prev_version.contract != CONTRACT_NAME {
+                return Err(ContractError::InvalidMigrationContract(
+                    prev_version.contract,
+                ));
+            }
+
+            prev_version.version
+        }
+        None => {
+            // markets without a stored version keep the original layout
+            migrate_legacy_config(deps.branch(), msg)?;
+            reindex_borrower_infos(deps.storage)?;
+            "unversioned".to_string()
+        }
+    };
+
+    store_contract_version(
+        deps.storage,
+        &ContractVersion {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "migrate"),
+        attr("from_version", prev_version),
+        attr("to_version", CONTRACT_VERSION),
+    ]))
+}
+
+fn migrate_legacy_config(deps: DepsMut, msg: MigrateMsg) -> Result<(), ContractError> {
+    let legacy_config: LegacyConfig = read_legacy_config(deps.storage)?;
+    let aterra_code_id = msg
+        .aterra_code_id
+        .ok_or(ContractError::MissingAterraCodeId {})?;
+    let token_info = query_token_info(
+        deps.as_ref(),
+        deps.api.addr_humanize(&legacy_config.aterra_contract)?,
+    )?;
+
+    store_config(
+        deps.storage,
+        &Config {
+            contract_addr: legacy_config.contract_addr,
+            owner_addr: legacy_config.owner_addr,
+            aterra_contract: legacy_config.aterra_contract,
+            interest_model: legacy_config.interest_model,
+            distribution_model: legacy_config.distribution_model,
+            overseer_contract: legacy_config.overseer_contract,
+            collector_contract: legacy_config.collector_contract,
+            distributor_contract: legacy_config.distributor_contract,
+            stable_denom: legacy_config.stable_denom,
+            stable_token: None,
+            max_borrow_factor: legacy_config.max_borrow_factor,
+            max_accrual_blocks: None,
+            min_borrow_rate: Decimal256::zero(),
+            insurance_fund: None,
+            insurance_split: Decimal256::zero(),
+            flash_loan_fee: Decimal256::zero(),
+            max_borrow_amount: None,
+            per_account_borrow_cap: None,
+            guardian: None,
+            paused_operations: vec![],
+            block_time: None,
+            min_borrow_amount: Uint256::zero(),
+            dust_threshold: Uint256::zero(),
+            borrow_whitelist_enabled: false,
+            aterra_name: token_info.name,
+            aterra_symbol: token_info.symbol,
+            aterra_code_id,
+            market_id: None,
+        },
+    )?;
+
+    Ok(())
+}
+
 pub fn receive_cw20(
     deps: DepsMut,
     env: Env,
@@ -230,7 +821,59 @@ pub fn receive_cw20(
 ) -> Result<Response, ContractError> {
     let contract_addr = info.sender;
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::RedeemStable {}) => {
+        Ok(Cw20HookMsg::RedeemStable {
+            deadline,
+            target_stable,
+        }) => {
+            assert_deadline(&env, deadline)?;
+
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            assert_not_paused(&config, MarketOperation::Redeem)?;
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount, target_stable)
+        }
+        Ok(Cw20HookMsg::RepayFromCollateral {}) => {
+            // only asset contract can execute this message
+            let config: Config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            assert_not_paused(&config, MarketOperation::Repay)?;
+
+            let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            repay_from_collateral(deps, env, cw20_sender_addr, cw20_msg.amount, false)
+        }
+        Ok(Cw20HookMsg::DepositStable { recipient }) => {
+            let config: Config = read_config(deps.storage)?;
+            let info = stable_token_info(deps.as_ref(), &config, &contract_addr, &cw20_msg)?;
+
+            assert_not_paused(&config, MarketOperation::Deposit)?;
+
+            let api = deps.api;
+            deposit_stable(deps, env, info, optional_addr_validate(api, recipient)?)
+        }
+        Ok(Cw20HookMsg::RepayStable {}) => {
+            let config: Config = read_config(deps.storage)?;
+            let info = stable_token_info(deps.as_ref(), &config, &contract_addr, &cw20_msg)?;
+
+            assert_not_paused(&config, MarketOperation::Repay)?;
+
+            repay_stable(deps, env, info)
+        }
+        Ok(Cw20HookMsg::FundReserves {}) => {
+            let config: Config = read_config(deps.storage)?;
+            let info = stable_token_info(deps.as_ref(), &config, &contract_addr, &cw20_msg)?;
+
+            fund_reserves(deps, env, info)
+        }
+        Ok(Cw20HookMsg::Bond {}) => {
             // only asset contract can execute this message
             let config: Config = read_config(deps.storage)?;
             if deps.api.addr_canonicalize(contract_addr.as_str())? != config.aterra_contract {
@@ -238,19 +881,73 @@ pub fn receive_cw20(
             }
 
             let cw20_sender_addr = deps.api.addr_validate(&cw20_msg.sender)?;
-            redeem_stable(deps, env, cw20_sender_addr, cw20_msg.amount)
+            bond(deps, env, cw20_sender_addr, cw20_msg.amount.into())
         }
         _ => Err(ContractError::MissingRedeemStableHook {}),
     }
 }
 
+/// Funds of a stable token hook, in the form native funds are attached
+fn stable_token_info(
+    deps: Deps,
+    config: &Config,
+    token: &Addr,
+    cw20_msg: &Cw20ReceiveMsg,
+) -> Result<MessageInfo, ContractError> {
+    // only the stable token contract can execute this message
+    match &config.stable_token {
+        Some(stable_token) if *stable_token == deps.api.addr_canonicalize(token.as_str())? => (),
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    Ok(MessageInfo {
+        sender: deps.api.addr_validate(&cw20_msg.sender)?,
+        funds: vec![Coin {
+            denom: config.stable_denom.clone(),
+            amount: cw20_msg.amount,
+        }],
+    })
+}
+
+fn assert_native_stable(config: &Config) -> Result<(), ContractError> {
+    if config.stable_token.is_some() {
+        return Err(ContractError::NativeStableNotAccepted {});
+    }
+
+    Ok(())
+}
+
+fn assert_not_paused(config: &Config, operation: MarketOperation) -> Result<(), ContractError> {
+    if config.paused_operations.contains(&operation) {
+        return Err(ContractError::OperationPaused(operation.to_string()));
+    }
+
+    Ok(())
+}
+
+fn assert_deadline(env: &Env, deadline: Option<u64>) -> Result<(), ContractError> {
+    match deadline {
+        Some(deadline) if env.block.height > deadline => Err(ContractError::TransactionExpired {}),
+        _ => Ok(()),
+    }
+}
+
 pub fn register_aterra(deps: DepsMut, token_addr: Addr) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
+    let token_addr_raw = deps.api.addr_canonicalize(token_addr.as_str())?;
     if config.aterra_contract != CanonicalAddr::from(vec![]) {
+        // Retried registration of the same token is a no-op
+        if config.aterra_contract == token_addr_raw {
+            return Ok(Response::new().add_attributes(vec![
+                attr("aterra", token_addr),
+                attr("already_registered", "true"),
+            ]));
+        }
+
         return Err(ContractError::Unauthorized {});
     }
 
-    config.aterra_contract = deps.api.addr_canonicalize(token_addr.as_str())?;
+    config.aterra_contract = token_addr_raw;
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(vec![attr("aterra", token_addr)]))
@@ -258,6 +955,7 @@ pub fn register_aterra(deps: DepsMut, token_addr: Addr) -> Result<Response, Cont
 
 pub fn register_contracts(
     deps: DepsMut,
+    env: Env,
     overseer_contract: Addr,
     interest_model: Addr,
     distribution_model: Addr,
@@ -274,6 +972,8 @@ pub fn register_contracts(
         return Err(ContractError::Unauthorized {});
     }
 
+    assert_interest_model(deps.as_ref(), &env, &config, &interest_model)?;
+
     config.overseer_contract = deps.api.addr_canonicalize(overseer_contract.as_str())?;
     config.interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
     config.distribution_model = deps.api.addr_canonicalize(distribution_model.as_str())?;
@@ -281,17 +981,32 @@ pub fn register_contracts(
     config.distributor_contract = deps.api.addr_canonicalize(distributor_contract.as_str())?;
     store_config(deps.storage, &config)?;
 
-    Ok(Response::default())
+    Ok(Response::new().add_attributes(vec![attr("action", "register_contracts")]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    owner_addr: Option<Addr>,
     interest_model: Option<Addr>,
     distribution_model: Option<Addr>,
     max_borrow_factor: Option<Decimal256>,
+    max_accrual_blocks: Option<u64>,
+    min_borrow_rate: Option<Decimal256>,
+    insurance_fund: Option<Addr>,
+    remove_insurance_fund: bool,
+    insurance_split: Option<Decimal256>,
+    flash_loan_fee: Option<Decimal256>,
+    max_borrow_amount: Option<Uint256>,
+    per_account_borrow_cap: Option<Uint256>,
+    guardian: Option<Addr>,
+    remove_guardian: bool,
+    block_time: Option<u64>,
+    min_borrow_amount: Option<Uint256>,
+    dust_threshold: Option<Uint256>,
+    overseer_contract: Option<Addr>,
+    force: bool,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -300,17 +1015,40 @@ pub fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner_addr) = owner_addr {
-        config.owner_addr = deps.api.addr_canonicalize(owner_addr.as_str())?;
-    }
-
-    if interest_model.is_some() {
+    let mut attributes = vec![attr("action", "update_config")];
+    if interest_model.is_some() || min_borrow_rate.is_some() || block_time.is_some() {
+        // Checkpoint the interest accrued with the previous rates,
+        // so the new ones only apply from this block on
         let mut state: State = read_state(deps.storage)?;
-        compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+        compute_interest(
+            deps.as_ref(),
+            &env,
+            &config,
+            &mut state,
+            env.block.height,
+            None,
+        )?;
+
+        if let Some(block_time) = block_time {
+            // Interest is accrued up to now, so the accrual clock
+            // converts exactly to the one of the new mode
+            config.block_time = if block_time == 0 {
+                None
+            } else {
+                Some(block_time)
+            };
+            state.last_interest_updated = accrual_clock(&config, &env, env.block.height);
+        }
+
         store_state(deps.storage, &state)?;
 
         if let Some(interest_model) = interest_model {
+            assert_interest_model(deps.as_ref(), &env, &config, &interest_model)?;
             config.interest_model = deps.api.addr_canonicalize(interest_model.as_str())?;
+            attributes.push(attr(
+                "interest_model_changed_at_height",
+                env.block.height.to_string(),
+            ));
         }
     }
 
@@ -322,11 +1060,427 @@ pub fn update_config(
         config.max_borrow_factor = max_borrow_factor;
     }
 
-    store_config(deps.storage, &config)?;
-    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
-}
+    if let Some(max_accrual_blocks) = max_accrual_blocks {
+        config.max_accrual_blocks = if max_accrual_blocks == 0 {
+            None
+        } else {
+            Some(max_accrual_blocks)
+        };
+    }
 
-pub fn execute_epoch_operations(
+    if let Some(min_borrow_rate) = min_borrow_rate {
+        config.min_borrow_rate = min_borrow_rate;
+    }
+
+    if let Some(insurance_fund) = insurance_fund {
+        config.insurance_fund = Some(deps.api.addr_canonicalize(insurance_fund.as_str())?);
+    } else if remove_insurance_fund {
+        config.insurance_fund = None;
+    }
+
+    if let Some(insurance_split) = insurance_split {
+        if insurance_split > Decimal256::one() {
+            return Err(ContractError::InvalidInsuranceSplit {});
+        }
+
+        config.insurance_split = insurance_split;
+    }
+
+    if let Some(flash_loan_fee) = flash_loan_fee {
+        config.flash_loan_fee = flash_loan_fee;
+    }
+
+    if let Some(max_borrow_amount) = max_borrow_amount {
+        config.max_borrow_amount = if max_borrow_amount.is_zero() {
+            None
+        } else {
+            Some(max_borrow_amount)
+        };
+    }
+
+    if let Some(per_account_borrow_cap) = per_account_borrow_cap {
+        config.per_account_borrow_cap = if per_account_borrow_cap.is_zero() {
+            None
+        } else {
+            Some(per_account_borrow_cap)
+        };
+    }
+
+    if let Some(min_borrow_amount) = min_borrow_amount {
+        config.min_borrow_amount = min_borrow_amount;
+    }
+
+    if let Some(dust_threshold) = dust_threshold {
+        config.dust_threshold = dust_threshold;
+    }
+
+    if let Some(guardian) = guardian {
+        config.guardian = Some(deps.api.addr_canonicalize(guardian.as_str())?);
+    } else if remove_guardian {
+        config.guardian = None;
+    }
+
+    if let Some(overseer_contract) = overseer_contract {
+        // Liquidations of outstanding loans are routed through the overseer
+        let state: State = read_state(deps.storage)?;
+        if !state.total_liabilities.is_zero() && !force {
+            return Err(ContractError::OverseerChangeWithActiveLoans {});
+        }
+
+        config.overseer_contract = deps.api.addr_canonicalize(overseer_contract.as_str())?;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn update_borrow_whitelist(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: Option<bool>,
+    add: Vec<Addr>,
+    remove: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(enabled) = enabled {
+        config.borrow_whitelist_enabled = enabled;
+        store_config(deps.storage, &config)?;
+    }
+
+    for borrower in add.iter() {
+        let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+        store_borrow_whitelist_elem(deps.storage, &borrower_raw)?;
+    }
+
+    for borrower in remove.iter() {
+        let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+        remove_borrow_whitelist_elem(deps.storage, &borrower_raw);
+    }
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_borrow_whitelist")]))
+}
+
+pub fn update_emission_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    emission_rate: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && sender_raw != config.distributor_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Compute interest and reward before updating anc_emission_rate
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
+    compute_reward(&mut state, env.block.height);
+
+    state.anc_emission_rate = emission_rate;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_emission_rate"),
+        attr("anc_emission_rate", emission_rate.to_string()),
+    ]))
+}
+
+pub fn pause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<MarketOperation>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && Some(sender_raw) != config.guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for operation in operations.iter() {
+        if !config.paused_operations.contains(operation) {
+            config.paused_operations.push(operation.clone());
+        }
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "pause"),
+        attr("operations", join_operations(&operations)),
+    ]))
+}
+
+pub fn unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Vec<MarketOperation>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config
+        .paused_operations
+        .retain(|operation| !operations.contains(operation));
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unpause"),
+        attr("operations", join_operations(&operations)),
+    ]))
+}
+
+fn join_operations(operations: &[MarketOperation]) -> String {
+    operations
+        .iter()
+        .map(|operation| operation.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Addr,
+    expires_at: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if expires_at <= env.block.height {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+
+    store_ownership_proposal(
+        deps.storage,
+        &OwnershipProposal {
+            owner: deps.api.addr_canonicalize(owner.as_str())?,
+            expires_at,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_owner"),
+        attr("owner", owner),
+        attr("expires_at", expires_at.to_string()),
+    ]))
+}
+
+pub fn claim_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let proposal = match read_ownership_proposal(deps.storage)? {
+        Some(proposal) => proposal,
+        None => return Err(ContractError::Unauthorized {}),
+    };
+
+    // only the proposed owner can claim
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != proposal.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.height > proposal.expires_at {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner_addr = sender_raw;
+    store_config(deps.storage, &config)?;
+    remove_ownership_proposal(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "claim_ownership"),
+        attr("owner", info.sender),
+    ]))
+}
+
+pub fn update_aterra_code_id(
+    deps: DepsMut,
+    info: MessageInfo,
+    code_id: u64,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.aterra_code_id = code_id;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_aterra_code_id"),
+        attr("code_id", code_id.to_string()),
+    ]))
+}
+
+pub fn withdraw_reserves(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint256>,
+    recipient: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && sender_raw != config.collector_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Accrue interest so that reserves are up to date
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
+    compute_reward(&mut state, env.block.height);
+
+    // Reserves can only be paid out of the current balance
+    let balance: Uint256 = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+    let available_reserves = std::cmp::min(state.total_reserves * Uint256::one(), balance);
+    let amount = amount.unwrap_or(available_reserves);
+    if amount.is_zero() || amount > available_reserves {
+        return Err(ContractError::InsufficientReserves(
+            available_reserves.into(),
+        ));
+    }
+
+    state.total_reserves = state.total_reserves - Decimal256::from_uint256(amount);
+    store_state(deps.storage, &state)?;
+
+    // The insurance fund takes its share as in the epoch operations
+    let recipient = recipient.unwrap_or(info.sender);
+    let transfers = reserve_transfers(deps.as_ref(), &config, recipient.to_string(), amount)?;
+    Ok(Response::new()
+        .add_messages(stable_transfer_msgs(deps.as_ref(), &config, transfers)?)
+        .add_attributes(vec![
+            attr("action", "withdraw_reserves"),
+            attr("recipient", recipient),
+            attr("amount", amount),
+            attr("total_reserves", state.total_reserves.to_string()),
+        ]))
+}
+
+/// Add the sent stable asset to the reserves. The balance and the
+/// reserves grow together, so the exchange rate is left unchanged
+pub fn fund_reserves(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+
+    if amount.is_zero() {
+        return Err(ContractError::ZeroDeposit(config.stable_denom));
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        Some(amount),
+    )?;
+    compute_reward(&mut state, env.block.height);
+
+    state.total_reserves += Decimal256::from_uint256(amount);
+    store_state(deps.storage, &state)?;
+
+    let reserves_funded = read_reserves_funded(deps.storage)? + amount;
+    store_reserves_funded(deps.storage, &reserves_funded)?;
+
+    Ok(Response::new()
+        .add_event(Event::new("reserves_funded").add_attributes(vec![
+            attr("funder", info.sender.as_str()),
+            attr("amount", amount),
+            attr("reserves_funded", reserves_funded),
+        ]))
+        .add_attributes(vec![
+            attr("action", "fund_reserves"),
+            attr("funder", info.sender),
+            attr("amount", amount),
+            attr("total_reserves", state.total_reserves.to_string()),
+        ]))
+}
+
+pub fn query_reserves_funded(deps: Deps) -> StdResult<ReservesFundedResponse> {
+    Ok(ReservesFundedResponse {
+        reserves_funded: read_reserves_funded(deps.storage)?,
+    })
+}
+
+/// Dry-run a borrow rate query against the given interest model,
+/// so a misconfigured address cannot break the next compute_interest
+fn assert_interest_model(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    interest_model: &Addr,
+) -> Result<(), ContractError> {
+    if *interest_model == env.contract.address {
+        return Err(ContractError::InvalidInterestModel {});
+    }
+
+    let state: State = read_state(deps.storage)?;
+    let balance = query_stable_balance(deps, config, env.contract.address.clone())?;
+
+    // A per-block rate of 100% or more can only come from a broken model
+    match query_borrow_rate(
+        deps,
+        interest_model.clone(),
+        &config.stable_denom,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    ) {
+        Ok(borrow_rate_res) if borrow_rate_res.rate < Decimal256::one() => Ok(()),
+        _ => Err(ContractError::InvalidInterestModel {}),
+    }
+}
+
+pub fn execute_epoch_operations(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
@@ -347,28 +1501,36 @@ pub fn execute_epoch_operations(
         deps.as_ref(),
         deps.api.addr_humanize(&config.aterra_contract)?,
     )?;
-    let balance: Uint256 = query_balance(
+    let balance: Uint256 = query_stable_balance(
         deps.as_ref(),
+        &config,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
     )? - distributed_interest;
 
-    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
-        deps.as_ref(),
-        deps.api.addr_humanize(&config.interest_model)?,
-        balance,
-        state.total_liabilities,
-        state.total_reserves,
-    )?;
+    // The borrow rate is only needed when interest accrues,
+    // skip the query when the market was already touched this block
+    let accrual_clock = accrual_clock(&config, &env, env.block.height);
+    if state.last_interest_updated < accrual_clock {
+        let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+            deps.as_ref(),
+            deps.api.addr_humanize(&config.interest_model)?,
+            &config.stable_denom,
+            balance,
+            state.total_liabilities,
+            state.total_reserves,
+        )?;
 
-    compute_interest_raw(
-        &mut state,
-        env.block.height,
-        balance,
-        aterra_supply,
-        borrow_rate_res.rate,
-        target_deposit_rate,
-    );
+        compute_interest_raw(
+            &mut state,
+            accrual_clock,
+            balance,
+            aterra_supply,
+            std::cmp::max(borrow_rate_res.rate, config.min_borrow_rate),
+            target_deposit_rate,
+            config.max_accrual_blocks,
+            config.block_time,
+        )?;
+    }
 
     // recompute prev_exchange_rate with distributed_interest
     state.prev_exchange_rate =
@@ -383,19 +1545,15 @@ pub fn execute_epoch_operations(
     let messages: Vec<CosmosMsg> = if !total_reserves.is_zero() && balance > total_reserves {
         state.total_reserves = state.total_reserves - Decimal256::from_uint256(total_reserves);
 
-        vec![CosmosMsg::Bank(BankMsg::Send {
-            to_address: deps
-                .api
+        let transfers = reserve_transfers(
+            deps.as_ref(),
+            &config,
+            deps.api
                 .addr_humanize(&config.collector_contract)?
                 .to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: config.stable_denom,
-                    amount: total_reserves.into(),
-                },
-            )?],
-        })]
+            total_reserves,
+        )?;
+        stable_transfer_msgs(deps.as_ref(), &config, transfers)?
     } else {
         vec![]
     };
@@ -412,6 +1570,7 @@ pub fn execute_epoch_operations(
     .emission_rate;
 
     store_state(deps.storage, &state)?;
+    store_interest_index_checkpoint(deps.storage, env.block.height, &state.global_interest_index)?;
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "execute_epoch_operations"),
@@ -420,16 +1579,52 @@ pub fn execute_epoch_operations(
     ]))
 }
 
+/// Split the paid out reserves between `recipient`
+/// and the insurance fund, when one is set
+fn reserve_transfers(
+    deps: Deps,
+    config: &Config,
+    recipient: String,
+    amount: Uint256,
+) -> StdResult<Vec<(String, Uint256)>> {
+    let insurance_amount = if config.insurance_fund.is_some() {
+        amount * config.insurance_split
+    } else {
+        Uint256::zero()
+    };
+    let recipient_amount = amount - insurance_amount;
+
+    let mut transfers: Vec<(String, Uint256)> = vec![];
+    if !recipient_amount.is_zero() {
+        transfers.push((recipient, recipient_amount));
+    }
+
+    if let Some(insurance_fund) = &config.insurance_fund {
+        if !insurance_amount.is_zero() {
+            transfers.push((
+                deps.api.addr_humanize(insurance_fund)?.to_string(),
+                insurance_amount,
+            ));
+        }
+    }
+
+    Ok(transfers)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::Markets {} => to_binary(&query_markets(deps)?),
+        QueryMsg::Market { denom, msg } => query_market(deps, env, denom, *msg),
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ReservesFunded {} => to_binary(&query_reserves_funded(deps)?),
         QueryMsg::State { block_height } => to_binary(&query_state(deps, env, block_height)?),
         QueryMsg::EpochState {
             block_height,
             distributed_interest,
         } => to_binary(&query_epoch_state(
             deps,
+            env,
             block_height,
             distributed_interest,
         )?),
@@ -442,7 +1637,123 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             deps.api.addr_validate(&borrower)?,
             block_height,
         )?),
-        QueryMsg::BorrowerInfos { start_after, limit } => to_binary(&query_borrower_infos(
+        QueryMsg::BorrowerInfos {
+            start_after,
+            limit,
+            block_height,
+        } => to_binary(&query_borrower_infos(
+            deps,
+            env,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+            block_height,
+        )?),
+        QueryMsg::Utilization { block_height } => {
+            to_binary(&query_utilization(deps, env, block_height)?)
+        }
+        QueryMsg::WiredContracts {} => to_binary(&query_wired_contracts(deps)?),
+        QueryMsg::InterestIndexAt { height } => to_binary(&query_interest_index_at(deps, height)?),
+        QueryMsg::LoanAmountAt { borrower, height } => to_binary(&query_loan_amount_at(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+            height,
+        )?),
+        QueryMsg::Escrow { borrower } => to_binary(&query_escrow(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::BorrowerIndex { borrower } => to_binary(&query_borrower_index(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::DepositValue {
+            depositor,
+            block_height,
+        } => to_binary(&query_deposit_value(
+            deps,
+            env,
+            deps.api.addr_validate(&depositor)?,
+            block_height,
+        )?),
+        QueryMsg::TotalDeposits { block_height } => {
+            to_binary(&query_total_deposits(deps, env, block_height)?)
+        }
+        QueryMsg::RequiredBorrowLimit {
+            borrower,
+            desired_borrow,
+            block_height,
+        } => to_binary(&query_required_borrow_limit(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            desired_borrow,
+            block_height,
+        )?),
+        QueryMsg::BorrowerPosition {
+            borrower,
+            block_height,
+        } => to_binary(&query_borrower_position(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            block_height,
+        )?),
+        QueryMsg::IsWhitelisted { borrower } => to_binary(&query_is_whitelisted(
+            deps,
+            deps.api.addr_validate(&borrower)?,
+        )?),
+        QueryMsg::BorrowerHealth {
+            borrower,
+            block_height,
+        } => to_binary(&query_borrower_health(
+            deps,
+            env,
+            deps.api.addr_validate(&borrower)?,
+            block_height,
+        )?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps)?),
+        QueryMsg::MarketState { block_height } => {
+            to_binary(&query_market_state(deps, env, block_height)?)
+        }
+        QueryMsg::SimulateDeposit { amount } => {
+            to_binary(&query_simulate_deposit(deps, env, amount)?)
+        }
+        QueryMsg::SimulateRedeem { burn_amount } => {
+            to_binary(&query_simulate_redeem(deps, env, burn_amount)?)
+        }
+        QueryMsg::BadDebts { start_after, limit } => to_binary(&query_bad_debts(
+            deps,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+        )?),
+        QueryMsg::LiabilitiesByAmount {
+            start_after,
+            limit,
+            descending,
+            block_height,
+        } => to_binary(&query_liabilities_by_amount(
+            deps,
+            env,
+            optional_addr_validate(deps.api, start_after)?,
+            limit,
+            descending,
+            block_height,
+        )?),
+        QueryMsg::StakingState { block_height } => {
+            to_binary(&query_staking_state(deps, env, block_height)?)
+        }
+        QueryMsg::StakerInfo {
+            staker,
+            block_height,
+        } => to_binary(&query_staker_info(
+            deps,
+            env,
+            deps.api.addr_validate(&staker)?,
+            block_height,
+        )?),
+        QueryMsg::StakerInfos { start_after, limit } => to_binary(&query_staker_infos(
             deps,
             optional_addr_validate(deps.api, start_after)?,
             limit,
@@ -450,6 +1761,47 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     }
 }
 
+/// Run `msg` against the market of `denom`
+fn query_market(deps: Deps, env: Env, denom: String, msg: QueryMsg) -> StdResult<Binary> {
+    if read_market_denom(deps.storage)?.is_some() {
+        return Err(StdError::generic_err("Only allowed on the primary market"));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    if denom == config.stable_denom {
+        return query(deps, env, msg);
+    }
+
+    if !is_market(deps.storage, &denom) {
+        return Err(StdError::not_found(format!("Market of {}", denom)));
+    }
+
+    query(
+        Deps {
+            storage: &market_storage_read(deps.storage, &denom),
+            api: deps.api,
+            querier: deps.querier,
+        },
+        env,
+        msg,
+    )
+}
+
+pub fn query_markets(deps: Deps) -> StdResult<MarketsResponse> {
+    let markets = read_markets(deps.storage)?
+        .into_iter()
+        .map(|denom| {
+            let config: Config = read_config(&market_storage_read(deps.storage, &denom))?;
+            Ok(MarketsResponseElem {
+                denom,
+                aterra_contract: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            })
+        })
+        .collect::<StdResult<Vec<MarketsResponseElem>>>()?;
+
+    Ok(MarketsResponse { markets })
+}
+
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config: Config = read_config(deps.storage)?;
     Ok(ConfigResponse {
@@ -473,7 +1825,80 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
             .addr_humanize(&config.distributor_contract)?
             .to_string(),
         stable_denom: config.stable_denom,
+        stable_token: config
+            .stable_token
+            .map(|stable_token| deps.api.addr_humanize(&stable_token))
+            .transpose()?
+            .map(|stable_token| stable_token.to_string()),
         max_borrow_factor: config.max_borrow_factor,
+        max_accrual_blocks: config.max_accrual_blocks,
+        min_borrow_rate: config.min_borrow_rate,
+        insurance_fund: config
+            .insurance_fund
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
+        insurance_split: config.insurance_split,
+        flash_loan_fee: config.flash_loan_fee,
+        max_borrow_amount: config.max_borrow_amount,
+        per_account_borrow_cap: config.per_account_borrow_cap,
+        guardian: config
+            .guardian
+            .map(|addr| deps.api.addr_humanize(&addr).map(|addr| addr.to_string()))
+            .transpose()?,
+        paused_operations: config.paused_operations,
+        block_time: config.block_time,
+        min_borrow_amount: config.min_borrow_amount,
+        dust_threshold: config.dust_threshold,
+        borrow_whitelist_enabled: config.borrow_whitelist_enabled,
+        aterra_name: config.aterra_name,
+        aterra_symbol: config.aterra_symbol,
+        aterra_code_id: config.aterra_code_id,
+        market_id: config.market_id,
+    })
+}
+
+pub fn query_wired_contracts(deps: Deps) -> StdResult<WiredContractsResponse> {
+    let config: Config = read_config(deps.storage)?;
+
+    // Unregistered contracts are kept as empty addresses
+    let humanize = |addr: &CanonicalAddr| -> StdResult<Option<String>> {
+        if *addr == CanonicalAddr::from(vec![]) {
+            Ok(None)
+        } else {
+            Ok(Some(deps.api.addr_humanize(addr)?.to_string()))
+        }
+    };
+
+    Ok(WiredContractsResponse {
+        anchor_token_registered: config.aterra_contract != CanonicalAddr::from(vec![]),
+        overseer_registered: config.overseer_contract != CanonicalAddr::from(vec![]),
+        aterra_contract: humanize(&config.aterra_contract)?,
+        overseer_contract: humanize(&config.overseer_contract)?,
+        interest_model: humanize(&config.interest_model)?,
+        distribution_model: humanize(&config.distribution_model)?,
+        collector_contract: humanize(&config.collector_contract)?,
+        distributor_contract: humanize(&config.distributor_contract)?,
+    })
+}
+
+pub fn query_pending_owner(deps: Deps) -> StdResult<PendingOwnerResponse> {
+    Ok(match read_ownership_proposal(deps.storage)? {
+        Some(proposal) => PendingOwnerResponse {
+            owner: Some(deps.api.addr_humanize(&proposal.owner)?.to_string()),
+            expires_at: Some(proposal.expires_at),
+        },
+        None => PendingOwnerResponse {
+            owner: None,
+            expires_at: None,
+        },
+    })
+}
+
+pub fn query_is_whitelisted(deps: Deps, borrower: Addr) -> StdResult<IsWhitelistedResponse> {
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    Ok(IsWhitelistedResponse {
+        borrower: borrower.to_string(),
+        is_whitelisted: read_is_borrow_whitelisted(deps.storage, &borrower_raw),
     })
 }
 
@@ -486,7 +1911,8 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         env.block.height
     };
 
-    if block_height < state.last_interest_updated {
+    let config: Config = read_config(deps.storage)?;
+    if accrual_clock(&config, &env, block_height) < state.last_interest_updated {
         return Err(StdError::generic_err(
             "block_height must bigger than last_interest_updated",
         ));
@@ -498,10 +1924,8 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
         ));
     }
 
-    let config: Config = read_config(deps.storage)?;
-
     // Compute interest rate with given block height
-    compute_interest(deps, &config, &mut state, block_height, None)?;
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
 
     // Compute reward rate with given block height
     compute_reward(&mut state, block_height);
@@ -519,8 +1943,151 @@ pub fn query_state(deps: Deps, env: Env, block_height: Option<u64>) -> StdResult
     })
 }
 
+pub fn query_utilization(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<UtilizationResponse> {
+    let mut state: State = read_state(deps.storage)?;
+
+    let block_height = if let Some(block_height) = block_height {
+        block_height
+    } else {
+        env.block.height
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    if accrual_clock(&config, &env, block_height) < state.last_interest_updated {
+        return Err(StdError::generic_err(
+            "block_height must bigger than last_interest_updated",
+        ));
+    }
+
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+
+    let balance = query_stable_balance(
+        deps,
+        &config,
+        deps.api.addr_humanize(&config.contract_addr)?,
+    )?;
+
+    let (available, utilization) = compute_utilization(&state, balance);
+    Ok(UtilizationResponse {
+        utilization,
+        total_liabilities: state.total_liabilities * Uint256::one(),
+        available: available * Uint256::one(),
+    })
+}
+
+pub fn query_deposit_value(
+    deps: Deps,
+    env: Env,
+    depositor: Addr,
+    block_height: u64,
+) -> StdResult<DepositValueResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let aterra_balance = query_token_balance(
+        deps,
+        deps.api.addr_humanize(&config.aterra_contract)?,
+        depositor.clone(),
+    )?;
+
+    // exchange rate falls back to one when no aterra is issued
+    let epoch_state = query_epoch_state(deps, env, Some(block_height), None)?;
+
+    Ok(DepositValueResponse {
+        depositor: depositor.to_string(),
+        aterra_balance,
+        stable_value: aterra_balance * epoch_state.exchange_rate,
+    })
+}
+
+/// Returns the lendable balance and the borrowed share of it
+fn compute_utilization(state: &State, balance: Uint256) -> (Decimal256, Decimal256) {
+    // utilization = total_liabilities / (total_liabilities + balance - total_reserves)
+    let balance = Decimal256::from_uint256(balance);
+    let available = if balance > state.total_reserves {
+        balance - state.total_reserves
+    } else {
+        Decimal256::zero()
+    };
+    let total_deposits = state.total_liabilities + available;
+    let utilization = if total_deposits.is_zero() {
+        Decimal256::zero()
+    } else {
+        state.total_liabilities / total_deposits
+    };
+
+    (available, utilization)
+}
+
+pub fn query_market_state(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<MarketStateResponse> {
+    let mut state: State = read_state(deps.storage)?;
+
+    let block_height = if let Some(block_height) = block_height {
+        block_height
+    } else {
+        env.block.height
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    if accrual_clock(&config, &env, block_height) < state.last_interest_updated {
+        return Err(StdError::generic_err(
+            "block_height must bigger than last_interest_updated",
+        ));
+    }
+
+    compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+
+    let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
+    let balance = query_stable_balance(
+        deps,
+        &config,
+        deps.api.addr_humanize(&config.contract_addr)?,
+    )?;
+
+    let exchange_rate = compute_exchange_rate_raw(&state, aterra_supply, balance);
+    let (_, utilization) = compute_utilization(&state, balance);
+    let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
+        deps,
+        deps.api.addr_humanize(&config.interest_model)?,
+        &config.stable_denom,
+        balance,
+        state.total_liabilities,
+        state.total_reserves,
+    )?;
+
+    Ok(MarketStateResponse {
+        total_deposits: aterra_supply * exchange_rate,
+        total_liabilities: state.total_liabilities,
+        total_reserves: state.total_reserves,
+        aterra_supply,
+        exchange_rate,
+        utilization,
+        borrow_rate: std::cmp::max(borrow_rate_res.rate, config.min_borrow_rate),
+    })
+}
+
+pub fn query_total_deposits(
+    deps: Deps,
+    env: Env,
+    block_height: u64,
+) -> StdResult<TotalDepositsResponse> {
+    let epoch_state = query_epoch_state(deps, env, Some(block_height), None)?;
+
+    Ok(TotalDepositsResponse {
+        aterra_supply: epoch_state.aterra_supply,
+        total_deposit_value: epoch_state.aterra_supply * epoch_state.exchange_rate,
+    })
+}
+
 pub fn query_epoch_state(
     deps: Deps,
+    env: Env,
     block_height: Option<u64>,
     distributed_interest: Option<Uint256>,
 ) -> StdResult<EpochStateResponse> {
@@ -529,13 +2096,14 @@ pub fn query_epoch_state(
 
     let distributed_interest = distributed_interest.unwrap_or_else(Uint256::zero);
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance = query_balance(
+    let balance = query_stable_balance(
         deps,
+        &config,
         deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
     )? - distributed_interest;
 
     if let Some(block_height) = block_height {
+        let block_height = accrual_clock(&config, &env, block_height);
         if block_height < state.last_interest_updated {
             return Err(StdError::generic_err(
                 "block_height must bigger than last_interest_updated",
@@ -545,6 +2113,7 @@ pub fn query_epoch_state(
         let borrow_rate_res: BorrowRateResponse = query_borrow_rate(
             deps,
             deps.api.addr_humanize(&config.interest_model)?,
+            &config.stable_denom,
             balance,
             state.total_liabilities,
             state.total_reserves,
@@ -559,9 +2128,11 @@ pub fn query_epoch_state(
             block_height,
             balance,
             aterra_supply,
-            borrow_rate_res.rate,
+            std::cmp::max(borrow_rate_res.rate, config.min_borrow_rate),
             target_deposit_rate,
-        );
+            config.max_accrual_blocks,
+            config.block_time,
+        )?;
     }
 
     // compute_interest_raw store current exchange rate
@@ -1,23 +1,57 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Uint128, WasmMsg,
 };
 
-use crate::borrow::{compute_interest, compute_reward};
+use crate::asset::{query_stable_balance, stable_amount_after_tax, stable_transfer_msg};
+use crate::borrow::{assert_market_state, compute_interest, compute_reward};
 use crate::error::ContractError;
 use crate::state::{read_config, read_state, store_state, Config, State};
 
 use cw20::Cw20ExecuteMsg;
-use moneymarket::querier::{deduct_tax, query_balance, query_supply};
+use moneymarket::market::{SimulateDepositResponse, SimulateRedeemResponse};
+use moneymarket::querier::query_supply;
 
 pub fn deposit_stable(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    recipient: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
+    let (deposit_amount, mint_amount, exchange_rate) =
+        compute_deposit(deps.branch(), &env, &config, &info)?;
 
+    let recipient = recipient.unwrap_or_else(|| info.sender.clone());
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount: mint_amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "deposit_stable"),
+            attr("depositor", info.sender),
+            attr("recipient", recipient),
+            attr("mint_amount", mint_amount),
+            attr("deposit_amount", deposit_amount),
+            attr("exchange_rate", exchange_rate.to_string()),
+        ]))
+}
+
+/// Validate the attached stable asset and account for the aterra
+/// minted for it; returns the deposit amount, the mint amount
+/// and the exchange rate used
+pub(crate) fn compute_deposit(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    info: &MessageInfo,
+) -> Result<(Uint256, Uint256, Decimal256), ContractError> {
     // Check base denom deposit
     let deposit_amount: Uint256 = info
         .funds
@@ -28,14 +62,21 @@ pub fn deposit_stable(
 
     // Cannot deposit zero amount
     if deposit_amount.is_zero() {
-        return Err(ContractError::ZeroDeposit(config.stable_denom));
+        return Err(ContractError::ZeroDeposit(config.stable_denom.clone()));
+    }
+
+    // Other denoms would be stuck in the contract
+    if info.funds.iter().any(|c| c.denom != config.stable_denom) {
+        return Err(ContractError::InvalidDepositDenoms {});
     }
 
     // Update interest related state
     let mut state: State = read_state(deps.storage)?;
+    assert_market_state(deps.as_ref(), config, &state)?;
     compute_interest(
         deps.as_ref(),
-        &config,
+        env,
+        config,
         &mut state,
         env.block.height,
         Some(deposit_amount),
@@ -43,82 +84,142 @@ pub fn deposit_stable(
     compute_reward(&mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
-    let exchange_rate =
-        compute_exchange_rate(deps.as_ref(), &config, &state, Some(deposit_amount))?;
+    let exchange_rate = compute_exchange_rate(deps.as_ref(), config, &state, Some(deposit_amount))?;
     let mint_amount = deposit_amount / exchange_rate;
 
     state.prev_aterra_supply += mint_amount;
     store_state(deps.storage, &state)?;
-    Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: info.sender.to_string(),
-                amount: mint_amount.into(),
-            })?,
-        }))
-        .add_attributes(vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", info.sender),
-            attr("mint_amount", mint_amount),
-            attr("deposit_amount", deposit_amount),
-        ]))
+
+    Ok((deposit_amount, mint_amount, exchange_rate))
 }
 
 pub fn redeem_stable(
     deps: DepsMut,
     env: Env,
     sender: Addr,
-    burn_amount: Uint128,
+    aterra_amount: Uint128,
+    target_stable: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
 
     // Update interest related state
     let mut state: State = read_state(deps.storage)?;
-    compute_interest(deps.as_ref(), &config, &mut state, env.block.height, None)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
     compute_reward(&mut state, env.block.height);
 
     // Load anchor token exchange rate with updated state
     let exchange_rate = compute_exchange_rate(deps.as_ref(), &config, &state, None)?;
-    let redeem_amount = Uint256::from(burn_amount) * exchange_rate;
+    let aterra_amount = Uint256::from(aterra_amount);
 
-    let current_balance = query_balance(
-        deps.as_ref(),
-        env.contract.address,
-        config.stable_denom.to_string(),
-    )?;
+    let burn_amount: Uint256;
+    let redeem_amount: Uint256;
+    if let Some(target_stable) = target_stable {
+        if target_stable.is_zero() {
+            return Err(ContractError::ZeroRedeemTarget {});
+        }
+
+        // Round up the aterra burned for the target, so the
+        // rounding never goes against the depositors
+        let target_in_aterra = Decimal256::from_uint256(target_stable) / exchange_rate;
+        let mut target_in_aterra_floor = target_in_aterra * Uint256::one();
+        if Decimal256::from_uint256(target_in_aterra_floor) < target_in_aterra {
+            target_in_aterra_floor += Uint256::one();
+        }
+
+        if target_in_aterra_floor > aterra_amount {
+            return Err(ContractError::InsufficientRedeemBurn(
+                target_in_aterra_floor.into(),
+            ));
+        }
+
+        burn_amount = target_in_aterra_floor;
+        redeem_amount = target_stable;
+    } else {
+        burn_amount = aterra_amount;
+        redeem_amount = aterra_amount * exchange_rate;
+    }
+
+    let current_balance = query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
 
     // Assert redeem amount
     assert_redeem_amount(&config, &state, current_balance, redeem_amount)?;
 
-    state.prev_aterra_supply = state.prev_aterra_supply - Uint256::from(burn_amount);
+    state.prev_aterra_supply = state.prev_aterra_supply - burn_amount;
     store_state(deps.storage, &state)?;
-    Ok(Response::new()
-        .add_messages(vec![
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Burn {
-                    amount: burn_amount,
-                })?,
-            }),
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: sender.to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom,
-                        amount: redeem_amount.into(),
-                    },
-                )?],
-            }),
-        ])
-        .add_attributes(vec![
-            attr("action", "redeem_stable"),
-            attr("burn_amount", burn_amount),
-            attr("redeem_amount", redeem_amount),
-        ]))
+
+    let aterra_contract = deps.api.addr_humanize(&config.aterra_contract)?;
+    let mut messages: Vec<CosmosMsg> = vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: aterra_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: burn_amount.into(),
+            })?,
+        }),
+        stable_transfer_msg(deps.as_ref(), &config, sender.to_string(), redeem_amount)?,
+    ];
+
+    // Return the aterra left over from a target redemption
+    let refund_amount = aterra_amount - burn_amount;
+    if !refund_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: aterra_contract.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: sender.to_string(),
+                amount: refund_amount.into(),
+            })?,
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "redeem_stable"),
+        attr("redeemer", sender),
+        attr("burn_amount", burn_amount),
+        attr("redeem_amount", redeem_amount),
+        attr("exchange_rate", exchange_rate.to_string()),
+    ]))
+}
+
+pub fn query_simulate_deposit(
+    deps: Deps,
+    env: Env,
+    amount: Uint256,
+) -> StdResult<SimulateDepositResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, &env, &config, &mut state, env.block.height, None)?;
+
+    let exchange_rate = compute_exchange_rate(deps, &config, &state, None)?;
+    Ok(SimulateDepositResponse {
+        mint_amount: amount / exchange_rate,
+        exchange_rate,
+    })
+}
+
+pub fn query_simulate_redeem(
+    deps: Deps,
+    env: Env,
+    burn_amount: Uint256,
+) -> StdResult<SimulateRedeemResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(deps, &env, &config, &mut state, env.block.height, None)?;
+
+    let exchange_rate = compute_exchange_rate(deps, &config, &state, None)?;
+    let redeem_amount = stable_amount_after_tax(deps, &config, burn_amount * exchange_rate)?;
+
+    Ok(SimulateRedeemResponse {
+        redeem_amount,
+        exchange_rate,
+    })
 }
 
 fn assert_redeem_amount(
@@ -145,11 +246,9 @@ pub(crate) fn compute_exchange_rate(
     deposit_amount: Option<Uint256>,
 ) -> StdResult<Decimal256> {
     let aterra_supply = query_supply(deps, deps.api.addr_humanize(&config.aterra_contract)?)?;
-    let balance = query_balance(
-        deps,
-        deps.api.addr_humanize(&config.contract_addr)?,
-        config.stable_denom.to_string(),
-    )? - deposit_amount.unwrap_or_else(Uint256::zero);
+    let balance =
+        query_stable_balance(deps, config, deps.api.addr_humanize(&config.contract_addr)?)?
+            - deposit_amount.unwrap_or_else(Uint256::zero);
 
     Ok(compute_exchange_rate_raw(state, aterra_supply, balance))
 }
@@ -12,27 +12,137 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Cannot write off a loan with collaterals left")]
+    CannotWriteOffCollateralizedLoan {},
+
+    #[error("Cannot write off a loan with aterra left in escrow")]
+    CannotWriteOffEscrowedLoan {},
+
+    #[error("Cannot liquidate safely collateralized loan")]
+    CannotLiquidateSafeLoan {},
+
+    #[error("Cannot change overseer with active loans")]
+    OverseerChangeWithActiveLoans {},
+
+    #[error("Borrow amount too high; Loan liability becomes greater than account borrow cap: {0}")]
+    AccountBorrowCapExceeded(u128),
+
     #[error("Borrow amount too high; Loan liability becomes greater than borrow limit: {0}")]
     BorrowExceedsLimit(u128),
 
+    #[error("Borrow amount too low; Loan liability must be at least {0}")]
+    BorrowBelowMinimum(u128),
+
+    #[error("Repay amount leaves a loan below the dust threshold: {0}")]
+    DustLoan(u128),
+
+    #[error("Withdraw amount too high; Loan liability becomes greater than borrow limit: {0}")]
+    EscrowWithdrawTooLarge(u128),
+
     #[error("Must deposit initial funds {0}{0}")]
     InitialFundsNotDeposited(u128, String),
 
+    #[error(
+        "Borrow amount too high; Total liabilities become greater than market borrow cap: {0}"
+    )]
+    GlobalBorrowCapExceeded(u128),
+
+    #[error("Flash loan not repaid; expected balance: {0}")]
+    FlashLoanNotRepaid(u128),
+
+    #[error("Market is locked by an outstanding flash loan")]
+    FlashLoanInProgress {},
+
+    #[error("Inconsistent market state")]
+    InconsistentMarketState {},
+
+    #[error("Attached funds short by {0}")]
+    InsufficientRepayAmount(u128),
+
+    #[error("Not enough aterra sent to redeem the target amount; required burn amount: {0}")]
+    InsufficientRedeemBurn(u128),
+
+    #[error("Invalid borrow recipient")]
+    InvalidBorrowRecipient {},
+
+    #[error("Only the base denom may be attached")]
+    InvalidDepositDenoms {},
+
+    #[error("Unbond amount too high; bonded amount: {0}")]
+    InsufficientBondAmount(u128),
+
+    #[error("Withdraw amount too high; escrowed amount: {0}")]
+    InsufficientEscrowAmount(u128),
+
+    #[error("Insurance split must be less than or equal to 1")]
+    InvalidInsuranceSplit {},
+
+    #[error("Cannot migrate from contract {0}")]
+    InvalidMigrationContract(String),
+
+    #[error("Invalid interest model")]
+    InvalidInterestModel {},
+
+    #[error("Transaction expired")]
+    TransactionExpired {},
+
+    #[error("Not enough reserves; available reserves: {0}")]
+    InsufficientReserves(u128),
+
     #[error("Invalid reply ID")]
     InvalidReplyId {},
 
+    #[error("Market {0} is paused")]
+    OperationPaused(String),
+
+    #[error("Market of {0} already exists")]
+    MarketAlreadyExists(String),
+
+    #[error("Market of {0} not found")]
+    MarketNotFound(String),
+
+    #[error("Only allowed on the primary market")]
+    NotPrimaryMarket {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
     #[error("Exceeds {0} max borrow factor; borrow demand too high")]
     MaxBorrowFactorReached(String),
 
+    #[error("Aterra code id required to migrate an unversioned market")]
+    MissingAterraCodeId {},
+
     #[error("Invalid request: \"redeem stable\" message not included in request")]
     MissingRedeemStableHook {},
 
+    #[error("Native funds not accepted; send the stable token with a hook message")]
+    NativeStableNotAccepted {},
+
+    #[error("No outstanding liability to write off")]
+    NoBadDebt {},
+
+    #[error("No aterra held in escrow")]
+    NoEscrow {},
+
+    #[error("No outstanding loan to repay")]
+    NoLoan {},
+
     #[error("Not enough {0} available; borrow demand too high")]
     NoStableAvailable(String),
 
+    #[error("Bond amount must be greater than 0")]
+    ZeroBond {},
+
     #[error("Deposit amount must be greater than 0 {0}")]
     ZeroDeposit(String),
 
+    #[error("Flash loan amount must be greater than 0")]
+    ZeroFlashLoan {},
+
+    #[error("Redeem target amount must be greater than 0")]
+    ZeroRedeemTarget {},
+
     #[error("Repay amount must be greater than 0 {0}")]
     ZeroRepay(String),
 }
@@ -0,0 +1,130 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, Addr, Binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, WasmMsg,
+};
+
+use crate::asset::{query_stable_balance, stable_transfer_msg};
+use crate::borrow::{compute_interest, compute_reward};
+use crate::contract::self_msg;
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_flash_loan, read_state, remove_flash_loan, store_flash_loan, store_state,
+    Config, FlashLoan, State,
+};
+
+use moneymarket::market::ExecuteMsg;
+use moneymarket::querier::deduct_tax;
+
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    amount: Uint256,
+    msg: Binary,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if amount.is_zero() {
+        return Err(ContractError::ZeroFlashLoan {});
+    }
+
+    // Accrue interest before the balance is lent out
+    let mut state: State = read_state(deps.storage)?;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &config,
+        &mut state,
+        env.block.height,
+        None,
+    )?;
+    compute_reward(&mut state, env.block.height);
+    store_state(deps.storage, &state)?;
+
+    let prev_balance: Uint256 =
+        query_stable_balance(deps.as_ref(), &config, env.contract.address.clone())?;
+
+    // Reserves cannot be lent out
+    if Decimal256::from_uint256(amount) + state.total_reserves
+        > Decimal256::from_uint256(prev_balance)
+    {
+        return Err(ContractError::NoStableAvailable(config.stable_denom));
+    }
+
+    // Lock the market until the loan and its fee are returned
+    let fee = amount * config.flash_loan_fee;
+    store_flash_loan(deps.storage, &FlashLoan { prev_balance, fee })?;
+
+    // Native loans are attached to the call, token loans transferred ahead of it
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let funds = if config.stable_token.is_some() {
+        messages.push(stable_transfer_msg(
+            deps.as_ref(),
+            &config,
+            recipient.to_string(),
+            amount,
+        )?);
+        vec![]
+    } else {
+        vec![deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: config.stable_denom,
+                amount: amount.into(),
+            },
+        )?]
+    };
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: recipient.to_string(),
+        funds,
+        msg,
+    }));
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: self_msg(deps.storage, ExecuteMsg::AssertFlashLoanRepaid {})?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "flash_loan"),
+        attr("recipient", recipient),
+        attr("amount", amount),
+        attr("fee", fee),
+    ]))
+}
+
+pub fn assert_flash_loan_repaid(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // only this contract can execute this message
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let flash_loan = match read_flash_loan(deps.storage)? {
+        Some(flash_loan) => flash_loan,
+        None => return Err(ContractError::Unauthorized {}),
+    };
+
+    let config: Config = read_config(deps.storage)?;
+    let current_balance: Uint256 =
+        query_stable_balance(deps.as_ref(), &config, env.contract.address)?;
+
+    let expected_balance = flash_loan.prev_balance + flash_loan.fee;
+    if current_balance < expected_balance {
+        return Err(ContractError::FlashLoanNotRepaid(expected_balance.into()));
+    }
+
+    // The fee accrues to the reserves
+    let mut state: State = read_state(deps.storage)?;
+    state.total_reserves += Decimal256::from_uint256(flash_loan.fee);
+    store_state(deps.storage, &state)?;
+    remove_flash_loan(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "assert_flash_loan_repaid"),
+        attr("fee", flash_loan.fee),
+    ]))
+}
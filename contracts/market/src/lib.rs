@@ -1,9 +1,12 @@
+pub mod asset;
 pub mod borrow;
 pub mod contract;
 pub mod deposit;
 pub mod error;
+pub mod flash_loan;
 pub mod querier;
 pub mod response;
+pub mod staking;
 pub mod state;
 
 #[cfg(test)]
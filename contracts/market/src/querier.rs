@@ -1,46 +1,60 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
 
 use moneymarket::distribution_model::{AncEmissionRateResponse, QueryMsg as DistributionQueryMsg};
 use moneymarket::interest_model::{BorrowRateResponse, QueryMsg as InterestQueryMsg};
-use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse, QueryMsg as OverseerQueryMsg};
+use moneymarket::overseer::{ConfigResponse, QueryMsg as OverseerQueryMsg};
 
 pub fn query_borrow_rate(
     deps: Deps,
     interest_addr: Addr,
+    stable_denom: &str,
     market_balance: Uint256,
     total_liabilities: Decimal256,
     total_reserves: Decimal256,
 ) -> StdResult<BorrowRateResponse> {
-    let borrow_rate: BorrowRateResponse =
+    // utilization = total_liabilities / (balance + total_liabilities - total_reserves)
+    let total_value_in_market = Decimal256::from_uint256(market_balance) + total_liabilities;
+    let utilization = if total_value_in_market > total_reserves {
+        total_liabilities / (total_value_in_market - total_reserves)
+    } else {
+        Decimal256::zero()
+    };
+
+    let borrow_rate: StdResult<BorrowRateResponse> =
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
             contract_addr: interest_addr.to_string(),
             msg: to_binary(&InterestQueryMsg::BorrowRate {
                 market_balance,
                 total_liabilities,
                 total_reserves,
+                stable_denom: Some(stable_denom.to_string()),
+                utilization: Some(utilization),
             })?,
-        }))?;
-
-    Ok(borrow_rate)
-}
+        }));
 
-pub fn query_borrow_limit(
-    deps: Deps,
-    overseer_addr: Addr,
-    borrower: Addr,
-    block_time: Option<u64>,
-) -> StdResult<BorrowLimitResponse> {
-    let borrow_limit: BorrowLimitResponse =
+    // Fall back to the message without market context
+    // for interest models which cannot parse it
+    borrow_rate.or_else(|_| {
         deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: overseer_addr.to_string(),
-            msg: to_binary(&OverseerQueryMsg::BorrowLimit {
-                borrower: borrower.to_string(),
-                block_time,
+            contract_addr: interest_addr.to_string(),
+            msg: to_binary(&InterestQueryMsg::BorrowRate {
+                market_balance,
+                total_liabilities,
+                total_reserves,
+                stable_denom: None,
+                utilization: None,
             })?,
-        }))?;
+        }))
+    })
+}
 
-    Ok(borrow_limit)
+pub fn query_token_info(deps: Deps, token_addr: Addr) -> StdResult<TokenInfoResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: token_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+    }))
 }
 
 pub fn query_anc_emission_rate(
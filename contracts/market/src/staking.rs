@@ -0,0 +1,256 @@
+use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult, Storage, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+use crate::error::ContractError;
+use crate::state::{
+    read_config, read_staker_info, read_staker_infos, read_staking_state, remove_staker_info,
+    store_staker_info, store_staking_state, Config, StakerInfo, StakingState,
+};
+
+use moneymarket::market::{StakerInfoResponse, StakerInfosResponse, StakingStateResponse};
+
+pub fn bond(
+    deps: DepsMut,
+    env: Env,
+    staker: Addr,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroBond {});
+    }
+
+    let staker_raw = deps.api.addr_canonicalize(staker.as_str())?;
+    let mut staking_state: StakingState = read_staking_state(deps.storage)?;
+    let mut staker_info: StakerInfo = read_staker_info(deps.storage, &staker_raw);
+
+    compute_staking_reward(&mut staking_state, env.block.height);
+    compute_staker_reward(&staking_state, &mut staker_info);
+
+    staking_state.total_bond_amount += amount;
+    staker_info.bond_amount += amount;
+
+    store_staking_state(deps.storage, &staking_state)?;
+    store_staker_info(deps.storage, &staker_raw, &staker_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "bond"),
+        attr("staker", staker),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint256,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroBond {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let staker_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut staking_state: StakingState = read_staking_state(deps.storage)?;
+    let mut staker_info: StakerInfo = read_staker_info(deps.storage, &staker_raw);
+
+    if staker_info.bond_amount < amount {
+        return Err(ContractError::InsufficientBondAmount(
+            staker_info.bond_amount.into(),
+        ));
+    }
+
+    compute_staking_reward(&mut staking_state, env.block.height);
+    compute_staker_reward(&staking_state, &mut staker_info);
+
+    staking_state.total_bond_amount = staking_state.total_bond_amount - amount;
+    staker_info.bond_amount = staker_info.bond_amount - amount;
+
+    store_staking_state(deps.storage, &staking_state)?;
+    store_or_remove_staker_info(deps.storage, &staker_raw, &staker_info)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.aterra_contract)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "unbond"),
+            attr("staker", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn claim_staking_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    to: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let staker_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut staking_state: StakingState = read_staking_state(deps.storage)?;
+    let mut staker_info: StakerInfo = read_staker_info(deps.storage, &staker_raw);
+
+    compute_staking_reward(&mut staking_state, env.block.height);
+    compute_staker_reward(&staking_state, &mut staker_info);
+
+    let claim_amount = staker_info.pending_rewards * Uint256::one();
+    staker_info.pending_rewards =
+        staker_info.pending_rewards - Decimal256::from_uint256(claim_amount);
+
+    store_staking_state(deps.storage, &staking_state)?;
+    store_or_remove_staker_info(deps.storage, &staker_raw, &staker_info)?;
+
+    let messages: Vec<CosmosMsg> = if !claim_amount.is_zero() {
+        vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.distributor_contract)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&FaucetExecuteMsg::Spend {
+                recipient: to.unwrap_or(info.sender).to_string(),
+                amount: claim_amount.into(),
+            })?,
+        })]
+    } else {
+        vec![]
+    };
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_staking_rewards"),
+        attr("claim_amount", claim_amount),
+    ]))
+}
+
+pub fn update_staking_emission_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    emission_rate: Decimal256,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+
+    // permission check
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if sender_raw != config.owner_addr && sender_raw != config.distributor_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Compute reward before updating emission_rate
+    let mut staking_state: StakingState = read_staking_state(deps.storage)?;
+    compute_staking_reward(&mut staking_state, env.block.height);
+
+    staking_state.emission_rate = emission_rate;
+    store_staking_state(deps.storage, &staking_state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_staking_emission_rate"),
+        attr("emission_rate", emission_rate.to_string()),
+    ]))
+}
+
+/// Stakers without bonded aterra or pending rewards are dropped,
+/// keeping the paginated staker query free of empty entries
+fn store_or_remove_staker_info(
+    storage: &mut dyn Storage,
+    staker: &CanonicalAddr,
+    staker_info: &StakerInfo,
+) -> StdResult<()> {
+    if staker_info.bond_amount.is_zero() && staker_info.pending_rewards.is_zero() {
+        remove_staker_info(storage, staker);
+        Ok(())
+    } else {
+        store_staker_info(storage, staker, staker_info)
+    }
+}
+
+/// Compute staking reward and update staking state
+pub fn compute_staking_reward(staking_state: &mut StakingState, block_height: u64) {
+    if staking_state.last_reward_updated >= block_height {
+        return;
+    }
+
+    let passed_blocks = Decimal256::from_uint256(block_height - staking_state.last_reward_updated);
+    let reward_accrued = passed_blocks * staking_state.emission_rate;
+    let total_bond_amount = Decimal256::from_uint256(staking_state.total_bond_amount);
+
+    if !reward_accrued.is_zero() && !total_bond_amount.is_zero() {
+        staking_state.global_reward_index += reward_accrued / total_bond_amount;
+    }
+
+    staking_state.last_reward_updated = block_height;
+}
+
+/// Compute reward amount a staker received
+fn compute_staker_reward(staking_state: &StakingState, staker_info: &mut StakerInfo) {
+    staker_info.pending_rewards += Decimal256::from_uint256(staker_info.bond_amount)
+        * (staking_state.global_reward_index - staker_info.reward_index);
+    staker_info.reward_index = staking_state.global_reward_index;
+}
+
+pub fn query_staking_state(
+    deps: Deps,
+    env: Env,
+    block_height: Option<u64>,
+) -> StdResult<StakingStateResponse> {
+    let block_height = block_height.unwrap_or(env.block.height);
+
+    let mut staking_state: StakingState = read_staking_state(deps.storage)?;
+    compute_staking_reward(&mut staking_state, block_height);
+
+    Ok(StakingStateResponse {
+        total_bond_amount: staking_state.total_bond_amount,
+        global_reward_index: staking_state.global_reward_index,
+        last_reward_updated: staking_state.last_reward_updated,
+        emission_rate: staking_state.emission_rate,
+    })
+}
+
+pub fn query_staker_info(
+    deps: Deps,
+    env: Env,
+    staker: Addr,
+    block_height: Option<u64>,
+) -> StdResult<StakerInfoResponse> {
+    let block_height = block_height.unwrap_or(env.block.height);
+
+    let mut staking_state: StakingState = read_staking_state(deps.storage)?;
+    let mut staker_info: StakerInfo =
+        read_staker_info(deps.storage, &deps.api.addr_canonicalize(staker.as_str())?);
+
+    compute_staking_reward(&mut staking_state, block_height);
+    compute_staker_reward(&staking_state, &mut staker_info);
+
+    Ok(StakerInfoResponse {
+        staker: staker.to_string(),
+        reward_index: staker_info.reward_index,
+        bond_amount: staker_info.bond_amount,
+        pending_rewards: staker_info.pending_rewards,
+    })
+}
+
+pub fn query_staker_infos(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<StakerInfosResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(start_after.as_str())?)
+    } else {
+        None
+    };
+
+    let staker_infos: Vec<StakerInfoResponse> = read_staker_infos(deps, start_after, limit)?;
+    Ok(StakerInfosResponse { staker_infos })
+}
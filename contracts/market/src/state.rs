@@ -2,15 +2,37 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
-use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
-use cosmwasm_storage::{bucket, bucket_read, ReadonlyBucket, ReadonlySingleton, Singleton};
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
+use cosmwasm_storage::{
+    bucket, bucket_read, Bucket, PrefixedStorage, ReadonlyBucket, ReadonlyPrefixedStorage,
+    ReadonlySingleton, Singleton,
+};
 
-use moneymarket::market::BorrowerInfoResponse;
+use moneymarket::market::{
+    BadDebtResponse, BorrowerInfoResponse, MarketOperation, StakerInfoResponse,
+};
 
 pub const KEY_CONFIG: &[u8] = b"config";
 pub const KEY_STATE: &[u8] = b"state";
+pub const KEY_FLASH_LOAN: &[u8] = b"flash_loan";
+pub const KEY_OWNERSHIP_PROPOSAL: &[u8] = b"ownership_proposal";
+pub const KEY_STAKING_STATE: &[u8] = b"staking_state";
+pub const KEY_CONTRACT_VERSION: &[u8] = b"contract_info";
+pub const KEY_RESERVES_FUNDED: &[u8] = b"reserves_funded";
+pub const KEY_MARKET_DENOM: &[u8] = b"market_denom";
+pub const KEY_PENDING_MARKETS: &[u8] = b"pending_markets";
 
 const PREFIX_LIABILITY: &[u8] = b"liability";
+const PREFIX_LIABILITY_BY_AMOUNT: &[u8] = b"liability_by_amount";
+const PREFIX_BORROW_WHITELIST: &[u8] = b"borrow_whitelist";
+const PREFIX_STAKER: &[u8] = b"staker";
+const PREFIX_INTEREST_INDEX_CHECKPOINT: &[u8] = b"interest_index_checkpoint";
+const PREFIX_LOAN_CHECKPOINT: &[u8] = b"loan_checkpoint";
+const PREFIX_BAD_DEBT: &[u8] = b"bad_debt";
+const PREFIX_ESCROW: &[u8] = b"escrow";
+const PREFIX_MARKET: &[u8] = b"market";
+const PREFIX_MARKETS: &[u8] = b"markets";
+const PREFIX_MARKET_TOKEN: &[u8] = b"market_token";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -23,7 +45,67 @@ pub struct Config {
     pub collector_contract: CanonicalAddr,
     pub distributor_contract: CanonicalAddr,
     pub stable_denom: String,
+    /// CW20 stablecoin used as the base asset; the native
+    /// `stable_denom` is the base asset otherwise
+    pub stable_token: Option<CanonicalAddr>,
     pub max_borrow_factor: Decimal256,
+    /// Upper bound of blocks accrued by a single interest computation.
+    /// Caps the interest shock after a chain halt, at the cost of the
+    /// index lagging behind the block height until it catches up
+    pub max_accrual_blocks: Option<u64>,
+    /// Lower bound of the per-block borrow rate used for accrual,
+    /// regardless of the rate returned by the interest model
+    pub min_borrow_rate: Decimal256,
+    /// Share of the reserves sent to the insurance fund,
+    /// instead of the collector, when the fund is set
+    pub insurance_fund: Option<CanonicalAddr>,
+    pub insurance_split: Decimal256,
+    /// Fee charged on flash loans, added to the reserves on repayment
+    pub flash_loan_fee: Decimal256,
+    /// Cap on the total liabilities of the market
+    pub max_borrow_amount: Option<Uint256>,
+    /// Cap on the loan amount of a single borrower
+    pub per_account_borrow_cap: Option<Uint256>,
+    /// Address allowed to pause operations, next to the owner
+    pub guardian: Option<CanonicalAddr>,
+    pub paused_operations: Vec<MarketOperation>,
+    /// Nominal seconds per block. When set, interest accrues on the
+    /// elapsed block time counted in blocks of this length, and
+    /// `State::last_interest_updated` holds a block time
+    pub block_time: Option<u64>,
+    /// Smallest loan a borrow can leave the borrower with
+    pub min_borrow_amount: Uint256,
+    /// Smallest nonzero loan a repayment can leave behind
+    pub dust_threshold: Uint256,
+    pub borrow_whitelist_enabled: bool,
+    pub aterra_name: String,
+    pub aterra_symbol: String,
+    /// Code id the aterra token was instantiated with, or the one
+    /// intended for its next migration once updated by the owner
+    pub aterra_code_id: u64,
+    pub market_id: Option<String>,
+}
+
+/// Config layout of markets instantiated before the contract
+/// version was stored
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub contract_addr: CanonicalAddr,
+    pub owner_addr: CanonicalAddr,
+    pub aterra_contract: CanonicalAddr,
+    pub interest_model: CanonicalAddr,
+    pub distribution_model: CanonicalAddr,
+    pub overseer_contract: CanonicalAddr,
+    pub collector_contract: CanonicalAddr,
+    pub distributor_contract: CanonicalAddr,
+    pub stable_denom: String,
+    pub max_borrow_factor: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -39,6 +121,36 @@ pub struct State {
     pub prev_exchange_rate: Decimal256,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    pub owner: CanonicalAddr,
+    pub expires_at: u64,
+}
+
+/// Outstanding flash loan; its presence locks the market
+/// until the loan is asserted to be repaid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoan {
+    pub prev_balance: Uint256,
+    pub fee: Uint256,
+}
+
+/// Aterra bonded to the market for ANC rewards
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingState {
+    pub total_bond_amount: Uint256,
+    pub global_reward_index: Decimal256,
+    pub last_reward_updated: u64,
+    pub emission_rate: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerInfo {
+    pub reward_index: Decimal256,
+    pub bond_amount: Uint256,
+    pub pending_rewards: Decimal256,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowerInfo {
     pub interest_index: Decimal256,
@@ -55,6 +167,18 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     ReadonlySingleton::new(storage, KEY_CONFIG).load()
 }
 
+pub fn read_legacy_config(storage: &dyn Storage) -> StdResult<LegacyConfig> {
+    ReadonlySingleton::new(storage, KEY_CONFIG).load()
+}
+
+pub fn store_contract_version(storage: &mut dyn Storage, data: &ContractVersion) -> StdResult<()> {
+    Singleton::new(storage, KEY_CONTRACT_VERSION).save(data)
+}
+
+pub fn read_contract_version(storage: &dyn Storage) -> StdResult<Option<ContractVersion>> {
+    ReadonlySingleton::new(storage, KEY_CONTRACT_VERSION).may_load()
+}
+
 pub fn store_state(storage: &mut dyn Storage, data: &State) -> StdResult<()> {
     Singleton::new(storage, KEY_STATE).save(data)
 }
@@ -63,14 +187,79 @@ pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
     ReadonlySingleton::new(storage, KEY_STATE).load()
 }
 
+pub fn store_ownership_proposal(
+    storage: &mut dyn Storage,
+    data: &OwnershipProposal,
+) -> StdResult<()> {
+    Singleton::new(storage, KEY_OWNERSHIP_PROPOSAL).save(data)
+}
+
+pub fn read_ownership_proposal(storage: &dyn Storage) -> StdResult<Option<OwnershipProposal>> {
+    ReadonlySingleton::new(storage, KEY_OWNERSHIP_PROPOSAL).may_load()
+}
+
+pub fn remove_ownership_proposal(storage: &mut dyn Storage) {
+    Singleton::<OwnershipProposal>::new(storage, KEY_OWNERSHIP_PROPOSAL).remove()
+}
+
+pub fn store_flash_loan(storage: &mut dyn Storage, data: &FlashLoan) -> StdResult<()> {
+    Singleton::new(storage, KEY_FLASH_LOAN).save(data)
+}
+
+pub fn read_flash_loan(storage: &dyn Storage) -> StdResult<Option<FlashLoan>> {
+    ReadonlySingleton::new(storage, KEY_FLASH_LOAN).may_load()
+}
+
+pub fn remove_flash_loan(storage: &mut dyn Storage) {
+    Singleton::<FlashLoan>::new(storage, KEY_FLASH_LOAN).remove()
+}
+
+pub fn store_reserves_funded(storage: &mut dyn Storage, data: &Uint256) -> StdResult<()> {
+    Singleton::new(storage, KEY_RESERVES_FUNDED).save(data)
+}
+
+pub fn read_reserves_funded(storage: &dyn Storage) -> StdResult<Uint256> {
+    Ok(ReadonlySingleton::new(storage, KEY_RESERVES_FUNDED)
+        .may_load()?
+        .unwrap_or_else(Uint256::zero))
+}
+
 pub fn store_borrower_info(
     storage: &mut dyn Storage,
     borrower: &CanonicalAddr,
     liability: &BorrowerInfo,
 ) -> StdResult<()> {
+    // keep the loan amount index in sync; zero loans are not indexed
+    let prev_liability: Option<BorrowerInfo> =
+        bucket_read(storage, PREFIX_LIABILITY).may_load(borrower.as_slice())?;
+    if let Some(prev_liability) = prev_liability {
+        if !prev_liability.loan_amount.is_zero() {
+            bucket::<bool>(storage, PREFIX_LIABILITY_BY_AMOUNT)
+                .remove(&liability_amount_key(prev_liability.loan_amount, borrower));
+        }
+    }
+
+    if !liability.loan_amount.is_zero() {
+        bucket(storage, PREFIX_LIABILITY_BY_AMOUNT).save(
+            &liability_amount_key(liability.loan_amount, borrower),
+            &true,
+        )?;
+    }
+
     bucket(storage, PREFIX_LIABILITY).save(borrower.as_slice(), liability)
 }
 
+/// Big-endian loan amount followed by the borrower, so the index
+/// iterates in loan amount order
+fn liability_amount_key(loan_amount: Uint256, borrower: &CanonicalAddr) -> Vec<u8> {
+    let mut key = [0u8; 32];
+    loan_amount.0.to_big_endian(&mut key);
+
+    let mut key = key.to_vec();
+    key.extend_from_slice(borrower.as_slice());
+    key
+}
+
 pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> BorrowerInfo {
     match bucket_read(storage, PREFIX_LIABILITY).load(borrower.as_slice()) {
         Ok(v) => v,
@@ -83,6 +272,145 @@ pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> Bo
     }
 }
 
+pub fn store_staking_state(storage: &mut dyn Storage, data: &StakingState) -> StdResult<()> {
+    Singleton::new(storage, KEY_STAKING_STATE).save(data)
+}
+
+pub fn read_staking_state(storage: &dyn Storage) -> StdResult<StakingState> {
+    Ok(ReadonlySingleton::new(storage, KEY_STAKING_STATE)
+        .may_load()?
+        .unwrap_or(StakingState {
+            total_bond_amount: Uint256::zero(),
+            global_reward_index: Decimal256::zero(),
+            last_reward_updated: 0,
+            emission_rate: Decimal256::zero(),
+        }))
+}
+
+pub fn store_staker_info(
+    storage: &mut dyn Storage,
+    staker: &CanonicalAddr,
+    staker_info: &StakerInfo,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_STAKER).save(staker.as_slice(), staker_info)
+}
+
+pub fn remove_staker_info(storage: &mut dyn Storage, staker: &CanonicalAddr) {
+    bucket::<StakerInfo>(storage, PREFIX_STAKER).remove(staker.as_slice())
+}
+
+pub fn read_staker_info(storage: &dyn Storage, staker: &CanonicalAddr) -> StakerInfo {
+    match bucket_read(storage, PREFIX_STAKER).load(staker.as_slice()) {
+        Ok(v) => v,
+        _ => StakerInfo {
+            reward_index: Decimal256::zero(),
+            bond_amount: Uint256::zero(),
+            pending_rewards: Decimal256::zero(),
+        },
+    }
+}
+
+/// Liability of a borrower as of a checkpointed block height
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanCheckpoint {
+    pub loan_amount: Uint256,
+    pub interest_index: Decimal256,
+}
+
+pub fn store_interest_index_checkpoint(
+    storage: &mut dyn Storage,
+    height: u64,
+    global_interest_index: &Decimal256,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_INTEREST_INDEX_CHECKPOINT)
+        .save(&height.to_be_bytes(), global_interest_index)
+}
+
+/// Latest interest index checkpoint at or before `height`
+pub fn read_interest_index_checkpoint(
+    storage: &dyn Storage,
+    height: u64,
+) -> StdResult<Option<(u64, Decimal256)>> {
+    let end = height.checked_add(1).map(|h| h.to_be_bytes());
+    bucket_read::<Decimal256>(storage, PREFIX_INTEREST_INDEX_CHECKPOINT)
+        .range(None, end.as_ref().map(|k| &k[..]), Order::Descending)
+        .next()
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((height_from_key(&k), v))
+        })
+        .transpose()
+}
+
+pub fn store_loan_checkpoint(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    height: u64,
+    checkpoint: &LoanCheckpoint,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_LOAN_CHECKPOINT, borrower.as_slice()])
+        .save(&height.to_be_bytes(), checkpoint)
+}
+
+/// Latest loan checkpoint of `borrower` at or before `height`
+pub fn read_loan_checkpoint(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+    height: u64,
+) -> StdResult<Option<(u64, LoanCheckpoint)>> {
+    let end = height.checked_add(1).map(|h| h.to_be_bytes());
+    ReadonlyBucket::<LoanCheckpoint>::multilevel(
+        storage,
+        &[PREFIX_LOAN_CHECKPOINT, borrower.as_slice()],
+    )
+    .range(None, end.as_ref().map(|k| &k[..]), Order::Descending)
+    .next()
+    .map(|elem| {
+        let (k, v) = elem?;
+        Ok((height_from_key(&k), v))
+    })
+    .transpose()
+}
+
+fn height_from_key(key: &[u8]) -> u64 {
+    let mut height = [0u8; 8];
+    height.copy_from_slice(key);
+    u64::from_be_bytes(height)
+}
+
+/// Re-store every liability, indexing the loan amounts
+/// of liabilities stored before the index existed
+pub fn reindex_borrower_infos(storage: &mut dyn Storage) -> StdResult<()> {
+    let liabilities: Vec<(Vec<u8>, BorrowerInfo)> =
+        bucket_read::<BorrowerInfo>(storage, PREFIX_LIABILITY)
+            .range(None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+
+    for (borrower, liability) in liabilities {
+        store_borrower_info(storage, &CanonicalAddr::from(borrower), &liability)?;
+    }
+
+    Ok(())
+}
+
+pub fn store_borrow_whitelist_elem(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_BORROW_WHITELIST).save(borrower.as_slice(), &true)
+}
+
+pub fn remove_borrow_whitelist_elem(storage: &mut dyn Storage, borrower: &CanonicalAddr) {
+    bucket::<bool>(storage, PREFIX_BORROW_WHITELIST).remove(borrower.as_slice())
+}
+
+pub fn read_is_borrow_whitelisted(storage: &dyn Storage, borrower: &CanonicalAddr) -> bool {
+    bucket_read::<bool>(storage, PREFIX_BORROW_WHITELIST)
+        .may_load(borrower.as_slice())
+        .unwrap_or(None)
+        .unwrap_or(false)
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -114,6 +442,130 @@ pub fn read_borrower_infos(
         .collect()
 }
 
+pub fn store_bad_debt(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    written_off_amount: &Uint256,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_BAD_DEBT).save(borrower.as_slice(), written_off_amount)
+}
+
+pub fn read_bad_debt(storage: &dyn Storage, borrower: &CanonicalAddr) -> Uint256 {
+    bucket_read(storage, PREFIX_BAD_DEBT)
+        .load(borrower.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+/// Aterra held by the market as additional collateral of a borrower,
+/// removed once fully released
+pub fn store_escrow(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    escrow_amount: &Uint256,
+) -> StdResult<()> {
+    if escrow_amount.is_zero() {
+        bucket::<Uint256>(storage, PREFIX_ESCROW).remove(borrower.as_slice());
+        return Ok(());
+    }
+
+    bucket(storage, PREFIX_ESCROW).save(borrower.as_slice(), escrow_amount)
+}
+
+pub fn read_escrow(storage: &dyn Storage, borrower: &CanonicalAddr) -> Uint256 {
+    bucket_read(storage, PREFIX_ESCROW)
+        .load(borrower.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+pub fn read_bad_debts(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BadDebtResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    bucket_read::<Uint256>(deps.storage, PREFIX_BAD_DEBT)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok(BadDebtResponse {
+                borrower: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                written_off_amount: v,
+            })
+        })
+        .collect()
+}
+
+pub fn read_staker_infos(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<StakerInfoResponse>> {
+    let staker_bucket: ReadonlyBucket<StakerInfo> = bucket_read(deps.storage, PREFIX_STAKER);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    staker_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let staker = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
+            Ok(StakerInfoResponse {
+                staker,
+                reward_index: v.reward_index,
+                bond_amount: v.bond_amount,
+                pending_rewards: v.pending_rewards,
+            })
+        })
+        .collect()
+}
+
+/// Liabilities ordered by their last recorded loan amount
+pub fn read_borrower_infos_by_amount(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+    descending: bool,
+) -> StdResult<Vec<BorrowerInfoResponse>> {
+    let index_bucket: ReadonlyBucket<bool> = bucket_read(deps.storage, PREFIX_LIABILITY_BY_AMOUNT);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_key = start_after.map(|borrower| {
+        let liability = read_borrower_info(deps.storage, &borrower);
+        liability_amount_key(liability.loan_amount, &borrower)
+    });
+
+    let iter = if descending {
+        index_bucket.range(None, start_key.as_deref(), Order::Descending)
+    } else {
+        let start = start_key.map(|mut key| {
+            key.push(1);
+            key
+        });
+        index_bucket.range(start.as_deref(), None, Order::Ascending)
+    };
+
+    iter.take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            let borrower_raw = CanonicalAddr::from(k[32..].to_vec());
+            let v = read_borrower_info(deps.storage, &borrower_raw);
+            let borrower = deps.api.addr_humanize(&borrower_raw)?.to_string();
+            Ok(BorrowerInfoResponse {
+                borrower,
+                interest_index: v.interest_index,
+                reward_index: v.reward_index,
+                loan_amount: v.loan_amount,
+                pending_rewards: v.pending_rewards,
+            })
+        })
+        .collect()
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
     start_after.map(|addr| {
@@ -122,3 +574,87 @@ fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
         v
     })
 }
+
+/// Storage of an additional stable denom market. It holds the same items
+/// as the root storage, which keeps the primary market
+pub fn market_storage<'a>(storage: &'a mut dyn Storage, denom: &str) -> PrefixedStorage<'a> {
+    PrefixedStorage::multilevel(storage, &[PREFIX_MARKET, denom.as_bytes()])
+}
+
+pub fn market_storage_read<'a>(
+    storage: &'a dyn Storage,
+    denom: &str,
+) -> ReadonlyPrefixedStorage<'a> {
+    ReadonlyPrefixedStorage::multilevel(storage, &[PREFIX_MARKET, denom.as_bytes()])
+}
+
+/// Denom of the additional market owning the storage, unset in the root storage
+pub fn store_market_denom(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    Singleton::new(storage, KEY_MARKET_DENOM).save(&denom.to_string())
+}
+
+pub fn read_market_denom(storage: &dyn Storage) -> StdResult<Option<String>> {
+    ReadonlySingleton::new(storage, KEY_MARKET_DENOM).may_load()
+}
+
+pub fn store_market(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    bucket(storage, PREFIX_MARKETS).save(denom.as_bytes(), &true)
+}
+
+pub fn is_market(storage: &dyn Storage, denom: &str) -> bool {
+    bucket_read::<bool>(storage, PREFIX_MARKETS)
+        .may_load(denom.as_bytes())
+        .unwrap_or(None)
+        .unwrap_or(false)
+}
+
+pub fn read_markets(storage: &dyn Storage) -> StdResult<Vec<String>> {
+    bucket_read::<bool>(storage, PREFIX_MARKETS)
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, _) = elem?;
+            String::from_utf8(k).map_err(StdError::from)
+        })
+        .collect()
+}
+
+/// Additional market of an aterra token, used to route its cw20 hooks
+pub fn store_market_token(
+    storage: &mut dyn Storage,
+    aterra_contract: &CanonicalAddr,
+    denom: &str,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_MARKET_TOKEN).save(aterra_contract.as_slice(), &denom.to_string())
+}
+
+pub fn read_market_token(
+    storage: &dyn Storage,
+    aterra_contract: &CanonicalAddr,
+) -> StdResult<Option<String>> {
+    bucket_read(storage, PREFIX_MARKET_TOKEN).may_load(aterra_contract.as_slice())
+}
+
+/// Additional markets awaiting the instantiation of their aterra token,
+/// in the order of the submessages
+pub fn push_pending_market(storage: &mut dyn Storage, denom: &str) -> StdResult<()> {
+    let mut pending = read_pending_markets(storage)?;
+    pending.push(denom.to_string());
+    Singleton::new(storage, KEY_PENDING_MARKETS).save(&pending)
+}
+
+pub fn pop_pending_market(storage: &mut dyn Storage) -> StdResult<String> {
+    let mut pending = read_pending_markets(storage)?;
+    if pending.is_empty() {
+        return Err(StdError::generic_err("No pending market"));
+    }
+
+    let denom = pending.remove(0);
+    Singleton::new(storage, KEY_PENDING_MARKETS).save(&pending)?;
+    Ok(denom)
+}
+
+fn read_pending_markets(storage: &dyn Storage) -> StdResult<Vec<String>> {
+    Ok(ReadonlySingleton::new(storage, KEY_PENDING_MARKETS)
+        .may_load()?
+        .unwrap_or_default())
+}
@@ -1,9 +1,13 @@
-use crate::borrow::{compute_borrower_interest, compute_interest};
-use crate::state::{store_state, BorrowerInfo, Config, State};
+use crate::borrow::{
+    compute_borrower_interest, compute_interest, compute_reward, query_liabilities_by_amount,
+};
+use crate::state::{store_borrower_info, store_config, store_state, BorrowerInfo, Config, State};
+use crate::testing::helpers::fast_forward_state;
 use crate::testing::mock_querier::mock_dependencies;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
-use cosmwasm_std::{Api, Coin, Uint128};
+use cosmwasm_std::{Addr, Api, Coin, StdError, Uint128};
+use std::str::FromStr;
 
 #[test]
 fn proper_compute_borrower_interest() {
@@ -85,7 +89,25 @@ fn proper_compute_interest() {
         collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
     };
 
     deps.querier
@@ -108,6 +130,7 @@ fn proper_compute_interest() {
 
     compute_interest(
         deps.as_ref(),
+        &env,
         &mock_config,
         &mut mock_state,
         env.block.height,
@@ -133,6 +156,7 @@ fn proper_compute_interest() {
 
     compute_interest(
         deps.as_ref(),
+        &env,
         &mock_config,
         &mut mock_state,
         env.block.height,
@@ -182,6 +206,7 @@ fn proper_compute_interest() {
     // target_deposit_rate: 0.01
     compute_interest(
         deps.as_ref(),
+        &env,
         &mock_config,
         &mut mock_state,
         env.block.height,
@@ -203,3 +228,722 @@ fn proper_compute_interest() {
         }
     );
 }
+
+#[test]
+fn fast_forward_matches_block_by_block() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+
+    let mut env = mock_env();
+
+    let mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+    store_config(&mut deps.storage, &mock_config).unwrap();
+
+    let mut mock_state = State {
+        total_liabilities: Decimal256::from_uint256(1000000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.height,
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::from(2000000u128),
+        prev_exchange_rate: Decimal256::one(),
+    };
+    store_state(&mut deps.storage, &mock_state).unwrap();
+
+    let projected = fast_forward_state(deps.as_ref(), 50).unwrap();
+
+    for _ in 0..50 {
+        env.block.height += 1;
+        compute_interest(
+            deps.as_ref(),
+            &env,
+            &mock_config,
+            &mut mock_state,
+            env.block.height,
+            None,
+        )
+        .unwrap();
+        compute_reward(&mut mock_state, env.block.height);
+    }
+
+    assert_eq!(projected, mock_state);
+    assert_eq!(projected.last_interest_updated, env.block.height);
+    assert!(projected.global_interest_index > Decimal256::from_str("1.05").unwrap());
+}
+
+#[test]
+fn compute_interest_liability_overflow() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let mut env = mock_env();
+
+    let mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+
+    let mut mock_state = State {
+        total_liabilities: Decimal256::from_uint256(Uint256::from(u128::MAX)),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.height,
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    // interest_factor = 1% * 1000000blocks = 10000
+    env.block.height += 1000000;
+    let res = compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Liability overflow"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn compute_interest_block_time() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let mut env = mock_env();
+
+    let mut mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: Some(6u64),
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+
+    let mut mock_state = State {
+        total_liabilities: Decimal256::from_uint256(1000000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.time.seconds(),
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    let mut block_state = mock_state.clone();
+    block_state.last_interest_updated = env.block.height;
+
+    // 600 seconds passed in 80 slow blocks, accrued as 100 blocks of 6 seconds
+    // interest_factor = 1% * 100blocks = 1
+    let mut time_env = env.clone();
+    time_env.block.height += 80;
+    time_env.block.time = time_env.block.time.plus_seconds(600);
+    compute_interest(
+        deps.as_ref(),
+        &time_env,
+        &mock_config,
+        &mut mock_state,
+        time_env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(2000000u128)
+    );
+    assert_eq!(
+        mock_state.global_interest_index,
+        Decimal256::from_uint256(2u128)
+    );
+    assert_eq!(
+        mock_state.last_interest_updated,
+        time_env.block.time.seconds()
+    );
+
+    // same accrual as 100 blocks on block height
+    mock_config.block_time = None;
+    env.block.height += 100;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut block_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(block_state.total_liabilities, mock_state.total_liabilities);
+    assert_eq!(
+        block_state.global_interest_index,
+        mock_state.global_interest_index
+    );
+
+    // half a block accrues half the interest
+    // interest_factor = 1% * 0.5blocks = 0.005
+    mock_config.block_time = Some(6u64);
+    time_env.block.time = time_env.block.time.plus_seconds(3);
+    compute_interest(
+        deps.as_ref(),
+        &time_env,
+        &mock_config,
+        &mut mock_state,
+        time_env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(2010000u128)
+    );
+}
+
+#[test]
+fn compute_interest_max_accrual_blocks() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let mut env = mock_env();
+
+    let mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: Some(10u64),
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+
+    let mut mock_state = State {
+        total_liabilities: Decimal256::from_uint256(1000000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.height,
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    // chain halted for 100 blocks, only 10 blocks are accrued
+    // interest_factor = 1% * 10blocks = 0.1
+    env.block.height += 100;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(1100000u128)
+    );
+    assert_eq!(
+        mock_state.global_interest_index,
+        Decimal256::from_str("1.1").unwrap()
+    );
+    assert_eq!(mock_state.last_interest_updated, env.block.height - 90);
+
+    // the remainder keeps accruing on the following computations
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(1210000u128)
+    );
+    assert_eq!(mock_state.last_interest_updated, env.block.height - 80);
+}
+
+#[test]
+fn compute_interest_min_borrow_rate() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(2000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    // interest model rate is below the floor
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+
+    let mut env = mock_env();
+
+    let mut mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::percent(1),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+
+    let mut mock_state = State {
+        total_liabilities: Decimal256::from_uint256(1000000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.height,
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    // interest_factor = max(0.1%, 1%) * 10blocks = 0.1
+    env.block.height += 10;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(1100000u128)
+    );
+    assert_eq!(
+        mock_state.global_interest_index,
+        Decimal256::from_str("1.1").unwrap()
+    );
+
+    // interest model rate is used once it is above the floor
+    // interest_factor = max(0.1%, 0.01%) * 10blocks = 0.01
+    mock_config.min_borrow_rate = Decimal256::from_str("0.0001").unwrap();
+    env.block.height += 10;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(1111000u128)
+    );
+}
+
+#[test]
+fn compute_interest_utilization_rate() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000000u128),
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    // rate = 0.1% + utilization * 1%
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+    deps.querier
+        .with_utilization_multiplier("interest", Decimal256::percent(1));
+
+    let mut env = mock_env();
+
+    let mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+
+    let mut mock_state = State {
+        total_liabilities: Decimal256::from_uint256(1000000u128),
+        total_reserves: Decimal256::zero(),
+        last_interest_updated: env.block.height,
+        last_reward_updated: env.block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+
+    // utilization = 1000000 / (1000000 + 1000000) = 0.5
+    // interest_factor = (0.1% + 0.5%) * 10blocks = 0.06
+    env.block.height += 10;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(1060000u128)
+    );
+
+    // higher utilization, higher rate
+    // utilization = 3000000 / (3000000 + 1000000) = 0.75
+    // interest_factor = (0.1% + 0.75%) * 10blocks = 0.085
+    mock_state.total_liabilities = Decimal256::from_uint256(3000000u128);
+    env.block.height += 10;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(3255000u128)
+    );
+
+    // models which cannot parse the market context get the legacy query
+    // interest_factor = 0.1% * 10blocks = 0.01
+    deps.querier.with_legacy_interest_model("interest");
+    mock_state.total_liabilities = Decimal256::from_uint256(1000000u128);
+    mock_state.total_reserves = Decimal256::zero();
+    mock_state.prev_exchange_rate = Decimal256::one();
+    env.block.height += 10;
+    compute_interest(
+        deps.as_ref(),
+        &env,
+        &mock_config,
+        &mut mock_state,
+        env.block.height,
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        mock_state.total_liabilities,
+        Decimal256::from_uint256(1010000u128)
+    );
+}
+
+#[test]
+fn liabilities_by_amount() {
+    let mut deps = mock_dependencies(&[]);
+
+    // loans are read as of the last interest update
+    let env = mock_env();
+    let mock_config = Config {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
+    };
+    store_config(&mut deps.storage, &mock_config).unwrap();
+    store_state(
+        &mut deps.storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let mut store_loan = |borrower: &str, loan_amount: u64| {
+        let borrower_raw = deps.api.addr_canonicalize(borrower).unwrap();
+        store_borrower_info(
+            deps.as_mut().storage,
+            &borrower_raw,
+            &BorrowerInfo {
+                interest_index: Decimal256::one(),
+                reward_index: Decimal256::zero(),
+                loan_amount: Uint256::from(loan_amount),
+                pending_rewards: Decimal256::zero(),
+            },
+        )
+        .unwrap();
+    };
+
+    store_loan("addr0000", 100);
+    store_loan("addr0001", 300);
+    store_loan("addr0002", 200);
+    store_loan("addr0003", 400);
+
+    // updated and repaid loans are re-indexed
+    store_loan("addr0001", 50);
+    store_loan("addr0003", 0);
+
+    let borrowers = |start_after: Option<&str>, limit: u32, descending: bool| {
+        query_liabilities_by_amount(
+            deps.as_ref(),
+            env.clone(),
+            start_after.map(Addr::unchecked),
+            Some(limit),
+            Some(descending),
+            None,
+        )
+        .unwrap()
+        .borrower_infos
+        .into_iter()
+        .map(|info| (info.borrower, info.loan_amount))
+        .collect::<Vec<(String, Uint256)>>()
+    };
+
+    assert_eq!(
+        borrowers(None, 2, true),
+        vec![
+            ("addr0002".to_string(), Uint256::from(200u64)),
+            ("addr0000".to_string(), Uint256::from(100u64)),
+        ]
+    );
+    assert_eq!(
+        borrowers(Some("addr0000"), 2, true),
+        vec![("addr0001".to_string(), Uint256::from(50u64))]
+    );
+    assert_eq!(
+        borrowers(None, 2, false),
+        vec![
+            ("addr0001".to_string(), Uint256::from(50u64)),
+            ("addr0000".to_string(), Uint256::from(100u64)),
+        ]
+    );
+    assert_eq!(
+        borrowers(Some("addr0000"), 2, false),
+        vec![("addr0002".to_string(), Uint256::from(200u64))]
+    );
+}
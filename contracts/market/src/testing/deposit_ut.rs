@@ -23,7 +23,25 @@ fn proper_compute_exchange_rate() {
         collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
         overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         max_borrow_factor: Decimal256::one(),
+        max_accrual_blocks: None,
+        min_borrow_rate: Decimal256::zero(),
+        insurance_fund: None,
+        insurance_split: Decimal256::zero(),
+        flash_loan_fee: Decimal256::zero(),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        paused_operations: vec![],
+        block_time: None,
+        min_borrow_amount: Uint256::zero(),
+        dust_threshold: Uint256::zero(),
+        borrow_whitelist_enabled: false,
+        aterra_name: "Anchor Terra USD".to_string(),
+        aterra_symbol: "aUST".to_string(),
+        aterra_code_id: 123u64,
+        market_id: None,
     };
     deps.querier.with_token_balances(&[(
         &"AT-uusd".to_string(),
@@ -0,0 +1,21 @@
+use crate::borrow::{compute_interest, compute_reward};
+use crate::state::{read_config, read_state, Config, State};
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{Deps, StdResult};
+
+/// Project the stored state `blocks` ahead, accruing interest and reward
+/// on every block as if the market was touched each block.
+/// Nothing is written back to the storage.
+pub fn fast_forward_state(deps: Deps, blocks: u64) -> StdResult<State> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+    let env = mock_env();
+
+    let start = state.last_interest_updated;
+    for block_height in start + 1..=start + blocks {
+        compute_interest(deps, &env, &config, &mut state, block_height, None)?;
+        compute_reward(&mut state, block_height);
+    }
+
+    Ok(state)
+}
@@ -0,0 +1,351 @@
+use crate::contract::{execute, instantiate, query, reply, INITIAL_DEPOSIT_AMOUNT};
+use crate::response::MsgInstantiateContractResponse;
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, BankMsg, Coin, ContractResult, CosmosMsg, Env, OwnedDeps, Reply,
+    Response, SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use moneymarket::market::{
+    BorrowerInfoResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+use proptest::prelude::*;
+use protobuf::Message;
+use std::collections::HashMap;
+
+const USERS: [&str; 3] = ["addr0000", "addr0001", "addr0002"];
+
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit(usize, u64),
+    Borrow(usize, u64),
+    Repay(usize, u64),
+    Redeem(usize, u8),
+    DepositRedeem(usize, u64),
+    Advance(u64),
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..USERS.len(), 1u64..10_000_000).prop_map(|(u, a)| Op::Deposit(u, a)),
+        (0..USERS.len(), 1u64..1_000_000).prop_map(|(u, a)| Op::Borrow(u, a)),
+        (0..USERS.len(), 1u64..1_000_000).prop_map(|(u, a)| Op::Repay(u, a)),
+        (0..USERS.len(), 1u8..=100).prop_map(|(u, p)| Op::Redeem(u, p)),
+        (0..USERS.len(), 1u64..10_000_000).prop_map(|(u, a)| Op::DepositRedeem(u, a)),
+        (1u64..20).prop_map(Op::Advance),
+    ]
+}
+
+/// Market balances kept in step with the messages the market returns,
+/// standing in for the bank and the aterra token
+struct Market {
+    deps: OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    env: Env,
+    stable_balance: u128,
+    aterra_balances: HashMap<String, u128>,
+}
+
+impl Market {
+    fn new() -> Self {
+        let mut deps = mock_dependencies(&[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }]);
+
+        let msg = InstantiateMsg {
+            owner_addr: "owner".to_string(),
+            stable_denom: "uusd".to_string(),
+            stable_token: None,
+            aterra_code_id: 123u64,
+            anc_emission_rate: Decimal256::one(),
+            max_borrow_factor: Decimal256::one(),
+            stable_decimals: None,
+            aterra_name: None,
+            aterra_symbol: None,
+            market_id: None,
+            additional_denoms: None,
+        };
+        let info = mock_info(
+            "owner",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            }],
+        );
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let mut token_inst_res = MsgInstantiateContractResponse::new();
+        token_inst_res.set_contract_address("AT-uusd".to_string());
+        let reply_msg = Reply {
+            id: 1,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+            }),
+        };
+        let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+        deps.querier
+            .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+
+        let msg = ExecuteMsg::RegisterContracts {
+            overseer_contract: "overseer".to_string(),
+            interest_model: "interest".to_string(),
+            distribution_model: "distribution".to_string(),
+            collector_contract: "collector".to_string(),
+            distributor_contract: "distributor".to_string(),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let borrow_limit = Uint256::from(1_000_000_000_000u64);
+        let users: Vec<String> = USERS.iter().map(|u| u.to_string()).collect();
+        deps.querier.with_borrow_limit(
+            &users
+                .iter()
+                .map(|u| (u, &borrow_limit))
+                .collect::<Vec<(&String, &Uint256)>>(),
+        );
+
+        let mut market = Market {
+            deps,
+            env: mock_env(),
+            stable_balance: INITIAL_DEPOSIT_AMOUNT,
+            aterra_balances: HashMap::new(),
+        };
+        market
+            .aterra_balances
+            .insert(MOCK_CONTRACT_ADDR.to_string(), INITIAL_DEPOSIT_AMOUNT);
+        market.sync_balances();
+        market
+    }
+
+    fn sync_balances(&mut self) {
+        self.deps.querier.update_balance(
+            MOCK_CONTRACT_ADDR,
+            vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(self.stable_balance),
+            }],
+        );
+
+        let holders: Vec<(String, Uint128)> = self
+            .aterra_balances
+            .iter()
+            .map(|(holder, balance)| (holder.clone(), Uint128::from(*balance)))
+            .collect();
+        let holders: Vec<(&String, &Uint128)> = holders.iter().map(|(h, b)| (h, b)).collect();
+        self.deps
+            .querier
+            .with_token_balances(&[(&"AT-uusd".to_string(), &holders[..])]);
+    }
+
+    fn aterra_balance(&self, holder: &str) -> u128 {
+        *self.aterra_balances.get(holder).unwrap_or(&0)
+    }
+
+    /// Apply the transfers requested by the market,
+    /// returning the stable amount paid out
+    fn apply(&mut self, res: &Response) -> u128 {
+        let mut paid_out = 0u128;
+        for sub_msg in res.messages.iter() {
+            match &sub_msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                    let amount: u128 = amount.iter().map(|c| c.amount.u128()).sum();
+                    self.stable_balance -= amount;
+                    paid_out += amount;
+                }
+                CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(msg).unwrap() {
+                    Cw20ExecuteMsg::Mint { recipient, amount } => {
+                        *self.aterra_balances.entry(recipient).or_insert(0) += amount.u128();
+                    }
+                    Cw20ExecuteMsg::Burn { amount } => {
+                        *self.aterra_balances.get_mut(MOCK_CONTRACT_ADDR).unwrap() -= amount.u128();
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                },
+                _ => panic!("DO NOT ENTER HERE"),
+            }
+        }
+
+        self.sync_balances();
+        paid_out
+    }
+
+    fn execute_with_funds(&mut self, user: &str, amount: u128, msg: ExecuteMsg) -> Option<u128> {
+        // Funds reach the market before it executes
+        self.stable_balance += amount;
+        self.sync_balances();
+
+        let info = mock_info(
+            user,
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        );
+        match execute(self.deps.as_mut(), self.env.clone(), info, msg) {
+            Ok(res) => Some(self.apply(&res)),
+            Err(_) => {
+                self.stable_balance -= amount;
+                self.sync_balances();
+                None
+            }
+        }
+    }
+
+    fn deposit(&mut self, user: &str, amount: u128) -> Option<u128> {
+        let before = self.aterra_balance(user);
+        let msg = ExecuteMsg::DepositStable {
+            recipient: None,
+            deadline: None,
+        };
+        self.execute_with_funds(user, amount, msg)?;
+        Some(self.aterra_balance(user) - before)
+    }
+
+    fn redeem(&mut self, user: &str, amount: u128) -> Option<u128> {
+        if amount == 0 || self.aterra_balance(user) < amount {
+            return None;
+        }
+
+        // Aterra is sent to the market along with the hook
+        *self.aterra_balances.get_mut(user).unwrap() -= amount;
+        *self
+            .aterra_balances
+            .entry(MOCK_CONTRACT_ADDR.to_string())
+            .or_insert(0) += amount;
+        self.sync_balances();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: user.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::RedeemStable {
+                deadline: None,
+                target_stable: None,
+            })
+            .unwrap(),
+        });
+        match execute(
+            self.deps.as_mut(),
+            self.env.clone(),
+            mock_info("AT-uusd", &[]),
+            msg,
+        ) {
+            Ok(res) => Some(self.apply(&res)),
+            Err(_) => {
+                *self.aterra_balances.get_mut(MOCK_CONTRACT_ADDR).unwrap() -= amount;
+                *self.aterra_balances.get_mut(user).unwrap() += amount;
+                self.sync_balances();
+                None
+            }
+        }
+    }
+
+    fn run(&mut self, op: &Op) {
+        match *op {
+            Op::Deposit(user, amount) => {
+                self.deposit(USERS[user], amount as u128);
+            }
+            Op::Borrow(user, amount) => {
+                let msg = ExecuteMsg::BorrowStable {
+                    borrow_amount: Uint256::from(amount),
+                    to: None,
+                    deadline: None,
+                };
+                self.execute_with_funds(USERS[user], 0, msg);
+            }
+            Op::Repay(user, amount) => {
+                let msg = ExecuteMsg::RepayStable { deadline: None };
+                self.execute_with_funds(USERS[user], amount as u128, msg);
+            }
+            Op::Redeem(user, percent) => {
+                let amount = self.aterra_balance(USERS[user]) * percent as u128 / 100;
+                self.redeem(USERS[user], amount);
+            }
+            Op::DepositRedeem(user, amount) => {
+                if let Some(minted) = self.deposit(USERS[user], amount as u128) {
+                    if let Some(received) = self.redeem(USERS[user], minted) {
+                        assert!(
+                            received <= amount as u128,
+                            "redeemed {} out of a {} deposit",
+                            received,
+                            amount
+                        );
+                    }
+                }
+            }
+            Op::Advance(blocks) => {
+                self.env.block.height += blocks;
+            }
+        }
+    }
+
+    fn assert_liabilities_covered(&self) {
+        let state: StateResponse = from_binary(
+            &query(
+                self.deps.as_ref(),
+                self.env.clone(),
+                QueryMsg::State {
+                    block_height: Some(self.env.block.height),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let mut loans = Uint256::zero();
+        for user in USERS.iter() {
+            let borrower_info: BorrowerInfoResponse = from_binary(
+                &query(
+                    self.deps.as_ref(),
+                    self.env.clone(),
+                    QueryMsg::BorrowerInfo {
+                        borrower: user.to_string(),
+                        block_height: Some(self.env.block.height),
+                    },
+                )
+                .unwrap(),
+            )
+            .unwrap();
+            loans += borrower_info.loan_amount;
+        }
+
+        // Each loan may round by one unit against the total
+        let epsilon = Decimal256::from_uint256(USERS.len() as u64);
+        assert!(
+            Decimal256::from_uint256(loans) <= state.total_liabilities + epsilon,
+            "loans {} exceed total liabilities {}",
+            loans,
+            state.total_liabilities
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn loans_never_exceed_total_liabilities(ops in prop::collection::vec(op_strategy(), 1..40)) {
+        let mut market = Market::new();
+        for op in ops.iter() {
+            market.run(op);
+            market.assert_liabilities_covered();
+        }
+    }
+
+    #[test]
+    fn deposit_then_redeem_never_profits(
+        ops in prop::collection::vec(op_strategy(), 0..20),
+        user in 0..USERS.len(),
+        amount in 1u64..10_000_000,
+    ) {
+        let mut market = Market::new();
+        for op in ops.iter() {
+            market.run(op);
+        }
+
+        market.run(&Op::DepositRedeem(user, amount));
+    }
+}
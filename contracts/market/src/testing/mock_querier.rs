@@ -10,10 +10,11 @@ use cosmwasm_std::{
 use cosmwasm_storage::to_length_prefixed;
 use std::collections::HashMap;
 
-use cw20::TokenInfoResponse;
+use cw20::{BalanceResponse as Cw20BalanceResponse, TokenInfoResponse};
 use moneymarket::distribution_model::AncEmissionRateResponse;
 use moneymarket::interest_model::BorrowRateResponse;
-use moneymarket::overseer::{BorrowLimitResponse, ConfigResponse};
+use moneymarket::overseer::{BorrowLimitResponse, CollateralsResponse, ConfigResponse};
+use moneymarket::tokens::TokensHuman;
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -24,12 +25,19 @@ pub enum QueryMsg {
         market_balance: Uint128,
         total_liabilities: Decimal256,
         total_reserves: Decimal256,
+        #[serde(default)]
+        stable_denom: Option<String>,
+        #[serde(default)]
+        utilization: Option<Decimal256>,
     },
     /// Query borrow limit to overseer contract
     BorrowLimit {
         borrower: String,
         block_time: Option<u64>,
+        escrow_value: Option<Uint256>,
     },
+    /// Query collaterals to overseer contract
+    Collaterals { borrower: String },
     /// Query ANC emission rate to distribution model contract
     AncEmissionRate {
         deposit_rate: Decimal256,
@@ -41,6 +49,8 @@ pub enum QueryMsg {
     Config {},
     /// Query cw20 Token Info
     TokenInfo {},
+    /// Query cw20 Token Balance
+    Balance { address: String },
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -123,12 +133,18 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 pub struct BorrowRateQuerier {
     // this lets us iterate over all pairs that match the first string
     borrower_rate: HashMap<String, Decimal256>,
+    // rate added per unit of utilization
+    utilization_multiplier: HashMap<String, Decimal256>,
+    // models which reject the market context
+    legacy: Vec<String>,
 }
 
 impl BorrowRateQuerier {
     pub fn new(borrower_rate: &[(&String, &Decimal256)]) -> Self {
         BorrowRateQuerier {
             borrower_rate: borrower_rate_to_map(borrower_rate),
+            utilization_multiplier: HashMap::new(),
+            legacy: vec![],
         }
     }
 }
@@ -147,12 +163,14 @@ pub(crate) fn borrower_rate_to_map(
 pub struct BorrowLimitQuerier {
     // this lets us iterate over all pairs that match the first string
     borrow_limit: HashMap<String, Uint256>,
+    collaterals: HashMap<String, TokensHuman>,
 }
 
 impl BorrowLimitQuerier {
     pub fn new(borrow_limit: &[(&String, &Uint256)]) -> Self {
         BorrowLimitQuerier {
             borrow_limit: borrow_limit_to_map(borrow_limit),
+            collaterals: HashMap::new(),
         }
     }
 }
@@ -217,10 +235,29 @@ impl WasmMockQuerier {
                         market_balance: _,
                         total_liabilities: _,
                         total_reserves: _,
+                        stable_denom,
+                        utilization,
                     } => {
+                        if self.borrow_rate_querier.legacy.contains(contract_addr)
+                            && (stable_denom.is_some() || utilization.is_some())
+                        {
+                            return SystemResult::Err(SystemError::InvalidRequest {
+                                error: "unknown field".to_string(),
+                                request: msg.as_slice().into(),
+                            });
+                        }
+
+                        let multiplier = self
+                            .borrow_rate_querier
+                            .utilization_multiplier
+                            .get(contract_addr)
+                            .copied()
+                            .unwrap_or_default();
                         match self.borrow_rate_querier.borrower_rate.get(contract_addr) {
                             Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
-                                &BorrowRateResponse { rate: *v },
+                                &BorrowRateResponse {
+                                    rate: *v + utilization.unwrap_or_default() * multiplier,
+                                },
                             ))),
                             None => SystemResult::Err(SystemError::InvalidRequest {
                                 error: "No borrow rate exists".to_string(),
@@ -231,11 +268,13 @@ impl WasmMockQuerier {
                     QueryMsg::BorrowLimit {
                         borrower,
                         block_time: _,
+                        escrow_value,
                     } => match self.borrow_limit_querier.borrow_limit.get(&borrower) {
                         Some(v) => SystemResult::Ok(ContractResult::from(to_binary(
                             &BorrowLimitResponse {
                                 borrower,
-                                borrow_limit: *v,
+                                borrow_limit: *v
+                                    + escrow_value.unwrap_or_default() * Decimal256::percent(50),
                             },
                         ))),
                         None => SystemResult::Err(SystemError::InvalidRequest {
@@ -243,6 +282,20 @@ impl WasmMockQuerier {
                             request: msg.as_slice().into(),
                         }),
                     },
+                    QueryMsg::Collaterals { borrower } => {
+                        let collaterals = self
+                            .borrow_limit_querier
+                            .collaterals
+                            .get(&borrower)
+                            .cloned()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&CollateralsResponse {
+                            borrower,
+                            collateral_prices: vec![Decimal256::one(); collaterals.len()],
+                            collateral_values: collaterals.iter().map(|c| c.1).collect(),
+                            collaterals,
+                        })))
+                    }
                     QueryMsg::AncEmissionRate {
                         deposit_rate: _,
                         target_deposit_rate: _,
@@ -267,6 +320,11 @@ impl WasmMockQuerier {
                             stable_denom: "uusd".to_string(),
                             epoch_period: 100u64,
                             price_timeframe: 100u64,
+                            max_total_collateral_value: None,
+                            keeper_reward: Uint256::zero(),
+                            liquidate_on_stale_price: false,
+                            escrow_max_ltv: Decimal256::percent(50),
+                            market_denom: None,
                         })))
                     }
                     QueryMsg::TokenInfo {} => {
@@ -289,6 +347,19 @@ impl WasmMockQuerier {
                             total_supply,
                         })))
                     }
+                    QueryMsg::Balance { address } => {
+                        let balance = self
+                            .token_querier
+                            .balances
+                            .get(contract_addr)
+                            .and_then(|balances| balances.get(&address))
+                            .cloned()
+                            .unwrap_or_default();
+
+                        SystemResult::Ok(ContractResult::from(to_binary(&Cw20BalanceResponse {
+                            balance,
+                        })))
+                    }
                 }
             }
             QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
@@ -368,7 +439,27 @@ impl WasmMockQuerier {
         self.borrow_rate_querier = BorrowRateQuerier::new(borrow_rate);
     }
 
+    // configure the rate added per unit of utilization
+    pub fn with_utilization_multiplier(&mut self, interest_model: &str, multiplier: Decimal256) {
+        self.borrow_rate_querier
+            .utilization_multiplier
+            .insert(interest_model.to_string(), multiplier);
+    }
+
+    // configure an interest model which cannot parse the market context
+    pub fn with_legacy_interest_model(&mut self, interest_model: &str) {
+        self.borrow_rate_querier
+            .legacy
+            .push(interest_model.to_string());
+    }
+
     pub fn with_borrow_limit(&mut self, borrow_limit: &[(&String, &Uint256)]) {
         self.borrow_limit_querier = BorrowLimitQuerier::new(borrow_limit);
     }
+
+    pub fn with_collaterals(&mut self, borrower: &str, collaterals: TokensHuman) {
+        self.borrow_limit_querier
+            .collaterals
+            .insert(borrower.to_string(), collaterals);
+    }
 }
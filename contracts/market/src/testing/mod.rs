@@ -1,4 +1,6 @@
 mod borrow_ut;
 mod deposit_ut;
+mod helpers;
+mod invariants;
 mod mock_querier;
 mod tests;
@@ -1,21 +1,36 @@
-use crate::contract::{execute, instantiate, query, reply, INITIAL_DEPOSIT_AMOUNT};
+use crate::contract::{
+    execute, instantiate, migrate, query, reply, CONTRACT_NAME, CONTRACT_VERSION,
+    INITIAL_DEPOSIT_AMOUNT,
+};
 use crate::error::ContractError;
 use crate::response::MsgInstantiateContractResponse;
-use crate::state::{read_borrower_infos, read_state, store_state, State};
+use crate::state::{
+    market_storage_read, read_borrower_infos, read_config, read_contract_version, read_state,
+    store_contract_version, store_escrow, store_interest_index_checkpoint, store_state,
+    BorrowerInfo, ContractVersion, LegacyConfig, State, KEY_CONFIG,
+};
 use crate::testing::mock_querier::mock_dependencies;
 
 use anchor_token::distributor::ExecuteMsg as FaucetExecuteMsg;
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Reply,
-    SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Decimal, Event, Reply, StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
 };
+use cosmwasm_storage::{bucket, singleton};
 use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use moneymarket::market::{
-    BorrowerInfoResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse,
+    BadDebtResponse, BadDebtsResponse, BorrowerHealthResponse, BorrowerIndexResponse,
+    BorrowerInfoResponse, BorrowerInfosResponse, BorrowerPositionResponse, ConfigResponse,
+    Cw20HookMsg, DepositValueResponse, EscrowResponse, ExecuteMsg, InstantiateMsg,
+    InterestIndexAtResponse, IsWhitelistedResponse, LoanAmountAtResponse, MarketOperation,
+    MarketStateResponse, MarketsResponse, MarketsResponseElem, MigrateMsg, PendingOwnerResponse,
+    QueryMsg, RequiredBorrowLimitResponse, ReservesFundedResponse, SimulateDepositResponse,
+    SimulateRedeemResponse, StakerInfoResponse, StakerInfosResponse, StakingStateResponse,
+    StateResponse, TotalDepositsResponse, UtilizationResponse, WiredContractsResponse,
 };
+use moneymarket::overseer::ExecuteMsg as OverseerExecuteMsg;
 use moneymarket::querier::deduct_tax;
 use protobuf::Message;
 use std::str::FromStr;
@@ -31,9 +46,15 @@ fn proper_initialization() {
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -85,8 +106,67 @@ fn proper_initialization() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg.clone()).unwrap();
 
-    // Cannot register again
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+    // Registering the same token again is a no-op
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("aterra", "AT-uusd"),
+            attr("already_registered", "true")
+        ]
+    );
+
+    // Cannot register another token
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd2".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::WiredContracts {}).unwrap();
+    let wired_res: WiredContractsResponse = from_binary(&res).unwrap();
+    assert_eq!(wired_res.aterra_contract, Some("AT-uusd".to_string()));
+
+    // Interest model which fails to answer borrow rate query
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidInterestModel {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Interest model cannot be the market itself
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: MOCK_CONTRACT_ADDR.to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidInterestModel {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
 
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
@@ -121,6 +201,9 @@ fn proper_initialization() {
     assert_eq!("overseer".to_string(), config_res.overseer_contract);
     assert_eq!("uusd".to_string(), config_res.stable_denom);
     assert_eq!(Decimal256::one(), config_res.max_borrow_factor);
+    assert_eq!("Anchor Terra USD".to_string(), config_res.aterra_name);
+    assert_eq!("aUST".to_string(), config_res.aterra_symbol);
+    assert_eq!(123u64, config_res.aterra_code_id);
 
     let query_res = query(
         deps.as_ref(),
@@ -139,20 +222,24 @@ fn proper_initialization() {
 }
 
 #[test]
-fn update_config() {
+fn instantiate_with_custom_token_info() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: Some(18u8),
+        aterra_name: Some("Anchor Stable".to_string()),
+        aterra_symbol: Some("aSTB".to_string()),
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -163,87 +250,204 @@ fn update_config() {
         }],
     );
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: 123u64,
+                funds: vec![],
+                label: "".to_string(),
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "Anchor Stable".to_string(),
+                    symbol: "aSTB".to_string(),
+                    decimals: 18u8,
+                    initial_balances: vec![Cw20Coin {
+                        address: MOCK_CONTRACT_ADDR.to_string(),
+                        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+                    }],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+            }),
+            1
+        )]
+    );
 
-    // Register anchor token contract
-    let mut token_inst_res = MsgInstantiateContractResponse::new();
-    token_inst_res.set_contract_address("AT-uusd".to_string());
-    let reply_msg = Reply {
-        id: 1,
-        result: ContractResult::Ok(SubMsgExecutionResponse {
-            events: vec![],
-            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
-        }),
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.aterra_name, "Anchor Stable".to_string());
+    assert_eq!(config.aterra_symbol, "aSTB".to_string());
+}
+
+#[test]
+fn ownership_transfer() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Register overseer contract
-    let msg = ExecuteMsg::RegisterContracts {
-        overseer_contract: "overseer".to_string(),
-        interest_model: "interest".to_string(),
-        distribution_model: "distribution".to_string(),
-        collector_contract: "collector".to_string(),
-        distributor_contract: "distributor".to_string(),
+    let mut env = mock_env();
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner1".to_string(),
+        expires_at: env.block.height + 100,
     };
+
+    // only owner can propose
     let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // update owner
+    // proposal must expire in the future
     let info = mock_info("owner", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: Some("owner1".to_string()),
-        interest_model: None,
-        distribution_model: None,
-        max_borrow_factor: None,
-    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ProposeNewOwner {
+            owner: "owner1".to_string(),
+            expires_at: env.block.height,
+        },
+    );
+    match res {
+        Err(ContractError::OwnershipProposalExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("owner", "owner1"),
+            attr("expires_at", (env.block.height + 100).to_string()),
+        ]
+    );
 
-    // it worked, let's query the state
-    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
-    let config_res: ConfigResponse = from_binary(&res).unwrap();
-    assert_eq!("owner1".to_string(), config_res.owner_addr);
+    let res: PendingOwnerResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingOwner {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        PendingOwnerResponse {
+            owner: Some("owner1".to_string()),
+            expires_at: Some(env.block.height + 100),
+        }
+    );
 
-    // update left items
-    let info = mock_info("owner1", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
-        interest_model: Some("interest2".to_string()),
-        distribution_model: Some("distribution2".to_string()),
-        max_borrow_factor: Some(Decimal256::percent(100)),
-    };
+    // the owner does not change until claimed
+    let owner_msg = ExecuteMsg::UpdateAterraCodeId { code_id: 124u64 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner1", &[]),
+        owner_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    // only the proposed owner can claim
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // it worked, let's query the state
-    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
-    let config_res: ConfigResponse = from_binary(&res).unwrap();
-    assert_eq!("owner1".to_string(), config_res.owner_addr);
-    assert_eq!("interest2".to_string(), config_res.interest_model);
-    assert_eq!("distribution2".to_string(), config_res.distribution_model);
-    assert_eq!(Decimal256::percent(100), config_res.max_borrow_factor);
+    let info = mock_info("owner1", &[]);
+    let mut expired_env = env.clone();
+    expired_env.block.height += 101;
+    let res = execute(
+        deps.as_mut(),
+        expired_env,
+        info.clone(),
+        ExecuteMsg::ClaimOwnership {},
+    );
+    match res {
+        Err(ContractError::OwnershipProposalExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // Unauthorized err
-    let info = mock_info("owner", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner_addr: None,
-        interest_model: None,
-        distribution_model: None,
-        max_borrow_factor: None,
-    };
+    env.block.height += 100;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "claim_ownership"), attr("owner", "owner1"),]
+    );
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), owner_msg.clone()).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        owner_msg,
+    );
     match res {
         Err(ContractError::Unauthorized {}) => (),
-        _ => panic!("Must return unauthorized error"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the proposal is consumed
+    let res: PendingOwnerResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::PendingOwner {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        PendingOwnerResponse {
+            owner: None,
+            expires_at: None,
+        }
+    );
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::ClaimOwnership {});
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
     }
 }
 
 #[test]
-fn deposit_stable_huge_amount() {
+fn update_aterra_code_id() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
@@ -252,9 +456,15 @@ fn deposit_stable_huge_amount() {
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -280,6 +490,9 @@ fn deposit_stable_huge_amount() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -291,33 +504,82 @@ fn deposit_stable_huge_amount() {
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Must deposit stable_denom
-    let msg = ExecuteMsg::DepositStable {};
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "ukrw".to_string(),
-            amount: Uint128::from(123u128),
-        }],
-    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.aterra_code_id, 123u64);
 
+    // Only owner can update the code id
+    let msg = ExecuteMsg::UpdateAterraCodeId { code_id: 456u64 };
+    let info = mock_info("addr0000", &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
-    let _uusd_string = "uusd";
     match res {
-        Err(ContractError::ZeroDeposit(_uusd_string)) => (),
+        Err(ContractError::Unauthorized {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_aterra_code_id"),
+            attr("code_id", "456"),
+        ]
+    );
+
+    // No migration is triggered
+    assert_eq!(res.messages.len(), 0);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.aterra_code_id, 456u64);
+}
+
+#[test]
+fn market_id_attribute() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: Some("uusd-main".to_string()),
+        additional_denoms: None,
+    };
+
     let info = mock_info(
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(55_555_555_000_000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
     deps.querier.with_token_balances(&[(
         &"AT-uusd".to_string(),
         &[(
@@ -325,92 +587,171 @@ fn deposit_stable_huge_amount() {
             &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         )],
     )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.market_id, Some("uusd-main".to_string()));
+
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 55_555_555_000_000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
         }],
     );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes.last(), Some(&attr("market_id", "uusd-main")));
+}
 
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", "addr0000"),
-            attr("mint_amount", "55555555000000"),
-            attr("deposit_amount", "55555555000000"),
-        ]
-    );
+#[test]
+fn wired_contracts() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
 
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "AT-uusd".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(55_555_555_000_000u128),
-            })
-            .unwrap(),
-        }))]
-    );
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
 
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(111_111_110_000_000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
 
-    deps.querier.with_token_balances(&[(
-        &"AT-uusd".to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(55_555_555_000_000u128),
-        )],
-    )]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res: WiredContractsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::WiredContracts {}).unwrap())
+            .unwrap();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", "addr0000"),
-            attr("mint_amount", "55555555000000"),
-            attr("deposit_amount", "55555555000000"),
-        ]
+        res,
+        WiredContractsResponse {
+            anchor_token_registered: false,
+            overseer_registered: false,
+            aterra_contract: None,
+            overseer_contract: None,
+            interest_model: None,
+            distribution_model: None,
+            collector_contract: None,
+            distributor_contract: None,
+        }
     );
 
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let res: WiredContractsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::WiredContracts {}).unwrap())
+            .unwrap();
+    assert!(res.anchor_token_registered);
+    assert!(!res.overseer_registered);
+    assert_eq!(res.aterra_contract, Some("AT-uusd".to_string()));
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: WiredContractsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::WiredContracts {}).unwrap())
+            .unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "AT-uusd".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(55_555_555_000_000u128),
-            })
-            .unwrap(),
-        }))]
+        res,
+        WiredContractsResponse {
+            anchor_token_registered: true,
+            overseer_registered: true,
+            aterra_contract: Some("AT-uusd".to_string()),
+            overseer_contract: Some("overseer".to_string()),
+            interest_model: Some("interest".to_string()),
+            distribution_model: Some("distribution".to_string()),
+            collector_contract: Some("collector".to_string()),
+            distributor_contract: Some("distributor".to_string()),
+        }
     );
 }
 
 #[test]
-fn deposit_stable() {
+fn update_config() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
+    deps.querier.with_borrow_rate(&[
+        (&"interest".to_string(), &Decimal256::percent(1)),
+        (&"interest2".to_string(), &Decimal256::percent(1)),
+        (&"interest4".to_string(), &Decimal256::one()),
+    ]);
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -447,202 +788,478 @@ fn deposit_stable() {
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Must deposit stable_denom
-    let msg = ExecuteMsg::DepositStable {};
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "ukrw".to_string(),
-            amount: Uint128::from(123u128),
-        }],
-    );
+    // update owner
+    let info = mock_info("owner", &[]);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner1".to_string(),
+        expires_at: mock_env().block.height + 100,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
-    let _uusd_string = "uusd";
+    let info = mock_info("owner1", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimOwnership {},
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner1".to_string(), config_res.owner_addr);
+
+    // interest model must answer borrow rate query
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: Some("interest3".to_string()),
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::ZeroDeposit(_uusd_string)) => (),
+        Err(ContractError::InvalidInterestModel {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // base denom, zero deposit
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::zero(),
-        }],
-    );
+    // interest model must return a sane borrow rate
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: Some("interest4".to_string()),
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::ZeroDeposit(_uusd_string)) => (),
+        Err(ContractError::InvalidInterestModel {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(1000000u128),
-        }],
-    );
+    // interest model cannot be the market itself
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: Some(MOCK_CONTRACT_ADDR.to_string()),
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier.with_token_balances(&[(
-        &"AT-uusd".to_string(),
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-        )],
-    )]);
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
-        }],
-    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidInterestModel {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
-    // 1- As the last place to modify the state is compute_interest, a check on the state ensures the invocation of compute_interest.
-    // However, because passed_blocks = 0, interest factor & interest accrued are also 0, and thus the values do not change
-    // (looking as if the function might not have been invoked at all.)
-    // Thus, later, the invocation of compute interest will be tested after increasing the block height.
-    assert_eq!(
-        read_state(deps.as_ref().storage).unwrap(),
-        State {
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            total_liabilities: Decimal256::zero(),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: mock_env().block.height,
-            last_reward_updated: mock_env().block.height,
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::from(1000000u64),
-            prev_exchange_rate: Decimal256::one(),
-        }
-    );
+    // update left items
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: Some("interest2".to_string()),
+        distribution_model: Some("distribution2".to_string()),
+        max_borrow_factor: Some(Decimal256::percent(100)),
+        max_accrual_blocks: Some(100u64),
+        min_borrow_rate: Some(Decimal256::permille(1)),
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", "addr0000"),
-            attr("mint_amount", "1000000"),
-            attr("deposit_amount", "1000000"),
-        ]
-    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(0, res.messages.len());
 
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "AT-uusd".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(1000000u128),
-            })
-            .unwrap(),
-        }))]
-    );
+    // it worked, let's query the state
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner1".to_string(), config_res.owner_addr);
+    assert_eq!("interest2".to_string(), config_res.interest_model);
+    assert_eq!("distribution2".to_string(), config_res.distribution_model);
+    assert_eq!(Decimal256::percent(100), config_res.max_borrow_factor);
+    assert_eq!(Some(100u64), config_res.max_accrual_blocks);
+    assert_eq!(Decimal256::permille(1), config_res.min_borrow_rate);
 
-    // make exchange rate to 50%
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(50000u128),
-            total_reserves: Decimal256::from_uint256(550000u128),
-            last_interest_updated: mock_env().block.height,
-            last_reward_updated: mock_env().block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
-        },
-    )
-    .unwrap();
+    // caps and roles can be set
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: Some("insurance".to_string()),
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: Some(Uint256::from(1000000u64)),
+        per_account_borrow_cap: Some(Uint256::from(100000u64)),
+        guardian: Some("guardian".to_string()),
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(Some("insurance".to_string()), config_res.insurance_fund);
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "deposit_stable"),
-            attr("depositor", "addr0000"),
-            attr("mint_amount", "2000000"),
-            attr("deposit_amount", "1000000"),
-        ]
+        Some(Uint256::from(1000000u64)),
+        config_res.max_borrow_amount
     );
-
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "AT-uusd".to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(2000000u128),
-            })
-            .unwrap(),
-        }))]
+        Some(Uint256::from(100000u64)),
+        config_res.per_account_borrow_cap
     );
+    assert_eq!(Some("guardian".to_string()), config_res.guardian);
+
+    // and removed again
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: Some(0u64),
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: Some(true),
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: Some(Uint256::zero()),
+        per_account_borrow_cap: Some(Uint256::zero()),
+        guardian: None,
+        remove_guardian: Some(true),
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(None, config_res.max_accrual_blocks);
+    assert_eq!(None, config_res.insurance_fund);
+    assert_eq!(None, config_res.max_borrow_amount);
+    assert_eq!(None, config_res.per_account_borrow_cap);
+    assert_eq!(None, config_res.guardian);
+
+    // overseer can be changed while nothing is borrowed
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: Some("overseer2".to_string()),
+        force: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("overseer2".to_string(), config_res.overseer_contract);
+
+    // but not once a loan is outstanding
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.total_liabilities = Decimal256::from_uint256(1000000u64);
+    store_state(deps.as_mut().storage, &state).unwrap();
+
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: Some("overseer3".to_string()),
+        force: Some(false),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::OverseerChangeWithActiveLoans {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // unless the owner forces it
+    let info = mock_info("owner1", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: Some("overseer3".to_string()),
+        force: Some(true),
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("overseer3".to_string(), config_res.overseer_contract);
+
+    // Unauthorized err
+    let info = mock_info("owner", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn update_interest_model_checkpoint() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
 
-    // Case: compute_interest & compute_reward with block increment
     let info = mock_info(
         "addr0000",
         &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(1000000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
-    let mut env = mock_env();
 
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier.with_borrow_rate(&[
+        (&"interest".to_string(), &Decimal256::percent(1)),
+        (&"interest2".to_string(), &Decimal256::percent(2)),
+    ]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut env = mock_env();
     store_state(
         deps.as_mut().storage,
         &State {
-            total_liabilities: Decimal256::from_uint256(50000u128),
-            total_reserves: Decimal256::from_uint256(550000u128),
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
             last_interest_updated: env.block.height,
             last_reward_updated: env.block.height,
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
+            prev_exchange_rate: Decimal256::one(),
         },
     )
     .unwrap();
 
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-
-    // State: global_interest_index: 1
-    // balance: 1000000
-    // aterra_supply: 1000000
-    // total_liabilities: 100000
-    // total_reserves: 550000
-    // exchange_rate: 0.55
-    // mint_amount: 0.55 * 1000000 = 1,818,181
+    env.block.height += 10;
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: Some("interest2".to_string()),
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr(
+                "interest_model_changed_at_height",
+                env.block.height.to_string()
+            ),
+        ]
+    );
 
+    // interest before the change is accrued with the old model
+    // interest_factor = 1% * 10blocks = 0.1
+    let state = read_state(deps.as_ref().storage).unwrap();
     assert_eq!(
-        read_state(deps.as_ref().storage).unwrap(),
-        State {
-            global_interest_index: Decimal256::from_uint256(2u128),
-            global_reward_index: Decimal256::from_str("0.002").unwrap(),
-            total_liabilities: Decimal256::from_uint256(100000u128),
-            total_reserves: Decimal256::from_uint256(550000u128),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1818181),
-            prev_exchange_rate: Decimal256::from_ratio(55u64, 100u64),
-        }
+        state.total_liabilities,
+        Decimal256::from_uint256(1100000u128)
     );
+    assert_eq!(state.last_interest_updated, env.block.height);
+
+    // and after the change with the new one
+    // interest_factor = 2% * 10blocks = 0.2
+    let res: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State {
+                block_height: Some(env.block.height + 10u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.total_liabilities, Decimal256::from_uint256(1320000u128));
 }
 
 #[test]
-fn redeem_stable() {
+fn update_config_block_time() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
@@ -651,9 +1268,15 @@ fn redeem_stable() {
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -679,6 +1302,11 @@ fn redeem_stable() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    deps.querier.with_borrow_rate(&[
+        (&"interest".to_string(), &Decimal256::percent(1)),
+        (&"interest2".to_string(), &Decimal256::percent(2)),
+    ]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -690,162 +1318,302 @@ fn redeem_stable() {
     let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // Deposit 1000000
-    let msg = ExecuteMsg::DepositStable {};
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(1000000u128),
-        }],
-    );
+    let mut env = mock_env();
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier.with_token_balances(&[(
-        &"AT-uusd".to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
-    )]);
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
+    // switch to block time accrual
+    env.block.height += 10;
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: Some(6u64),
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.block_time, Some(6u64));
+
+    // interest before the switch is accrued on block height
+    // interest_factor = 1% * 10blocks = 0.1
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(1100000u128)
+    );
+    assert_eq!(state.last_interest_updated, env.block.time.seconds());
+
+    // heights ahead are projected with the nominal block time
+    // interest_factor = 1% * 10blocks = 0.1
+    let res: StateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::State {
+                block_height: Some(env.block.height + 10u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.total_liabilities, Decimal256::from_uint256(1210000u128));
+
+    // 60 seconds in 5 slow blocks still accrue 10 blocks
+    env.block.height += 5;
+    env.block.time = env.block.time.plus_seconds(60);
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: Some(0u64),
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.block_time, None);
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(1210000u128)
+    );
+    assert_eq!(state.last_interest_updated, env.block.height);
+}
+
+#[test]
+fn deposit_stable_huge_amount() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
         }],
     );
 
-    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    deps.querier.with_token_balances(&[(
-        &"AT-uusd".to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
-    )]);
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // Redeem 1000000
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: Uint128::from(1000000u128),
-        msg: to_binary(&Cw20HookMsg::RedeemStable {}).unwrap(),
-    });
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
     let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Must deposit stable_denom
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(123u128),
+        }],
+    );
+
     let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    let _uusd_string = "uusd";
     match res {
-        Err(ContractError::Unauthorized {}) => (),
+        Err(ContractError::ZeroDeposit(_uusd_string)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let info = mock_info("AT-uusd", &[]);
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "AT-uusd".to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Burn {
-                    amount: Uint128::from(1000000u128),
-                })
-                .unwrap()
-            })),
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: "addr0000".to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(1000000u128),
-                    }
-                )
-                .unwrap(),]
-            }))
-        ]
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(55_555_555_000_000u128),
+        }],
     );
 
-    // make exchange rate to 50%
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(500000u128),
-            total_reserves: Decimal256::from_uint256(100000u128),
-            last_interest_updated: mock_env().block.height,
-            last_reward_updated: mock_env().block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::from(2000000u64),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
-
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(500000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 55_555_555_000_000u128),
         }],
     );
 
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
-    println!("{:?}", res);
-    match res {
-        Err(ContractError::NoStableAvailable(_uusd_string)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_stable"),
+            attr("depositor", "addr0000"),
+            attr("recipient", "addr0000"),
+            attr("mint_amount", "55555555000000"),
+            attr("deposit_amount", "55555555000000"),
+            attr("exchange_rate", "1"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(55_555_555_000_000u128),
+            })
+            .unwrap(),
+        }))]
+    );
 
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(600000u128),
+            amount: Uint128::from(111_111_110_000_000u128),
         }],
     );
 
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(55_555_555_000_000u128),
+        )],
+    )]);
+
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        res.messages,
+        res.attributes,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "AT-uusd".to_string(),
-                funds: vec![],
-                msg: to_binary(&Cw20ExecuteMsg::Burn {
-                    amount: Uint128::from(1000000u128),
-                })
-                .unwrap()
-            })),
-            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-                to_address: "addr0000".to_string(),
-                amount: vec![deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: "uusd".to_string(),
-                        amount: Uint128::from(500000u128),
-                    }
-                )
-                .unwrap(),]
-            }))
+            attr("action", "deposit_stable"),
+            attr("depositor", "addr0000"),
+            attr("recipient", "addr0000"),
+            attr("mint_amount", "55555555000000"),
+            attr("deposit_amount", "55555555000000"),
+            attr("exchange_rate", "1"),
         ]
     );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(55_555_555_000_000u128),
+            })
+            .unwrap(),
+        }))]
+    );
 }
 
 #[test]
-fn borrow_stable() {
+fn deposit_stable() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -871,6 +1639,9 @@ fn borrow_stable() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -879,218 +1650,248 @@ fn borrow_stable() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
     let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    // Must deposit stable_denom
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(123u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::ZeroDeposit(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // base denom, zero deposit
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::zero(),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::ZeroDeposit(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // other denoms attached next to the base denom
+    let info = mock_info(
+        "addr0000",
+        &[
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(123u128),
+            },
+        ],
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::InvalidDepositDenoms {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    // 1- As the last place to modify the state is compute_interest, a check on the state ensures the invocation of compute_interest.
+    // However, because passed_blocks = 0, interest factor & interest accrued are also 0, and thus the values do not change
+    // (looking as if the function might not have been invoked at all.)
+    // Thus, later, the invocation of compute interest will be tested after increasing the block height.
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap(),
+        State {
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(1000000u64),
+            prev_exchange_rate: Decimal256::one(),
+        }
+    );
 
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deposit_stable"),
+            attr("depositor", "addr0000"),
+            attr("recipient", "addr0000"),
+            attr("mint_amount", "1000000"),
+            attr("deposit_amount", "1000000"),
+            attr("exchange_rate", "1"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // make exchange rate to 50%
     store_state(
         deps.as_mut().storage,
         &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
+            total_liabilities: Decimal256::from_uint256(50000u128),
+            total_reserves: Decimal256::from_uint256(550000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
+            prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
         },
     )
     .unwrap();
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
-    };
-
-    env.block.height += 100;
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // interest_factor = 1% * 100blocks = 1
-    // interest_accrued = 1000000
-    // global_interest_index = 2
-    // total_liabilities = 2500000
-    // total_reserves = 3000
-    // last_interest_updated = 100
-    // reward_accrued = 100
-    // global_reward_index = 0.00002
-    // last_rewards_updated = 100
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "borrow_stable"),
-            attr("borrower", "addr0000"),
-            attr("borrow_amount", "500000")
+            attr("action", "deposit_stable"),
+            attr("depositor", "addr0000"),
+            attr("recipient", "addr0000"),
+            attr("mint_amount", "2000000"),
+            attr("deposit_amount", "1000000"),
+            attr("exchange_rate", "0.5"),
         ]
     );
 
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(500000u128),
-                }
-            )
-            .unwrap()],
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(2000000u128),
+            })
+            .unwrap(),
         }))]
     );
 
-    assert_eq!(
-        from_binary::<State>(
-            &query(
-                deps.as_ref(),
-                env.clone(),
-                QueryMsg::State { block_height: None }
-            )
-            .unwrap()
-        )
-        .unwrap(),
-        State {
-            total_liabilities: Decimal256::from_uint256(2500000u128),
-            total_reserves: Decimal256::zero(),
+    // Case: compute_interest & compute_reward with block increment
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let mut env = mock_env();
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(50000u128),
+            total_reserves: Decimal256::from_uint256(550000u128),
             last_interest_updated: env.block.height,
             last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::from_uint256(2u128),
-            global_reward_index: Decimal256::from_str("0.0001").unwrap(),
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
             prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        }
-    );
+            prev_exchange_rate: Decimal256::from_ratio(1u64, 2u64),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // State: global_interest_index: 1
+    // balance: 1000000
+    // aterra_supply: 1000000
+    // total_liabilities: 100000
+    // total_reserves: 550000
+    // exchange_rate: 0.55
+    // mint_amount: 0.55 * 1000000 = 1,818,181
 
-    // after 1 block state
     assert_eq!(
-        from_binary::<State>(
-            &query(
-                deps.as_ref(),
-                mock_env(),
-                QueryMsg::State {
-                    block_height: Some(env.block.height + 1u64)
-                }
-            )
-            .unwrap()
-        )
-        .unwrap(),
+        read_state(deps.as_ref().storage).unwrap(),
         State {
-            total_liabilities: Decimal256::from_uint256(2525000u128),
-            total_reserves: Decimal256::from_uint256(0u128),
-            last_interest_updated: env.block.height + 1u64,
-            last_reward_updated: env.block.height + 1u64,
-            global_interest_index: Decimal256::from_str("2.02").unwrap(),
-            global_reward_index: Decimal256::from_str("0.0001008").unwrap(),
+            global_interest_index: Decimal256::from_uint256(2u128),
+            global_reward_index: Decimal256::from_str("0.002").unwrap(),
+            total_liabilities: Decimal256::from_uint256(100000u128),
+            total_reserves: Decimal256::from_uint256(550000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
             anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1818181),
+            prev_exchange_rate: Decimal256::from_ratio(55u64, 100u64),
         }
     );
-
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::BorrowerInfo {
-            borrower: "addr0000".to_string(),
-            block_height: None,
-        },
-    )
-    .unwrap();
-
-    let liability: BorrowerInfoResponse = from_binary(&res).unwrap();
-    assert_eq!(
-        liability,
-        BorrowerInfoResponse {
-            borrower: "addr0000".to_string(),
-            interest_index: Decimal256::from_uint256(2u128),
-            reward_index: Decimal256::from_str("0.0001").unwrap(),
-            loan_amount: Uint256::from(500000u64),
-            pending_rewards: Decimal256::zero(),
-        }
-    );
-
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::BorrowerInfo {
-            borrower: "addr0000".to_string(),
-            block_height: Some(env.block.height),
-        },
-    )
-    .unwrap();
-
-    let borrower_info: BorrowerInfoResponse = from_binary(&res).unwrap();
-    assert_eq!(
-        borrower_info,
-        BorrowerInfoResponse {
-            borrower: "addr0000".to_string(),
-            interest_index: Decimal256::from_uint256(2u128),
-            reward_index: Decimal256::from_str("0.0001").unwrap(),
-            loan_amount: Uint256::from(500000u64),
-            pending_rewards: Decimal256::zero(),
-        }
-    );
-
-    // Query to future blocks
-    // interest_factor is 100%
-    let res = query(
-        deps.as_ref(),
-        mock_env(),
-        QueryMsg::BorrowerInfo {
-            borrower: "addr0000".to_string(),
-            block_height: Some(env.block.height + 100),
-        },
-    )
-    .unwrap();
-
-    let borrower_info: BorrowerInfoResponse = from_binary(&res).unwrap();
-    assert_eq!(
-        borrower_info,
-        BorrowerInfoResponse {
-            borrower: "addr0000".to_string(),
-            interest_index: Decimal256::from_uint256(4u128),
-            reward_index: Decimal256::from_str("0.00018").unwrap(),
-            loan_amount: Uint256::from(1000000u64),
-            pending_rewards: Decimal256::from_uint256(20u64),
-        }
-    );
-
-    // Cannot borrow more than borrow limit
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500001u64),
-        to: None,
-    };
-    let res = execute(deps.as_mut(), env, info, msg);
-    match res {
-        Err(ContractError::BorrowExceedsLimit(1000000)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-}
+}
 
 #[test]
-fn assert_max_borrow_factor() {
+fn deposit_stable_to_recipient() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
-        max_borrow_factor: Decimal256::percent(1),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -1116,6 +1917,9 @@ fn assert_max_borrow_factor() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -1125,82 +1929,82 @@ fn assert_max_borrow_factor() {
         distributor_contract: "distributor".to_string(),
     };
     let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::zero(),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: mock_env().block.height,
-            last_reward_updated: mock_env().block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(10000u64),
-        to: None,
+    // addr0000 deposits, addr0001 receives the minted aterra
+    let msg = ExecuteMsg::DepositStable {
+        recipient: Some("addr0001".to_string()),
+        deadline: None,
     };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
 
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "borrow_stable"),
-            attr("borrower", "addr0000"),
-            attr("borrow_amount", "10000")
+            attr("action", "deposit_stable"),
+            attr("depositor", "addr0000"),
+            attr("recipient", "addr0001"),
+            attr("mint_amount", "1000000"),
+            attr("deposit_amount", "1000000"),
+            attr("exchange_rate", "1"),
         ]
     );
 
-    // subtract borrow amount
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR,
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 10000u128),
-        }],
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
     );
-
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(1u64),
-        to: None,
-    };
-    let res = execute(deps.as_mut(), mock_env(), info, msg);
-    let _uusd_string = "uusd";
-    match res {
-        Err(ContractError::MaxBorrowFactorReached(_uusd_string)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
 }
 
 #[test]
-fn repay_stable() {
+fn redeem_stable() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -1226,6 +2030,9 @@ fn repay_stable() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -1234,156 +2041,172 @@ fn repay_stable() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let mut info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
-
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
+    // Deposit 1000000
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
     };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
 
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    let msg = ExecuteMsg::RepayStable {};
-    info.funds = vec![Coin {
-        denom: "ukrw".to_string(),
-        amount: Uint128::from(100000u128),
-    }];
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
-    match res {
-        Err(ContractError::ZeroRepay(_uusd_string)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::zero(),
-    }];
-
-    let res2 = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res2 {
-        Err(ContractError::ZeroRepay(_uusd_string)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
         }],
     );
 
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(100000u128),
-    }];
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "100000"),
-        ]
-    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    //Loan amount and Total liability have decreased according to the repayment
-    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .loan_amount;
-    assert_eq!(res_loan, Uint256::from(400000u128));
-    assert_eq!(
-        read_state(deps.as_ref().storage).unwrap().total_liabilities,
-        Decimal256::from_uint256(2400000u128)
-    );
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
 
-    info.funds = vec![Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(500000u128),
-    }];
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // Redeem 1000000
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: None,
+            target_stable: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("AT-uusd", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
     assert_eq!(
-        res.attributes,
+        res.messages,
         vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "400000"),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(1000000u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(1000000u128),
+                    }
+                )
+                .unwrap(),]
+            }))
         ]
     );
 
-    //Loan amount and Total liability have decreased according to the repayment
-    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .loan_amount;
-    assert_eq!(res_loan, Uint256::zero());
-    assert_eq!(
-        read_state(deps.as_ref().storage).unwrap().total_liabilities,
-        Decimal256::from_uint256(2000000u128)
+    // make exchange rate to 50%
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(500000u128),
+            total_reserves: Decimal256::from_uint256(100000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(2000000u64),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u128),
+        }],
     );
 
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    let _uusd_string = "uusd";
+    println!("{:?}", res);
+    match res {
+        Err(ContractError::NoStableAvailable(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(600000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(100000u128),
-                }
-            )
-            .unwrap()]
-        }))]
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(1000000u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(500000u128),
+                    }
+                )
+                .unwrap(),]
+            }))
+        ]
     );
 }
 
 #[test]
-fn repay_stable_from_liquidation() {
+fn redeem_stable_target() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
     }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
 
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -1409,6 +2232,9 @@ fn repay_stable_from_liquidation() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -1417,129 +2243,151 @@ fn repay_stable_from_liquidation() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
     let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-    deps.querier
-        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    // Deposit 1000000
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
 
+    // make exchange rate to 55%
     store_state(
         deps.as_mut().storage,
         &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
+            total_liabilities: Decimal256::from_uint256(500000u128),
+            total_reserves: Decimal256::from_uint256(100000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
+            prev_aterra_supply: Uint256::from(2000000u64),
             prev_exchange_rate: Decimal256::one(),
         },
     )
     .unwrap();
 
-    let msg = ExecuteMsg::BorrowStable {
-        borrow_amount: Uint256::from(500000u64),
-        to: None,
-    };
-
-    env.block.height += 100;
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // update balance to make repay
     deps.querier.update_balance(
         MOCK_CONTRACT_ADDR.to_string(),
-        vec![
-            Coin {
-                denom: "ukrw".to_string(),
-                amount: Uint128::from(100000u128),
-            },
-            Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-            },
-        ],
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(700000u128),
+        }],
     );
 
-    let msg = ExecuteMsg::RepayStableFromLiquidation {
-        borrower: "addr0000".to_string(),
-        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
-    };
-
-    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    let info = mock_info("AT-uusd", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(300000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: None,
+            target_stable: Some(Uint256::zero()),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match res {
-        Err(ContractError::Unauthorized {}) => (),
+        Err(ContractError::ZeroRedeemTarget {}) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    let info = mock_info("overseer", &[]);
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    let _uusd_string = "uusd";
+    // 100000 / 0.55 = 181818.18, rounded up
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(181818u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: None,
+            target_stable: Some(Uint256::from(100000u64)),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match res {
-        Err(ContractError::ZeroRepay(__uusd_string)) => (),
+        Err(ContractError::InsufficientRedeemBurn(burn_amount)) => {
+            assert_eq!(burn_amount, 181819u128)
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    // update balance to make repay
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
-        }],
-    );
-
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "100000"),
-        ]
-    );
-
-    deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
-        }],
-    );
-
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // the aterra left over is returned to the sender
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(300000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: None,
+            target_stable: Some(Uint256::from(100000u64)),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        res.attributes,
+        res.messages,
         vec![
-            attr("action", "repay_stable"),
-            attr("borrower", "addr0000"),
-            attr("repay_amount", "400000"),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(181819u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(100000u128),
+                    }
+                )
+                .unwrap(),]
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(118181u128),
+                })
+                .unwrap()
+            })),
         ]
     );
 
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![deduct_tax(
-                deps.as_ref(),
-                Coin {
-                    denom: "uusd".to_string(),
-                    amount: Uint128::from(100000u128),
-                }
-            )
-            .unwrap()]
-        }))]
-    );
+    let state: State = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.prev_aterra_supply, Uint256::from(1818181u64));
 }
 
 #[test]
-fn claim_rewards() {
+fn borrow_stable() {
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
         amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
@@ -1552,9 +2400,15 @@ fn claim_rewards() {
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         stable_denom: "uusd".to_string(),
+        stable_token: None,
         aterra_code_id: 123u64,
         anc_emission_rate: Decimal256::one(),
         max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
     let info = mock_info(
@@ -1580,6 +2434,18 @@ fn claim_rewards() {
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -1588,12 +2454,10 @@ fn claim_rewards() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let info = mock_info("addr0000", &[]);
     let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
 
@@ -1613,104 +2477,291 @@ fn claim_rewards() {
     )
     .unwrap();
 
-    // zero loan claim, will return empty messages
-    let msg = ExecuteMsg::ClaimRewards { to: None };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
     let msg = ExecuteMsg::BorrowStable {
         borrow_amount: Uint256::from(500000u64),
         to: None,
+        deadline: None,
     };
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // zero block passed
-    let msg = ExecuteMsg::ClaimRewards {
-        to: Some("addr0001".to_string()),
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
-    assert_eq!(res.messages.len(), 0);
 
-    // 100 blocks passed
     env.block.height += 100;
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // interest_factor = 1% * 100blocks = 1
+    // interest_accrued = 1000000
+    // global_interest_index = 2
+    // total_liabilities = 2500000
+    // total_reserves = 3000
+    // last_interest_updated = 100
+    // reward_accrued = 100
+    // global_reward_index = 0.00002
+    // last_rewards_updated = 100
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable"),
+            attr("borrower", "addr0000"),
+            attr("borrow_amount", "500000"),
+            attr("remaining_loan", "500000"),
+        ]
+    );
+
     assert_eq!(
         res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "distributor".to_string(),
-            funds: vec![],
-            msg: to_binary(&FaucetExecuteMsg::Spend {
-                recipient: "addr0001".to_string(),
-                amount: Uint128::from(33u128),
-            })
-            .unwrap(),
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(500000u128),
+                }
+            )
+            .unwrap()],
         }))]
     );
 
-    let res: BorrowerInfoResponse = from_binary(
-        &query(
-            deps.as_ref(),
-            mock_env(),
-            QueryMsg::BorrowerInfo {
-                borrower: "addr0000".to_string(),
-                block_height: None,
-            },
+    assert_eq!(
+        from_binary::<State>(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::State { block_height: None }
+            )
+            .unwrap()
         )
         .unwrap(),
-    )
-    .unwrap();
-    assert_eq!(
-        res.pending_rewards,
-        Decimal256::from_str("0.333333333333").unwrap()
+        State {
+            total_liabilities: Decimal256::from_uint256(2500000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::from_uint256(2u128),
+            global_reward_index: Decimal256::from_str("0.0001").unwrap(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+            prev_exchange_rate: Decimal256::from_uint256(3u128),
+        }
     );
+
+    // after 1 block state
     assert_eq!(
-        res.reward_index,
-        Decimal256::from_str("0.000066666666666666").unwrap()
+        from_binary::<State>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::State {
+                    block_height: Some(env.block.height + 1u64)
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        State {
+            total_liabilities: Decimal256::from_uint256(2525000u128),
+            total_reserves: Decimal256::from_uint256(495000u128),
+            last_interest_updated: env.block.height + 1u64,
+            last_reward_updated: env.block.height + 1u64,
+            global_interest_index: Decimal256::from_str("2.02").unwrap(),
+            global_reward_index: Decimal256::from_str("0.0001008").unwrap(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+            prev_exchange_rate: Decimal256::from_str("3.03").unwrap(),
+        }
     );
-}
 
-#[test]
-fn execute_epoch_operations() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-    }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: None,
+        },
+    )
+    .unwrap();
+
+    let liability: BorrowerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        liability,
+        BorrowerInfoResponse {
+            borrower: "addr0000".to_string(),
+            interest_index: Decimal256::from_uint256(2u128),
+            reward_index: Decimal256::from_str("0.0001").unwrap(),
+            loan_amount: Uint256::from(500000u64),
+            pending_rewards: Decimal256::zero(),
+        }
     );
 
-    let msg = InstantiateMsg {
-        owner_addr: "owner".to_string(),
-        stable_denom: "uusd".to_string(),
-        aterra_code_id: 123u64,
-        anc_emission_rate: Decimal256::one(),
-        max_borrow_factor: Decimal256::one(),
-    };
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: Some(env.block.height),
+        },
+    )
+    .unwrap();
 
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-        }],
+    let borrower_info: BorrowerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrower_info,
+        BorrowerInfoResponse {
+            borrower: "addr0000".to_string(),
+            interest_index: Decimal256::from_uint256(2u128),
+            reward_index: Decimal256::from_str("0.0001").unwrap(),
+            loan_amount: Uint256::from(500000u64),
+            pending_rewards: Decimal256::zero(),
+        }
     );
 
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-
-    // Register anchor token contract
-    let mut token_inst_res = MsgInstantiateContractResponse::new();
-    token_inst_res.set_contract_address("AT-uusd".to_string());
-    let reply_msg = Reply {
-        id: 1,
-        result: ContractResult::Ok(SubMsgExecutionResponse {
-            events: vec![],
-            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+    // Query to future blocks
+    // interest_factor is 100%
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowerInfo {
+            borrower: "addr0000".to_string(),
+            block_height: Some(env.block.height + 100),
+        },
+    )
+    .unwrap();
+
+    let borrower_info: BorrowerInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrower_info,
+        BorrowerInfoResponse {
+            borrower: "addr0000".to_string(),
+            interest_index: Decimal256::from_uint256(4u128),
+            reward_index: Decimal256::from_str("0.00018").unwrap(),
+            loan_amount: Uint256::from(1000000u64),
+            pending_rewards: Decimal256::from_uint256(20u64),
+        }
+    );
+
+    // Paginated liabilities accrue interest the same way
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowerInfos {
+            start_after: None,
+            limit: None,
+            block_height: Some(env.block.height + 100),
+        },
+    )
+    .unwrap();
+    let borrower_infos: BorrowerInfosResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower_infos.borrower_infos, vec![borrower_info.clone()]);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::LiabilitiesByAmount {
+            start_after: None,
+            limit: None,
+            descending: None,
+            block_height: Some(env.block.height + 100),
+        },
+    )
+    .unwrap();
+    let borrower_infos: BorrowerInfosResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower_infos.borrower_infos, vec![borrower_info]);
+
+    // Stored index lags behind until the borrower interacts again
+    let mut future_env = env.clone();
+    future_env.block.height += 100;
+    let res = query(
+        deps.as_ref(),
+        future_env.clone(),
+        QueryMsg::BorrowerIndex {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+
+    let borrower_index: BorrowerIndexResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        borrower_index,
+        BorrowerIndexResponse {
+            borrower: "addr0000".to_string(),
+            stored_index: Decimal256::from_uint256(2u128),
+            global_index: Decimal256::from_uint256(4u128),
+            block_height: future_env.block.height,
+        }
+    );
+    assert!(borrower_index.global_index > borrower_index.stored_index);
+
+    // Cannot borrow more than borrow limit
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500001u64),
+        to: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::BorrowExceedsLimit(1000000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn assert_max_borrow_factor() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::percent(1),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
         }),
     };
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
     // Register overseer contract
     let msg = ExecuteMsg::RegisterContracts {
         overseer_contract: "overseer".to_string(),
@@ -1719,22 +2770,19 @@ fn execute_epoch_operations() {
         collector_contract: "collector".to_string(),
         distributor_contract: "distributor".to_string(),
     };
-    let mut env = mock_env();
-    let mut info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    deps.querier
-        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
     deps.querier
         .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
 
     store_state(
         deps.as_mut().storage,
         &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::from_uint256(3000u128),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
             global_interest_index: Decimal256::one(),
             global_reward_index: Decimal256::zero(),
             anc_emission_rate: Decimal256::one(),
@@ -1744,111 +2792,6769 @@ fn execute_epoch_operations() {
     )
     .unwrap();
 
-    env.block.height += 100;
-
-    // reserve == 3000
-    let msg = ExecuteMsg::ExecuteEpochOperations {
-        deposit_rate: Decimal256::one(),
-        target_deposit_rate: Decimal256::one(),
-        threshold_deposit_rate: Decimal256::one(),
-        distributed_interest: Uint256::zero(),
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(10000u64),
+        to: None,
+        deadline: None,
     };
 
-    // only overseer can execute this
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
-    match res {
-        Err(ContractError::Unauthorized {}) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    info.sender = Addr::unchecked("overseer");
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "collector".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(2970u128), // 1% tax
-            }],
-        }))]
-    );
-
-    let state = read_state(deps.as_ref().storage).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
     assert_eq!(
-        state,
-        State {
-            total_liabilities: Decimal256::from_uint256(2000000u128),
-            total_reserves: Decimal256::zero(),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::from_uint256(2u64),
-            global_reward_index: Decimal256::from_str("0.0001").unwrap(),
-            anc_emission_rate: Decimal256::from_uint256(5u64),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        }
+        res.attributes,
+        vec![
+            attr("action", "borrow_stable"),
+            attr("borrower", "addr0000"),
+            attr("borrow_amount", "10000"),
+            attr("remaining_loan", "10000"),
+        ]
     );
 
-    // When there is not enough balance to cover reserve
-    // no message will be sent and reserve will be left as same
+    // subtract borrow amount
     deps.querier.update_balance(
-        MOCK_CONTRACT_ADDR.to_string(),
+        MOCK_CONTRACT_ADDR,
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: Uint128::from(2999u128),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 10000u128),
         }],
     );
 
-    let mut env = mock_env();
-    let info = mock_info("overseer", &[]);
-    store_state(
-        deps.as_mut().storage,
-        &State {
-            total_liabilities: Decimal256::from_uint256(1000000u128),
-            total_reserves: Decimal256::from_uint256(3000u128),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::one(),
-            global_reward_index: Decimal256::zero(),
-            anc_emission_rate: Decimal256::one(),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        },
-    )
-    .unwrap();
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(1u64),
+        to: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::MaxBorrowFactorReached(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
 
-    env.block.height += 100;
+#[test]
+fn borrow_whitelist() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
 
-    // reserve == 3000
-    let msg = ExecuteMsg::ExecuteEpochOperations {
-        deposit_rate: Decimal256::one(),
-        target_deposit_rate: Decimal256::one(),
-        threshold_deposit_rate: Decimal256::one(),
-        distributed_interest: Uint256::zero(),
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
     };
 
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    let state = read_state(deps.as_ref().storage).unwrap();
-    assert_eq!(
-        state,
-        State {
-            total_liabilities: Decimal256::from_uint256(2000000u128),
-            total_reserves: Decimal256::from_uint256(3000u128),
-            last_interest_updated: env.block.height,
-            last_reward_updated: env.block.height,
-            global_interest_index: Decimal256::from_uint256(2u64),
-            global_reward_index: Decimal256::from_str("0.0001").unwrap(),
-            anc_emission_rate: Decimal256::from_uint256(5u64),
-            prev_aterra_supply: Uint256::zero(),
-            prev_exchange_rate: Decimal256::one(),
-        }
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
     );
-}
 
-// #[test]
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let borrow_msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: None,
+        deadline: None,
+    };
+
+    // Only owner can update the whitelist
+    let msg = ExecuteMsg::UpdateBorrowWhitelist {
+        enabled: Some(true),
+        add: vec![],
+        remove: vec![],
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Enabled with nobody on the list
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert!(config_res.borrow_whitelist_enabled);
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, borrow_msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Deposits stay open to everyone
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateBorrowWhitelist {
+        enabled: None,
+        add: vec!["addr0000".to_string()],
+        remove: vec![],
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: IsWhitelistedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsWhitelisted {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        IsWhitelistedResponse {
+            borrower: "addr0000".to_string(),
+            is_whitelisted: true,
+        }
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, borrow_msg.clone()).unwrap();
+
+    let msg = ExecuteMsg::UpdateBorrowWhitelist {
+        enabled: None,
+        add: vec![],
+        remove: vec!["addr0000".to_string()],
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: IsWhitelistedResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsWhitelisted {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(!res.is_whitelisted);
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, borrow_msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Disabling opens borrowing again
+    let msg = ExecuteMsg::UpdateBorrowWhitelist {
+        enabled: Some(false),
+        add: vec![],
+        remove: vec![],
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, borrow_msg).unwrap();
+}
+
+#[test]
+fn pause_operations() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: Some("guardian".to_string()),
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let pause_msg = ExecuteMsg::Pause {
+        operations: vec![MarketOperation::Borrow, MarketOperation::Deposit],
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, pause_msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("guardian", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, pause_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "pause"),
+            attr("operations", "borrow,deposit"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.guardian, Some("guardian".to_string()));
+    assert_eq!(
+        config_res.paused_operations,
+        vec![MarketOperation::Borrow, MarketOperation::Deposit]
+    );
+
+    let borrow_msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, borrow_msg.clone());
+    match res {
+        Err(ContractError::OperationPaused(operation)) => assert_eq!(operation, "borrow"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let deposit_msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let deposit_info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        deposit_info.clone(),
+        deposit_msg.clone(),
+    );
+    match res {
+        Err(ContractError::OperationPaused(operation)) => assert_eq!(operation, "deposit"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Only owner can unpause
+    let unpause_msg = ExecuteMsg::Unpause {
+        operations: vec![MarketOperation::Deposit],
+    };
+    let info = mock_info("guardian", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, unpause_msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, unpause_msg).unwrap();
+
+    let _res = execute(deps.as_mut(), mock_env(), deposit_info, deposit_msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, borrow_msg);
+    match res {
+        Err(ContractError::OperationPaused(operation)) => assert_eq!(operation, "borrow"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Pausing repays leaves liquidations running
+    let info = mock_info("guardian", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Pause {
+            operations: vec![MarketOperation::Repay],
+        },
+    )
+    .unwrap();
+
+    let liquidation_msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::zero(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        liquidation_msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("guardian", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Pause {
+            operations: vec![MarketOperation::Liquidate],
+        },
+    )
+    .unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, liquidation_msg);
+    match res {
+        Err(ContractError::OperationPaused(operation)) => assert_eq!(operation, "liquidate"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn borrow_caps() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+    ]);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: Some(Uint256::from(300000u64)),
+        per_account_borrow_cap: Some(Uint256::from(200000u64)),
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.max_borrow_amount, Some(Uint256::from(300000u64)));
+    assert_eq!(
+        config_res.per_account_borrow_cap,
+        Some(Uint256::from(200000u64))
+    );
+
+    // Within the borrow limit, but above the account cap
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(250000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::AccountBorrowCapExceeded(200000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(200000u64),
+        to: None,
+        deadline: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // total_liabilities = 200000, the market cap leaves 100000
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(150000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::GlobalBorrowCapExceeded(300000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100000u64),
+        to: None,
+        deadline: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn borrow_minimum_and_dust() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::from(1000000u64)),
+    ]);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: Some(Uint256::from(1000u64)),
+        dust_threshold: Some(Uint256::from(500u64)),
+        overseer_contract: None,
+        force: None,
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.min_borrow_amount, Uint256::from(1000u64));
+    assert_eq!(config_res.dust_threshold, Uint256::from(500u64));
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(999u64),
+            to: None,
+            deadline: None,
+        },
+    );
+    match res {
+        Err(ContractError::BorrowBelowMinimum(1000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(1000u64),
+            to: None,
+            deadline: None,
+        },
+    )
+    .unwrap();
+
+    // Topping up an existing loan is fine
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(10u64),
+            to: None,
+            deadline: None,
+        },
+    )
+    .unwrap();
+
+    // loan_amount = 1010, repaying 600 leaves dust
+    let msg = ExecuteMsg::RepayStable { deadline: None };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(600u128),
+            }],
+        ),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::DustLoan(500)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(510u128),
+            }],
+        ),
+        msg.clone(),
+    )
+    .unwrap();
+
+    // Repaying the whole loan is always allowed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(600u128),
+            }],
+        ),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "500"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "500"),
+            attr("remaining_loan", "0"),
+        ]
+    );
+
+    // Liquidations can leave dust behind
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(1000u64),
+            to: None,
+            deadline: None,
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("overseer", &[]),
+        ExecuteMsg::RepayStableFromLiquidation {
+            borrower: "addr0001".to_string(),
+            prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT - 600u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0001"),
+            attr("repay_amount", "600"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "600"),
+            attr("remaining_loan", "400"),
+        ]
+    );
+}
+
+#[test]
+fn required_borrow_limit() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    // No loan yet
+    let res: RequiredBorrowLimitResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequiredBorrowLimit {
+                borrower: "addr0000".to_string(),
+                desired_borrow: Uint256::from(500000u64),
+                block_height: mock_env().block.height,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        RequiredBorrowLimitResponse {
+            borrower: "addr0000".to_string(),
+            required_limit: Uint256::from(500000u64),
+            current_limit: Uint256::from(1000000u64),
+            shortfall: Uint256::zero(),
+        }
+    );
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // interest_factor = 1% * 100blocks = 1
+    // required_limit = 500000 * 2 + 200000
+    let res: RequiredBorrowLimitResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequiredBorrowLimit {
+                borrower: "addr0000".to_string(),
+                desired_borrow: Uint256::from(200000u64),
+                block_height: mock_env().block.height + 100u64,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        RequiredBorrowLimitResponse {
+            borrower: "addr0000".to_string(),
+            required_limit: Uint256::from(1200000u64),
+            current_limit: Uint256::from(1000000u64),
+            shortfall: Uint256::from(200000u64),
+        }
+    );
+}
+
+#[test]
+fn borrow_stable_recipient() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let info = mock_info("addr0000", &[]);
+    for recipient in [MOCK_CONTRACT_ADDR, "overseer", "AT-uusd"].iter() {
+        let msg = ExecuteMsg::BorrowStable {
+            borrow_amount: Uint256::from(100u64),
+            to: Some(recipient.to_string()),
+            deadline: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidBorrowRecipient {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    // Third party recipient
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: Some("addr0001".to_string()),
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(100u64));
+}
+
+#[test]
+fn borrow_stable_with_collateral() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStableWithCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+        borrow_amount: Uint256::from(100u64),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "overseer".to_string(),
+                funds: vec![],
+                msg: to_binary(&OverseerExecuteMsg::LockCollateralFor {
+                    borrower: "addr0000".to_string(),
+                    collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::BorrowStableFor {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(100u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // only the market itself can borrow on behalf of others
+    let msg = ExecuteMsg::BorrowStableFor {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(100u64),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(100u64));
+}
+
+#[test]
+fn deposit_and_borrow() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the minted aterra stays in the market as escrow
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+    let msg = ExecuteMsg::DepositAndBorrow {
+        borrow_amount: Uint256::from(100u64),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(1000000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::BorrowStableFor {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(100u64),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        )],
+    )]);
+    let res: EscrowResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Escrow {
+                borrower: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        EscrowResponse {
+            borrower: "addr0000".to_string(),
+            escrow_amount: Uint256::from(1000000u64),
+            escrow_value: Uint256::from(1000000u64),
+        }
+    );
+
+    // the overseer adds half the escrow value sent by the market to the borrow limit
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(50u64))]);
+    let msg = ExecuteMsg::BorrowStableFor {
+        borrower: "addr0000".to_string(),
+        borrow_amount: Uint256::from(100u64),
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 999900u128),
+        }],
+    );
+
+    // without the escrow only the collateral limit is left
+    let msg = ExecuteMsg::WithdrawEscrow { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::EscrowWithdrawTooLarge(limit)) => assert_eq!(limit, 50u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::WithdrawEscrow {
+        amount: Some(Uint256::from(999800u64)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(999800u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::WithdrawEscrow {
+        amount: Some(Uint256::from(201u64)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::InsufficientEscrowAmount(escrow)) => assert_eq!(escrow, 200u128),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the overseer settles loans with escrows
+    let msg = ExecuteMsg::RepayStableFromEscrow {
+        borrower: "addr0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("overseer", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::zero());
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("overseer", &[]), msg);
+    match res {
+        Err(ContractError::NoEscrow {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn execute_liquidation() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::ExecuteLiquidation {
+        borrower: "addr0000".to_string(),
+        max_repay: Some(Uint256::from(50u64)),
+    };
+    let info = mock_info("liquidator", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(99u64))]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "overseer".to_string(),
+            funds: vec![],
+            msg: to_binary(&OverseerExecuteMsg::LiquidateCollateralFor {
+                liquidator: "liquidator".to_string(),
+                borrower: "addr0000".to_string(),
+                max_repay: Some(Uint256::from(50u64)),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_liquidation"),
+            attr("borrower", "addr0000"),
+            attr("liquidator", "liquidator"),
+            attr("loan_amount", "100"),
+            attr("borrow_limit", "99"),
+            attr("max_repay", "50"),
+        ]
+    );
+}
+
+#[test]
+fn write_off_bad_debt() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.total_reserves = Decimal256::from_uint256(40u64);
+    store_state(deps.as_mut().storage, &state).unwrap();
+
+    let msg = ExecuteMsg::WriteOffBadDebt {
+        borrower: "addr0000".to_string(),
+    };
+
+    // only the owner or the overseer can write off
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_collaterals("addr0000", vec![("bluna".to_string(), Uint256::from(1u64))]);
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotWriteOffCollateralizedLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_collaterals("addr0000", vec![]);
+    let borrower_raw = deps.api.addr_canonicalize("addr0000").unwrap();
+    store_escrow(deps.as_mut().storage, &borrower_raw, &Uint256::from(10u64)).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotWriteOffEscrowedLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    store_escrow(deps.as_mut().storage, &borrower_raw, &Uint256::zero()).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("bad_debt").add_attributes(vec![
+            attr("borrower", "addr0000"),
+            attr("written_off_amount", "100"),
+            attr("reserves_used", "40"),
+            attr("socialized_amount", "60"),
+        ])]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_reserves, Decimal256::zero());
+    assert_eq!(state.total_liabilities, Decimal256::zero());
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::zero());
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::NoBadDebt {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res: BadDebtsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BadDebts {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BadDebtsResponse {
+            bad_debts: vec![BadDebtResponse {
+                borrower: "addr0000".to_string(),
+                written_off_amount: Uint256::from(100u64),
+            }]
+        }
+    );
+}
+
+#[test]
+fn fund_reserves() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::FundReserves {};
+    let info = mock_info(
+        "community",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::ZeroDeposit(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        "community",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.events,
+        vec![Event::new("reserves_funded").add_attributes(vec![
+            attr("funder", "community"),
+            attr("amount", "1000"),
+            attr("reserves_funded", "1000"),
+        ])]
+    );
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_reserves,
+        Decimal256::from_uint256(1000u64)
+    );
+
+    let info = mock_info(
+        "addr0001",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_reserves,
+        Decimal256::from_uint256(1500u64)
+    );
+
+    let res: ReservesFundedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ReservesFunded {}).unwrap())
+            .unwrap();
+    assert_eq!(res.reserves_funded, Uint256::from(1500u64));
+}
+
+#[test]
+fn borrower_position() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // interest_factor = 1% * 50blocks = 0.5
+    let block_height = mock_env().block.height + 50u64;
+    let res: BorrowerPositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerPosition {
+                borrower: "addr0000".to_string(),
+                block_height: Some(block_height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BorrowerPositionResponse {
+            borrower: "addr0000".to_string(),
+            loan_amount: Uint256::from(750000u64),
+            principal: Uint256::from(500000u64),
+            interest_index: Decimal256::from_str("1.5").unwrap(),
+            borrow_limit: Uint256::from(1000000u64),
+            borrowable: Uint256::from(250000u64),
+            is_over_limit: false,
+        }
+    );
+
+    // Matches the single purpose queries
+    let info_res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: Some(block_height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, info_res.loan_amount);
+    assert_eq!(res.interest_index, info_res.interest_index);
+
+    let limit_res: RequiredBorrowLimitResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequiredBorrowLimit {
+                borrower: "addr0000".to_string(),
+                desired_borrow: Uint256::zero(),
+                block_height,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, limit_res.required_limit);
+    assert_eq!(res.borrow_limit, limit_res.current_limit);
+
+    // Collateral value dropped
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(700000u64))]);
+    let res: BorrowerPositionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerPosition {
+                borrower: "addr0000".to_string(),
+                block_height: Some(block_height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.borrowable, Uint256::zero());
+    assert!(res.is_over_limit);
+}
+
+#[test]
+fn borrower_health() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_borrow_limit(&[
+        (&"addr0000".to_string(), &Uint256::from(1000000u64)),
+        (&"addr0001".to_string(), &Uint256::zero()),
+    ]);
+    deps.querier.with_collaterals(
+        "addr0000",
+        vec![("bluna".to_string(), Uint256::from(2000000u64))],
+    );
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // interest_factor = 1% * 50blocks = 0.5
+    let block_height = mock_env().block.height + 50u64;
+    let res: BorrowerHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerHealth {
+                borrower: "addr0000".to_string(),
+                block_height: Some(block_height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BorrowerHealthResponse {
+            borrower: "addr0000".to_string(),
+            loan_amount: Uint256::from(750000u64),
+            borrow_limit: Uint256::from(1000000u64),
+            collaterals: vec![("bluna".to_string(), Uint256::from(2000000u64))],
+            ltv: Some(Decimal256::percent(75)),
+            health_factor: Some(Decimal256::from_ratio(4, 3)),
+        }
+    );
+
+    // Neither ratio is defined without a loan or a borrow limit
+    let res: BorrowerHealthResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerHealth {
+                borrower: "addr0001".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BorrowerHealthResponse {
+            borrower: "addr0001".to_string(),
+            loan_amount: Uint256::zero(),
+            borrow_limit: Uint256::zero(),
+            collaterals: vec![],
+            ltv: None,
+            health_factor: None,
+        }
+    );
+}
+
+#[test]
+fn migrate_legacy_state() {
+    let mut deps = mock_dependencies(&[]);
+
+    let legacy_config = LegacyConfig {
+        contract_addr: deps.api.addr_canonicalize(MOCK_CONTRACT_ADDR).unwrap(),
+        owner_addr: deps.api.addr_canonicalize("owner").unwrap(),
+        aterra_contract: deps.api.addr_canonicalize("AT-uusd").unwrap(),
+        interest_model: deps.api.addr_canonicalize("interest").unwrap(),
+        distribution_model: deps.api.addr_canonicalize("distribution").unwrap(),
+        overseer_contract: deps.api.addr_canonicalize("overseer").unwrap(),
+        collector_contract: deps.api.addr_canonicalize("collector").unwrap(),
+        distributor_contract: deps.api.addr_canonicalize("distributor").unwrap(),
+        stable_denom: "uusd".to_string(),
+        max_borrow_factor: Decimal256::one(),
+    };
+    singleton(deps.as_mut().storage, KEY_CONFIG)
+        .save(&legacy_config)
+        .unwrap();
+
+    let state = State {
+        total_liabilities: Decimal256::from_uint256(3000u64),
+        total_reserves: Decimal256::from_uint256(100u64),
+        last_interest_updated: mock_env().block.height,
+        last_reward_updated: mock_env().block.height,
+        global_interest_index: Decimal256::one(),
+        global_reward_index: Decimal256::zero(),
+        anc_emission_rate: Decimal256::one(),
+        prev_aterra_supply: Uint256::zero(),
+        prev_exchange_rate: Decimal256::one(),
+    };
+    store_state(deps.as_mut().storage, &state).unwrap();
+
+    // liabilities stored before the loan amount index existed
+    for (borrower, loan_amount) in [("addr0000", 1000u64), ("addr0001", 2000u64)] {
+        let borrower_raw = deps.api.addr_canonicalize(borrower).unwrap();
+        bucket(deps.as_mut().storage, b"liability")
+            .save(
+                borrower_raw.as_slice(),
+                &BorrowerInfo {
+                    interest_index: Decimal256::one(),
+                    reward_index: Decimal256::zero(),
+                    loan_amount: Uint256::from(loan_amount),
+                    pending_rewards: Decimal256::zero(),
+                },
+            )
+            .unwrap();
+    }
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(3000u128))],
+    )]);
+
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            aterra_code_id: None,
+        },
+    );
+    match res {
+        Err(ContractError::MissingAterraCodeId {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            aterra_code_id: Some(123u64),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("from_version", "unversioned"),
+            attr("to_version", CONTRACT_VERSION),
+        ]
+    );
+
+    let config = read_config(deps.as_ref().storage).unwrap();
+    assert_eq!(config.owner_addr, legacy_config.owner_addr);
+    assert_eq!(config.aterra_contract, legacy_config.aterra_contract);
+    assert_eq!(config.stable_denom, "uusd".to_string());
+    assert_eq!(config.aterra_name, "mAPPL".to_string());
+    assert_eq!(config.aterra_code_id, 123u64);
+    assert_eq!(config.paused_operations, vec![]);
+    assert_eq!(read_state(deps.as_ref().storage).unwrap(), state);
+    assert_eq!(
+        read_contract_version(deps.as_ref().storage).unwrap(),
+        Some(ContractVersion {
+            contract: CONTRACT_NAME.to_string(),
+            version: CONTRACT_VERSION.to_string(),
+        })
+    );
+
+    // legacy liabilities are indexed by loan amount
+    let res: BorrowerInfosResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::LiabilitiesByAmount {
+                start_after: None,
+                limit: None,
+                descending: Some(true),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.borrower_infos
+            .iter()
+            .map(|info| info.borrower.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["addr0001", "addr0000"]
+    );
+
+    // versioned markets keep their state
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            aterra_code_id: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("from_version", CONTRACT_VERSION));
+    assert_eq!(read_config(deps.as_ref().storage).unwrap(), config);
+
+    store_contract_version(
+        deps.as_mut().storage,
+        &ContractVersion {
+            contract: "crates.io:other".to_string(),
+            version: "0.1.0".to_string(),
+        },
+    )
+    .unwrap();
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            aterra_code_id: None,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidMigrationContract(contract)) => {
+            assert_eq!(contract, "crates.io:other")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn inconsistent_market_state() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    // Liabilities exist but no aterra is in circulation
+    deps.querier
+        .with_token_balances(&[(&"AT-uusd".to_string(), &[])]);
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InconsistentMarketState {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InconsistentMarketState {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Once the supply is back, borrowing works again
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn borrow_stable_liability_overflow() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    // Liabilities right below the Decimal256 boundary
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::MAX - Decimal256::from_uint256(100u64),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(1000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { msg, .. })) => {
+            assert_eq!(msg, "Liability overflow")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn user_actions_deadline() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let env = mock_env();
+    let expired = Some(env.block.height - 1);
+    let valid = Some(env.block.height);
+
+    // Deposit
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: expired,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::TransactionExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: valid,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Borrow
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: None,
+        deadline: expired,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::TransactionExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(100u64),
+        to: None,
+        deadline: valid,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Repay
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(10u128),
+        }],
+    );
+    let msg = ExecuteMsg::RepayStable { deadline: expired };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::TransactionExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RepayStable { deadline: valid };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Redeem
+    let info = mock_info("AT-uusd", &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: expired,
+            target_stable: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::TransactionExpired {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // The deadline block itself is still valid
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: valid,
+            target_stable: None,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn repay_stable() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::RepayStable { deadline: None };
+    info.funds = vec![Coin {
+        denom: "ukrw".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::zero(),
+    }];
+
+    let res2 = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res2 {
+        Err(ContractError::ZeroRepay(_uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "100000"),
+            attr("remaining_loan", "400000"),
+        ]
+    );
+
+    //Loan amount and Total liability have decreased according to the repayment
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::from(400000u128));
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::from_uint256(2400000u128)
+    );
+
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(500000u128),
+    }];
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "400000"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "400000"),
+            attr("remaining_loan", "0"),
+        ]
+    );
+
+    //Loan amount and Total liability have decreased according to the repayment
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::zero());
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::from_uint256(2000000u128)
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+
+    // Borrow again and let interest accrue
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    info.funds = vec![];
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // interest_factor = 1% * 10blocks = 0.1
+    // loan_amount = 550000
+    env.block.height += 10;
+    let msg = ExecuteMsg::RepayStable { deadline: None };
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+            attr("interest_paid", "50000"),
+            attr("principal_paid", "50000"),
+            attr("remaining_loan", "450000"),
+        ]
+    );
+
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::from(450000u128));
+}
+
+#[test]
+fn repay_stable_refund_excess() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(10000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 600000u128),
+        }],
+    );
+
+    // The excess is refunded net of tax, the reserves are untouched
+    let reserves = read_state(deps.as_ref().storage).unwrap().total_reserves;
+    let msg = ExecuteMsg::RepayStable { deadline: None };
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(600000u128),
+    }];
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_reserves,
+        reserves
+    );
+
+    // Repaying without a loan is rejected
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::NoLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::ClosePosition {});
+    match res {
+        Err(ContractError::NoLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn interest_index_history() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let start_height = env.block.height;
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    // interest_factor = 1% * 100blocks = 1
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    // interest_factor = 1% * 10blocks = 0.1
+    // loan_amount = 550000
+    env.block.height += 10;
+    let msg = ExecuteMsg::RepayStable { deadline: None };
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100000u128),
+    }];
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::InterestIndexAt {
+            height: start_height + 105,
+        },
+    )
+    .unwrap();
+    let index_res: InterestIndexAtResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        index_res,
+        InterestIndexAtResponse {
+            height: start_height + 105,
+            checkpoint_height: start_height + 100,
+            global_interest_index: Decimal256::from_uint256(2u64),
+        }
+    );
+
+    // No checkpoint before the first borrow
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::InterestIndexAt {
+            height: start_height + 99,
+        },
+    );
+    match res {
+        Err(StdError::NotFound { .. }) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LoanAmountAt {
+            borrower: "addr0000".to_string(),
+            height: start_height + 105,
+        },
+    )
+    .unwrap();
+    let loan_res: LoanAmountAtResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        loan_res,
+        LoanAmountAtResponse {
+            borrower: "addr0000".to_string(),
+            height: start_height + 105,
+            checkpoint_height: start_height + 100,
+            loan_amount: Uint256::from(500000u64),
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LoanAmountAt {
+            borrower: "addr0000".to_string(),
+            height: start_height + 115,
+        },
+    )
+    .unwrap();
+    let loan_res: LoanAmountAtResponse = from_binary(&res).unwrap();
+    assert_eq!(loan_res.checkpoint_height, start_height + 110);
+    assert_eq!(loan_res.loan_amount, Uint256::from(450000u64));
+
+    // Later index checkpoints accrue interest on the loan
+    store_interest_index_checkpoint(
+        deps.as_mut().storage,
+        start_height + 120,
+        &Decimal256::from_str("4.4").unwrap(),
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::LoanAmountAt {
+            borrower: "addr0000".to_string(),
+            height: start_height + 130,
+        },
+    )
+    .unwrap();
+    let loan_res: LoanAmountAtResponse = from_binary(&res).unwrap();
+    assert_eq!(loan_res.checkpoint_height, start_height + 120);
+    assert_eq!(loan_res.loan_amount, Uint256::from(900000u64));
+
+    // The loan is scaled by the index ratio with a single rounding:
+    // 450000 * 3.3333333 / 2.2 = 681818.17
+    store_interest_index_checkpoint(
+        deps.as_mut().storage,
+        start_height + 140,
+        &Decimal256::from_str("3.3333333").unwrap(),
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::LoanAmountAt {
+            borrower: "addr0000".to_string(),
+            height: start_height + 140,
+        },
+    )
+    .unwrap();
+    let loan_res: LoanAmountAtResponse = from_binary(&res).unwrap();
+    assert_eq!(loan_res.loan_amount, Uint256::from(681818u64));
+}
+
+#[test]
+fn repay_stable_for() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    // a third party repays the loan of addr0000
+    let msg = ExecuteMsg::RepayStableFor {
+        borrower: "addr0000".to_string(),
+        deadline: None,
+    };
+    let payer_info = mock_info(
+        "payer",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), payer_info, msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable_for"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "100000"),
+            attr("remaining_loan", "400000"),
+            attr("payer", "payer"),
+        ]
+    );
+
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::from(400000u128));
+
+    // excess is refunded to the payer
+    let payer_info = mock_info(
+        "payer",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(500000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env, payer_info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "payer".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::zero());
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::from_uint256(2000000u128)
+    );
+}
+
+#[test]
+fn repay_from_collateral() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let mut env = mock_env();
+
+    // Deposit 1000000
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(1000000u128)),
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            ),
+        ],
+    )]);
+
+    // Borrow 500000
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1500000u128),
+        }],
+    );
+
+    // interest_factor = 1% * 100blocks = 1
+    // exchange_rate = (1500000 + 1000000) / 2000000 = 1.25
+    env.block.height += 100;
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(400000u128),
+        msg: to_binary(&Cw20HookMsg::RepayFromCollateral {}).unwrap(),
+    });
+
+    // only aterra can be used
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("AT-uusd", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(400000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_from_collateral"),
+            attr("borrower", "addr0000"),
+            attr("burn_amount", "400000"),
+            attr("repay_amount", "500000"),
+            attr("interest_paid", "500000"),
+            attr("principal_paid", "0"),
+            attr("remaining_loan", "500000"),
+        ]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(500000u64));
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_liabilities, Decimal256::from_uint256(500000u64));
+
+    // Aterra worth more than the loan
+    // exchange_rate = (1500000 + 500000) / 1600000 = 1.25
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(600000u128)),
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            ),
+        ],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(600000u128),
+        msg: to_binary(&Cw20HookMsg::RepayFromCollateral {}).unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(400000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-uusd".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(200000u128),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::zero());
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_liabilities, Decimal256::zero());
+}
+
+#[test]
+fn close_position() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let borrow_msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), borrow_msg.clone()).unwrap();
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 600000u128),
+        }],
+    );
+
+    // under funding
+    let msg = ExecuteMsg::ClosePosition {};
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(400000u128),
+    }];
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InsufficientRepayAmount(100000u128)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // exact funding
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(500000u128),
+    }];
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "close_position"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "500000"),
+            attr("remaining_loan", "0"),
+        ]
+    );
+    assert_eq!(0, res.messages.len());
+
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::zero());
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::from_uint256(2000000u128)
+    );
+
+    // over funding
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), borrow_msg).unwrap();
+
+    info.funds = vec![Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(600000u128),
+    }];
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "close_position"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "500000"),
+            attr("remaining_loan", "0"),
+        ]
+    );
+
+    let res_loan = read_borrower_infos(deps.as_ref(), None, None)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .loan_amount;
+    assert_eq!(res_loan, Uint256::zero());
+    assert_eq!(
+        read_state(deps.as_ref().storage).unwrap().total_liabilities,
+        Decimal256::from_uint256(2000000u128)
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+}
+
+#[test]
+fn repay_stable_from_liquidation() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(100000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+        ],
+    );
+
+    let msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("overseer", &[]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let _uusd_string = "uusd";
+    match res {
+        Err(ContractError::ZeroRepay(__uusd_string)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 100000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "100000"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "100000"),
+            attr("remaining_loan", "400000"),
+        ]
+    );
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 500000u128),
+        }],
+    );
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "400000"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "400000"),
+            attr("remaining_loan", "0"),
+        ]
+    );
+
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100000u128),
+                }
+            )
+            .unwrap()]
+        }))]
+    );
+}
+
+#[test]
+fn repay_stable_from_liquidation_with_tax() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+
+    env.block.height += 100;
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // update balance to make repay
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(100000u128),
+            },
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+        ],
+    );
+
+    // 1% tax on the transfer from the liquidation contract
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = ExecuteMsg::RepayStableFromLiquidation {
+        borrower: "addr0000".to_string(),
+        prev_balance: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+    };
+    let info = mock_info("overseer", &[]);
+
+    // Balance dropped below prev_balance
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT - 1u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::ZeroRepay(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // 100000 sent, 991 taxed on the way
+    let received = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        },
+    )
+    .unwrap()
+    .amount;
+    assert_eq!(received, Uint128::from(99009u128));
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT) + received,
+        }],
+    );
+
+    let prev_state = read_state(deps.as_ref().storage).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "repay_stable"),
+            attr("borrower", "addr0000"),
+            attr("repay_amount", "99009"),
+            attr("interest_paid", "0"),
+            attr("principal_paid", "99009"),
+            attr("remaining_loan", "400991"),
+        ]
+    );
+    assert_eq!(res.messages, vec![]);
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        prev_state.total_liabilities - Decimal256::from_uint256(99009u64)
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.loan_amount, Uint256::from(500000u64 - 99009u64));
+}
+
+#[test]
+fn claim_rewards() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // Initial deposit aterra is held by the market itself
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // zero loan claim, will return empty messages
+    let msg = ExecuteMsg::ClaimRewards { to: None };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // zero block passed
+    let msg = ExecuteMsg::ClaimRewards {
+        to: Some("addr0001".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // 100 blocks passed
+    env.block.height += 100;
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "distributor".to_string(),
+            funds: vec![],
+            msg: to_binary(&FaucetExecuteMsg::Spend {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(33u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res: BorrowerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowerInfo {
+                borrower: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.pending_rewards,
+        Decimal256::from_str("0.333333333333").unwrap()
+    );
+    assert_eq!(
+        res.reward_index,
+        Decimal256::from_str("0.000066666666666666").unwrap()
+    );
+}
+
+#[test]
+fn update_emission_rate() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1200000u128),
+            total_reserves: Decimal256::from_uint256(200000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let mut env = mock_env();
+    env.block.height += 100;
+
+    // only owner or distributor can update the emission rate
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        emission_rate: Decimal256::percent(200),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("distributor", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_emission_rate"),
+            attr("anc_emission_rate", "2"),
+        ]
+    );
+
+    // rewards of the passed blocks are accrued with the previous rate
+    // borrow_amount = 2400000 / 2 = 1200000
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.anc_emission_rate, Decimal256::percent(200));
+    assert_eq!(state.last_reward_updated, env.block.height);
+    assert_eq!(
+        state.global_reward_index,
+        Decimal256::from_uint256(100u64) / Decimal256::from_uint256(1200000u64)
+    );
+
+    // owner can update the emission rate as well
+    let msg = ExecuteMsg::UpdateEmissionRate {
+        emission_rate: Decimal256::one(),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.anc_emission_rate, Decimal256::one());
+}
+
+#[test]
+fn staking() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let env = mock_env();
+    let msg = ExecuteMsg::UpdateStakingEmissionRate {
+        emission_rate: Decimal256::one(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("distributor", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // only aterra can be bonded
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("token", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("AT-uusd", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "bond"),
+            attr("staker", "addr0000"),
+            attr("amount", "1000"),
+        ]
+    );
+
+    // 10 blocks later, addr0001 bonds the same amount
+    let mut env = mock_env();
+    env.block.height += 10;
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::Bond {}).unwrap(),
+    });
+    let info = mock_info("AT-uusd", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // global_reward_index = 10 / 1000 + 10 / 2000
+    env.block.height += 10;
+    let res: StakingStateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakingState { block_height: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        StakingStateResponse {
+            total_bond_amount: Uint256::from(2000u64),
+            global_reward_index: Decimal256::from_str("0.015").unwrap(),
+            last_reward_updated: env.block.height,
+            emission_rate: Decimal256::one(),
+        }
+    );
+
+    let res: StakerInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerInfo {
+                staker: "addr0000".to_string(),
+                block_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.bond_amount, Uint256::from(1000u64));
+    assert_eq!(res.pending_rewards, Decimal256::from_uint256(15u64));
+
+    let res: StakerInfosResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakerInfos {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.staker_infos.len(), 2);
+
+    // cannot unbond more than bonded
+    let msg = ExecuteMsg::Unbond {
+        amount: Uint256::from(2000u64),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::InsufficientBondAmount(1000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Unbond {
+        amount: Uint256::from(1000u64),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::ClaimStakingRewards { to: None };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "distributor".to_string(),
+            funds: vec![],
+            msg: to_binary(&FaucetExecuteMsg::Spend {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(5u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // fully unbonded and claimed stakers are removed
+    let res: StakerInfosResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::StakerInfos {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.staker_infos,
+        vec![StakerInfoResponse {
+            staker: "addr0000".to_string(),
+            reward_index: Decimal256::zero(),
+            bond_amount: Uint256::from(1000u64),
+            pending_rewards: Decimal256::zero(),
+        }]
+    );
+}
+
+#[test]
+fn stable_token_market() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: Some("stable".to_string()),
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    // token markets start without the initial deposit
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: 123u64,
+                funds: vec![],
+                label: "".to_string(),
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "Anchor Terra USD".to_string(),
+                    symbol: "aUST".to_string(),
+                    decimals: 6u8,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+            }),
+            1
+        )]
+    );
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.stable_token, Some("stable".to_string()));
+
+    // native funds are not accepted
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::NativeStableNotAccepted {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the stable token can be deposited
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::DepositStable { recipient: None }).unwrap(),
+    });
+    let info = mock_info("AT-uusd", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.with_token_balances(&[(
+        &"stable".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+    let info = mock_info("stable", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-uusd".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // loans are paid out in the stable token, without tax
+    deps.querier.with_token_balances(&[
+        (
+            &"stable".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+        ),
+        (
+            &"AT-uusd".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1000000u128))],
+        ),
+    ]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "stable".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // excess repayment is refunded in the stable token
+    deps.querier.with_token_balances(&[
+        (
+            &"stable".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1100000u128))],
+        ),
+        (
+            &"AT-uusd".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(1000000u128))],
+        ),
+    ]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(600000u128),
+        msg: to_binary(&Cw20HookMsg::RepayStable {}).unwrap(),
+    });
+    let info = mock_info("stable", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "stable".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(res.attributes[2], attr("repay_amount", "500000"));
+}
+
+#[test]
+fn utilization() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // No liabilities yet
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Utilization { block_height: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        UtilizationResponse {
+            utilization: Decimal256::zero(),
+            total_liabilities: Uint256::zero(),
+            available: Uint256::from(INITIAL_DEPOSIT_AMOUNT),
+        }
+    );
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1200000u128),
+            total_reserves: Decimal256::from_uint256(200000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // utilization = 1200000 / (1200000 + 1000000 - 200000)
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Utilization { block_height: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        UtilizationResponse {
+            utilization: Decimal256::percent(60),
+            total_liabilities: Uint256::from(1200000u64),
+            available: Uint256::from(800000u64),
+        }
+    );
+
+    // interest_factor = 1% * 100blocks = 1
+    // utilization = 2400000 / (2400000 + 1000000 - 200000)
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Utilization {
+                block_height: Some(mock_env().block.height + 100u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        UtilizationResponse {
+            utilization: Decimal256::percent(75),
+            total_liabilities: Uint256::from(2400000u64),
+            available: Uint256::from(800000u64),
+        }
+    );
+
+    // Empty market
+    deps.querier.update_balance(MOCK_CONTRACT_ADDR, vec![]);
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::zero(),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+    let res: UtilizationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Utilization { block_height: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.utilization, Decimal256::zero());
+}
+
+#[test]
+fn market_state() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1200000u128),
+            total_reserves: Decimal256::from_uint256(200000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // exchange_rate = (1000000 + 1200000 - 200000) / 2000000 = 1
+    // utilization = 1200000 / (1200000 + 1000000 - 200000)
+    let res: MarketStateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::MarketState { block_height: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        MarketStateResponse {
+            total_deposits: Uint256::from(2000000u64),
+            total_liabilities: Decimal256::from_uint256(1200000u64),
+            total_reserves: Decimal256::from_uint256(200000u64),
+            aterra_supply: Uint256::from(2000000u64),
+            exchange_rate: Decimal256::one(),
+            utilization: Decimal256::percent(60),
+            borrow_rate: Decimal256::percent(1),
+        }
+    );
+
+    // interest_factor = 1% * 100blocks = 1
+    // exchange_rate = (1000000 + 2400000 - 200000) / 2000000 = 1.6
+    // utilization = 2400000 / (2400000 + 1000000 - 200000)
+    let res: MarketStateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::MarketState {
+                block_height: Some(mock_env().block.height + 100u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        MarketStateResponse {
+            total_deposits: Uint256::from(3200000u64),
+            total_liabilities: Decimal256::from_uint256(2400000u64),
+            total_reserves: Decimal256::from_uint256(200000u64),
+            aterra_supply: Uint256::from(2000000u64),
+            exchange_rate: Decimal256::from_str("1.6").unwrap(),
+            utilization: Decimal256::percent(75),
+            borrow_rate: Decimal256::percent(1),
+        }
+    );
+
+    // cannot query the past
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::MarketState {
+            block_height: Some(mock_env().block.height - 1),
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "block_height must bigger than last_interest_updated")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn deposit_value() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // No aterra issued yet
+    let res: DepositValueResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DepositValue {
+                depositor: "addr0000".to_string(),
+                block_height: mock_env().block.height,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DepositValueResponse {
+            depositor: "addr0000".to_string(),
+            aterra_balance: Uint256::zero(),
+            stable_value: Uint256::zero(),
+        }
+    );
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(500000u128)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500000u128)),
+        ],
+    )]);
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1200000u128),
+            total_reserves: Decimal256::from_uint256(200000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(2000000u64),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // exchange_rate = (1000000 + 1200000 - 200000) / 2000000 = 1
+    let res: DepositValueResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DepositValue {
+                depositor: "addr0000".to_string(),
+                block_height: mock_env().block.height,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DepositValueResponse {
+            depositor: "addr0000".to_string(),
+            aterra_balance: Uint256::from(500000u64),
+            stable_value: Uint256::from(500000u64),
+        }
+    );
+
+    // interest_factor = 0.1% * 100blocks = 0.1
+    // exchange_rate = (1000000 + 1320000 - 200000) / 2000000 = 1.06
+    let res: DepositValueResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DepositValue {
+                depositor: "addr0000".to_string(),
+                block_height: mock_env().block.height + 100u64,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DepositValueResponse {
+            depositor: "addr0000".to_string(),
+            aterra_balance: Uint256::from(500000u64),
+            stable_value: Uint256::from(530000u64),
+        }
+    );
+
+    // Holder without aterra
+    let res: DepositValueResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DepositValue {
+                depositor: "addr0001".to_string(),
+                block_height: mock_env().block.height + 100u64,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.aterra_balance, Uint256::zero());
+    assert_eq!(res.stable_value, Uint256::zero());
+}
+
+#[test]
+fn simulate_deposit_and_redeem() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(500000u128)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500000u128)),
+        ],
+    )]);
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1200000u128),
+            total_reserves: Decimal256::from_uint256(200000u128),
+            last_interest_updated: mock_env().block.height,
+            last_reward_updated: mock_env().block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::from(2000000u64),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // interest_factor = 0.1% * 100blocks = 0.1
+    // exchange_rate = (1000000 + 1320000 - 200000) / 2000000 = 1.06
+    let mut env = mock_env();
+    env.block.height += 100;
+
+    let res: SimulateDepositResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateDeposit {
+                amount: Uint256::from(1060000u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SimulateDepositResponse {
+            mint_amount: Uint256::from(1000000u64),
+            exchange_rate: Decimal256::from_str("1.06").unwrap(),
+        }
+    );
+
+    let res: SimulateRedeemResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateRedeem {
+                burn_amount: Uint256::from(1000000u64),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SimulateRedeemResponse {
+            redeem_amount: deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(1060000u128),
+                }
+            )
+            .unwrap()
+            .amount
+            .into(),
+            exchange_rate: Decimal256::from_str("1.06").unwrap(),
+        }
+    );
+}
+
+#[test]
+fn total_deposits() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::permille(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Deposit 1000000 on top of the initial deposit
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+        }],
+    );
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(1000000u128)),
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            ),
+        ],
+    )]);
+
+    let res: TotalDepositsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TotalDeposits {
+                block_height: mock_env().block.height,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        TotalDepositsResponse {
+            aterra_supply: Uint256::from(2000000u64),
+            total_deposit_value: Uint256::from(2000000u64),
+        }
+    );
+
+    // Lend out 1000000
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.total_liabilities = Decimal256::from_uint256(1000000u64);
+    state.prev_aterra_supply = Uint256::from(2000000u64);
+    store_state(deps.as_mut().storage, &state).unwrap();
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+
+    // interest_factor = 0.1% * 100blocks = 0.1
+    // total_deposit_value = 1000000 + 1100000
+    let res: TotalDepositsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TotalDeposits {
+                block_height: mock_env().block.height + 100u64,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        TotalDepositsResponse {
+            aterra_supply: Uint256::from(2000000u64),
+            total_deposit_value: Uint256::from(2100000u64),
+        }
+    );
+    assert!(res.total_deposit_value > Uint256::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128));
+}
+
+#[test]
+fn execute_epoch_operations() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+
+    // reserve == 3000
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+
+    // only overseer can execute this
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    info.sender = Addr::unchecked("overseer");
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(2970u128), // 1% tax
+            }],
+        }))]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state,
+        State {
+            total_liabilities: Decimal256::from_uint256(2000000u128),
+            total_reserves: Decimal256::zero(),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::from_uint256(2u64),
+            global_reward_index: Decimal256::from_str("0.0001").unwrap(),
+            anc_emission_rate: Decimal256::from_uint256(5u64),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        }
+    );
+
+    // When there is not enough balance to cover reserve
+    // no message will be sent and reserve will be left as same
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(2999u128),
+        }],
+    );
+
+    let mut env = mock_env();
+    let info = mock_info("overseer", &[]);
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+
+    // reserve == 3000
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state,
+        State {
+            total_liabilities: Decimal256::from_uint256(2000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::from_uint256(2u64),
+            global_reward_index: Decimal256::from_str("0.0001").unwrap(),
+            anc_emission_rate: Decimal256::from_uint256(5u64),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        }
+    );
+}
+
+#[test]
+fn execute_epoch_operations_same_block() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let mut info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // No interest accrues within the block, so the interest
+    // model must not be queried
+    deps.querier.with_borrow_rate(&[]);
+
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+    info.sender = Addr::unchecked("overseer");
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_uint256(1000000u128)
+    );
+    assert_eq!(state.global_interest_index, Decimal256::one());
+}
+
+#[test]
+fn execute_epoch_operations_insurance_split() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let mut env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    env.block.height += 100;
+
+    // reserve == 3000
+    let msg = ExecuteMsg::ExecuteEpochOperations {
+        deposit_rate: Decimal256::one(),
+        target_deposit_rate: Decimal256::one(),
+        threshold_deposit_rate: Decimal256::one(),
+        distributed_interest: Uint256::zero(),
+    };
+
+    // Split cannot exceed the whole reserves
+    let update_msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: Some("insurance".to_string()),
+        remove_insurance_fund: None,
+        insurance_split: Some(Decimal256::percent(101)),
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let owner_info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), env.clone(), owner_info.clone(), update_msg);
+    match res {
+        Err(ContractError::InvalidInsuranceSplit {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Insurance fund without a split, everything goes to the collector
+    let update_msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: Some("insurance".to_string()),
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner_info.clone(), update_msg).unwrap();
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.insurance_fund, Some("insurance".to_string()));
+    assert_eq!(config_res.insurance_split, Decimal256::zero());
+
+    let overseer_info = mock_info("overseer", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        overseer_info.clone(),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(2970u128), // 1% tax
+            }],
+        }))]
+    );
+
+    // 30% of the reserves goes to the insurance fund
+    let update_msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: Some(Decimal256::percent(30)),
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner_info, update_msg).unwrap();
+
+    let mut state = read_state(deps.as_ref().storage).unwrap();
+    state.total_reserves = Decimal256::from_uint256(3000u128);
+    store_state(deps.as_mut().storage, &state).unwrap();
+    env.block.height += 100;
+
+    let res = execute(deps.as_mut(), env, overseer_info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(2100u128),
+                    }
+                )
+                .unwrap()],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "insurance".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(900u128),
+                    }
+                )
+                .unwrap()],
+            })),
+        ]
+    );
+
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_reserves, Decimal256::zero());
+}
+
+#[test]
+fn withdraw_reserves() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    // only the owner or the collector can withdraw reserves
+    let msg = ExecuteMsg::WithdrawReserves {
+        amount: Some(Uint256::from(1000u64)),
+        recipient: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // cannot withdraw more than the accrued reserves
+    let owner_info = mock_info("owner", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        owner_info.clone(),
+        ExecuteMsg::WithdrawReserves {
+            amount: Some(Uint256::from(5000u64)),
+            recipient: None,
+        },
+    );
+    match res {
+        Err(ContractError::InsufficientReserves(3000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let collector_info = mock_info("collector", &[]);
+    let res = execute(deps.as_mut(), env.clone(), collector_info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(990u128),
+            }]
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw_reserves"),
+            attr("recipient", "collector"),
+            attr("amount", "1000"),
+            attr("total_reserves", "2000"),
+        ]
+    );
+
+    // withdraw the remaining reserves to another address,
+    // the insurance fund taking its share
+    let msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: Some("insurance".to_string()),
+        remove_insurance_fund: None,
+        insurance_split: Some(Decimal256::percent(50)),
+        flash_loan_fee: None,
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::WithdrawReserves {
+        amount: None,
+        recipient: Some("addr0001".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), owner_info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(990u128),
+                }]
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "insurance".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(990u128),
+                }]
+            }))
+        ]
+    );
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_reserves, Decimal256::zero());
+
+    let res = execute(deps.as_mut(), env, owner_info, msg);
+    match res {
+        Err(ContractError::InsufficientReserves(0)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn flash_loan() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    // Register overseer contract
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    store_state(
+        deps.as_mut().storage,
+        &State {
+            total_liabilities: Decimal256::from_uint256(1000000u128),
+            total_reserves: Decimal256::from_uint256(3000u128),
+            last_interest_updated: env.block.height,
+            last_reward_updated: env.block.height,
+            global_interest_index: Decimal256::one(),
+            global_reward_index: Decimal256::zero(),
+            anc_emission_rate: Decimal256::one(),
+            prev_aterra_supply: Uint256::zero(),
+            prev_exchange_rate: Decimal256::one(),
+        },
+    )
+    .unwrap();
+
+    let update_msg = ExecuteMsg::UpdateConfig {
+        interest_model: None,
+        distribution_model: None,
+        max_borrow_factor: None,
+        max_accrual_blocks: None,
+        min_borrow_rate: None,
+        insurance_fund: None,
+        remove_insurance_fund: None,
+        insurance_split: None,
+        flash_loan_fee: Some(Decimal256::permille(1)),
+        max_borrow_amount: None,
+        per_account_borrow_cap: None,
+        guardian: None,
+        remove_guardian: None,
+        block_time: None,
+        min_borrow_amount: None,
+        dust_threshold: None,
+        overseer_contract: None,
+        force: None,
+    };
+    let owner_info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), owner_info, update_msg).unwrap();
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(res.flash_loan_fee, Decimal256::permille(1));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::FlashLoan {
+            amount: Uint256::zero(),
+            msg: Binary::from(b"{}".to_vec()),
+            recipient: "receiver".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::ZeroFlashLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // reserves cannot be lent out
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::FlashLoan {
+            amount: Uint256::from(998000u64),
+            msg: Binary::from(b"{}".to_vec()),
+            recipient: "receiver".to_string(),
+        },
+    );
+    match res {
+        Err(ContractError::NoStableAvailable(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::FlashLoan {
+            amount: Uint256::from(500000u64),
+            msg: Binary::from(b"{}".to_vec()),
+            recipient: "receiver".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "receiver".to_string(),
+                funds: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "uusd".to_string(),
+                        amount: Uint128::from(500000u128),
+                    }
+                )
+                .unwrap()],
+                msg: Binary::from(b"{}".to_vec()),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertFlashLoanRepaid {}).unwrap(),
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "flash_loan"),
+            attr("recipient", "receiver"),
+            attr("amount", "500000"),
+            attr("fee", "500"),
+        ]
+    );
+
+    // the market is locked until the loan is repaid
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        ),
+        ExecuteMsg::DepositStable {
+            recipient: None,
+            deadline: None,
+        },
+    );
+    match res {
+        Err(ContractError::FlashLoanInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // only the market can assert the repayment
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // loan returned without the fee
+    let contract_info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        contract_info.clone(),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    );
+    match res {
+        Err(ContractError::FlashLoanNotRepaid(1000500)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000500u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        contract_info.clone(),
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "assert_flash_loan_repaid"),
+            attr("fee", "500"),
+        ]
+    );
+
+    // fee accrues to the reserves
+    let state = read_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_reserves, Decimal256::from_uint256(3500u64));
+
+    // the lock is released
+    let res = execute(
+        deps.as_mut(),
+        env,
+        contract_info,
+        ExecuteMsg::AssertFlashLoanRepaid {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+// #[test]
 // fn borrow_repay_execute_operations() {
 //     let mut deps = mock_dependencies(
 //         20,
@@ -1858,83 +9564,571 @@ fn execute_epoch_operations() {
 //         }],
 //     );
 
-//     let msg = InstantiateMsg {
-//         owner_addr: "owner".to_string(),
-//         stable_denom: "uusd".to_string(),
-//         aterra_code_id: 123u64,
-//         anc_emission_rate: Decimal256::one(),
-//         max_borrow_factor: Decimal256::one(),
-//     };
+//     let msg = InstantiateMsg {
+//         owner_addr: "owner".to_string(),
+//         stable_denom: "uusd".to_string(),
+//         aterra_code_id: 123u64,
+//         anc_emission_rate: Decimal256::one(),
+//         max_borrow_factor: Decimal256::one(),
+//         stable_decimals: None,
+//         aterra_name: None,
+//         aterra_symbol: None,
+//     };
+
+//     let env = mock_env(
+//         "addr0000",
+//         &[Coin {
+//             denom: "uusd".to_string(),
+//             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+//         }],
+//     );
+
+//     // we can just call .unwrap() to assert this was a success
+//     let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+//     // Register anchor token contract
+//     let msg = ExecuteMsg::RegisterATerra {};
+//     let env = mock_env("AT-uusd", &[]);
+//     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+//     // Register overseer contract
+//     let msg = ExecuteMsg::RegisterContracts {
+//         overseer_contract: "overseer".to_string(),
+//         interest_model: "interest".to_string(),
+//         distribution_model: "distribution".to_string(),
+//         collector_contract: "collector".to_string(),
+//         distributor_contract: "distributor".to_string(),
+//     };
+//     let env = mock_env("addr0000", &[]);
+//     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+//     deps.querier
+//         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+//     deps.querier.with_token_balances(&[(
+//         &HumanAddr::from("AT-uusd"),
+//         &[(
+//             &MOCK_CONTRACT_ADDR.to_string(),
+//             &Uint128::from(1000000u128),
+//         )],
+//     )]);
+//     deps.querier.update_balance(
+//         MOCK_CONTRACT_ADDR.to_string(),
+//         vec![Coin {
+//             denom: "uusd".to_string(),
+//             amount: Uint128::from(373025692u128),
+//         }],
+//     );
+
+//     let mut env = mock_env("overseer", &[]);
+
+//     store_state(
+//         deps.as_mut().storage,
+//         &State {
+//             total_liabilities: Decimal256::from_str("8.198749212085782102").unwrap(),
+//             total_reserves: Decimal256::from_str("372025697.802295205294219818").unwrap(),
+//             last_interest_updated: env.block.height,
+//             last_reward_updated: env.block.height,
+//             global_interest_index: Decimal256::from_str("1.000005078160215988").unwrap(),
+//             global_reward_index: Decimal256::from_str("119531.277425251814227128").unwrap(),
+//             anc_emission_rate: Decimal256::from_str("980001.99").unwrap(),
+//             prev_aterra_supply: Uint256::from(1000000001000000u128),
+//             prev_exchange_rate: Decimal256::from_str("1.0000022").unwrap(),
+//         },
+//     )
+//     .unwrap();
+
+//     env.block.height += 100;
+
+//     let msg = ExecuteMsg::ExecuteEpochOperations {
+//         deposit_rate: Decimal256::one(),
+//         target_deposit_rate: Decimal256::from_str("0.000000040762727704").unwrap(),
+//         threshold_deposit_rate: Decimal256::from_str("0.000000030572045778").unwrap(),
+//     };
+
+//     // only overseer can execute this
+//     let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+// }
+
+fn assert_event_schema(attributes: &[cosmwasm_std::Attribute]) {
+    assert_eq!(attributes[0].key, "action");
+    for attribute in attributes.iter() {
+        if attribute.key.ends_with("_amount") || attribute.key == "remaining_loan" {
+            attribute
+                .value
+                .parse::<u128>()
+                .unwrap_or_else(|_| panic!("{} is not in integer micro-units", attribute.key));
+        }
+        if attribute.key == "exchange_rate" {
+            Decimal256::from_str(&attribute.value).unwrap();
+        }
+    }
+}
+
+#[test]
+fn event_schema() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"AT-uusd".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        )],
+    )]);
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+    deps.querier
+        .with_borrow_limit(&[(&"addr0000".to_string(), &Uint256::from(1000000u64))]);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_event_schema(&res.attributes);
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let msg = ExecuteMsg::DepositStable {
+        recipient: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_event_schema(&res.attributes);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::BorrowStable {
+        borrow_amount: Uint256::from(500000u64),
+        to: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_event_schema(&res.attributes);
+    assert!(res.attributes.contains(&attr("remaining_loan", "500000")));
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100000u128),
+        }],
+    );
+    let msg = ExecuteMsg::RepayStable { deadline: None };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_event_schema(&res.attributes);
+    assert!(res.attributes.contains(&attr("remaining_loan", "400000")));
+}
+
+#[test]
+fn add_market() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }]);
+
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        stable_denom: "uusd".to_string(),
+        stable_token: None,
+        aterra_code_id: 123u64,
+        anc_emission_rate: Decimal256::one(),
+        max_borrow_factor: Decimal256::one(),
+        stable_decimals: None,
+        aterra_name: None,
+        aterra_symbol: None,
+        market_id: None,
+        additional_denoms: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register anchor token contract
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-uusd".to_string());
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    deps.querier
+        .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
+
+    let msg = ExecuteMsg::RegisterContracts {
+        overseer_contract: "overseer".to_string(),
+        interest_model: "interest".to_string(),
+        distribution_model: "distribution".to_string(),
+        collector_contract: "collector".to_string(),
+        distributor_contract: "distributor".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::AddMarket {
+        denom: "ukrw".to_string(),
+        aterra_code_id: 123u64,
+    };
+    let krw_deposit = [Coin {
+        denom: "ukrw".to_string(),
+        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+    }];
 
-//     let env = mock_env(
-//         "addr0000",
-//         &[Coin {
-//             denom: "uusd".to_string(),
-//             amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
-//         }],
-//     );
+    let info = mock_info("addr0000", &krw_deposit);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-//     // we can just call .unwrap() to assert this was a success
-//     let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-//     // Register anchor token contract
-//     let msg = ExecuteMsg::RegisterATerra {};
-//     let env = mock_env("AT-uusd", &[]);
-//     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("owner", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::InitialFundsNotDeposited(_, denom)) => assert_eq!(denom, "ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-//     // Register overseer contract
-//     let msg = ExecuteMsg::RegisterContracts {
-//         overseer_contract: "overseer".to_string(),
-//         interest_model: "interest".to_string(),
-//         distribution_model: "distribution".to_string(),
-//         collector_contract: "collector".to_string(),
-//         distributor_contract: "distributor".to_string(),
-//     };
-//     let env = mock_env("addr0000", &[]);
-//     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    // The primary market already holds uusd
+    let info = mock_info(
+        "owner",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AddMarket {
+            denom: "uusd".to_string(),
+            aterra_code_id: 123u64,
+        },
+    );
+    match res {
+        Err(ContractError::MarketAlreadyExists(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-//     deps.querier
-//         .with_borrow_rate(&[(&"interest".to_string(), &Decimal256::percent(1))]);
-//     deps.querier.with_token_balances(&[(
-//         &HumanAddr::from("AT-uusd"),
-//         &[(
-//             &MOCK_CONTRACT_ADDR.to_string(),
-//             &Uint128::from(1000000u128),
-//         )],
-//     )]);
-//     deps.querier.update_balance(
-//         MOCK_CONTRACT_ADDR.to_string(),
-//         vec![Coin {
-//             denom: "uusd".to_string(),
-//             amount: Uint128::from(373025692u128),
-//         }],
-//     );
+    let info = mock_info("owner", &krw_deposit);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: 123u64,
+                funds: vec![],
+                label: "".to_string(),
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "Anchor Terra KRW".to_string(),
+                    symbol: "aKRT".to_string(),
+                    decimals: 6u8,
+                    initial_balances: vec![Cw20Coin {
+                        address: MOCK_CONTRACT_ADDR.to_string(),
+                        amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+                    }],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+            }),
+            2
+        )]
+    );
 
-//     let mut env = mock_env("overseer", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::MarketAlreadyExists(denom)) => assert_eq!(denom, "ukrw"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-//     store_state(
-//         deps.as_mut().storage,
-//         &State {
-//             total_liabilities: Decimal256::from_str("8.198749212085782102").unwrap(),
-//             total_reserves: Decimal256::from_str("372025697.802295205294219818").unwrap(),
-//             last_interest_updated: env.block.height,
-//             last_reward_updated: env.block.height,
-//             global_interest_index: Decimal256::from_str("1.000005078160215988").unwrap(),
-//             global_reward_index: Decimal256::from_str("119531.277425251814227128").unwrap(),
-//             anc_emission_rate: Decimal256::from_str("980001.99").unwrap(),
-//             prev_aterra_supply: Uint256::from(1000000001000000u128),
-//             prev_exchange_rate: Decimal256::from_str("1.0000022").unwrap(),
-//         },
-//     )
-//     .unwrap();
+    // Register the aterra of the new market
+    let mut token_inst_res = MsgInstantiateContractResponse::new();
+    token_inst_res.set_contract_address("AT-ukrw".to_string());
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(token_inst_res.write_to_bytes().unwrap().into()),
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("aterra", "AT-ukrw"), attr("market", "ukrw")]
+    );
 
-//     env.block.height += 100;
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Markets {}).unwrap();
+    let markets_res: MarketsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        markets_res.markets,
+        vec![MarketsResponseElem {
+            denom: "ukrw".to_string(),
+            aterra_contract: "AT-ukrw".to_string(),
+        }]
+    );
 
-//     let msg = ExecuteMsg::ExecuteEpochOperations {
-//         deposit_rate: Decimal256::one(),
-//         target_deposit_rate: Decimal256::from_str("0.000000040762727704").unwrap(),
-//         threshold_deposit_rate: Decimal256::from_str("0.000000030572045778").unwrap(),
-//     };
+    // The new market shares the wired contracts but the overseer
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Market {
+            denom: "ukrw".to_string(),
+            msg: Box::new(QueryMsg::WiredContracts {}),
+        },
+    )
+    .unwrap();
+    let wired_res: WiredContractsResponse = from_binary(&res).unwrap();
+    assert_eq!(wired_res.aterra_contract, Some("AT-ukrw".to_string()));
+    assert_eq!(wired_res.overseer_contract, None);
+    assert_eq!(wired_res.interest_model, Some("interest".to_string()));
 
-//     // only overseer can execute this
-//     let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
-// }
+    let msg = ExecuteMsg::Market {
+        denom: "ukrw".to_string(),
+        msg: Box::new(ExecuteMsg::UpdateConfig {
+            max_borrow_factor: None,
+            interest_model: None,
+            distribution_model: None,
+            max_accrual_blocks: None,
+            min_borrow_rate: None,
+            insurance_fund: None,
+            remove_insurance_fund: None,
+            insurance_split: None,
+            flash_loan_fee: None,
+            max_borrow_amount: None,
+            per_account_borrow_cap: None,
+            guardian: None,
+            remove_guardian: None,
+            block_time: None,
+            min_borrow_amount: None,
+            dust_threshold: None,
+            overseer_contract: Some("overseer-krw".to_string()),
+            force: None,
+        }),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Unknown and nested markets are rejected
+    let msg = ExecuteMsg::Market {
+        denom: "ujpy".to_string(),
+        msg: Box::new(ExecuteMsg::ClaimOwnership {}),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::MarketNotFound(denom)) => assert_eq!(denom, "ujpy"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Market {
+        denom: "ukrw".to_string(),
+        msg: Box::new(ExecuteMsg::Market {
+            denom: "ukrw".to_string(),
+            msg: Box::new(ExecuteMsg::ClaimOwnership {}),
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::NotPrimaryMarket {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Deposit into the new market
+    deps.querier.with_token_balances(&[
+        (
+            &"AT-uusd".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            )],
+        ),
+        (
+            &"AT-ukrw".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            )],
+        ),
+    ]);
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR.to_string(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            },
+            Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(INITIAL_DEPOSIT_AMOUNT + 1000000u128),
+            },
+        ],
+    );
+
+    let msg = ExecuteMsg::Market {
+        denom: "ukrw".to_string(),
+        msg: Box::new(ExecuteMsg::DepositStable {
+            recipient: None,
+            deadline: None,
+        }),
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(1000000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "AT-ukrw".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(res.attributes.last(), Some(&attr("market", "ukrw")));
+
+    // The primary market is left untouched
+    assert_eq!(
+        read_state(deps.as_ref().storage)
+            .unwrap()
+            .prev_aterra_supply,
+        Uint256::zero()
+    );
+    assert_eq!(
+        read_state(&market_storage_read(deps.as_ref().storage, "ukrw"))
+            .unwrap()
+            .prev_aterra_supply,
+        Uint256::from(INITIAL_DEPOSIT_AMOUNT)
+    );
+
+    // Hooks of the new aterra run on the new market
+    deps.querier.with_token_balances(&[(
+        &"AT-ukrw".to_string(),
+        &[
+            (
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(INITIAL_DEPOSIT_AMOUNT),
+            ),
+            (&"addr0000".to_string(), &Uint128::from(1000000u128)),
+        ],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::RedeemStable {
+            deadline: None,
+            target_stable: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("AT-ukrw", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "AT-ukrw".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(1000000u128),
+                })
+                .unwrap()
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![deduct_tax(
+                    deps.as_ref(),
+                    Coin {
+                        denom: "ukrw".to_string(),
+                        amount: Uint128::from(1000000u128),
+                    }
+                )
+                .unwrap()],
+            })),
+        ]
+    );
+
+    // Messages the market sends itself are addressed to the new market
+    let msg = ExecuteMsg::Market {
+        denom: "ukrw".to_string(),
+        msg: Box::new(ExecuteMsg::BorrowStableWithCollateral {
+            collaterals: vec![("bluna".to_string(), Uint256::from(100u64))],
+            borrow_amount: Uint256::from(50u64),
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::Market {
+                denom: "ukrw".to_string(),
+                msg: Box::new(ExecuteMsg::BorrowStableFor {
+                    borrower: "addr0000".to_string(),
+                    borrow_amount: Uint256::from(50u64),
+                }),
+            })
+            .unwrap(),
+        }))
+    );
+}
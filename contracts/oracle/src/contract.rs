@@ -1,15 +1,20 @@
 use crate::error::ContractError;
 use crate::state::{
-    read_config, read_feeder, read_price, read_prices, store_config, store_feeder, store_price,
-    Config, PriceInfo,
+    read_config, read_feeder, read_feeder_set, read_price, read_price_observation_at, read_prices,
+    read_submission, store_config, store_feeder, store_feeder_set, store_price,
+    store_price_observation, store_submission, Config, FeederSet, PriceInfo, PriceObservation,
 };
 use cosmwasm_bignumber::Decimal256;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{attr, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage,
+};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeederResponse, FeedersResponse, InstantiateMsg, PriceResponse,
+    PricesResponse, PricesResponseElem, QueryMsg, SubmissionsResponse, SubmissionsResponseElem,
+    TwapPriceResponse,
 };
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -40,6 +45,12 @@ pub fn execute(
     match msg {
         ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
         ExecuteMsg::RegisterFeeder { asset, feeder } => register_feeder(deps, info, asset, feeder),
+        ExecuteMsg::RegisterFeeders {
+            asset,
+            feeders,
+            quorum,
+            validity_window,
+        } => register_feeders(deps, info, asset, feeders, quorum, validity_window),
         ExecuteMsg::FeedPrice { prices } => feed_prices(deps, env, info, prices),
     }
 }
@@ -82,6 +93,46 @@ pub fn register_feeder(
     ]))
 }
 
+pub fn register_feeders(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset: String,
+    feeders: Vec<String>,
+    quorum: u32,
+    validity_window: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if quorum == 0 || quorum as usize > feeders.len() {
+        return Err(ContractError::InvalidQuorum(quorum));
+    }
+
+    let feeders_raw = feeders
+        .iter()
+        .map(|feeder| deps.api.addr_canonicalize(feeder))
+        .collect::<StdResult<Vec<CanonicalAddr>>>()?;
+
+    store_feeder_set(
+        deps.storage,
+        &asset,
+        &FeederSet {
+            feeders: feeders_raw,
+            quorum,
+            validity_window,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_feeders"),
+        attr("asset", asset),
+        attr("feeders", feeders.join(",")),
+        attr("quorum", quorum.to_string()),
+    ]))
+}
+
 pub fn feed_prices(
     deps: DepsMut,
     env: Env,
@@ -94,37 +145,119 @@ pub fn feed_prices(
         let asset: String = price.0;
         let price: Decimal256 = price.1;
 
+        attributes.push(attr("asset", asset.to_string()));
+        attributes.push(attr("price", price.to_string()));
+
+        let price_info = PriceInfo {
+            last_updated_time: env.block.time.seconds(),
+            price,
+        };
+
+        // Assets with several feeders are priced by the median submission
+        if let Some(feeder_set) = read_feeder_set(deps.storage, &asset)? {
+            if !feeder_set.feeders.contains(&sender_raw) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            store_submission(deps.storage, &asset, &sender_raw, &price_info)?;
+            if let Some(median) = compute_median_price(deps.as_ref(), &env, &asset, &feeder_set)? {
+                attributes.push(attr("median_price", median.to_string()));
+                update_price(
+                    deps.storage,
+                    &asset,
+                    &PriceInfo {
+                        last_updated_time: env.block.time.seconds(),
+                        price: median,
+                    },
+                )?;
+            }
+
+            continue;
+        }
+
         // Check feeder permission
         let feeder = read_feeder(deps.storage, &asset)?;
         if feeder != sender_raw {
             return Err(ContractError::Unauthorized {});
         }
 
-        attributes.push(attr("asset", asset.to_string()));
-        attributes.push(attr("price", price.to_string()));
-
-        store_price(
-            deps.storage,
-            &asset,
-            &PriceInfo {
-                last_updated_time: env.block.time.seconds(),
-                price,
-            },
-        )?;
+        update_price(deps.storage, &asset, &price_info)?;
     }
 
     Ok(Response::new().add_attributes(attributes))
 }
 
+/// Store the new price and record an observation
+/// accumulating the previous price over the time it was in effect
+fn update_price(storage: &mut dyn Storage, asset: &str, price_info: &PriceInfo) -> StdResult<()> {
+    let now = price_info.last_updated_time;
+    let cumulative_price = match read_price_observation_at(storage, asset, now)? {
+        Some(last) => {
+            last.cumulative_price + last.price * Decimal256::from_uint256(now - last.time)
+        }
+        None => Decimal256::zero(),
+    };
+
+    store_price_observation(
+        storage,
+        asset,
+        &PriceObservation {
+            time: now,
+            price: price_info.price,
+            cumulative_price,
+        },
+    )?;
+    store_price(storage, asset, price_info)
+}
+
+/// Median of the submissions within the validity window,
+/// or None while fewer than the quorum are valid
+fn compute_median_price(
+    deps: Deps,
+    env: &Env,
+    asset: &str,
+    feeder_set: &FeederSet,
+) -> StdResult<Option<Decimal256>> {
+    let now = env.block.time.seconds();
+    let mut prices: Vec<Decimal256> = vec![];
+    for feeder in feeder_set.feeders.iter() {
+        if let Some(submission) = read_submission(deps.storage, asset, feeder)? {
+            if submission.last_updated_time + feeder_set.validity_window >= now {
+                prices.push(submission.price);
+            }
+        }
+    }
+
+    if prices.len() < feeder_set.quorum as usize {
+        return Ok(None);
+    }
+
+    prices.sort();
+    let mid = prices.len() / 2;
+    let median = if prices.len().is_multiple_of(2) {
+        (prices[mid - 1] + prices[mid]) / Decimal256::from_uint256(2u64)
+    } else {
+        prices[mid]
+    };
+
+    Ok(Some(median))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::Feeder { asset } => to_binary(&query_feeder(deps, asset)?),
+        QueryMsg::Feeders { asset } => to_binary(&query_feeders(deps, asset)?),
+        QueryMsg::Submissions { asset } => to_binary(&query_submissions(deps, asset)?),
         QueryMsg::Price { base, quote } => to_binary(&query_price(deps, base, quote)?),
         QueryMsg::Prices { start_after, limit } => {
             to_binary(&query_prices(deps, start_after, limit)?)
         }
+        QueryMsg::TwapPrice {
+            asset,
+            window_seconds,
+        } => to_binary(&query_twap_price(deps, env, asset, window_seconds)?),
     }
 }
 
@@ -148,6 +281,40 @@ fn query_feeder(deps: Deps, asset: String) -> StdResult<FeederResponse> {
     Ok(resp)
 }
 
+fn query_feeders(deps: Deps, asset: String) -> StdResult<FeedersResponse> {
+    let feeder_set = read_feeder_set(deps.storage, &asset)?
+        .ok_or_else(|| StdError::generic_err("No feeder set for the specified asset exist"))?;
+
+    Ok(FeedersResponse {
+        asset,
+        feeders: feeder_set
+            .feeders
+            .iter()
+            .map(|feeder| Ok(deps.api.addr_humanize(feeder)?.to_string()))
+            .collect::<StdResult<Vec<String>>>()?,
+        quorum: feeder_set.quorum,
+        validity_window: feeder_set.validity_window,
+    })
+}
+
+fn query_submissions(deps: Deps, asset: String) -> StdResult<SubmissionsResponse> {
+    let feeder_set = read_feeder_set(deps.storage, &asset)?
+        .ok_or_else(|| StdError::generic_err("No feeder set for the specified asset exist"))?;
+
+    let mut submissions: Vec<SubmissionsResponseElem> = vec![];
+    for feeder in feeder_set.feeders.iter() {
+        if let Some(submission) = read_submission(deps.storage, &asset, feeder)? {
+            submissions.push(SubmissionsResponseElem {
+                feeder: deps.api.addr_humanize(feeder)?.to_string(),
+                price: submission.price,
+                last_updated_time: submission.last_updated_time,
+            });
+        }
+    }
+
+    Ok(SubmissionsResponse { asset, submissions })
+}
+
 fn query_price(deps: Deps, base: String, quote: String) -> StdResult<PriceResponse> {
     let config: Config = read_config(deps.storage)?;
     let quote_price = if config.base_asset == quote {
@@ -183,3 +350,49 @@ fn query_prices(
     let prices: Vec<PricesResponseElem> = read_prices(deps.storage, start_after, limit)?;
     Ok(PricesResponse { prices })
 }
+
+/// Time-weighted average of the asset price, in the base asset,
+/// over the last `window_seconds` seconds
+fn query_twap_price(
+    deps: Deps,
+    env: Env,
+    asset: String,
+    window_seconds: u64,
+) -> StdResult<TwapPriceResponse> {
+    if window_seconds == 0 {
+        return Err(StdError::generic_err("Window must be longer than zero"));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    if config.base_asset == asset {
+        return Ok(TwapPriceResponse {
+            asset,
+            price: Decimal256::one(),
+            window_seconds,
+        });
+    }
+
+    let now = env.block.time.seconds();
+    let start = now.checked_sub(window_seconds).ok_or_else(|| {
+        StdError::generic_err("Not enough price history for the specified window")
+    })?;
+
+    let cumulative_price_at = |time: u64| -> StdResult<Decimal256> {
+        let observation =
+            read_price_observation_at(deps.storage, &asset, time)?.ok_or_else(|| {
+                StdError::generic_err("Not enough price history for the specified window")
+            })?;
+
+        Ok(observation.cumulative_price
+            + observation.price * Decimal256::from_uint256(time - observation.time))
+    };
+
+    let price = (cumulative_price_at(now)? - cumulative_price_at(start)?)
+        / Decimal256::from_uint256(window_seconds);
+
+    Ok(TwapPriceResponse {
+        asset,
+        price,
+        window_seconds,
+    })
+}
@@ -11,4 +11,7 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized {},
+
+    #[error("Quorum must be between 1 and the number of feeders: {0}")]
+    InvalidQuorum(u32),
 }
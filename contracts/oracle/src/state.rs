@@ -9,6 +9,9 @@ use moneymarket::oracle::PricesResponseElem;
 
 static PREFIX_PRICE: &[u8] = b"price";
 static PREFIX_FEEDER: &[u8] = b"feeder";
+static PREFIX_FEEDER_SET: &[u8] = b"feeder_set";
+static PREFIX_SUBMISSION: &[u8] = b"submission";
+static PREFIX_PRICE_OBSERVATION: &[u8] = b"price_observation";
 
 static KEY_CONFIG: &[u8] = b"config";
 
@@ -97,6 +100,86 @@ pub fn read_feeder(storage: &dyn Storage, asset: &str) -> StdResult<CanonicalAdd
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeederSet {
+    pub feeders: Vec<CanonicalAddr>,
+    pub quorum: u32,
+    pub validity_window: u64,
+}
+
+pub fn store_feeder_set(
+    storage: &mut dyn Storage,
+    asset: &str,
+    feeder_set: &FeederSet,
+) -> StdResult<()> {
+    let mut feeder_set_bucket: Bucket<FeederSet> = Bucket::new(storage, PREFIX_FEEDER_SET);
+    feeder_set_bucket.save(asset.as_bytes(), feeder_set)
+}
+
+pub fn read_feeder_set(storage: &dyn Storage, asset: &str) -> StdResult<Option<FeederSet>> {
+    let feeder_set_bucket: ReadonlyBucket<FeederSet> =
+        ReadonlyBucket::new(storage, PREFIX_FEEDER_SET);
+    feeder_set_bucket.may_load(asset.as_bytes())
+}
+
+pub fn store_submission(
+    storage: &mut dyn Storage,
+    asset: &str,
+    feeder: &CanonicalAddr,
+    price: &PriceInfo,
+) -> StdResult<()> {
+    let mut submission_bucket: Bucket<PriceInfo> =
+        Bucket::multilevel(storage, &[PREFIX_SUBMISSION, asset.as_bytes()]);
+    submission_bucket.save(feeder.as_slice(), price)
+}
+
+pub fn read_submission(
+    storage: &dyn Storage,
+    asset: &str,
+    feeder: &CanonicalAddr,
+) -> StdResult<Option<PriceInfo>> {
+    let submission_bucket: ReadonlyBucket<PriceInfo> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_SUBMISSION, asset.as_bytes()]);
+    submission_bucket.may_load(feeder.as_slice())
+}
+
+/// Price set at `time`, with the sum of every earlier
+/// price weighted by the seconds it was in effect
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PriceObservation {
+    pub time: u64,
+    pub price: Decimal256,
+    pub cumulative_price: Decimal256,
+}
+
+pub fn store_price_observation(
+    storage: &mut dyn Storage,
+    asset: &str,
+    observation: &PriceObservation,
+) -> StdResult<()> {
+    let mut observation_bucket: Bucket<PriceObservation> =
+        Bucket::multilevel(storage, &[PREFIX_PRICE_OBSERVATION, asset.as_bytes()]);
+    observation_bucket.save(&observation.time.to_be_bytes(), observation)
+}
+
+/// Latest observation made at or before `time`
+pub fn read_price_observation_at(
+    storage: &dyn Storage,
+    asset: &str,
+    time: u64,
+) -> StdResult<Option<PriceObservation>> {
+    let observation_bucket: ReadonlyBucket<PriceObservation> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_PRICE_OBSERVATION, asset.as_bytes()]);
+
+    let end = (time + 1).to_be_bytes();
+    let observation = observation_bucket
+        .range(None, Some(&end), Order::Descending)
+        .next()
+        .map(|item| item.map(|(_, v)| v))
+        .transpose();
+    observation
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start(start_after: Option<String>) -> Option<Vec<u8>> {
     start_after.map(|idx| {
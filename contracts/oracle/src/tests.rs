@@ -1,11 +1,12 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
 use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::from_binary;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{attr, from_binary, StdError};
 use moneymarket::oracle::{
-    ConfigResponse, ExecuteMsg, FeederResponse, InstantiateMsg, PriceResponse, PricesResponse,
-    PricesResponseElem, QueryMsg,
+    ConfigResponse, ExecuteMsg, FeederResponse, FeedersResponse, InstantiateMsg, PriceResponse,
+    PricesResponse, PricesResponseElem, QueryMsg, SubmissionsResponse, SubmissionsResponseElem,
+    TwapPriceResponse,
 };
 use std::str::FromStr;
 
@@ -234,3 +235,284 @@ fn feed_price() {
         _ => panic!("Must return unauthorized error"),
     }
 }
+
+#[test]
+fn feed_price_median() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let feeders: Vec<String> = vec![
+        "feeder0000".to_string(),
+        "feeder0001".to_string(),
+        "feeder0002".to_string(),
+    ];
+
+    // Quorum cannot exceed the number of feeders
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterFeeders {
+        asset: "mAAPL".to_string(),
+        feeders: feeders.clone(),
+        quorum: 4,
+        validity_window: 60,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InvalidQuorum(4)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::RegisterFeeders {
+        asset: "mAAPL".to_string(),
+        feeders: feeders.clone(),
+        quorum: 2,
+        validity_window: 60,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Feeders {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: FeedersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        FeedersResponse {
+            asset: "mAAPL".to_string(),
+            feeders,
+            quorum: 2,
+            validity_window: 60,
+        }
+    );
+
+    let feed = |price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+    };
+
+    // Not a registered feeder
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        feed("1.0"),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // A single submission does not reach the quorum
+    let mut env = mock_env();
+    let first_time = env.block.time.seconds();
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        feed("1.0"),
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Price {
+            base: "mAAPL".to_string(),
+            quote: "base0000".to_string(),
+        },
+    );
+    assert!(res.is_err());
+
+    // median of two submissions
+    env.block.time = env.block.time.plus_seconds(30);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0001", &[]),
+        feed("1.2"),
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Price {
+            base: "mAAPL".to_string(),
+            quote: "base0000".to_string(),
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.1").unwrap());
+    assert_eq!(value.last_updated_base, env.block.time.seconds());
+
+    // an outlier moves the median only to the middle submission
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0002", &[]),
+        feed("100"),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("median_price", "1.2")));
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Submissions {
+            asset: "mAAPL".to_string(),
+        },
+    )
+    .unwrap();
+    let value: SubmissionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.submissions,
+        vec![
+            SubmissionsResponseElem {
+                feeder: "feeder0000".to_string(),
+                price: Decimal256::from_str("1.0").unwrap(),
+                last_updated_time: first_time,
+            },
+            SubmissionsResponseElem {
+                feeder: "feeder0001".to_string(),
+                price: Decimal256::from_str("1.2").unwrap(),
+                last_updated_time: env.block.time.seconds(),
+            },
+            SubmissionsResponseElem {
+                feeder: "feeder0002".to_string(),
+                price: Decimal256::from_str("100").unwrap(),
+                last_updated_time: env.block.time.seconds(),
+            },
+        ]
+    );
+
+    // Stale submissions fall out of the window, leaving no quorum
+    env.block.time = env.block.time.plus_seconds(61);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder0000", &[]),
+        feed("1.3"),
+    )
+    .unwrap();
+    assert!(!res.attributes.iter().any(|a| a.key == "median_price"));
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Price {
+            base: "mAAPL".to_string(),
+            quote: "base0000".to_string(),
+        },
+    )
+    .unwrap();
+    let value: PriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.rate, Decimal256::from_str("1.2").unwrap());
+}
+
+#[test]
+fn twap_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        base_asset: "base0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterFeeder {
+        asset: "mAAPL".to_string(),
+        feeder: "addr0000".to_string(),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let feed = |price: &str| ExecuteMsg::FeedPrice {
+        prices: vec![("mAAPL".to_string(), Decimal256::from_str(price).unwrap())],
+    };
+    let twap = |window_seconds: u64| QueryMsg::TwapPrice {
+        asset: "mAAPL".to_string(),
+        window_seconds,
+    };
+
+    // price 1.0 for 100 seconds, then 2.0 for 50 seconds
+    let mut env = mock_env();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), feed("1.0")).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), feed("3.0")).unwrap();
+    // overwritten within the same block
+    let _res = execute(deps.as_mut(), env.clone(), info, feed("2.0")).unwrap();
+    env.block.time = env.block.time.plus_seconds(50);
+
+    let res = query(deps.as_ref(), env.clone(), twap(100)).unwrap();
+    let value: TwapPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value,
+        TwapPriceResponse {
+            asset: "mAAPL".to_string(),
+            price: Decimal256::from_str("1.5").unwrap(),
+            window_seconds: 100,
+        }
+    );
+
+    let res = query(deps.as_ref(), env.clone(), twap(150)).unwrap();
+    let value: TwapPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        value.price,
+        Decimal256::from_str("1.333333333333333333").unwrap()
+    );
+
+    let res = query(deps.as_ref(), env.clone(), twap(40)).unwrap();
+    let value: TwapPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.price, Decimal256::from_str("2").unwrap());
+
+    // The window cannot reach before the first price
+    let res = query(deps.as_ref(), env.clone(), twap(151));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Not enough price history for the specified window")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(deps.as_ref(), env.clone(), twap(0));
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Window must be longer than zero")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::TwapPrice {
+            asset: "base0000".to_string(),
+            window_seconds: 100,
+        },
+    )
+    .unwrap();
+    let value: TwapPriceResponse = from_binary(&res).unwrap();
+    assert_eq!(value.price, Decimal256::one());
+}
@@ -1,30 +1,56 @@
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{
-    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg,
-    WasmMsg,
+    attr, to_binary, Addr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    Storage, SubMsg, WasmMsg,
 };
 
+use std::collections::BTreeMap;
+
 use crate::error::ContractError;
-use crate::querier::{query_borrower_info, query_liquidation_amount};
 use crate::state::{
-    read_all_collaterals, read_collaterals, read_config, read_whitelist_elem, store_collaterals,
-    Config, WhitelistElem,
+    read_all_collaterals, read_collaterals, read_config, read_pending_unlocks, read_recent_unlocks,
+    read_total_collateral, read_total_collaterals, read_whitelist_elem, store_collaterals,
+    store_pending_unlocks, store_recent_unlocks, store_total_collateral, Config, PendingUnlock,
+    RecentUnlock, WhitelistElem,
 };
 
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::liquidation::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, ExecuteMsg as MarketExecuteMsg};
+use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::oracle::PriceResponse;
-use moneymarket::overseer::{AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse};
-use moneymarket::querier::{query_balance, query_price, TimeConstraints};
+use moneymarket::overseer::{
+    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ExecuteMsg,
+    PendingUnlockResponse, PendingUnlocksResponse,
+};
+use moneymarket::querier::{
+    query_balance, query_escrow, query_liquidation_amount, query_loan_amount, query_price,
+    TimeConstraints,
+};
 use moneymarket::tokens::{Tokens, TokensHuman, TokensMath, TokensToHuman, TokensToRaw};
 
-pub fn lock_collateral(
+pub fn lock_collateral_for(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
+    borrower: Addr,
+    collaterals_human: TokensHuman,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.market_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    lock_collateral(deps, env, borrower, collaterals_human)
+}
+
+pub fn lock_collateral(
+    deps: DepsMut,
+    env: Env,
+    borrower: Addr,
     collaterals_human: TokensHuman,
 ) -> Result<Response, ContractError> {
-    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let config: Config = read_config(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
 
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
@@ -35,6 +61,18 @@ pub fn lock_collateral(
     let mut messages: Vec<CosmosMsg> = vec![];
     for collateral in collaterals {
         let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+
+        let total_collateral = read_total_collateral(deps.storage, &collateral.0) + collateral.1;
+        if let Some(max_collateral) = whitelist_elem.max_collateral {
+            if total_collateral > max_collateral {
+                return Err(ContractError::CollateralCapExceeded(
+                    whitelist_elem.symbol,
+                    max_collateral.into(),
+                ));
+            }
+        }
+        store_total_collateral(deps.storage, &collateral.0, &total_collateral)?;
+
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -42,12 +80,22 @@ pub fn lock_collateral(
                 .to_string(),
             funds: vec![],
             msg: to_binary(&CustodyExecuteMsg::LockCollateral {
-                borrower: info.sender.to_string(),
+                borrower: borrower.to_string(),
                 amount: collateral.1,
             })?,
         }));
     }
 
+    if let Some(max_total_collateral_value) = config.max_total_collateral_value {
+        let total_collateral_value =
+            compute_total_collateral_value(deps.as_ref(), env.block.time.seconds())?;
+        if total_collateral_value > max_total_collateral_value {
+            return Err(ContractError::TotalCollateralCapExceeded(
+                max_total_collateral_value.into(),
+            ));
+        }
+    }
+
     // Logging stuff, so can be removed
     let collateral_logs: Vec<String> = collaterals_human
         .iter()
@@ -56,11 +104,15 @@ pub fn lock_collateral(
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "lock_collateral"),
-        attr("borrower", info.sender),
+        attr("borrower", borrower),
         attr("collaterals", collateral_logs.join(",")),
     ]))
 }
 
+/// Unlock collaterals; once the amounts pending or unlocked within the
+/// unlock delay exceed the threshold of a collateral, further unlocks
+/// stay locked, and keep backing the loan, until they are claimed
+/// after the delay
 pub fn unlock_collateral(
     deps: DepsMut,
     env: Env,
@@ -74,29 +126,84 @@ pub fn unlock_collateral(
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
     let collaterals: Tokens = collaterals_human.to_raw(deps.as_ref())?;
+    let mut pending_unlocks: Vec<PendingUnlock> = read_pending_unlocks(deps.storage, &borrower_raw);
+
+    // Pending unlocks are already promised to the borrower,
+    // so they are reserved along with the unlock target collaterals
+    let mut reserved_collaterals: Tokens = collaterals.clone();
+    for pending_unlock in pending_unlocks.iter() {
+        reserved_collaterals.add(vec![(
+            pending_unlock.collateral_token.clone(),
+            pending_unlock.amount,
+        )]);
+    }
 
     // Underflow check is done in sub_collateral
-    if cur_collaterals.sub(collaterals.clone()).is_err() {
+    let mut left_collaterals: Tokens = cur_collaterals.clone();
+    if left_collaterals.sub(reserved_collaterals).is_err() {
         return Err(ContractError::UnlockExceedsLocked {});
     }
 
     // Compute borrow limit with collaterals except unlock target collaterals
     let (borrow_limit, _) = compute_borrow_limit(
         deps.as_ref(),
-        &cur_collaterals,
+        &left_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    if borrow_limit < borrow_amount_res.loan_amount {
+    let (_, escrow_value) = compute_escrow(deps.as_ref(), &config, &borrower)?;
+    let borrow_limit = borrow_limit + escrow_value * config.escrow_max_ltv;
+    let loan_amount = query_loan_amount(
+        deps.as_ref(),
+        market,
+        config.market_denom.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?;
+    if borrow_limit < loan_amount {
         return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
     }
 
-    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
-
+    let mut recent_unlocks: Vec<RecentUnlock> = read_recent_unlocks(deps.storage, &borrower_raw);
+    let mut unlocked_collaterals: Tokens = vec![];
     let mut messages: Vec<SubMsg> = vec![];
-    for collateral in collaterals.clone() {
+    for collateral in collaterals {
         let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        let unlock_delay = whitelist_elem.unlock_delay.unwrap_or_default();
+        let unlock_delay_threshold = whitelist_elem.unlock_delay_threshold.unwrap_or_default();
+        recent_unlocks.retain(|recent_unlock| {
+            recent_unlock.collateral_token != collateral.0
+                || recent_unlock.height + unlock_delay > env.block.height
+        });
+
+        if unlock_delay > 0 {
+            // Splitting an unlock into several calls must not skip the delay
+            let unlocked_amount = pending_unlocks
+                .iter()
+                .filter(|pending_unlock| pending_unlock.collateral_token == collateral.0)
+                .map(|pending_unlock| pending_unlock.amount)
+                .chain(
+                    recent_unlocks
+                        .iter()
+                        .filter(|recent_unlock| recent_unlock.collateral_token == collateral.0)
+                        .map(|recent_unlock| recent_unlock.amount),
+                )
+                .fold(collateral.1, |sum, amount| sum + amount);
+            if unlocked_amount > unlock_delay_threshold {
+                pending_unlocks.push(PendingUnlock {
+                    collateral_token: collateral.0,
+                    amount: collateral.1,
+                    release_height: env.block.height + unlock_delay,
+                });
+                continue;
+            }
+
+            recent_unlocks.push(RecentUnlock {
+                collateral_token: collateral.0.clone(),
+                amount: collateral.1,
+                height: env.block.height,
+            });
+        }
+
         messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: deps
                 .api
@@ -108,8 +215,15 @@ pub fn unlock_collateral(
                 amount: collateral.1,
             })?,
         })));
+        unlocked_collaterals.push(collateral);
     }
 
+    cur_collaterals.sub(unlocked_collaterals.clone())?;
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    store_pending_unlocks(deps.storage, &borrower_raw, &pending_unlocks)?;
+    store_recent_unlocks(deps.storage, &borrower_raw, &recent_unlocks)?;
+    decrease_total_collaterals(deps.storage, &unlocked_collaterals)?;
+
     // Logging stuff, so can be removed
     let collateral_logs: Vec<String> = collaterals_human
         .iter()
@@ -125,54 +239,247 @@ pub fn unlock_collateral(
         ]))
 }
 
-pub fn liquidate_collateral(
+/// Release the pending unlocks whose delay has passed; liquidations
+/// may have taken part of them meanwhile, so each one is capped to
+/// the amount still locked
+pub fn claim_unlocked(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    borrower: Addr,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let market = deps.api.addr_humanize(&config.market_contract)?;
 
+    let borrower = info.sender;
     let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
     let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+    let (claimable_unlocks, pending_unlocks): (Vec<PendingUnlock>, Vec<PendingUnlock>) =
+        read_pending_unlocks(deps.storage, &borrower_raw)
+            .into_iter()
+            .partition(|pending_unlock| pending_unlock.release_height <= env.block.height);
+    if claimable_unlocks.is_empty() {
+        return Err(ContractError::NoClaimableUnlock {});
+    }
 
-    // Compute borrow limit with collaterals except unlock target collaterals
-    let (borrow_limit, collateral_prices) = compute_borrow_limit(
+    let mut claimed_collaterals: Tokens = vec![];
+    for pending_unlock in claimable_unlocks {
+        let mut left_collaterals: Tokens = cur_collaterals.clone();
+        left_collaterals.sub(claimed_collaterals.clone())?;
+        let locked_amount = left_collaterals
+            .iter()
+            .find(|c| c.0 == pending_unlock.collateral_token)
+            .map(|c| c.1)
+            .unwrap_or_else(Uint256::zero);
+
+        let amount = if pending_unlock.amount > locked_amount {
+            locked_amount
+        } else {
+            pending_unlock.amount
+        };
+        if !amount.is_zero() {
+            claimed_collaterals.add(vec![(pending_unlock.collateral_token, amount)]);
+        }
+    }
+
+    cur_collaterals.sub(claimed_collaterals.clone())?;
+
+    // Compute borrow limit with collaterals except claimed collaterals
+    let (borrow_limit, _) = compute_borrow_limit(
         deps.as_ref(),
         &cur_collaterals,
         Some(env.block.time.seconds()),
     )?;
-    let borrow_amount_res: BorrowerInfoResponse =
-        query_borrower_info(deps.as_ref(), market, borrower.clone(), env.block.height)?;
-    let borrow_amount = borrow_amount_res.loan_amount;
+    let (_, escrow_value) = compute_escrow(deps.as_ref(), &config, &borrower)?;
+    let borrow_limit = borrow_limit + escrow_value * config.escrow_max_ltv;
+    let loan_amount = query_loan_amount(
+        deps.as_ref(),
+        market,
+        config.market_denom.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?;
+    if borrow_limit < loan_amount {
+        return Err(ContractError::UnlockTooLarge(borrow_limit.into()));
+    }
+
+    store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    store_pending_unlocks(deps.storage, &borrower_raw, &pending_unlocks)?;
+    decrease_total_collaterals(deps.storage, &claimed_collaterals)?;
+
+    let mut messages: Vec<SubMsg> = vec![];
+    for collateral in claimed_collaterals.iter() {
+        let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&whitelist_elem.custody_contract)?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: borrower.to_string(),
+                amount: collateral.1,
+            })?,
+        })));
+    }
+
+    // Logging stuff, so can be removed
+    let collateral_logs: Vec<String> = claimed_collaterals
+        .to_human(deps.as_ref())?
+        .iter()
+        .map(|c| format!("{}{}", c.1, c.0))
+        .collect();
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "claim_unlocked"),
+            attr("borrower", borrower),
+            attr("collaterals", collateral_logs.join(",")),
+        ]))
+}
+
+pub fn liquidate_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    borrower: Addr,
+) -> Result<Response, ContractError> {
+    liquidate(deps, env, info.sender, borrower, None)
+}
+
+pub fn liquidate_collateral_for(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    liquidator: Addr,
+    borrower: Addr,
+    max_repay: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.market_contract {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    liquidate(deps, env, liquidator, borrower, max_repay)
+}
+
+fn liquidate(
+    deps: DepsMut,
+    env: Env,
+    liquidator: Addr,
+    borrower: Addr,
+    max_repay: Option<Uint256>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let market = deps.api.addr_humanize(&config.market_contract)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let mut cur_collaterals: Tokens = read_collaterals(deps.storage, &borrower_raw);
+
+    // Compute borrow limit with collaterals except unlock target collaterals;
+    // stale prices are accepted only when configured, so liquidations
+    // can go on during feeder outages
+    let block_time = if config.liquidate_on_stale_price {
+        None
+    } else {
+        Some(env.block.time.seconds())
+    };
+    let (borrow_limit, collateral_prices) =
+        compute_borrow_limit(deps.as_ref(), &cur_collaterals, block_time)?;
+    let (escrow_amount, escrow_value) = compute_escrow(deps.as_ref(), &config, &borrower)?;
+    let escrow_limit = escrow_value * config.escrow_max_ltv;
+    let borrow_amount = query_loan_amount(
+        deps.as_ref(),
+        market.clone(),
+        config.market_denom.clone(),
+        borrower.clone(),
+        env.block.height,
+    )?;
 
     // borrow limit is equal or bigger than loan amount
     // cannot liquidation collaterals
-    if borrow_limit >= borrow_amount {
+    if borrow_limit + escrow_limit >= borrow_amount {
         return Err(ContractError::CannotLiquidateSafeLoan {});
     }
 
+    // Escrowed aterra settles the loan before any collateral is sold;
+    // collaterals are only liquidated for what the escrow cannot cover
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut left_borrow_amount = borrow_amount;
+    if !escrow_amount.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: market.to_string(),
+            funds: vec![],
+            msg: to_binary(
+                &MarketExecuteMsg::RepayStableFromEscrow {
+                    borrower: borrower.to_string(),
+                }
+                .for_market(config.market_denom.clone()),
+            )?,
+        }));
+
+        left_borrow_amount = if borrow_amount > escrow_value {
+            borrow_amount - escrow_value
+        } else {
+            Uint256::zero()
+        };
+        if borrow_limit >= left_borrow_amount {
+            return Ok(Response::new().add_messages(messages).add_attributes(vec![
+                attr("action", "liquidate_escrow"),
+                attr("liquidator", liquidator),
+                attr("borrower", borrower),
+                attr("loan_amount", borrow_amount),
+                attr("borrow_limit", borrow_limit + escrow_limit),
+                attr("escrow_amount", escrow_amount),
+            ]));
+        }
+    }
+
+    let cur_collaterals_human = cur_collaterals.to_human(deps.as_ref())?;
     let liquidation_amount_res: LiquidationAmountResponse = query_liquidation_amount(
         deps.as_ref(),
         deps.api.addr_humanize(&config.liquidation_contract)?,
-        borrow_amount,
+        left_borrow_amount,
         borrow_limit,
-        &cur_collaterals.to_human(deps.as_ref())?,
-        collateral_prices,
+        &cur_collaterals_human,
+        collateral_prices.clone(),
     )?;
 
-    let liquidation_amount = liquidation_amount_res.collaterals.to_raw(deps.as_ref())?;
+    let mut liquidation_collaterals = liquidation_amount_res.collaterals;
+    if let Some(max_repay) = max_repay {
+        liquidation_collaterals = cap_liquidation_value(
+            liquidation_collaterals,
+            &cur_collaterals_human,
+            &collateral_prices,
+            max_repay,
+        );
+    }
+
+    let liquidation_amount = liquidation_collaterals.to_raw(deps.as_ref())?;
+    let collateral_logs: Vec<String> = liquidation_collaterals
+        .iter()
+        .map(|c| format!("{}{}", c.1, c.0))
+        .collect();
 
     // Store left collaterals
     cur_collaterals.sub(liquidation_amount.clone())?;
     store_collaterals(deps.storage, &borrower_raw, &cur_collaterals)?;
+    decrease_total_collaterals(deps.storage, &liquidation_amount)?;
 
     let market_contract = deps.api.addr_humanize(&config.market_contract)?;
-    let prev_balance: Uint256 =
-        query_balance(deps.as_ref(), market_contract.clone(), config.stable_denom)?;
+    let prev_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        market_contract.clone(),
+        config.stable_denom.clone(),
+    )?;
+    // Bid fees are paid to the overseer as plain transfers
+    let prev_fee_balance: Uint256 = query_balance(
+        deps.as_ref(),
+        env.contract.address.clone(),
+        config.stable_denom,
+    )?;
 
-    let liquidation_messages: Vec<CosmosMsg> = liquidation_amount
+    let mut liquidation_messages: Vec<CosmosMsg> = liquidation_amount
         .iter()
         .map(|collateral| {
             let whitelist_elem: WhitelistElem = read_whitelist_elem(deps.storage, &collateral.0)?;
@@ -184,39 +491,160 @@ pub fn liquidate_collateral(
                     .to_string(),
                 funds: vec![],
                 msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: info.sender.to_string(),
+                    liquidator: liquidator.to_string(),
                     borrower: borrower.to_string(),
                     amount: collateral.1,
                 })?,
             }))
         })
-        .filter(|msg| msg.is_ok())
         .collect::<StdResult<Vec<CosmosMsg>>>()?;
+    messages.append(&mut liquidation_messages);
+
+    let mut attributes = vec![
+        attr("action", "liquidate_collateral"),
+        attr("liquidator", liquidator),
+        attr("borrower", borrower.clone()),
+        attr("loan_amount", borrow_amount),
+        attr("borrow_limit", borrow_limit),
+        attr("collaterals", collateral_logs.join(",")),
+    ];
+    if !escrow_amount.is_zero() {
+        attributes.push(attr("escrow_amount", escrow_amount));
+    }
 
     Ok(Response::new()
-        .add_messages(liquidation_messages)
+        .add_messages(messages)
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: market_contract.to_string(),
             funds: vec![],
-            msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
-                borrower: borrower.to_string(),
-                prev_balance,
+            msg: to_binary(
+                &MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: borrower.to_string(),
+                    prev_balance,
+                }
+                .for_market(config.market_denom),
+            )?,
+        }))
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::FundReserveFromLiquidation {
+                prev_balance: prev_fee_balance,
             })?,
-        })))
+        }))
+        .add_attributes(attributes))
+}
+
+/// Collaterals locked before their totals were tracked are not counted,
+/// so the totals saturate at zero instead of underflowing
+#[allow(clippy::ptr_arg)]
+fn decrease_total_collaterals(storage: &mut dyn Storage, collaterals: &Tokens) -> StdResult<()> {
+    for collateral in collaterals.iter() {
+        let total_collateral = read_total_collateral(storage, &collateral.0);
+        let total_collateral = if total_collateral > collateral.1 {
+            total_collateral - collateral.1
+        } else {
+            Uint256::zero()
+        };
+        store_total_collateral(storage, &collateral.0, &total_collateral)?;
+    }
+
+    Ok(())
+}
+
+/// Stable value of the collaterals locked by all borrowers
+fn compute_total_collateral_value(deps: Deps, block_time: u64) -> StdResult<Uint256> {
+    let config: Config = read_config(deps.storage)?;
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+
+    let mut total_value: Uint256 = Uint256::zero();
+    for collateral in read_total_collaterals(deps.storage)?.iter() {
+        let price: PriceResponse = query_price(
+            deps,
+            oracle_contract.clone(),
+            (deps.api.addr_humanize(&collateral.0)?).to_string(),
+            config.stable_denom.to_string(),
+            Some(TimeConstraints {
+                block_time,
+                valid_timeframe: config.price_timeframe,
+            }),
+        )?;
+        total_value += collateral.1 * price.rate;
+    }
+
+    Ok(total_value)
+}
+
+/// Scale down the collaterals to liquidate so their value at
+/// oracle prices does not exceed `max_repay`
+fn cap_liquidation_value(
+    liquidation_collaterals: TokensHuman,
+    collaterals: &TokensHuman,
+    collateral_prices: &[Decimal256],
+    max_repay: Uint256,
+) -> TokensHuman {
+    let price_of = |token: &String| {
+        collaterals
+            .iter()
+            .zip(collateral_prices.iter())
+            .find(|(c, _)| c.0 == *token)
+            .map(|(_, price)| *price)
+            .unwrap_or_else(Decimal256::zero)
+    };
+
+    let liquidation_value: Uint256 = liquidation_collaterals
+        .iter()
+        .map(|c| c.1 * price_of(&c.0))
+        .fold(Uint256::zero(), |sum, value| sum + value);
+    if liquidation_value <= max_repay {
+        return liquidation_collaterals;
+    }
+
+    let ratio = Decimal256::from_ratio(max_repay.0, liquidation_value.0);
+    liquidation_collaterals
+        .into_iter()
+        .map(|(token, amount)| (token, amount * ratio))
+        .filter(|c| !c.1.is_zero())
+        .collect()
 }
 
 pub fn query_collaterals(deps: Deps, borrower: Addr) -> StdResult<CollateralsResponse> {
+    let config: Config = read_config(deps.storage)?;
     let collaterals: Tokens = read_collaterals(
         deps.storage,
         &deps.api.addr_canonicalize(borrower.as_str())?,
     );
 
-    Ok(CollateralsResponse {
+    collaterals_response(
+        deps,
+        &config,
+        borrower.to_string(),
+        collaterals.to_human(deps)?,
+        &mut BTreeMap::new(),
+    )
+}
+
+pub fn query_pending_unlocks(deps: Deps, borrower: Addr) -> StdResult<PendingUnlocksResponse> {
+    let pending_unlocks: Vec<PendingUnlockResponse> = read_pending_unlocks(
+        deps.storage,
+        &deps.api.addr_canonicalize(borrower.as_str())?,
+    )
+    .into_iter()
+    .map(|pending_unlock| {
+        Ok(PendingUnlockResponse {
+            collateral_token: deps
+                .api
+                .addr_humanize(&pending_unlock.collateral_token)?
+                .to_string(),
+            amount: pending_unlock.amount,
+            release_height: pending_unlock.release_height,
+        })
+    })
+    .collect::<StdResult<Vec<PendingUnlockResponse>>>()?;
+
+    Ok(PendingUnlocksResponse {
         borrower: borrower.to_string(),
-        collaterals: collaterals
-            .iter()
-            .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
-            .collect::<StdResult<Vec<(String, Uint256)>>>()?,
+        pending_unlocks,
     })
 }
 
@@ -231,11 +659,60 @@ pub fn query_all_collaterals(
         None
     };
 
-    let all_collaterals: Vec<CollateralsResponse> = read_all_collaterals(deps, start_after, limit)?;
+    // Collaterals shared by several borrowers are priced once
+    let config: Config = read_config(deps.storage)?;
+    let mut prices: BTreeMap<String, Decimal256> = BTreeMap::new();
+    let all_collaterals: Vec<CollateralsResponse> = read_all_collaterals(deps, start_after, limit)?
+        .into_iter()
+        .map(|(borrower, collaterals)| {
+            collaterals_response(deps, &config, borrower, collaterals, &mut prices)
+        })
+        .collect::<StdResult<Vec<CollateralsResponse>>>()?;
 
     Ok(AllCollateralsResponse { all_collaterals })
 }
 
+/// Value locked collaterals at their current oracle prices,
+/// without enforcing the price timeframe
+fn collaterals_response(
+    deps: Deps,
+    config: &Config,
+    borrower: String,
+    collaterals: TokensHuman,
+    prices: &mut BTreeMap<String, Decimal256>,
+) -> StdResult<CollateralsResponse> {
+    let oracle_contract = deps.api.addr_humanize(&config.oracle_contract)?;
+
+    let mut collateral_prices: Vec<Decimal256> = vec![];
+    let mut collateral_values: Vec<Uint256> = vec![];
+    for collateral in collaterals.iter() {
+        let price = match prices.get(&collateral.0) {
+            Some(price) => *price,
+            None => {
+                let price: PriceResponse = query_price(
+                    deps,
+                    oracle_contract.clone(),
+                    collateral.0.to_string(),
+                    config.stable_denom.to_string(),
+                    None,
+                )?;
+                prices.insert(collateral.0.to_string(), price.rate);
+                price.rate
+            }
+        };
+
+        collateral_prices.push(price);
+        collateral_values.push(collateral.1 * price);
+    }
+
+    Ok(CollateralsResponse {
+        borrower,
+        collaterals,
+        collateral_prices,
+        collateral_values,
+    })
+}
+
 #[allow(clippy::ptr_arg)]
 pub(crate) fn compute_borrow_limit(
     deps: Deps,
@@ -272,11 +749,32 @@ pub(crate) fn compute_borrow_limit(
     Ok((borrow_limit, collateral_prices))
 }
 
+/// Aterra `borrower` keeps in escrow in the market, along with its
+/// stable value; escrows are not queried while their LTV is zero
+fn compute_escrow(deps: Deps, config: &Config, borrower: &Addr) -> StdResult<(Uint256, Uint256)> {
+    if config.escrow_max_ltv.is_zero() {
+        return Ok((Uint256::zero(), Uint256::zero()));
+    }
+
+    let escrow_res = query_escrow(
+        deps,
+        deps.api.addr_humanize(&config.market_contract)?,
+        config.market_denom.clone(),
+        borrower.clone(),
+    )?;
+
+    Ok((escrow_res.escrow_amount, escrow_res.escrow_value))
+}
+
+/// The market passes the `escrow_value` of the borrower along,
+/// so its borrow checks do not have to be queried back
 pub fn query_borrow_limit(
     deps: Deps,
     borrower: Addr,
     block_time: Option<u64>,
+    escrow_value: Option<Uint256>,
 ) -> StdResult<BorrowLimitResponse> {
+    let config: Config = read_config(deps.storage)?;
     let collaterals = read_collaterals(
         deps.storage,
         &deps.api.addr_canonicalize(borrower.as_str())?,
@@ -284,9 +782,13 @@ pub fn query_borrow_limit(
 
     // Compute borrow limit with collaterals
     let (borrow_limit, _) = compute_borrow_limit(deps, &collaterals, block_time)?;
+    let escrow_value = match escrow_value {
+        Some(escrow_value) => escrow_value,
+        None => compute_escrow(deps, &config, &borrower)?.1,
+    };
 
     Ok(BorrowLimitResponse {
         borrower: borrower.to_string(),
-        borrow_limit,
+        borrow_limit: borrow_limit + escrow_value * config.escrow_max_ltv,
     })
 }
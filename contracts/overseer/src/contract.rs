@@ -6,14 +6,16 @@ use cosmwasm_std::{
 };
 
 use crate::collateral::{
-    liquidate_collateral, lock_collateral, query_all_collaterals, query_borrow_limit,
-    query_collaterals, unlock_collateral,
+    claim_unlocked, liquidate_collateral, liquidate_collateral_for, lock_collateral,
+    lock_collateral_for, query_all_collaterals, query_borrow_limit, query_collaterals,
+    query_pending_unlocks, unlock_collateral,
 };
 use crate::error::ContractError;
-use crate::querier::query_epoch_state;
 use crate::state::{
-    read_config, read_epoch_state, read_whitelist, read_whitelist_elem, store_config,
-    store_epoch_state, store_whitelist_elem, Config, EpochState, WhitelistElem,
+    read_buffer_history, read_buffer_state, read_config, read_epoch_state, read_total_collateral,
+    read_whitelist, read_whitelist_elem, store_buffer_history_elem, store_buffer_state,
+    store_config, store_epoch_state, store_whitelist_elem, BufferState, Config, EpochState,
+    WhitelistElem,
 };
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -22,9 +24,10 @@ use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::EpochStateResponse;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    BufferHistoryElem, BufferStateResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+    WhitelistResponse, WhitelistResponseElem,
 };
-use moneymarket::querier::{deduct_tax, query_balance};
+use moneymarket::querier::{query_balance, query_epoch_state, TaxCache};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -39,6 +42,7 @@ pub fn instantiate(
             owner_addr: deps.api.addr_canonicalize(&msg.owner_addr)?,
             oracle_contract: deps.api.addr_canonicalize(&msg.oracle_contract)?,
             market_contract: deps.api.addr_canonicalize(&msg.market_contract)?,
+            market_denom: msg.market_denom,
             liquidation_contract: deps.api.addr_canonicalize(&msg.liquidation_contract)?,
             collector_contract: deps.api.addr_canonicalize(&msg.collector_contract)?,
             stable_denom: msg.stable_denom,
@@ -48,6 +52,10 @@ pub fn instantiate(
             buffer_distribution_factor: msg.buffer_distribution_factor,
             anc_purchase_factor: msg.anc_purchase_factor,
             price_timeframe: msg.price_timeframe,
+            max_total_collateral_value: None,
+            keeper_reward: Uint256::zero(),
+            liquidate_on_stale_price: false,
+            escrow_max_ltv: Decimal256::zero(),
         },
     )?;
 
@@ -56,12 +64,13 @@ pub fn instantiate(
         &EpochState {
             deposit_rate: Decimal256::zero(),
             prev_aterra_supply: Uint256::zero(),
-            prev_interest_buffer: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
             last_executed_height: env.block.height,
         },
     )?;
 
+    store_buffer_state(deps.storage, &BufferState::default())?;
+
     Ok(Response::default())
 }
 
@@ -83,6 +92,10 @@ pub fn execute(
             anc_purchase_factor,
             epoch_period,
             price_timeframe,
+            max_total_collateral_value,
+            keeper_reward,
+            liquidate_on_stale_price,
+            escrow_max_ltv,
         } => {
             let api = deps.api;
             update_config(
@@ -97,6 +110,10 @@ pub fn execute(
                 anc_purchase_factor,
                 epoch_period,
                 price_timeframe,
+                max_total_collateral_value,
+                keeper_reward,
+                liquidate_on_stale_price,
+                escrow_max_ltv,
             )
         }
         ExecuteMsg::Whitelist {
@@ -105,6 +122,9 @@ pub fn execute(
             collateral_token,
             custody_contract,
             max_ltv,
+            max_collateral,
+            unlock_delay,
+            unlock_delay_threshold,
         } => {
             let api = deps.api;
             register_whitelist(
@@ -115,12 +135,18 @@ pub fn execute(
                 api.addr_validate(&collateral_token)?,
                 api.addr_validate(&custody_contract)?,
                 max_ltv,
+                max_collateral,
+                unlock_delay,
+                unlock_delay_threshold,
             )
         }
         ExecuteMsg::UpdateWhitelist {
             collateral_token,
             custody_contract,
             max_ltv,
+            max_collateral,
+            unlock_delay,
+            unlock_delay_threshold,
         } => {
             let api = deps.api;
             update_whitelist(
@@ -129,21 +155,53 @@ pub fn execute(
                 api.addr_validate(&collateral_token)?,
                 optional_addr_validate(api, custody_contract)?,
                 max_ltv,
+                max_collateral,
+                unlock_delay,
+                unlock_delay_threshold,
             )
         }
-        ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env),
+        ExecuteMsg::ExecuteEpochOperations {} => execute_epoch_operations(deps, env, info),
         ExecuteMsg::UpdateEpochState {
             interest_buffer,
             distributed_interest,
         } => update_epoch_state(deps, env, info, interest_buffer, distributed_interest),
-        ExecuteMsg::LockCollateral { collaterals } => lock_collateral(deps, info, collaterals),
+        ExecuteMsg::LockCollateralFor {
+            borrower,
+            collaterals,
+        } => {
+            let api = deps.api;
+            lock_collateral_for(deps, env, info, api.addr_validate(&borrower)?, collaterals)
+        }
+        ExecuteMsg::LiquidateCollateralFor {
+            liquidator,
+            borrower,
+            max_repay,
+        } => {
+            let api = deps.api;
+            liquidate_collateral_for(
+                deps,
+                env,
+                info,
+                api.addr_validate(&liquidator)?,
+                api.addr_validate(&borrower)?,
+                max_repay,
+            )
+        }
+        ExecuteMsg::LockCollateral { collaterals } => {
+            lock_collateral(deps, env, info.sender, collaterals)
+        }
         ExecuteMsg::UnlockCollateral { collaterals } => {
             unlock_collateral(deps, env, info, collaterals)
         }
+        ExecuteMsg::ClaimUnlocked {} => claim_unlocked(deps, env, info),
         ExecuteMsg::LiquidateCollateral { borrower } => {
             let api = deps.api;
             liquidate_collateral(deps, env, info, api.addr_validate(&borrower)?)
         }
+        ExecuteMsg::FundReserve {} => fund_reserve(deps, info),
+        ExecuteMsg::FundReserveFromLiquidation { prev_balance } => {
+            fund_reserve_from_liquidation(deps, env, info, prev_balance)
+        }
     }
 }
 
@@ -160,6 +218,10 @@ pub fn update_config(
     anc_purchase_factor: Option<Decimal256>,
     epoch_period: Option<u64>,
     price_timeframe: Option<u64>,
+    max_total_collateral_value: Option<Uint256>,
+    keeper_reward: Option<Uint256>,
+    liquidate_on_stale_price: Option<bool>,
+    escrow_max_ltv: Option<Decimal256>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -205,11 +267,32 @@ pub fn update_config(
         config.price_timeframe = price_timeframe;
     }
 
+    if let Some(max_total_collateral_value) = max_total_collateral_value {
+        config.max_total_collateral_value = if max_total_collateral_value.is_zero() {
+            None
+        } else {
+            Some(max_total_collateral_value)
+        };
+    }
+
+    if let Some(keeper_reward) = keeper_reward {
+        config.keeper_reward = keeper_reward;
+    }
+
+    if let Some(liquidate_on_stale_price) = liquidate_on_stale_price {
+        config.liquidate_on_stale_price = liquidate_on_stale_price;
+    }
+
+    if let Some(escrow_max_ltv) = escrow_max_ltv {
+        config.escrow_max_ltv = escrow_max_ltv;
+    }
+
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn register_whitelist(
     deps: DepsMut,
     info: MessageInfo,
@@ -218,12 +301,17 @@ pub fn register_whitelist(
     collateral_token: Addr,
     custody_contract: Addr,
     max_ltv: Decimal256,
+    max_collateral: Option<Uint256>,
+    unlock_delay: Option<u64>,
+    unlock_delay_threshold: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
         return Err(ContractError::Unauthorized {});
     }
 
+    assert_max_ltv(max_ltv)?;
+
     let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
     if read_whitelist_elem(deps.storage, &collateral_token_raw).is_ok() {
         return Err(ContractError::TokenAlreadyRegistered {});
@@ -237,6 +325,9 @@ pub fn register_whitelist(
             symbol: symbol.to_string(),
             custody_contract: deps.api.addr_canonicalize(custody_contract.as_str())?,
             max_ltv,
+            max_collateral,
+            unlock_delay,
+            unlock_delay_threshold,
         },
     )?;
 
@@ -250,12 +341,16 @@ pub fn register_whitelist(
     ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_whitelist(
     deps: DepsMut,
     info: MessageInfo,
     collateral_token: Addr,
     custody_contract: Option<Addr>,
     max_ltv: Option<Decimal256>,
+    max_collateral: Option<Uint256>,
+    unlock_delay: Option<u64>,
+    unlock_delay_threshold: Option<Uint256>,
 ) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner_addr {
@@ -271,9 +366,34 @@ pub fn update_whitelist(
     }
 
     if let Some(max_ltv) = max_ltv {
+        assert_max_ltv(max_ltv)?;
         whitelist_elem.max_ltv = max_ltv;
     }
 
+    if let Some(max_collateral) = max_collateral {
+        whitelist_elem.max_collateral = if max_collateral.is_zero() {
+            None
+        } else {
+            Some(max_collateral)
+        };
+    }
+
+    if let Some(unlock_delay) = unlock_delay {
+        whitelist_elem.unlock_delay = if unlock_delay == 0 {
+            None
+        } else {
+            Some(unlock_delay)
+        };
+    }
+
+    if let Some(unlock_delay_threshold) = unlock_delay_threshold {
+        whitelist_elem.unlock_delay_threshold = if unlock_delay_threshold.is_zero() {
+            None
+        } else {
+            Some(unlock_delay_threshold)
+        };
+    }
+
     store_whitelist_elem(deps.storage, &collateral_token_raw, &whitelist_elem)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -287,7 +407,20 @@ pub fn update_whitelist(
     ]))
 }
 
-pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+/// A collateral must always be worth more than what can be borrowed against it
+fn assert_max_ltv(max_ltv: Decimal256) -> Result<(), ContractError> {
+    if max_ltv >= Decimal256::one() {
+        return Err(ContractError::InvalidMaxLtv {});
+    }
+
+    Ok(())
+}
+
+pub fn execute_epoch_operations(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let config: Config = read_config(deps.storage)?;
     let state: EpochState = read_epoch_state(deps.storage)?;
     if env.block.height < state.last_executed_height + config.epoch_period {
@@ -302,25 +435,26 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
     let epoch_state: EpochStateResponse = query_epoch_state(
         deps.as_ref(),
         market_contract.clone(),
+        config.market_denom.clone(),
         env.block.height,
         None,
     )?;
 
-    // effective_deposit_rate = cur_exchange_rate / prev_exchange_rate
-    // deposit_rate = (effective_deposit_rate - 1) / blocks
-    let effective_deposit_rate = epoch_state.exchange_rate / state.prev_exchange_rate;
     let deposit_rate =
-        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks);
+        compute_deposit_rate(epoch_state.exchange_rate, state.prev_exchange_rate, blocks);
+
+    // The buffer is tracked explicitly, so that coins sent to the
+    // contract without FundReserve do not change the payouts
+    let buffer_state: BufferState = read_buffer_state(deps.storage)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
-    let mut interest_buffer = query_balance(
-        deps.as_ref(),
-        env.contract.address.clone(),
-        config.stable_denom.to_string(),
-    )?;
+    let mut interest_buffer = buffer_state.interest_buffer;
+
+    // Tax parameters are shared by all the payouts below
+    let mut tax_cache = TaxCache::default();
 
     // Send accrued_buffer * config.anc_purchase_factor amount stable token to collector
-    let accrued_buffer = interest_buffer - state.prev_interest_buffer;
+    let accrued_buffer = buffer_state.accrued_buffer;
     let anc_purchase_amount = accrued_buffer * config.anc_purchase_factor;
     if !anc_purchase_amount.is_zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
@@ -328,7 +462,7 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
                 .api
                 .addr_humanize(&config.collector_contract)?
                 .to_string(),
-            amount: vec![deduct_tax(
+            amount: vec![tax_cache.deduct_tax(
                 deps.as_ref(),
                 Coin {
                     denom: config.stable_denom.to_string(),
@@ -341,9 +475,27 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
     // Deduct anc_purchase_amount from the interest_buffer
     interest_buffer = interest_buffer - anc_purchase_amount;
 
+    // Reward the caller for running the epoch operations,
+    // at most the whole interest buffer
+    let keeper_reward = std::cmp::min(config.keeper_reward, interest_buffer);
+    if !keeper_reward.is_zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![tax_cache.deduct_tax(
+                deps.as_ref(),
+                Coin {
+                    denom: config.stable_denom.to_string(),
+                    amount: keeper_reward.into(),
+                },
+            )?],
+        }));
+    }
+    interest_buffer = interest_buffer - keeper_reward;
+
     // Distribute Interest Buffer to depositor
     // Only executed when deposit rate < threshold_deposit_rate
     let mut distributed_interest: Uint256 = Uint256::zero();
+    let mut buffer_distribution: Uint256 = Uint256::zero();
     if deposit_rate < config.threshold_deposit_rate {
         // missing_deposit_rate(_per_block)
         let missing_deposit_rate = config.threshold_deposit_rate - deposit_rate;
@@ -357,18 +509,20 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
         // distribute interest to market contract
         distributed_interest = std::cmp::min(missing_deposits, distribution_buffer);
         interest_buffer = interest_buffer - distributed_interest;
+        buffer_distribution = distributed_interest;
 
         if !distributed_interest.is_zero() {
             // deduct tax
             distributed_interest = Uint256::from(
-                deduct_tax(
-                    deps.as_ref(),
-                    Coin {
-                        denom: config.stable_denom.to_string(),
-                        amount: distributed_interest.into(),
-                    },
-                )?
-                .amount,
+                tax_cache
+                    .deduct_tax(
+                        deps.as_ref(),
+                        Coin {
+                            denom: config.stable_denom.to_string(),
+                            amount: distributed_interest.into(),
+                        },
+                    )?
+                    .amount,
             );
 
             // Send some portion of interest buffer to Market contract
@@ -382,6 +536,27 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
         }
     }
 
+    // Rewards sent by the custody contracts below
+    // accrue to the next epoch
+    store_buffer_state(
+        deps.storage,
+        &BufferState {
+            interest_buffer,
+            accrued_buffer: Uint256::zero(),
+        },
+    )?;
+    store_buffer_history_elem(
+        deps.storage,
+        &BufferHistoryElem {
+            height: env.block.height,
+            accrued_buffer,
+            anc_purchase_amount,
+            keeper_reward,
+            distributed_interest: buffer_distribution,
+            interest_buffer,
+        },
+    )?;
+
     // Execute DistributeRewards
     let whitelist: Vec<WhitelistResponseElem> = read_whitelist(deps.as_ref(), None, None)?;
     for elem in whitelist.iter() {
@@ -410,15 +585,95 @@ pub fn execute_epoch_operations(deps: DepsMut, env: Env) -> Result<Response, Con
         attr("aterra_supply", epoch_state.aterra_supply),
         attr("distributed_interest", distributed_interest),
         attr("anc_purchase_amount", anc_purchase_amount),
+        attr("keeper", info.sender),
+        attr("keeper_reward", keeper_reward),
+    ]))
+}
+
+/// Deposit the sent stable coins to the interest buffer
+/// Executor: anyone, e.g. custody contracts sending rewards
+pub fn fund_reserve(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let amount: Uint256 = info
+        .funds
+        .iter()
+        .find(|c| c.denom == config.stable_denom)
+        .map(|c| Uint256::from(c.amount))
+        .unwrap_or_else(Uint256::zero);
+    if amount.is_zero() {
+        return Err(ContractError::ZeroReserveFund(config.stable_denom));
+    }
+
+    let mut buffer_state: BufferState = read_buffer_state(deps.storage)?;
+    buffer_state.interest_buffer += amount;
+    buffer_state.accrued_buffer += amount;
+    store_buffer_state(deps.storage, &buffer_state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "fund_reserve"),
+        attr("funder", info.sender),
+        attr("funded_amount", amount),
     ]))
 }
 
+/// Deposit the bid fees the liquidation contracts sent to the overseer
+/// during a liquidation; the fees arrive as plain transfers, so they are
+/// measured against the balance queried before the liquidation
+pub fn fund_reserve_from_liquidation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prev_balance: Uint256,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let cur_balance: Uint256 =
+        query_balance(deps.as_ref(), env.contract.address, config.stable_denom)?;
+    let amount = if cur_balance > prev_balance {
+        cur_balance - prev_balance
+    } else {
+        Uint256::zero()
+    };
+
+    if !amount.is_zero() {
+        let mut buffer_state: BufferState = read_buffer_state(deps.storage)?;
+        buffer_state.interest_buffer += amount;
+        buffer_state.accrued_buffer += amount;
+        store_buffer_state(deps.storage, &buffer_state)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "fund_reserve_from_liquidation"),
+        attr("funded_amount", amount),
+    ]))
+}
+
+/// effective_deposit_rate = cur_exchange_rate / prev_exchange_rate
+/// deposit_rate = (effective_deposit_rate - 1) / blocks
+///
+/// A falling exchange rate, e.g. after socializing a bad debt,
+/// counts as a zero deposit rate
+fn compute_deposit_rate(
+    exchange_rate: Decimal256,
+    prev_exchange_rate: Decimal256,
+    blocks: Uint256,
+) -> Decimal256 {
+    let effective_deposit_rate = exchange_rate / prev_exchange_rate;
+    if effective_deposit_rate > Decimal256::one() {
+        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks)
+    } else {
+        Decimal256::zero()
+    }
+}
+
 pub fn update_epoch_state(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    // To store interest buffer before receiving epoch staking rewards,
-    // pass interest_buffer from execute_epoch_operations
+    // Interest buffer left by execute_epoch_operations, for logging
     interest_buffer: Uint256,
     distributed_interest: Uint256,
 ) -> Result<Response, ContractError> {
@@ -436,16 +691,16 @@ pub fn update_epoch_state(
     let market_epoch_state: EpochStateResponse = query_epoch_state(
         deps.as_ref(),
         market_contract.clone(),
+        config.market_denom.clone(),
         env.block.height,
         Some(distributed_interest),
     )?;
 
-    // effective_deposit_rate = cur_exchange_rate / prev_exchange_rate
-    // deposit_rate = (effective_deposit_rate - 1) / blocks
-    let effective_deposit_rate =
-        market_epoch_state.exchange_rate / overseer_epoch_state.prev_exchange_rate;
-    let deposit_rate =
-        (effective_deposit_rate - Decimal256::one()) / Decimal256::from_uint256(blocks);
+    let deposit_rate = compute_deposit_rate(
+        market_epoch_state.exchange_rate,
+        overseer_epoch_state.prev_exchange_rate,
+        blocks,
+    );
 
     // store updated epoch state
     store_epoch_state(
@@ -454,7 +709,6 @@ pub fn update_epoch_state(
             last_executed_height: env.block.height,
             prev_aterra_supply: market_epoch_state.aterra_supply,
             prev_exchange_rate: market_epoch_state.exchange_rate,
-            prev_interest_buffer: interest_buffer,
             deposit_rate,
         },
     )?;
@@ -463,12 +717,15 @@ pub fn update_epoch_state(
         .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: market_contract.to_string(),
             funds: vec![],
-            msg: to_binary(&MarketExecuteMsg::ExecuteEpochOperations {
-                deposit_rate,
-                target_deposit_rate: config.target_deposit_rate,
-                threshold_deposit_rate: config.threshold_deposit_rate,
-                distributed_interest,
-            })?,
+            msg: to_binary(
+                &MarketExecuteMsg::ExecuteEpochOperations {
+                    deposit_rate,
+                    target_deposit_rate: config.target_deposit_rate,
+                    threshold_deposit_rate: config.threshold_deposit_rate,
+                    distributed_interest,
+                }
+                .for_market(config.market_denom),
+            )?,
         }))
         .add_attributes(vec![
             attr("action", "update_epoch_state"),
@@ -509,10 +766,17 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::BorrowLimit {
             borrower,
             block_time,
+            escrow_value,
         } => to_binary(&query_borrow_limit(
             deps,
             deps.api.addr_validate(&borrower)?,
             block_time,
+            escrow_value,
+        )?),
+        QueryMsg::BufferState { limit } => to_binary(&query_buffer_state(deps, limit)?),
+        QueryMsg::PendingUnlocks { borrower } => to_binary(&query_pending_unlocks(
+            deps,
+            deps.api.addr_validate(&borrower)?,
         )?),
     }
 }
@@ -538,6 +802,11 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         buffer_distribution_factor: config.buffer_distribution_factor,
         anc_purchase_factor: config.anc_purchase_factor,
         price_timeframe: config.price_timeframe,
+        max_total_collateral_value: config.max_total_collateral_value,
+        keeper_reward: config.keeper_reward,
+        liquidate_on_stale_price: config.liquidate_on_stale_price,
+        escrow_max_ltv: config.escrow_max_ltv,
+        market_denom: config.market_denom,
     })
 }
 
@@ -545,6 +814,15 @@ pub fn query_state(deps: Deps) -> StdResult<EpochState> {
     read_epoch_state(deps.storage)
 }
 
+pub fn query_buffer_state(deps: Deps, limit: Option<u32>) -> StdResult<BufferStateResponse> {
+    let buffer_state: BufferState = read_buffer_state(deps.storage)?;
+    Ok(BufferStateResponse {
+        interest_buffer: buffer_state.interest_buffer,
+        accrued_buffer: buffer_state.accrued_buffer,
+        history: read_buffer_history(deps.storage, limit)?,
+    })
+}
+
 pub fn query_whitelist(
     deps: Deps,
     collateral_token: Option<Addr>,
@@ -552,10 +830,9 @@ pub fn query_whitelist(
     limit: Option<u32>,
 ) -> StdResult<WhitelistResponse> {
     if let Some(collateral_token) = collateral_token {
-        let whitelist_elem: WhitelistElem = read_whitelist_elem(
-            deps.storage,
-            &deps.api.addr_canonicalize(collateral_token.as_str())?,
-        )?;
+        let collateral_token_raw = deps.api.addr_canonicalize(collateral_token.as_str())?;
+        let whitelist_elem: WhitelistElem =
+            read_whitelist_elem(deps.storage, &collateral_token_raw)?;
         Ok(WhitelistResponse {
             elems: vec![WhitelistResponseElem {
                 name: whitelist_elem.name,
@@ -566,6 +843,10 @@ pub fn query_whitelist(
                     .addr_humanize(&whitelist_elem.custody_contract)?
                     .to_string(),
                 collateral_token: collateral_token.to_string(),
+                max_collateral: whitelist_elem.max_collateral,
+                unlock_delay: whitelist_elem.unlock_delay,
+                unlock_delay_threshold: whitelist_elem.unlock_delay_threshold,
+                total_collateral: read_total_collateral(deps.storage, &collateral_token_raw),
             }],
         })
     } else {
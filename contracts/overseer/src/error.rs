@@ -12,6 +12,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Reserve fund amount must be greater than 0 {0}")]
+    ZeroReserveFund(String),
+
     #[error("Cannot liquidate safely collateralized loan")]
     CannotLiquidateSafeLoan {},
 
@@ -21,9 +24,21 @@ pub enum ContractError {
     #[error("Token is already registered as collateral")]
     TokenAlreadyRegistered {},
 
+    #[error("Max LTV must be lower than 1")]
+    InvalidMaxLtv {},
+
+    #[error("Collateral cap exceeded; max collateral of {0}: {1}")]
+    CollateralCapExceeded(String, u128),
+
+    #[error("Total collateral value cap exceeded: {0}")]
+    TotalCollateralCapExceeded(u128),
+
     #[error("Unlock amount cannot exceed locked amount")]
     UnlockExceedsLocked {},
 
     #[error("Unlock amount too high; Loan liability becomes greater than borrow limit: {0}")]
     UnlockTooLarge(u128),
+
+    #[error("No pending unlock can be claimed yet")]
+    NoClaimableUnlock {},
 }
@@ -1,7 +1,6 @@
 pub mod collateral;
 pub mod contract;
 pub mod error;
-pub mod querier;
 pub mod state;
 
 #[cfg(test)]
@@ -5,20 +5,28 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{CanonicalAddr, Deps, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{Bucket, ReadonlyBucket, ReadonlySingleton, Singleton};
 
-use moneymarket::overseer::{CollateralsResponse, WhitelistResponseElem};
-use moneymarket::tokens::Tokens;
+use moneymarket::overseer::{BufferHistoryElem, WhitelistResponseElem};
+use moneymarket::tokens::{Tokens, TokensHuman};
 
 const KEY_CONFIG: &[u8] = b"config";
 const KEY_EPOCH_STATE: &[u8] = b"epoch_state";
+const KEY_BUFFER_STATE: &[u8] = b"buffer_state";
 
 const PREFIX_WHITELIST: &[u8] = b"whitelist";
 const PREFIX_COLLATERALS: &[u8] = b"collateral";
+const PREFIX_TOTAL_COLLATERAL: &[u8] = b"total_collateral";
+const PREFIX_BUFFER_HISTORY: &[u8] = b"buffer_history";
+const PREFIX_PENDING_UNLOCKS: &[u8] = b"pending_unlocks";
+const PREFIX_RECENT_UNLOCKS: &[u8] = b"recent_unlocks";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner_addr: CanonicalAddr,
     pub oracle_contract: CanonicalAddr,
     pub market_contract: CanonicalAddr,
+    /// Market of the market contract served by this overseer,
+    /// the primary one when unset
+    pub market_denom: Option<String>,
     pub liquidation_contract: CanonicalAddr,
     pub collector_contract: CanonicalAddr,
     pub stable_denom: String,
@@ -28,6 +36,10 @@ pub struct Config {
     pub buffer_distribution_factor: Decimal256,
     pub anc_purchase_factor: Decimal256,
     pub price_timeframe: u64,
+    pub max_total_collateral_value: Option<Uint256>,
+    pub keeper_reward: Uint256,
+    pub liquidate_on_stale_price: bool,
+    pub escrow_max_ltv: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -35,16 +47,40 @@ pub struct EpochState {
     pub deposit_rate: Decimal256,
     pub prev_aterra_supply: Uint256,
     pub prev_exchange_rate: Decimal256,
-    pub prev_interest_buffer: Uint256,
     pub last_executed_height: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct BufferState {
+    pub interest_buffer: Uint256,
+    pub accrued_buffer: Uint256,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistElem {
     pub name: String,
     pub symbol: String,
     pub max_ltv: Decimal256,
     pub custody_contract: CanonicalAddr,
+    pub max_collateral: Option<Uint256>,
+    pub unlock_delay: Option<u64>,
+    pub unlock_delay_threshold: Option<Uint256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUnlock {
+    pub collateral_token: CanonicalAddr,
+    pub amount: Uint256,
+    pub release_height: u64,
+}
+
+/// Unlock released right away, counted against the unlock
+/// delay threshold until the delay of its collateral has passed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecentUnlock {
+    pub collateral_token: CanonicalAddr,
+    pub amount: Uint256,
+    pub height: u64,
 }
 
 pub fn store_config(storage: &mut dyn Storage, data: &Config) -> StdResult<()> {
@@ -63,6 +99,40 @@ pub fn read_epoch_state(storage: &dyn Storage) -> StdResult<EpochState> {
     ReadonlySingleton::new(storage, KEY_EPOCH_STATE).load()
 }
 
+pub fn store_buffer_state(storage: &mut dyn Storage, data: &BufferState) -> StdResult<()> {
+    Singleton::new(storage, KEY_BUFFER_STATE).save(data)
+}
+
+pub fn read_buffer_state(storage: &dyn Storage) -> StdResult<BufferState> {
+    ReadonlySingleton::new(storage, KEY_BUFFER_STATE).load()
+}
+
+pub fn store_buffer_history_elem(
+    storage: &mut dyn Storage,
+    history_elem: &BufferHistoryElem,
+) -> StdResult<()> {
+    let mut history_bucket: Bucket<BufferHistoryElem> = Bucket::new(storage, PREFIX_BUFFER_HISTORY);
+    history_bucket.save(&history_elem.height.to_be_bytes(), history_elem)
+}
+
+pub fn read_buffer_history(
+    storage: &dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<Vec<BufferHistoryElem>> {
+    let history_bucket: ReadonlyBucket<BufferHistoryElem> =
+        ReadonlyBucket::new(storage, PREFIX_BUFFER_HISTORY);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    history_bucket
+        .range(None, None, Order::Descending)
+        .take(limit)
+        .map(|elem| {
+            let (_, v) = elem?;
+            Ok(v)
+        })
+        .collect()
+}
+
 pub fn store_whitelist_elem(
     storage: &mut dyn Storage,
     collateral_token: &CanonicalAddr,
@@ -104,7 +174,8 @@ pub fn read_whitelist(
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let collateral_token = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
+            let collateral_token_raw = CanonicalAddr::from(k);
+            let collateral_token = deps.api.addr_humanize(&collateral_token_raw)?.to_string();
             let custody_contract = deps.api.addr_humanize(&v.custody_contract)?.to_string();
             Ok(WhitelistResponseElem {
                 name: v.name,
@@ -112,6 +183,10 @@ pub fn read_whitelist(
                 collateral_token,
                 custody_contract,
                 max_ltv: v.max_ltv,
+                max_collateral: v.max_collateral,
+                unlock_delay: v.unlock_delay,
+                unlock_delay_threshold: v.unlock_delay_threshold,
+                total_collateral: read_total_collateral(deps.storage, &collateral_token_raw),
             })
         })
         .collect()
@@ -142,6 +217,91 @@ pub fn read_collaterals(storage: &dyn Storage, borrower: &CanonicalAddr) -> Toke
     }
 }
 
+pub fn store_total_collateral(
+    storage: &mut dyn Storage,
+    collateral_token: &CanonicalAddr,
+    amount: &Uint256,
+) -> StdResult<()> {
+    let mut total_collateral_bucket: Bucket<Uint256> =
+        Bucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    if amount.is_zero() {
+        total_collateral_bucket.remove(collateral_token.as_slice());
+    } else {
+        total_collateral_bucket.save(collateral_token.as_slice(), amount)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_total_collateral(storage: &dyn Storage, collateral_token: &CanonicalAddr) -> Uint256 {
+    let total_collateral_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket
+        .load(collateral_token.as_slice())
+        .unwrap_or_else(|_| Uint256::zero())
+}
+
+pub fn store_pending_unlocks(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    pending_unlocks: &[PendingUnlock],
+) -> StdResult<()> {
+    let mut pending_unlocks_bucket: Bucket<Vec<PendingUnlock>> =
+        Bucket::new(storage, PREFIX_PENDING_UNLOCKS);
+    if pending_unlocks.is_empty() {
+        pending_unlocks_bucket.remove(borrower.as_slice());
+    } else {
+        pending_unlocks_bucket.save(borrower.as_slice(), &pending_unlocks.to_vec())?;
+    }
+
+    Ok(())
+}
+
+pub fn read_pending_unlocks(storage: &dyn Storage, borrower: &CanonicalAddr) -> Vec<PendingUnlock> {
+    let pending_unlocks_bucket: ReadonlyBucket<Vec<PendingUnlock>> =
+        ReadonlyBucket::new(storage, PREFIX_PENDING_UNLOCKS);
+    pending_unlocks_bucket
+        .load(borrower.as_slice())
+        .unwrap_or_default()
+}
+
+pub fn store_recent_unlocks(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    recent_unlocks: &[RecentUnlock],
+) -> StdResult<()> {
+    let mut recent_unlocks_bucket: Bucket<Vec<RecentUnlock>> =
+        Bucket::new(storage, PREFIX_RECENT_UNLOCKS);
+    if recent_unlocks.is_empty() {
+        recent_unlocks_bucket.remove(borrower.as_slice());
+    } else {
+        recent_unlocks_bucket.save(borrower.as_slice(), &recent_unlocks.to_vec())?;
+    }
+
+    Ok(())
+}
+
+pub fn read_recent_unlocks(storage: &dyn Storage, borrower: &CanonicalAddr) -> Vec<RecentUnlock> {
+    let recent_unlocks_bucket: ReadonlyBucket<Vec<RecentUnlock>> =
+        ReadonlyBucket::new(storage, PREFIX_RECENT_UNLOCKS);
+    recent_unlocks_bucket
+        .load(borrower.as_slice())
+        .unwrap_or_default()
+}
+
+/// Amounts locked by all borrowers, for every collateral with a locked balance
+pub fn read_total_collaterals(storage: &dyn Storage) -> StdResult<Tokens> {
+    let total_collateral_bucket: ReadonlyBucket<Uint256> =
+        ReadonlyBucket::new(storage, PREFIX_TOTAL_COLLATERAL);
+    total_collateral_bucket
+        .range(None, None, Order::Ascending)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -149,7 +309,7 @@ pub fn read_all_collaterals(
     deps: Deps,
     start_after: Option<CanonicalAddr>,
     limit: Option<u32>,
-) -> StdResult<Vec<CollateralsResponse>> {
+) -> StdResult<Vec<(String, TokensHuman)>> {
     let whitelist_bucket: ReadonlyBucket<Tokens> =
         ReadonlyBucket::new(deps.storage, PREFIX_COLLATERALS);
 
@@ -162,15 +322,12 @@ pub fn read_all_collaterals(
         .map(|elem| {
             let (k, v) = elem?;
             let borrower = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
-            let collaterals: Vec<(String, Uint256)> = v
+            let collaterals: TokensHuman = v
                 .iter()
                 .map(|c| Ok((deps.api.addr_humanize(&c.0)?.to_string(), c.1)))
-                .collect::<StdResult<Vec<(String, Uint256)>>>()?;
+                .collect::<StdResult<TokensHuman>>()?;
 
-            Ok(CollateralsResponse {
-                borrower,
-                collaterals,
-            })
+            Ok((borrower, collaterals))
         })
         .collect()
 }
@@ -28,6 +28,7 @@ fn proper_compute_borrow_limit() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -40,6 +41,9 @@ fn proper_compute_borrow_limit() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -50,6 +54,9 @@ fn proper_compute_borrow_limit() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
@@ -10,7 +10,7 @@ use cosmwasm_std::{
 use std::collections::HashMap;
 
 use moneymarket::liquidation::LiquidationAmountResponse;
-use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse};
+use moneymarket::market::{BorrowerInfoResponse, EpochStateResponse, EscrowResponse};
 use moneymarket::oracle::PriceResponse;
 use moneymarket::tokens::TokensHuman;
 
@@ -29,6 +29,10 @@ pub enum QueryMsg {
         borrower: String,
         block_height: Option<u64>,
     },
+    /// Query escrowed aterra to market contract
+    Escrow { borrower: String },
+    /// Query addressed to one of the markets of the market contract
+    Market { denom: String, msg: Box<QueryMsg> },
     /// Query oracle price to oracle contract
     Price { base: String, quote: String },
     /// Query liquidation amount to liquidation model contract
@@ -62,6 +66,7 @@ pub struct WasmMockQuerier {
     oracle_price_querier: OraclePriceQuerier,
     loan_amount_querier: LoanAmountQuerier,
     liquidation_percent_querier: LiquidationPercentQuerier,
+    escrow_querier: EscrowQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -163,6 +168,23 @@ pub(crate) fn borrower_amount_to_map(
     borrower_amount_map
 }
 
+#[derive(Clone, Default)]
+pub struct EscrowQuerier {
+    // escrowed aterra amount and its stable value by borrower
+    escrows: HashMap<String, (Uint256, Uint256)>,
+}
+
+impl EscrowQuerier {
+    pub fn new(escrows: &[(&String, &(Uint256, Uint256))]) -> Self {
+        EscrowQuerier {
+            escrows: escrows
+                .iter()
+                .map(|(borrower, escrow)| (borrower.to_string(), **escrow))
+                .collect(),
+        }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -266,6 +288,25 @@ impl WasmMockQuerier {
                             request: msg.as_slice().into(),
                         }),
                     },
+                    QueryMsg::Escrow { borrower } => {
+                        let escrow = self
+                            .escrow_querier
+                            .escrows
+                            .get(&borrower)
+                            .copied()
+                            .unwrap_or_default();
+                        SystemResult::Ok(ContractResult::from(to_binary(&EscrowResponse {
+                            borrower,
+                            escrow_amount: escrow.0,
+                            escrow_value: escrow.1,
+                        })))
+                    }
+                    QueryMsg::Market { denom: _, msg } => {
+                        self.handle_query(&QueryRequest::Wasm(WasmQuery::Smart {
+                            contract_addr: contract_addr.clone(),
+                            msg: to_binary(&msg).unwrap(),
+                        }))
+                    }
                     QueryMsg::Price { base, quote } => {
                         match self.oracle_price_querier.oracle_price.get(&(base, quote)) {
                             Some(v) => {
@@ -301,12 +342,14 @@ impl WasmMockQuerier {
                                                 .map(|x| (x.0.clone(), x.1 * *v))
                                                 .collect::<TokensHuman>()
                                                 .to_vec(),
+                                            max_repay_amount: Uint256::zero(),
                                         },
                                     )))
                                 } else {
                                     SystemResult::Ok(ContractResult::from(to_binary(
                                         &LiquidationAmountResponse {
                                             collaterals: vec![],
+                                            max_repay_amount: Uint256::zero(),
                                         },
                                     )))
                                 }
@@ -333,9 +376,19 @@ impl WasmMockQuerier {
             oracle_price_querier: OraclePriceQuerier::default(),
             loan_amount_querier: LoanAmountQuerier::default(),
             liquidation_percent_querier: LiquidationPercentQuerier::default(),
+            escrow_querier: EscrowQuerier::default(),
         }
     }
 
+    // set a new balance for the given address and return the old balance
+    pub fn update_balance<U: Into<String>>(
+        &mut self,
+        addr: U,
+        balance: Vec<Coin>,
+    ) -> Option<Vec<Coin>> {
+        self.base.update_balance(addr, balance)
+    }
+
     // configure the tax mock querier
     pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
@@ -360,4 +413,8 @@ impl WasmMockQuerier {
     pub fn with_liquidation_percent(&mut self, liquidation_percent: &[(&String, &Decimal256)]) {
         self.liquidation_percent_querier = LiquidationPercentQuerier::new(liquidation_percent);
     }
+
+    pub fn with_escrows(&mut self, escrows: &[(&String, &(Uint256, Uint256))]) {
+        self.escrow_querier = EscrowQuerier::new(escrows);
+    }
 }
@@ -1,7 +1,8 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
-use crate::querier::query_epoch_state;
-use crate::state::{read_epoch_state, store_epoch_state, EpochState};
+use crate::state::{
+    read_epoch_state, store_buffer_state, store_epoch_state, BufferState, EpochState,
+};
 use crate::testing::mock_querier::mock_dependencies;
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
@@ -14,10 +15,11 @@ use cosmwasm_std::{
 use moneymarket::custody::ExecuteMsg as CustodyExecuteMsg;
 use moneymarket::market::ExecuteMsg as MarketExecuteMsg;
 use moneymarket::overseer::{
-    AllCollateralsResponse, BorrowLimitResponse, CollateralsResponse, ConfigResponse, ExecuteMsg,
-    InstantiateMsg, QueryMsg, WhitelistResponse, WhitelistResponseElem,
+    AllCollateralsResponse, BorrowLimitResponse, BufferHistoryElem, BufferStateResponse,
+    CollateralsResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, PendingUnlockResponse,
+    PendingUnlocksResponse, QueryMsg, WhitelistResponse, WhitelistResponseElem,
 };
-use moneymarket::querier::deduct_tax;
+use moneymarket::querier::{deduct_tax, query_epoch_state};
 
 use std::str::FromStr;
 
@@ -38,6 +40,7 @@ fn proper_initialization() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -62,6 +65,11 @@ fn proper_initialization() {
             buffer_distribution_factor: Decimal256::percent(20),
             anc_purchase_factor: Decimal256::percent(20),
             price_timeframe: 60u64,
+            max_total_collateral_value: None,
+            keeper_reward: Uint256::zero(),
+            liquidate_on_stale_price: false,
+            escrow_max_ltv: Decimal256::zero(),
+            market_denom: None,
         }
     );
 
@@ -74,7 +82,6 @@ fn proper_initialization() {
             last_executed_height: mock_env().block.height,
             prev_aterra_supply: Uint256::zero(),
             prev_exchange_rate: Decimal256::one(),
-            prev_interest_buffer: Uint256::zero(),
         }
     );
 }
@@ -97,6 +104,7 @@ fn update_config() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -114,6 +122,10 @@ fn update_config() {
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        max_total_collateral_value: None,
+        keeper_reward: None,
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -136,6 +148,10 @@ fn update_config() {
         anc_purchase_factor: Some(Decimal256::percent(10)),
         epoch_period: Some(100000u64),
         price_timeframe: Some(120u64),
+        max_total_collateral_value: None,
+        keeper_reward: None,
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -169,6 +185,10 @@ fn update_config() {
         anc_purchase_factor: None,
         epoch_period: None,
         price_timeframe: None,
+        max_total_collateral_value: None,
+        keeper_reward: None,
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -196,6 +216,7 @@ fn whitelist() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -207,6 +228,9 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -216,7 +240,21 @@ fn whitelist() {
         _ => panic!("DO NOT ENTER HERE"),
     };
 
+    // LTV of 100% or more is rejected
     let info = mock_info("owner", &[]);
+    let invalid_msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody".to_string(),
+        max_ltv: Decimal256::one(),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidMaxLtv {});
+
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
@@ -250,6 +288,10 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody".to_string(),
                 max_ltv: Decimal256::percent(60),
+                max_collateral: None,
+                unlock_delay: None,
+                unlock_delay_threshold: None,
+                total_collateral: Uint256::zero(),
             }]
         }
     );
@@ -261,6 +303,9 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let info = mock_info("owner", &[]);
@@ -274,6 +319,9 @@ fn whitelist() {
         collateral_token: "bluna".to_string(),
         custody_contract: Some("custody2".to_string()),
         max_ltv: Some(Decimal256::percent(30)),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -284,6 +332,17 @@ fn whitelist() {
     };
 
     let info = mock_info("owner", &[]);
+    let invalid_msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(120)),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidMaxLtv {});
+
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
@@ -315,6 +374,10 @@ fn whitelist() {
                 collateral_token: "bluna".to_string(),
                 custody_contract: "custody2".to_string(),
                 max_ltv: Decimal256::percent(30),
+                max_collateral: None,
+                unlock_delay: None,
+                unlock_delay_threshold: None,
+                total_collateral: Uint256::zero(),
             }]
         }
     );
@@ -342,6 +405,7 @@ fn execute_epoch_operations() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -372,6 +436,9 @@ fn execute_epoch_operations() {
         collateral_token: bluna_collat_token,
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
@@ -382,10 +449,29 @@ fn execute_epoch_operations() {
         collateral_token: batom_collat_token,
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
 
+    // custody rewards are deposited to the interest buffer
+    let msg = ExecuteMsg::FundReserve {};
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "custody_bluna",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(10000000000u128),
+            }],
+        ),
+        msg,
+    )
+    .unwrap();
+
     let msg = ExecuteMsg::ExecuteEpochOperations {};
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
     match res {
@@ -451,6 +537,8 @@ fn execute_epoch_operations() {
             attr("aterra_supply", "1000000"),
             attr("distributed_interest", "0"),
             attr("anc_purchase_amount", "2000000000"),
+            attr("keeper", "owner"),
+            attr("keeper_reward", "0"),
         ]
     );
 
@@ -461,11 +549,18 @@ fn execute_epoch_operations() {
             last_executed_height: env.block.height,
             prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
             prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
-            prev_interest_buffer: Uint256::from_str("9999000000").unwrap(),
             deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
         },
     )
     .unwrap();
+    store_buffer_state(
+        deps.as_mut().storage,
+        &BufferState {
+            interest_buffer: Uint256::from_str("10000000000").unwrap(),
+            accrued_buffer: Uint256::from_str("1000000").unwrap(),
+        },
+    )
+    .unwrap();
 
     // If deposit rate is bigger than threshold
     deps.querier.with_epoch_state(&[(
@@ -540,7 +635,281 @@ fn execute_epoch_operations() {
             attr("exchange_rate", "1.25"),
             attr("aterra_supply", "1000000"),
             attr("distributed_interest", "53148"),
-            attr("anc_purchase_amount", "200000")
+            attr("anc_purchase_amount", "200000"),
+            attr("keeper", "owner"),
+            attr("keeper_reward", "0"),
+        ]
+    );
+}
+
+#[test]
+fn execute_epoch_operations_keeper_reward() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token,
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token,
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        max_total_collateral_value: None,
+        keeper_reward: Some(Uint256::from(1000000u64)),
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::FundReserve {};
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "custody_bluna",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(10000000000u128),
+            }],
+        ),
+        msg,
+    )
+    .unwrap();
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // anyone can run the epoch operations and collect the reward
+    // interest_buffer = 10,000,000,000 - 2,000,000,000 - 1,000,000
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        res.messages[4],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::UpdateEpochState {
+                interest_buffer: Uint256::from(7_999_000_000u128),
+                distributed_interest: Uint256::zero(),
+            })
+            .unwrap(),
+        }))
+    );
+    assert_eq!(
+        res.attributes[6..],
+        [attr("keeper", "keeper"), attr("keeper_reward", "1000000")]
+    );
+}
+
+#[test]
+fn execute_epoch_operations_exchange_rate_drop() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let mut env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token,
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token,
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    store_epoch_state(
+        deps.as_mut().storage,
+        &EpochState {
+            last_executed_height: env.block.height,
+            prev_exchange_rate: Decimal256::from_str("1.2").unwrap(),
+            prev_aterra_supply: Uint256::from_str("1000000").unwrap(),
+            deposit_rate: Decimal256::zero(),
+        },
+    )
+    .unwrap();
+    store_buffer_state(
+        deps.as_mut().storage,
+        &BufferState {
+            interest_buffer: Uint256::from_str("10000000000").unwrap(),
+            accrued_buffer: Uint256::zero(),
+        },
+    )
+    .unwrap();
+
+    // Exchange rate fell below the previous epoch, e.g. after a bad debt
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(110)),
+    )]);
+
+    env.block.height += 86400u64;
+
+    // deposit rate is floored at zero
+    // missing_deposits = 1,200,000 * 86400 * 0.000001 = 103,680
+    let msg = ExecuteMsg::ExecuteEpochOperations {};
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "market".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(103680u128),
+            }],
+        }))
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "epoch_operations"),
+            attr("deposit_rate", "0"),
+            attr("exchange_rate", "1.1"),
+            attr("aterra_supply", "1000000"),
+            attr("distributed_interest", "103680"),
+            attr("anc_purchase_amount", "0"),
+            attr("keeper", "owner"),
+            attr("keeper_reward", "0"),
         ]
     );
 }
@@ -566,6 +935,7 @@ fn update_epoch_state() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -578,6 +948,9 @@ fn update_epoch_state() {
         collateral_token: "bluna".to_string(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -588,6 +961,9 @@ fn update_epoch_state() {
         collateral_token: "batom".to_string(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -675,6 +1051,7 @@ fn update_epoch_state() {
     let epoch_state_response = query_epoch_state(
         deps.as_ref(),
         Addr::unchecked("market"),
+        None,
         env.block.height,
         None,
     )
@@ -688,16 +1065,20 @@ fn update_epoch_state() {
             deposit_rate: Decimal256::from_ratio(482253086419u64, 1000000000000000000u64),
             prev_aterra_supply: epoch_state_response.aterra_supply,
             prev_exchange_rate: epoch_state_response.exchange_rate,
-            prev_interest_buffer: Uint256::from(10000000000u128),
             last_executed_height: env.block.height,
         }
     )
 }
 
 #[test]
-fn lock_collateral() {
-    let mut deps = mock_dependencies(&[]);
+fn fund_reserve() {
+    // coins sent without FundReserve are not part of the buffer
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
 
+    let mut env = mock_env();
     let info = mock_info("owner", &[]);
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
@@ -707,41 +1088,279 @@ fn lock_collateral() {
         collector_contract: "collector".to_string(),
         stable_denom: "uusd".to_string(),
         epoch_period: 86400u64,
-        threshold_deposit_rate: Decimal256::permille(3),
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
         target_deposit_rate: Decimal256::permille(5),
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-
-    let batom_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    let bluna_collat_token = deps
-        .api
-        .addr_humanize(&CanonicalAddr::from(vec![
-            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-        ]))
-        .unwrap()
-        .to_string();
+    let msg = ExecuteMsg::FundReserve {};
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "custody_bluna",
+            &[Coin {
+                denom: "ukrw".to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        ),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::ZeroReserveFund(denom)) => assert_eq!(denom, "uusd"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
-    // store whitelist elems
-    let msg = ExecuteMsg::Whitelist {
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "custody_bluna",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(1500000u128),
+            }],
+        ),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "fund_reserve"),
+            attr("funder", "custody_bluna"),
+            attr("funded_amount", "1500000"),
+        ]
+    );
+
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "custody_batom",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(500000u128),
+            }],
+        ),
+        msg.clone(),
+    )
+    .unwrap();
+
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // accrued_buffer = 2,000,000
+    // anc_purchase_amount = accrued_buffer * 0.2 = 400,000
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecuteEpochOperations {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "collector".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(400000u128),
+            }],
+        }))
+    );
+
+    // rewards sent during the epoch operations accrue to the next epoch
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "custody_bluna",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(300000u128),
+            }],
+        ),
+        msg,
+    )
+    .unwrap();
+
+    let res: BufferStateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BufferState { limit: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        BufferStateResponse {
+            interest_buffer: Uint256::from(1900000u64),
+            accrued_buffer: Uint256::from(300000u64),
+            history: vec![BufferHistoryElem {
+                height: env.block.height,
+                accrued_buffer: Uint256::from(2000000u64),
+                anc_purchase_amount: Uint256::from(400000u64),
+                keeper_reward: Uint256::zero(),
+                distributed_interest: Uint256::zero(),
+                interest_buffer: Uint256::from(1600000u64),
+            }],
+        }
+    );
+}
+
+#[test]
+fn fund_reserve_from_liquidation() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000000u128),
+    }]);
+
+    let env = mock_env();
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the liquidation contract paid a bid fee of 5000
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1005000u128),
+        }],
+    );
+
+    let msg = ExecuteMsg::FundReserveFromLiquidation {
+        prev_balance: Uint256::from(1000000u64),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("liquidation", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "fund_reserve_from_liquidation"),
+            attr("funded_amount", "5000"),
+        ]
+    );
+
+    let res: BufferStateResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BufferState { limit: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.interest_buffer, Uint256::from(5000u64));
+    assert_eq!(res.accrued_buffer, Uint256::from(5000u64));
+
+    // nothing is funded when no fee was paid
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::FundReserveFromLiquidation {
+            prev_balance: Uint256::from(1005000u64),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("funded_amount", "0"));
+}
+
+#[test]
+fn lock_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
         collateral_token: bluna_collat_token.clone(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
@@ -752,6 +1371,9 @@ fn lock_collateral() {
         collateral_token: batom_collat_token.clone(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -803,6 +1425,26 @@ fn lock_collateral() {
         ]
     );
 
+    // collaterals are valued at oracle prices
+    deps.querier.with_oracle_price(&[
+        (
+            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                mock_env().block.time.seconds(),
+                mock_env().block.time.seconds(),
+            ),
+        ),
+        (
+            &(batom_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                mock_env().block.time.seconds(),
+                mock_env().block.time.seconds(),
+            ),
+        ),
+    ]);
+
     let res = query(
         deps.as_ref(),
         mock_env(),
@@ -819,7 +1461,12 @@ fn lock_collateral() {
             collaterals: vec![
                 (batom_collat_token.clone(), Uint256::from(10000000u64)),
                 (bluna_collat_token.clone(), Uint256::from(1000000u64)),
-            ]
+            ],
+            collateral_prices: vec![
+                Decimal256::from_ratio(2000u64, 1u64),
+                Decimal256::from_ratio(1000u64, 1u64),
+            ],
+            collateral_values: vec![Uint256::from(20000000000u64), Uint256::from(1000000000u64),],
         }
     );
 
@@ -841,18 +1488,25 @@ fn lock_collateral() {
                 collaterals: vec![
                     (batom_collat_token, Uint256::from(10000000u64)),
                     (bluna_collat_token, Uint256::from(1000000u64)),
-                ]
+                ],
+                collateral_prices: vec![
+                    Decimal256::from_ratio(2000u64, 1u64),
+                    Decimal256::from_ratio(1000u64, 1u64),
+                ],
+                collateral_values: vec![
+                    Uint256::from(20000000000u64),
+                    Uint256::from(1000000000u64),
+                ],
             }]
         }
     );
 }
 
 #[test]
-fn unlock_collateral() {
+fn collateral_caps() {
     let mut deps = mock_dependencies(&[]);
 
     let info = mock_info("owner", &[]);
-    let env = mock_env();
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         oracle_contract: "oracle".to_string(),
@@ -866,203 +1520,241 @@ fn unlock_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
 
     // store whitelist elems
     let msg = ExecuteMsg::Whitelist {
         name: "bluna".to_string(),
         symbol: "bluna".to_string(),
-        collateral_token: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
 
     let msg = ExecuteMsg::Whitelist {
         name: "batom".to_string(),
         symbol: "batom".to_string(),
-        collateral_token: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
-    let _res = execute(deps.as_mut(), env.clone(), info, msg);
-
-    let msg = ExecuteMsg::LockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1000000u64)),
-            ("batom".to_string(), Uint256::from(10000000u64)),
-        ],
-    };
-    let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-
-    // Failed to unlock more than locked amount
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1000001u64)),
-            ("batom".to_string(), Uint256::from(10000001u64)),
-        ],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-    match res {
-        Err(ContractError::UnlockExceedsLocked {}) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let _res = execute(deps.as_mut(), mock_env(), info, msg);
 
     deps.querier.with_oracle_price(&[
         (
-            &("bluna".to_string(), "uusd".to_string()),
+            &(bluna_collat_token.clone(), "uusd".to_string()),
             &(
                 Decimal256::from_ratio(1000u64, 1u64),
-                env.block.time.seconds(),
-                env.block.time.seconds(),
+                mock_env().block.time.seconds(),
+                mock_env().block.time.seconds(),
             ),
         ),
         (
-            &("batom".to_string(), "uusd".to_string()),
+            &(batom_collat_token.clone(), "uusd".to_string()),
             &(
                 Decimal256::from_ratio(2000u64, 1u64),
-                env.block.time.seconds(),
-                env.block.time.seconds(),
+                mock_env().block.time.seconds(),
+                mock_env().block.time.seconds(),
             ),
         ),
     ]);
-
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
     deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
 
-    // cannot unlock any tokens
-    // Failed to unlock more than locked amount
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: None,
+        max_ltv: None,
+        max_collateral: Some(Uint256::from(1500000u64)),
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-    match res {
-        Err(ContractError::UnlockTooLarge(12599999400)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("batom".to_string(), Uint256::one())],
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(1000000u64))],
     };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-    match res {
-        Err(ContractError::UnlockTooLarge(12599998800)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Locks of all borrowers count against the cap
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(600000u64))],
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::CollateralCapExceeded("bluna".to_string(), 1500000u128)
+    );
 
-    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
-    // = 12,600,000,000 uusd
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12599999400u64))]);
     let res = query(
         deps.as_ref(),
-        env.clone(),
-        QueryMsg::BorrowLimit {
-            borrower: "addr0000".to_string(),
-            block_time: None,
+        mock_env(),
+        QueryMsg::Whitelist {
+            collateral_token: Some(bluna_collat_token.clone()),
+            start_after: None,
+            limit: None,
         },
     )
     .unwrap();
-    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
-    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
-
-    // Cannot unlock 2bluna
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::from(2u64))],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
-    match res {
-        Err(ContractError::UnlockTooLarge(12599998800)) => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-
-    // Can unlock 1bluna
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![("bluna".to_string(), Uint256::one())],
-    };
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "custody_bluna".to_string(),
-            funds: vec![],
-            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                borrower: "addr0000".to_string(),
-                amount: Uint256::one(),
-            })
-            .unwrap(),
-        }))]
+        whitelist_res.elems[0].max_collateral,
+        Some(Uint256::from(1500000u64))
     );
-
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "unlock_collateral"),
-            attr("borrower", "addr0000"),
-            attr("collaterals", "1bluna"),
-        ]
+        whitelist_res.elems[0].total_collateral,
+        Uint256::from(1000000u64)
     );
 
-    //testing for unlocking more collaterals
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(125999900u128))]);
+    // Unlocking frees up room under the cap
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(400000u64))],
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        unlock_msg,
+    )
+    .unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-    let msg = ExecuteMsg::UnlockCollateral {
-        collaterals: vec![
-            ("bluna".to_string(), Uint256::from(1u128)),
-            ("batom".to_string(), Uint256::from(1u128)),
-        ],
+    // total collateral value = 1,200,000 * 1000 = 1,200,000,000
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        max_total_collateral_value: Some(Uint256::from(2000000000u64)),
+        keeper_reward: None,
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: None,
     };
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(
-        res.messages,
-        vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_bluna".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1u128),
-                })
-                .unwrap(),
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(1u128),
-                })
-                .unwrap(),
-            }))
-        ]
-    );
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // 1,200,000,000 + 1000 * 2000
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(batom_collat_token.clone(), Uint256::from(1000u64))],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // 1,202,000,000 + 400,000 * 2000
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(batom_collat_token, Uint256::from(400000u64))],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
     assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "unlock_collateral"),
-            attr("borrower", "addr0000"),
-            attr("collaterals", "1bluna,1batom"),
-        ]
+        res,
+        ContractError::TotalCollateralCapExceeded(2000000000u128)
     );
+
+    // Zero removes the caps
+    let update_config_msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        max_total_collateral_value: Some(Uint256::zero()),
+        keeper_reward: None,
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_config_msg,
+    )
+    .unwrap();
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let update_whitelist_msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: None,
+        max_ltv: None,
+        max_collateral: Some(Uint256::zero()),
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        update_whitelist_msg,
+    )
+    .unwrap();
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config_res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config_res.max_total_collateral_value, None);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Whitelist {
+            collateral_token: Some(bluna_collat_token),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(whitelist_res.elems[0].max_collateral, None);
 }
 
 #[test]
-fn liquidate_collateral() {
+fn lock_collateral_for() {
     let mut deps = mock_dependencies(&[]);
-    deps.querier
-        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
 
     let info = mock_info("owner", &[]);
-    let env = mock_env();
     let msg = InstantiateMsg {
         owner_addr: "owner".to_string(),
         oracle_contract: "oracle".to_string(),
@@ -1076,10 +1768,11 @@ fn liquidate_collateral() {
         buffer_distribution_factor: Decimal256::percent(20),
         anc_purchase_factor: Decimal256::percent(20),
         price_timeframe: 60u64,
+        market_denom: None,
     };
 
     // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
     let batom_collat_token = deps
         .api
@@ -1106,9 +1799,12 @@ fn liquidate_collateral() {
         collateral_token: bluna_collat_token.clone(),
         custody_contract: "custody_bluna".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
-    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
 
     let msg = ExecuteMsg::Whitelist {
         name: "batom".to_string(),
@@ -1116,22 +1812,146 @@ fn liquidate_collateral() {
         collateral_token: batom_collat_token.clone(),
         custody_contract: "custody_batom".to_string(),
         max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateralFor {
+        borrower: "addr0000".to_string(),
+        collaterals: vec![(bluna_collat_token.clone(), Uint256::from(1000000u64))],
+    };
+
+    // only the market can lock on behalf of a borrower
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("market", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    deps.querier.with_oracle_price(&[(
+        &(bluna_collat_token.clone(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            mock_env().block.time.seconds(),
+            mock_env().block.time.seconds(),
+        ),
+    )]);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![(bluna_collat_token, Uint256::from(1000000u64))],
+            collateral_prices: vec![Decimal256::from_ratio(1000u64, 1u64)],
+            collateral_values: vec![Uint256::from(1000000000u64)],
+        }
+    );
+}
+
+#[test]
+fn unlock_collateral() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: "batom".to_string(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
     };
 
     let _res = execute(deps.as_mut(), env.clone(), info, msg);
 
     let msg = ExecuteMsg::LockCollateral {
         collaterals: vec![
-            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
-            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+            ("bluna".to_string(), Uint256::from(1000000u64)),
+            ("batom".to_string(), Uint256::from(10000000u64)),
         ],
     };
     let info = mock_info("addr0000", &[]);
-    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Failed to unlock more than locked amount
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1000001u64)),
+            ("batom".to_string(), Uint256::from(10000001u64)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockExceedsLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
 
     deps.querier.with_oracle_price(&[
         (
-            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &("bluna".to_string(), "uusd".to_string()),
             &(
                 Decimal256::from_ratio(1000u64, 1u64),
                 env.block.time.seconds(),
@@ -1139,7 +1959,7 @@ fn liquidate_collateral() {
             ),
         ),
         (
-            &(batom_collat_token.clone(), "uusd".to_string()),
+            &("batom".to_string(), "uusd".to_string()),
             &(
                 Decimal256::from_ratio(2000u64, 1u64),
                 env.block.time.seconds(),
@@ -1153,57 +1973,235 @@ fn liquidate_collateral() {
     deps.querier
         .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
 
-    let msg = ExecuteMsg::LiquidateCollateral {
-        borrower: "addr0000".to_string(),
+    // cannot unlock any tokens
+    // Failed to unlock more than locked amount
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
     };
-    let info = mock_info("addr0001", &[]);
-    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
     match res {
-        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        Err(ContractError::UnlockTooLarge(12599999400)) => (),
         _ => panic!("DO NOT ENTER HERE"),
     }
 
-    deps.querier
-        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
-    assert_eq!(
-        res.messages,
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("batom".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12599999400u64))]);
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::BorrowLimit {
+            borrower: "addr0000".to_string(),
+            block_time: None,
+            escrow_value: None,
+        },
+    )
+    .unwrap();
+    let borrow_limit_res: BorrowLimitResponse = from_binary(&res).unwrap();
+    assert_eq!(borrow_limit_res.borrow_limit, Uint256::from(12600000000u64),);
+
+    // Cannot unlock 2bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(2u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockTooLarge(12599998800)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // Can unlock 1bluna
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::one())],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::one(),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1bluna"),
+        ]
+    );
+
+    //testing for unlocking more collaterals
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(125999900u128))]);
+
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![
+            ("bluna".to_string(), Uint256::from(1u128)),
+            ("batom".to_string(), Uint256::from(1u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "custody_batom".to_string(),
-                funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: "addr0001".to_string(),
-                    borrower: "addr0000".to_string(),
-                    amount: Uint256::from(100000u64),
-                })
-                .unwrap(),
-            })),
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: "custody_bluna".to_string(),
                 funds: vec![],
-                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
-                    liquidator: "addr0001".to_string(),
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
                     borrower: "addr0000".to_string(),
-                    amount: Uint256::from(10000u64),
+                    amount: Uint256::from(1u128),
                 })
                 .unwrap(),
             })),
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "market".to_string(),
+                contract_addr: "custody_batom".to_string(),
                 funds: vec![],
-                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
                     borrower: "addr0000".to_string(),
-                    prev_balance: Uint256::zero(),
+                    amount: Uint256::from(1u128),
                 })
                 .unwrap(),
             }))
         ]
     );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "1bluna,1batom"),
+        ]
+    );
+}
+
+#[test]
+fn unlock_collateral_with_delay() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // unlocks above 1000bluna wait for 100 blocks
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: Some(100u64),
+        unlock_delay_threshold: Some(Uint256::from(1000u64)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    // small unlocks are released right away
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(1000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    // large unlocks stay locked until claimed
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(500000u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "unlock_collateral"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "500000bluna"),
+        ]
+    );
 
     let res = query(
         deps.as_ref(),
-        env,
+        env.clone(),
+        QueryMsg::PendingUnlocks {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let pending_unlocks_res: PendingUnlocksResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_unlocks_res,
+        PendingUnlocksResponse {
+            borrower: "addr0000".to_string(),
+            pending_unlocks: vec![PendingUnlockResponse {
+                collateral_token: "bluna".to_string(),
+                amount: Uint256::from(500000u64),
+                release_height: env.block.height + 100,
+            }],
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
         QueryMsg::Collaterals {
             borrower: "addr0000".to_string(),
         },
@@ -1211,13 +2209,1054 @@ fn liquidate_collateral() {
     .unwrap();
     let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
     assert_eq!(
-        collaterals_res,
-        CollateralsResponse {
+        collaterals_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(999000u64))]
+    );
+
+    // pending unlocks cannot be unlocked twice
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(499001u64))],
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::UnlockExceedsLocked {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ClaimUnlocked {},
+    );
+    match res {
+        Err(ContractError::NoClaimableUnlock {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // borrow_limit without the pending unlock = 1000 * 499000 * 0.6
+    // = 299,400,000 uusd
+    let mut env = env;
+    env.block.height += 100;
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(299400001u64))]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ClaimUnlocked {},
+    );
+    match res {
+        Err(ContractError::UnlockTooLarge(299400000)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(299400000u64))]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ClaimUnlocked {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(500000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_unlocked"),
+            attr("borrower", "addr0000"),
+            attr("collaterals", "500000bluna"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PendingUnlocks {
             borrower: "addr0000".to_string(),
-            collaterals: vec![
-                (batom_collat_token, Uint256::from(9900000u64)),
-                (bluna_collat_token, Uint256::from(990000u64)),
-            ]
-        }
+        },
+    )
+    .unwrap();
+    let pending_unlocks_res: PendingUnlocksResponse = from_binary(&res).unwrap();
+    assert_eq!(pending_unlocks_res.pending_unlocks, vec![]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res.collaterals,
+        vec![("bluna".to_string(), Uint256::from(499000u64))]
+    );
+
+    // zero removes the unlock delay and its threshold
+    let msg = ExecuteMsg::UpdateWhitelist {
+        collateral_token: "bluna".to_string(),
+        custody_contract: None,
+        max_ltv: None,
+        max_collateral: None,
+        unlock_delay: Some(0u64),
+        unlock_delay_threshold: Some(Uint256::zero()),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Whitelist {
+            collateral_token: Some("bluna".to_string()),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let whitelist_res: WhitelistResponse = from_binary(&res).unwrap();
+    assert_eq!(whitelist_res.elems[0].unlock_delay, None);
+    assert_eq!(whitelist_res.elems[0].unlock_delay_threshold, None);
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(400000u64))],
+    };
+    let res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(400000u64),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn unlock_collateral_split_below_delay_threshold() {
+    let mut deps = mock_dependencies(&[]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: Some(100u64),
+        unlock_delay_threshold: Some(Uint256::from(1000u64)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(600u64))],
+    };
+    let custody_unlock_msg = SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "custody_bluna".to_string(),
+        funds: vec![],
+        msg: to_binary(&CustodyExecuteMsg::UnlockCollateral {
+            borrower: "addr0000".to_string(),
+            amount: Uint256::from(600u64),
+        })
+        .unwrap(),
+    }));
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), unlock_msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![custody_unlock_msg.clone()]);
+
+    // 600 + 600 exceeds the threshold within the delay
+    let mut env = env;
+    env.block.height += 10;
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), unlock_msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::PendingUnlocks {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let pending_unlocks_res: PendingUnlocksResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_unlocks_res.pending_unlocks,
+        vec![PendingUnlockResponse {
+            collateral_token: "bluna".to_string(),
+            amount: Uint256::from(600u64),
+            release_height: env.block.height + 100,
+        }]
+    );
+
+    // pending unlocks count against the threshold as well
+    env.block.height += 95;
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), unlock_msg.clone()).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    // once claimed and out of the delay, unlocks are released right away
+    env.block.height += 100;
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ClaimUnlocked {},
+    )
+    .unwrap();
+    let res = execute(deps.as_mut(), env, info, unlock_msg).unwrap();
+    assert_eq!(res.messages, vec![custody_unlock_msg]);
+}
+
+#[test]
+fn liquidate_collateral() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &(batom_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(100000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::FundReserveFromLiquidation {
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidate_collateral"),
+            attr("liquidator", "addr0001"),
+            attr("borrower", "addr0000"),
+            attr("loan_amount", "12600000001"),
+            attr("borrow_limit", "12600000000"),
+            attr(
+                "collaterals",
+                format!("100000{},10000{}", batom_collat_token, bluna_collat_token)
+            ),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                (batom_collat_token, Uint256::from(9900000u64)),
+                (bluna_collat_token, Uint256::from(990000u64)),
+            ],
+            collateral_prices: vec![
+                Decimal256::from_ratio(2000u64, 1u64),
+                Decimal256::from_ratio(1000u64, 1u64),
+            ],
+            collateral_values: vec![Uint256::from(19800000000u64), Uint256::from(990000000u64),],
+        }
+    );
+}
+
+#[test]
+fn liquidate_collateral_for() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let batom_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    let bluna_collat_token = deps
+        .api
+        .addr_humanize(&CanonicalAddr::from(vec![
+            1, 1, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]))
+        .unwrap()
+        .to_string();
+
+    // store whitelist elems
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: bluna_collat_token.clone(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "batom".to_string(),
+        symbol: "batom".to_string(),
+        collateral_token: batom_collat_token.clone(),
+        custody_contract: "custody_batom".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+
+    let _res = execute(deps.as_mut(), env.clone(), info, msg);
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![
+            (bluna_collat_token.clone(), Uint256::from(1000000u64)),
+            (batom_collat_token.clone(), Uint256::from(10000000u64)),
+        ],
+    };
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_oracle_price(&[
+        (
+            &(bluna_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(1000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+        (
+            &(batom_collat_token.clone(), "uusd".to_string()),
+            &(
+                Decimal256::from_ratio(2000u64, 1u64),
+                env.block.time.seconds(),
+                env.block.time.seconds(),
+            ),
+        ),
+    ]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 2000 * 10000000 * 0.6
+    // = 12,600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000000u64))]);
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(12600000001u64))]);
+
+    // liquidation value = 2000 * 100000 + 1000 * 10000 = 210,000,000 uusd
+    let msg = ExecuteMsg::LiquidateCollateralFor {
+        liquidator: "addr0001".to_string(),
+        borrower: "addr0000".to_string(),
+        max_repay: Some(Uint256::from(105000000u64)),
+    };
+
+    // only the market can liquidate on behalf of a liquidator
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info("market", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_batom".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(50000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(5000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::FundReserveFromLiquidation {
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::Collaterals {
+            borrower: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let collaterals_res: CollateralsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        collaterals_res,
+        CollateralsResponse {
+            borrower: "addr0000".to_string(),
+            collaterals: vec![
+                (batom_collat_token, Uint256::from(9950000u64)),
+                (bluna_collat_token, Uint256::from(995000u64)),
+            ],
+            collateral_prices: vec![
+                Decimal256::from_ratio(2000u64, 1u64),
+                Decimal256::from_ratio(1000u64, 1u64),
+            ],
+            collateral_values: vec![Uint256::from(19900000000u64), Uint256::from(995000000u64),],
+        }
+    );
+}
+
+#[test]
+fn liquidate_collateral_on_stale_price() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // the last price is older than the price timeframe
+    let last_updated = env.block.time.seconds() - 61;
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            last_updated,
+            last_updated,
+        ),
+    )]);
+
+    // borrow_limit = 1000 * 1000000 * 0.6 = 600,000,000 uusd
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(600000001u64))]);
+
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let liquidator_info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        liquidator_info.clone(),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Std(e)) => assert_eq!(e.to_string(), "Generic error: Price is too old"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let update_msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        max_total_collateral_value: None,
+        keeper_reward: None,
+        liquidate_on_stale_price: Some(true),
+        escrow_max_ltv: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, update_msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert!(config_res.liquidate_on_stale_price);
+
+    // liquidations go on with the last known price
+    let res = execute(deps.as_mut(), env.clone(), liquidator_info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "custody_bluna".to_string(),
+                funds: vec![],
+                msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                    liquidator: "addr0001".to_string(),
+                    borrower: "addr0000".to_string(),
+                    amount: Uint256::from(10000u64),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "market".to_string(),
+                funds: vec![],
+                msg: to_binary(&MarketExecuteMsg::RepayStableFromLiquidation {
+                    borrower: "addr0000".to_string(),
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::FundReserveFromLiquidation {
+                    prev_balance: Uint256::zero(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // while unlocking still needs a fresh price
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::zero())]);
+    let msg = ExecuteMsg::UnlockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1u64))],
+    };
+    let res = execute(deps.as_mut(), env, mock_info("addr0000", &[]), msg);
+    match res {
+        Err(ContractError::Std(e)) => assert_eq!(e.to_string(), "Generic error: Price is too old"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn liquidate_escrow() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier
+        .with_liquidation_percent(&[(&"liquidation".to_string(), &Decimal256::percent(1))]);
+
+    let info = mock_info("owner", &[]);
+    let env = mock_env();
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "uusd".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::permille(3),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Whitelist {
+        name: "bluna".to_string(),
+        symbol: "bluna".to_string(),
+        collateral_token: "bluna".to_string(),
+        custody_contract: "custody_bluna".to_string(),
+        max_ltv: Decimal256::percent(60),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+    let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::LockCollateral {
+        collaterals: vec![("bluna".to_string(), Uint256::from(1000000u64))],
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+
+    deps.querier.with_oracle_price(&[(
+        &("bluna".to_string(), "uusd".to_string()),
+        &(
+            Decimal256::from_ratio(1000u64, 1u64),
+            env.block.time.seconds(),
+            env.block.time.seconds(),
+        ),
+    )]);
+    deps.querier.with_escrows(&[(
+        &"addr0000".to_string(),
+        &(Uint256::from(100000000u64), Uint256::from(110000000u64)),
+    )]);
+
+    // escrows do not count until their LTV is configured
+    let res: BorrowLimitResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+                escrow_value: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.borrow_limit, Uint256::from(600000000u64));
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner_addr: None,
+        oracle_contract: None,
+        liquidation_contract: None,
+        threshold_deposit_rate: None,
+        target_deposit_rate: None,
+        buffer_distribution_factor: None,
+        anc_purchase_factor: None,
+        epoch_period: None,
+        price_timeframe: None,
+        max_total_collateral_value: None,
+        keeper_reward: None,
+        liquidate_on_stale_price: None,
+        escrow_max_ltv: Some(Decimal256::percent(50)),
+    };
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // borrow_limit = 1000 * 1000000 * 0.6 + 110000000 * 0.5
+    let res: BorrowLimitResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+                escrow_value: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.borrow_limit, Uint256::from(655000000u64));
+
+    // the market sends its own escrow value instead of being queried back
+    let res: BorrowLimitResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowLimit {
+                borrower: "addr0000".to_string(),
+                block_time: None,
+                escrow_value: Some(Uint256::from(20000000u64)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.borrow_limit, Uint256::from(610000000u64));
+
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(655000000u64))]);
+    let msg = ExecuteMsg::LiquidateCollateral {
+        borrower: "addr0000".to_string(),
+    };
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::CannotLiquidateSafeLoan {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the escrow settles the loan before any collateral is sold
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(655000001u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::RepayStableFromEscrow {
+                borrower: "addr0000".to_string(),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "liquidate_escrow"),
+            attr("liquidator", "addr0001"),
+            attr("borrower", "addr0000"),
+            attr("loan_amount", "655000001"),
+            attr("borrow_limit", "655000000"),
+            attr("escrow_amount", "100000000"),
+        ]
+    );
+
+    // collaterals are liquidated for what the escrow cannot cover
+    deps.querier
+        .with_loan_amount(&[(&"addr0000".to_string(), &Uint256::from(710000001u64))]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 4);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::RepayStableFromEscrow {
+                borrower: "addr0000".to_string(),
+            })
+            .unwrap(),
+        }))
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "custody_bluna".to_string(),
+            funds: vec![],
+            msg: to_binary(&CustodyExecuteMsg::LiquidateCollateral {
+                liquidator: "addr0001".to_string(),
+                borrower: "addr0000".to_string(),
+                amount: Uint256::from(10000u64),
+            })
+            .unwrap(),
+        }))
+    );
+    assert_eq!(res.attributes[0], attr("action", "liquidate_collateral"));
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("escrow_amount", "100000000")
+    );
+
+    // without an escrow left, collaterals are liquidated
+    deps.querier.with_escrows(&[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(res.attributes[0], attr("action", "liquidate_collateral"));
+}
+
+#[test]
+fn update_epoch_state_for_market() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "ukrw".to_string(),
+        amount: Uint128::from(10000000000u128),
+    }]);
+
+    let info = mock_info("owner", &[]);
+    let msg = InstantiateMsg {
+        owner_addr: "owner".to_string(),
+        oracle_contract: "oracle".to_string(),
+        market_contract: "market".to_string(),
+        liquidation_contract: "liquidation".to_string(),
+        collector_contract: "collector".to_string(),
+        stable_denom: "ukrw".to_string(),
+        epoch_period: 86400u64,
+        threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+        target_deposit_rate: Decimal256::permille(5),
+        buffer_distribution_factor: Decimal256::percent(20),
+        anc_purchase_factor: Decimal256::percent(20),
+        price_timeframe: 60u64,
+        market_denom: Some("ukrw".to_string()),
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_res.market_denom, Some("ukrw".to_string()));
+
+    let mut env = mock_env();
+    env.block.height += 86400u64;
+    deps.querier.with_epoch_state(&[(
+        &"market".to_string(),
+        &(Uint256::from(1000000u64), Decimal256::percent(120)),
+    )]);
+
+    // Epoch operations are addressed to the market of the overseer
+    let msg = ExecuteMsg::UpdateEpochState {
+        interest_buffer: Uint256::from(10000000000u128),
+        distributed_interest: Uint256::from(1000000u128),
+    };
+    let res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "market".to_string(),
+            funds: vec![],
+            msg: to_binary(&MarketExecuteMsg::Market {
+                denom: "ukrw".to_string(),
+                msg: Box::new(MarketExecuteMsg::ExecuteEpochOperations {
+                    deposit_rate: Decimal256::from_str("0.000002314814814814").unwrap(),
+                    target_deposit_rate: Decimal256::permille(5),
+                    threshold_deposit_rate: Decimal256::from_ratio(1u64, 1000000u64),
+                    distributed_interest: Uint256::from(1000000u128),
+                }),
+            })
+            .unwrap(),
+        }))]
     );
 }
@@ -0,0 +1,245 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::error::ContractError;
+use crate::state::{
+    has_vesting_info, read_config, read_vesting_info, read_vesting_infos, store_config,
+    store_vesting_info, Config, VestingInfo,
+};
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{
+    attr, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::vesting::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VestingAccount, VestingAccountResponse,
+    VestingAccountsResponse, VestingSchedule,
+};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(&msg.owner)?,
+            anchor_token: deps.api.addr_canonicalize(&msg.anchor_token)?,
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
+            register_vesting_accounts(deps, info, vesting_accounts)
+        }
+        ExecuteMsg::Claim {} => claim(deps, env, info),
+    }
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    store_config(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+pub fn register_vesting_accounts(
+    deps: DepsMut,
+    info: MessageInfo,
+    vesting_accounts: Vec<VestingAccount>,
+) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for vesting_account in vesting_accounts.iter() {
+        assert_vesting_schedule(&vesting_account.schedule)?;
+
+        let address_raw = deps.api.addr_canonicalize(&vesting_account.address)?;
+        if has_vesting_info(deps.storage, &address_raw) {
+            return Err(ContractError::VestingAccountAlreadyRegistered(
+                vesting_account.address.clone(),
+            ));
+        }
+
+        store_vesting_info(
+            deps.storage,
+            &address_raw,
+            &VestingInfo {
+                schedule: vesting_account.schedule.clone(),
+                claimed_amount: Uint256::zero(),
+            },
+        )?;
+    }
+
+    Ok(Response::new().add_attributes(vec![attr("action", "register_vesting_accounts")]))
+}
+
+/// Send the vested tokens that are not claimed yet
+/// Executor: vesting account
+pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = read_config(deps.storage)?;
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut vesting_info: VestingInfo = read_vesting_info(deps.storage, &sender_raw)?;
+
+    let vested_amount = compute_vested_amount(&vesting_info.schedule, env.block.time.seconds());
+    let claim_amount = vested_amount - vesting_info.claimed_amount;
+    if claim_amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    vesting_info.claimed_amount = vested_amount;
+    store_vesting_info(deps.storage, &sender_raw, &vesting_info)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: claim_amount.into(),
+            })?,
+        }))
+        .add_attributes(vec![
+            attr("action", "claim"),
+            attr("address", info.sender),
+            attr("claim_amount", claim_amount),
+        ]))
+}
+
+fn assert_vesting_schedule(schedule: &VestingSchedule) -> Result<(), ContractError> {
+    if schedule.amount.is_zero()
+        || schedule.start_time >= schedule.end_time
+        || schedule.cliff_time < schedule.start_time
+        || schedule.cliff_time > schedule.end_time
+        || schedule.unlock_period == 0
+        || schedule.unlock_period > schedule.end_time - schedule.start_time
+    {
+        return Err(ContractError::InvalidVestingSchedule {});
+    }
+
+    Ok(())
+}
+
+/// Amount unlocked at the given time; tokens unlock in steps of the
+/// unlock period counted from the start, and the steps elapsed before
+/// the cliff are released at once when it is reached
+fn compute_vested_amount(schedule: &VestingSchedule, time: u64) -> Uint256 {
+    if time < schedule.cliff_time {
+        return Uint256::zero();
+    }
+
+    if time >= schedule.end_time {
+        return schedule.amount;
+    }
+
+    let elapsed = time - schedule.start_time;
+    let unlocked_period = elapsed / schedule.unlock_period * schedule.unlock_period;
+    schedule.amount
+        * Decimal256::from_ratio(unlocked_period, schedule.end_time - schedule.start_time)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::VestingAccount { address } => {
+            to_binary(&query_vesting_account(deps, env, address)?)
+        }
+        QueryMsg::VestingAccounts { start_after, limit } => {
+            to_binary(&query_vesting_accounts(deps, env, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config: Config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        anchor_token: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+    })
+}
+
+fn query_vesting_account(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<VestingAccountResponse> {
+    let address_raw = deps.api.addr_canonicalize(&address)?;
+    let vesting_info: VestingInfo = read_vesting_info(deps.storage, &address_raw)?;
+
+    Ok(vesting_account_response(
+        address,
+        vesting_info,
+        env.block.time.seconds(),
+    ))
+}
+
+fn query_vesting_accounts(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VestingAccountsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(deps.api.addr_canonicalize(&start_after)?)
+    } else {
+        None
+    };
+
+    let vesting_accounts = read_vesting_infos(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(address_raw, vesting_info)| {
+            Ok(vesting_account_response(
+                deps.api.addr_humanize(&address_raw)?.to_string(),
+                vesting_info,
+                env.block.time.seconds(),
+            ))
+        })
+        .collect::<StdResult<Vec<VestingAccountResponse>>>()?;
+
+    Ok(VestingAccountsResponse { vesting_accounts })
+}
+
+fn vesting_account_response(
+    address: String,
+    vesting_info: VestingInfo,
+    time: u64,
+) -> VestingAccountResponse {
+    let vested_amount = compute_vested_amount(&vesting_info.schedule, time);
+    VestingAccountResponse {
+        address,
+        schedule: vesting_info.schedule,
+        vested_amount,
+        claimed_amount: vesting_info.claimed_amount,
+        claimable_amount: vested_amount - vesting_info.claimed_amount,
+    }
+}
@@ -0,0 +1,83 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use moneymarket::vesting::VestingSchedule;
+
+static KEY_CONFIG: &[u8] = b"config";
+
+static PREFIX_VESTING_INFO: &[u8] = b"vesting_info";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub anchor_token: CanonicalAddr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingInfo {
+    pub schedule: VestingSchedule,
+    pub claimed_amount: Uint256,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_vesting_info(
+    storage: &mut dyn Storage,
+    address: &CanonicalAddr,
+    vesting_info: &VestingInfo,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_VESTING_INFO).save(address.as_slice(), vesting_info)
+}
+
+pub fn read_vesting_info(storage: &dyn Storage, address: &CanonicalAddr) -> StdResult<VestingInfo> {
+    ReadonlyBucket::new(storage, PREFIX_VESTING_INFO).load(address.as_slice())
+}
+
+pub fn has_vesting_info(storage: &dyn Storage, address: &CanonicalAddr) -> bool {
+    ReadonlyBucket::<VestingInfo>::new(storage, PREFIX_VESTING_INFO)
+        .may_load(address.as_slice())
+        .unwrap_or(None)
+        .is_some()
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_vesting_infos(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, VestingInfo)>> {
+    let vesting_infos: ReadonlyBucket<VestingInfo> =
+        ReadonlyBucket::new(storage, PREFIX_VESTING_INFO);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    vesting_infos
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, vesting_info) = elem?;
+            Ok((CanonicalAddr::from(k), vesting_info))
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
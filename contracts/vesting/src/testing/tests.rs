@@ -0,0 +1,379 @@
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, CosmosMsg, Env, SubMsg, Timestamp, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use moneymarket::vesting::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VestingAccount, VestingAccountResponse,
+    VestingAccountsResponse, VestingSchedule,
+};
+
+fn init_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        anchor_token: "anchor0000".to_string(),
+    }
+}
+
+fn env_at(time: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(time);
+    env
+}
+
+fn schedule() -> VestingSchedule {
+    VestingSchedule {
+        start_time: 1_000,
+        end_time: 2_000,
+        cliff_time: 1_300,
+        unlock_period: 100,
+        amount: Uint256::from(1_000_000u64),
+    }
+}
+
+fn register_msg() -> ExecuteMsg {
+    ExecuteMsg::RegisterVestingAccounts {
+        vesting_accounts: vec![
+            VestingAccount {
+                address: "addr0000".to_string(),
+                schedule: schedule(),
+            },
+            VestingAccount {
+                address: "addr0001".to_string(),
+                schedule: VestingSchedule {
+                    cliff_time: 1_000,
+                    unlock_period: 1_000,
+                    ..schedule()
+                },
+            },
+        ],
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            anchor_token: "anchor0000".to_string(),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: Some("owner0001".to_string()),
+        },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            owner: Some("owner0001".to_string()),
+        },
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.owner, "owner0001".to_string());
+}
+
+#[test]
+fn register_vesting_accounts() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        register_msg(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // the cliff cannot come before the start
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: "addr0000".to_string(),
+                schedule: VestingSchedule {
+                    cliff_time: 900,
+                    ..schedule()
+                },
+            }],
+        },
+    );
+    match res {
+        Err(ContractError::InvalidVestingSchedule {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        register_msg(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "register_vesting_accounts")]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        register_msg(),
+    );
+    match res {
+        Err(ContractError::VestingAccountAlreadyRegistered(address)) => {
+            assert_eq!(address, "addr0000")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res: VestingAccountsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(1_500),
+            QueryMsg::VestingAccounts {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.vesting_accounts,
+        vec![
+            VestingAccountResponse {
+                address: "addr0000".to_string(),
+                schedule: schedule(),
+                vested_amount: Uint256::from(500_000u64),
+                claimed_amount: Uint256::zero(),
+                claimable_amount: Uint256::from(500_000u64),
+            },
+            VestingAccountResponse {
+                address: "addr0001".to_string(),
+                schedule: VestingSchedule {
+                    cliff_time: 1_000,
+                    unlock_period: 1_000,
+                    ..schedule()
+                },
+                vested_amount: Uint256::zero(),
+                claimed_amount: Uint256::zero(),
+                claimable_amount: Uint256::zero(),
+            },
+        ]
+    );
+
+    let res: VestingAccountsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(1_500),
+            QueryMsg::VestingAccounts {
+                start_after: Some("addr0000".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.vesting_accounts.len(), 1);
+    assert_eq!(res.vesting_accounts[0].address, "addr0001".to_string());
+}
+
+#[test]
+fn vested_amount() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        register_msg(),
+    )
+    .unwrap();
+
+    let vested_amount_at = |time: u64| -> Uint256 {
+        let res: VestingAccountResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env_at(time),
+                QueryMsg::VestingAccount {
+                    address: "addr0000".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.vested_amount
+    };
+
+    // nothing vests before the cliff
+    assert_eq!(vested_amount_at(999), Uint256::zero());
+    assert_eq!(vested_amount_at(1_299), Uint256::zero());
+
+    // the periods before the cliff unlock at once
+    assert_eq!(vested_amount_at(1_300), Uint256::from(300_000u64));
+    assert_eq!(vested_amount_at(1_399), Uint256::from(300_000u64));
+    assert_eq!(vested_amount_at(1_400), Uint256::from(400_000u64));
+    assert_eq!(vested_amount_at(1_999), Uint256::from(900_000u64));
+    assert_eq!(vested_amount_at(2_000), Uint256::from(1_000_000u64));
+    assert_eq!(vested_amount_at(3_000), Uint256::from(1_000_000u64));
+}
+
+#[test]
+fn claim() {
+    let mut deps = mock_dependencies(&[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        init_msg(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        register_msg(),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(1_200),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Claim {},
+    );
+    match res {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(1_450),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Claim {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "anchor0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(400_000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("address", "addr0000"),
+            attr("claim_amount", "400000"),
+        ]
+    );
+
+    // claimed tokens cannot be claimed twice
+    let res = execute(
+        deps.as_mut(),
+        env_at(1_499),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Claim {},
+    );
+    match res {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env_at(2_500),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::Claim {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("address", "addr0000"),
+            attr("claim_amount", "600000"),
+        ]
+    );
+
+    let res: VestingAccountResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(2_500),
+            QueryMsg::VestingAccount {
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.claimed_amount, Uint256::from(1_000_000u64));
+    assert_eq!(res.claimable_amount, Uint256::zero());
+
+    // accounts without a schedule cannot claim
+    let res = execute(
+        deps.as_mut(),
+        env_at(2_500),
+        mock_info("addr0002", &[]),
+        ExecuteMsg::Claim {},
+    );
+    assert!(res.is_err());
+}
@@ -0,0 +1,612 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Bound;
+
+use cosmwasm_std::testing::MockApi;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, Attribute, BalanceResponse, BankMsg, BankQuery, Binary, BlockInfo,
+    Coin, ContractInfo, ContractResult, CosmosMsg, Deps, DepsMut, Env, Event, MessageInfo, Order,
+    Pair, Querier, QuerierResult, QuerierWrapper, QueryRequest, Reply, ReplyOn, Response, StdError,
+    StdResult, Storage, SubMsgExecutionResponse, SystemError, SystemResult, Timestamp, Uint128,
+    WasmMsg, WasmQuery,
+};
+use serde::de::DeserializeOwned;
+use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper};
+
+pub type AppResult<T> = Result<T, String>;
+
+type InstantiateFn = Box<dyn Fn(DepsMut, Env, MessageInfo, &[u8]) -> AppResult<Response>>;
+type ExecuteFn = Box<dyn Fn(DepsMut, Env, MessageInfo, &[u8]) -> AppResult<Response>>;
+type QueryFn = Box<dyn Fn(Deps, Env, &[u8]) -> StdResult<Binary>>;
+type ReplyFn = Box<dyn Fn(DepsMut, Env, Reply) -> AppResult<Response>>;
+
+/// Entry points of a stored contract code
+pub struct ContractCode {
+    instantiate: InstantiateFn,
+    execute: ExecuteFn,
+    query: QueryFn,
+    reply: Option<ReplyFn>,
+}
+
+impl ContractCode {
+    pub fn new<I, X, Q, E1, E2>(
+        instantiate: fn(DepsMut, Env, MessageInfo, I) -> Result<Response, E1>,
+        execute: fn(DepsMut, Env, MessageInfo, X) -> Result<Response, E2>,
+        query: fn(Deps, Env, Q) -> StdResult<Binary>,
+    ) -> Self
+    where
+        I: DeserializeOwned + 'static,
+        X: DeserializeOwned + 'static,
+        Q: DeserializeOwned + 'static,
+        E1: ToString + 'static,
+        E2: ToString + 'static,
+    {
+        ContractCode {
+            instantiate: Box::new(move |deps, env, info, msg| {
+                let msg: I = from_slice(msg).map_err(|e| e.to_string())?;
+                instantiate(deps, env, info, msg).map_err(|e| e.to_string())
+            }),
+            execute: Box::new(move |deps, env, info, msg| {
+                let msg: X = from_slice(msg).map_err(|e| e.to_string())?;
+                execute(deps, env, info, msg).map_err(|e| e.to_string())
+            }),
+            query: Box::new(move |deps, env, msg| query(deps, env, from_slice(msg)?)),
+            reply: None,
+        }
+    }
+
+    pub fn with_reply<E>(mut self, reply: fn(DepsMut, Env, Reply) -> Result<Response, E>) -> Self
+    where
+        E: ToString + 'static,
+    {
+        self.reply = Some(Box::new(move |deps, env, msg| {
+            reply(deps, env, msg).map_err(|e| e.to_string())
+        }));
+        self
+    }
+}
+
+/// In-memory contract storage that can be snapshotted,
+/// so a failed transaction leaves no trace
+#[derive(Clone, Default)]
+struct ContractStorage(BTreeMap<Vec<u8>, Vec<u8>>);
+
+impl Storage for ContractStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.get(key).cloned()
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Pair> + 'a> {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start >= end {
+                return Box::new(std::iter::empty());
+            }
+        }
+
+        let start = start.map_or(Bound::Unbounded, |s| Bound::Included(s.to_vec()));
+        let end = end.map_or(Bound::Unbounded, |e| Bound::Excluded(e.to_vec()));
+        let iter = self
+            .0
+            .range((start, end))
+            .map(|(k, v)| (k.clone(), v.clone()));
+        match order {
+            Order::Ascending => Box::new(iter),
+            Order::Descending => Box::new(iter.rev()),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.0.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.0.remove(key);
+    }
+}
+
+/// Handle a contract call works through. Every access borrows
+/// the storage only briefly, so other contracts can query the
+/// caller back while it runs, as they can on chain.
+struct StorageRef<'a>(&'a RefCell<ContractStorage>);
+
+impl<'a> Storage for StorageRef<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.borrow().get(key)
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Pair> + 'b> {
+        let pairs: Vec<Pair> = self.0.borrow().range(start, end, order).collect();
+        Box::new(pairs.into_iter())
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.0.borrow_mut().set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.0.borrow_mut().remove(key)
+    }
+}
+
+struct ContractInstance {
+    code_id: u64,
+    storage: RefCell<ContractStorage>,
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    storages: HashMap<String, ContractStorage>,
+    balances: BTreeMap<(String, String), Uint128>,
+}
+
+/// A single-node chain running the contracts natively.
+/// Messages are dispatched depth-first like wasmd does,
+/// and every contract sees the others through its querier.
+/// Terra tax queries always answer a zero rate.
+pub struct App {
+    block: RefCell<BlockInfo>,
+    api: MockApi,
+    codes: Vec<ContractCode>,
+    contracts: RefCell<HashMap<String, ContractInstance>>,
+    balances: RefCell<BTreeMap<(String, String), Uint128>>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        App {
+            block: RefCell::new(BlockInfo {
+                height: 12_345,
+                time: Timestamp::from_seconds(1_571_797_419),
+                chain_id: "columbus-5".to_string(),
+            }),
+            api: MockApi::default(),
+            codes: vec![],
+            contracts: RefCell::new(HashMap::new()),
+            balances: RefCell::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl App {
+    pub fn block_info(&self) -> BlockInfo {
+        self.block.borrow().clone()
+    }
+
+    /// Move to the next block, `seconds` later
+    pub fn next_block(&self, seconds: u64) {
+        let mut block = self.block.borrow_mut();
+        block.height += 1;
+        block.time = block.time.plus_seconds(seconds);
+    }
+
+    pub fn store_code(&mut self, code: ContractCode) -> u64 {
+        self.codes.push(code);
+        self.codes.len() as u64
+    }
+
+    /// Credit native coins to an account out of thin air
+    pub fn mint_native(&self, addr: &str, coins: &[Coin]) {
+        let mut balances = self.balances.borrow_mut();
+        for coin in coins {
+            *balances
+                .entry((addr.to_string(), coin.denom.clone()))
+                .or_default() += coin.amount;
+        }
+    }
+
+    pub fn native_balance(&self, addr: &str, denom: &str) -> Uint128 {
+        self.balances
+            .borrow()
+            .get(&(addr.to_string(), denom.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn query_wasm<T: DeserializeOwned>(
+        &self,
+        contract: &Addr,
+        msg: &impl serde::Serialize,
+    ) -> StdResult<T> {
+        QuerierWrapper::new(self).query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: contract.to_string(),
+            msg: to_binary(msg)?,
+        }))
+    }
+
+    /// Instantiate a stored code at `label`, which doubles as the address
+    pub fn instantiate(
+        &self,
+        sender: &str,
+        code_id: u64,
+        label: &str,
+        msg: &impl serde::Serialize,
+        funds: &[Coin],
+    ) -> AppResult<Addr> {
+        let msg = to_binary(msg).map_err(|e| e.to_string())?;
+        self.transact(|| {
+            self.instantiate_contract(&Addr::unchecked(sender), code_id, label, &msg, funds)
+                .map(|(addr, _)| addr)
+        })
+    }
+
+    /// Execute a message as a transaction; state is rolled back on failure
+    pub fn execute(
+        &self,
+        sender: &str,
+        contract: &Addr,
+        msg: &impl serde::Serialize,
+        funds: &[Coin],
+    ) -> AppResult<Vec<Event>> {
+        let msg = to_binary(msg).map_err(|e| e.to_string())?;
+        self.transact(|| self.execute_contract(&Addr::unchecked(sender), contract, &msg, funds))
+    }
+
+    fn transact<T>(&self, f: impl FnOnce() -> AppResult<T>) -> AppResult<T> {
+        let snapshot = self.snapshot();
+        let res = f();
+        if res.is_err() {
+            self.restore(snapshot);
+        }
+        res
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            storages: self
+                .contracts
+                .borrow()
+                .iter()
+                .map(|(addr, contract)| (addr.clone(), contract.storage.borrow().clone()))
+                .collect(),
+            balances: self.balances.borrow().clone(),
+        }
+    }
+
+    fn restore(&self, snapshot: Snapshot) {
+        let mut contracts = self.contracts.borrow_mut();
+        contracts.retain(|addr, _| snapshot.storages.contains_key(addr));
+        for (addr, storage) in snapshot.storages {
+            *contracts[&addr].storage.borrow_mut() = storage;
+        }
+        *self.balances.borrow_mut() = snapshot.balances;
+    }
+
+    fn env(&self, contract: &Addr) -> Env {
+        Env {
+            block: self.block_info(),
+            contract: ContractInfo {
+                address: contract.clone(),
+            },
+        }
+    }
+
+    fn transfer(&self, from: &Addr, to: &Addr, coins: &[Coin]) -> AppResult<()> {
+        let mut balances = self.balances.borrow_mut();
+        for coin in coins {
+            let from_key = (from.to_string(), coin.denom.clone());
+            let from_balance = balances.get(&from_key).copied().unwrap_or_default();
+            let left = from_balance.checked_sub(coin.amount).map_err(|_| {
+                format!(
+                    "{} has insufficient funds: {}{} < {}",
+                    from, from_balance, coin.denom, coin
+                )
+            })?;
+            balances.insert(from_key, left);
+            *balances
+                .entry((to.to_string(), coin.denom.clone()))
+                .or_default() += coin.amount;
+        }
+        Ok(())
+    }
+
+    fn instantiate_contract(
+        &self,
+        sender: &Addr,
+        code_id: u64,
+        label: &str,
+        msg: &[u8],
+        funds: &[Coin],
+    ) -> AppResult<(Addr, Vec<Event>)> {
+        let code = self
+            .codes
+            .get((code_id as usize).wrapping_sub(1))
+            .ok_or_else(|| format!("no code with id {}", code_id))?;
+
+        let address = {
+            let mut contracts = self.contracts.borrow_mut();
+            let address = if label.is_empty() {
+                format!("contract{}", contracts.len())
+            } else {
+                label.to_string()
+            };
+            if contracts.contains_key(&address) {
+                return Err(format!("contract {} already exists", address));
+            }
+            contracts.insert(
+                address.clone(),
+                ContractInstance {
+                    code_id,
+                    storage: RefCell::new(ContractStorage::default()),
+                },
+            );
+            Addr::unchecked(address)
+        };
+
+        self.transfer(sender, &address, funds)?;
+        let res = self.with_storage(&address, |storage| {
+            (code.instantiate)(
+                DepsMut {
+                    storage,
+                    api: &self.api,
+                    querier: QuerierWrapper::new(self),
+                },
+                self.env(&address),
+                MessageInfo {
+                    sender: sender.clone(),
+                    funds: funds.to_vec(),
+                },
+                msg,
+            )
+        })?;
+
+        let (events, _) = self.process_response(&address, res)?;
+        Ok((address, events))
+    }
+
+    fn execute_contract(
+        &self,
+        sender: &Addr,
+        contract: &Addr,
+        msg: &[u8],
+        funds: &[Coin],
+    ) -> AppResult<Vec<Event>> {
+        self.transfer(sender, contract, funds)?;
+
+        let code = self.code_of(contract)?;
+        let res = self.with_storage(contract, |storage| {
+            (code.execute)(
+                DepsMut {
+                    storage,
+                    api: &self.api,
+                    querier: QuerierWrapper::new(self),
+                },
+                self.env(contract),
+                MessageInfo {
+                    sender: sender.clone(),
+                    funds: funds.to_vec(),
+                },
+                msg,
+            )
+        })?;
+
+        self.process_response(contract, res)
+            .map(|(events, _)| events)
+    }
+
+    fn code_of(&self, contract: &Addr) -> AppResult<&ContractCode> {
+        let code_id = self
+            .contracts
+            .borrow()
+            .get(contract.as_str())
+            .map(|c| c.code_id)
+            .ok_or_else(|| format!("no contract at {}", contract))?;
+        Ok(&self.codes[code_id as usize - 1])
+    }
+
+    /// Lend the contract storage out for the duration of one call
+    fn with_storage<T>(
+        &self,
+        contract: &Addr,
+        f: impl FnOnce(&mut dyn Storage) -> AppResult<T>,
+    ) -> AppResult<T> {
+        let contracts = self.contracts.borrow();
+        let instance = contracts
+            .get(contract.as_str())
+            .ok_or_else(|| format!("no contract at {}", contract))?;
+        f(&mut StorageRef(&instance.storage))
+    }
+
+    /// Run the messages a contract returned, depth-first,
+    /// handing sub-message results back to its reply entry point
+    fn process_response(
+        &self,
+        contract: &Addr,
+        res: Response,
+    ) -> AppResult<(Vec<Event>, Option<Binary>)> {
+        let mut events = vec![wasm_event(contract, res.attributes)];
+        events.extend(res.events);
+        let mut data = res.data;
+
+        for sub_msg in res.messages {
+            let snapshot = self.snapshot();
+            let reply_id = sub_msg.id;
+            let result = self.dispatch(contract, sub_msg.msg);
+            let reply_result = match (result, &sub_msg.reply_on) {
+                (Ok((sub_events, sub_data)), ReplyOn::Always)
+                | (Ok((sub_events, sub_data)), ReplyOn::Success) => {
+                    ContractResult::Ok(SubMsgExecutionResponse {
+                        events: sub_events,
+                        data: sub_data,
+                    })
+                }
+                (Ok((sub_events, _)), _) => {
+                    events.extend(sub_events);
+                    continue;
+                }
+                (Err(err), ReplyOn::Always) | (Err(err), ReplyOn::Error) => {
+                    self.restore(snapshot);
+                    ContractResult::Err(err)
+                }
+                (Err(err), _) => return Err(err),
+            };
+
+            let code = self.code_of(contract)?;
+            let reply = code
+                .reply
+                .as_ref()
+                .ok_or_else(|| format!("{} has no reply entry point", contract))?;
+            let reply_res = self.with_storage(contract, |storage| {
+                reply(
+                    DepsMut {
+                        storage,
+                        api: &self.api,
+                        querier: QuerierWrapper::new(self),
+                    },
+                    self.env(contract),
+                    Reply {
+                        id: reply_id,
+                        result: reply_result,
+                    },
+                )
+            })?;
+            let (reply_events, reply_data) = self.process_response(contract, reply_res)?;
+            events.extend(reply_events);
+            if reply_data.is_some() {
+                data = reply_data;
+            }
+        }
+
+        Ok((events, data))
+    }
+
+    fn dispatch(&self, sender: &Addr, msg: CosmosMsg) -> AppResult<(Vec<Event>, Option<Binary>)> {
+        match msg {
+            CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                self.transfer(sender, &Addr::unchecked(to_address), &amount)?;
+                Ok((vec![], None))
+            }
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => Ok((
+                self.execute_contract(sender, &Addr::unchecked(contract_addr), &msg, &funds)?,
+                None,
+            )),
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id,
+                msg,
+                funds,
+                label,
+                ..
+            }) => {
+                let (address, events) =
+                    self.instantiate_contract(sender, code_id, &label, &msg, &funds)?;
+                Ok((events, Some(instantiate_response_data(address.as_str()))))
+            }
+            msg => Err(format!("unsupported message: {:?}", msg)),
+        }
+    }
+
+    fn query_contract(&self, contract_addr: &str, msg: &[u8]) -> StdResult<Binary> {
+        let contract = Addr::unchecked(contract_addr);
+        let code = self.code_of(&contract).map_err(StdError::generic_err)?;
+        let contracts = self.contracts.borrow();
+        (code.query)(
+            Deps {
+                storage: &StorageRef(&contracts[contract_addr].storage),
+                api: &self.api,
+                querier: QuerierWrapper::new(self),
+            },
+            self.env(&contract),
+            msg,
+        )
+    }
+}
+
+impl Querier for App {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+            Ok(v) => v,
+            Err(e) => {
+                return SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Parsing query request: {}", e),
+                    request: bin_request.into(),
+                })
+            }
+        };
+
+        let res = match request {
+            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
+                to_binary(&BalanceResponse {
+                    amount: Coin {
+                        amount: self.native_balance(&address, &denom),
+                        denom,
+                    },
+                })
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                self.query_contract(&contract_addr, &msg)
+            }
+            QueryRequest::Custom(TerraQueryWrapper { query_data, .. }) => match query_data {
+                TerraQuery::TaxRate {} => to_binary(&TaxRateResponse {
+                    rate: Default::default(),
+                }),
+                TerraQuery::TaxCap { .. } => to_binary(&TaxCapResponse {
+                    cap: Uint128::zero(),
+                }),
+                _ => {
+                    return SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: "terra".to_string(),
+                    })
+                }
+            },
+            _ => {
+                return SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: format!("{:?}", request),
+                })
+            }
+        };
+
+        SystemResult::Ok(ContractResult::from(res))
+    }
+}
+
+fn wasm_event(contract: &Addr, attributes: Vec<Attribute>) -> Event {
+    Event::new("wasm")
+        .add_attribute("_contract_address", contract)
+        .add_attributes(attributes)
+}
+
+/// Protobuf encoding of `MsgInstantiateContractResponse`,
+/// which carries the new address in field 1
+fn instantiate_response_data(address: &str) -> Binary {
+    let mut data = vec![0x0a];
+    let mut len = address.len();
+    while len >= 0x80 {
+        data.push((len as u8 & 0x7f) | 0x80);
+        len >>= 7;
+    }
+    data.push(len as u8);
+    data.extend_from_slice(address.as_bytes());
+    Binary(data)
+}
+
+/// Attribute value of the first `wasm` event emitted by `contract`
+/// with the given key
+pub fn event_attribute(events: &[Event], contract: &Addr, key: &str) -> Option<String> {
+    events
+        .iter()
+        .filter(|e| e.ty == "wasm")
+        .filter(|e| {
+            e.attributes
+                .iter()
+                .any(|a| a.key == "_contract_address" && a.value == contract.as_str())
+        })
+        .find_map(|e| {
+            e.attributes
+                .iter()
+                .find(|a| a.key == key)
+                .map(|a| a.value.clone())
+        })
+}
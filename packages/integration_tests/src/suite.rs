@@ -0,0 +1,550 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{to_binary, Addr, Coin, Event, Uint128};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+
+use moneymarket::custody::Cw20HookMsg as CustodyCw20HookMsg;
+use moneymarket::custody_cw20::InstantiateMsg as CustodyInstantiateMsg;
+use moneymarket::custody_native::{
+    ExecuteMsg as NativeCustodyExecuteMsg, InstantiateMsg as NativeCustodyInstantiateMsg,
+};
+use moneymarket::distribution_model::InstantiateMsg as DistributionModelInstantiateMsg;
+use moneymarket::interest_model::InstantiateMsg as InterestModelInstantiateMsg;
+use moneymarket::liquidation::{
+    ExecuteMsg as LiquidationExecuteMsg, InstantiateMsg as LiquidationInstantiateMsg,
+};
+use moneymarket::market::{
+    BorrowerInfoResponse, ConfigResponse as MarketConfigResponse, Cw20HookMsg as MarketCw20HookMsg,
+    ExecuteMsg as MarketExecuteMsg, InstantiateMsg as MarketInstantiateMsg,
+    QueryMsg as MarketQueryMsg,
+};
+use moneymarket::oracle::{ExecuteMsg as OracleExecuteMsg, InstantiateMsg as OracleInstantiateMsg};
+use moneymarket::overseer::{
+    ExecuteMsg as OverseerExecuteMsg, InstantiateMsg as OverseerInstantiateMsg,
+};
+
+use crate::app::{App, AppResult, ContractCode};
+use crate::token;
+
+pub const OWNER: &str = "owner";
+pub const STABLE_DENOM: &str = "uusd";
+pub const COLLECTOR: &str = "collector";
+pub const DISTRIBUTOR: &str = "distributor";
+pub const COLLATERAL_DENOM: &str = "uluna";
+
+/// One money market with a CW20 and a native collateral,
+/// wired together the way it is deployed
+pub struct Suite {
+    pub app: App,
+    pub market: Addr,
+    pub aterra: Addr,
+    pub overseer: Addr,
+    pub custody: Addr,
+    pub collateral_token: Addr,
+    pub native_custody: Addr,
+    pub oracle: Addr,
+    pub liquidation: Addr,
+    pub interest_model: Addr,
+    pub distribution_model: Addr,
+}
+
+impl Suite {
+    /// Deploy the protocol with a zero borrow rate, a 50% max LTV
+    /// and collateral prices of one stable unit
+    pub fn new() -> Self {
+        let mut app = App::default();
+
+        let token_code = app.store_code(ContractCode::new(
+            token::instantiate,
+            token::execute,
+            token::query,
+        ));
+        let market_code = app.store_code(
+            ContractCode::new(
+                moneymarket_market::contract::instantiate,
+                moneymarket_market::contract::execute,
+                moneymarket_market::contract::query,
+            )
+            .with_reply(moneymarket_market::contract::reply),
+        );
+        let overseer_code = app.store_code(ContractCode::new(
+            moneymarket_overseer::contract::instantiate,
+            moneymarket_overseer::contract::execute,
+            moneymarket_overseer::contract::query,
+        ));
+        let custody_code = app.store_code(ContractCode::new(
+            moneymarket_custody_cw20::contract::instantiate,
+            moneymarket_custody_cw20::contract::execute,
+            moneymarket_custody_cw20::contract::query,
+        ));
+        let native_custody_code = app.store_code(ContractCode::new(
+            moneymarket_custody_native::contract::instantiate,
+            moneymarket_custody_native::contract::execute,
+            moneymarket_custody_native::contract::query,
+        ));
+        let oracle_code = app.store_code(ContractCode::new(
+            moneymarket_oracle::contract::instantiate,
+            moneymarket_oracle::contract::execute,
+            moneymarket_oracle::contract::query,
+        ));
+        let liquidation_code = app.store_code(ContractCode::new(
+            moneymarket_liquidation::contract::instantiate,
+            moneymarket_liquidation::contract::execute,
+            moneymarket_liquidation::contract::query,
+        ));
+        let interest_model_code = app.store_code(ContractCode::new(
+            moneymarket_interest_model::contract::instantiate,
+            moneymarket_interest_model::contract::execute,
+            moneymarket_interest_model::contract::query,
+        ));
+        let distribution_model_code = app.store_code(ContractCode::new(
+            moneymarket_distribution_model::contract::instantiate,
+            moneymarket_distribution_model::contract::execute,
+            moneymarket_distribution_model::contract::query,
+        ));
+
+        let oracle = app
+            .instantiate(
+                OWNER,
+                oracle_code,
+                "oracle",
+                &OracleInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    base_asset: STABLE_DENOM.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+
+        let interest_model = app
+            .instantiate(
+                OWNER,
+                interest_model_code,
+                "interest_model",
+                &InterestModelInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    base_rate: Decimal256::zero(),
+                    interest_multiplier: Decimal256::zero(),
+                    optimal_utilization: None,
+                    slope_2: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let distribution_model = app
+            .instantiate(
+                OWNER,
+                distribution_model_code,
+                "distribution_model",
+                &DistributionModelInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    emission_cap: Decimal256::from_uint256(100u64),
+                    emission_floor: Decimal256::from_uint256(10u64),
+                    increment_multiplier: Decimal256::percent(110),
+                    decrement_multiplier: Decimal256::percent(90),
+                },
+                &[],
+            )
+            .unwrap();
+
+        let initial_deposit = Coin {
+            denom: STABLE_DENOM.to_string(),
+            amount: Uint128::from(moneymarket_market::contract::INITIAL_DEPOSIT_AMOUNT),
+        };
+        app.mint_native(OWNER, std::slice::from_ref(&initial_deposit));
+        let market = app
+            .instantiate(
+                OWNER,
+                market_code,
+                "market",
+                &MarketInstantiateMsg {
+                    owner_addr: OWNER.to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    stable_token: None,
+                    aterra_code_id: token_code,
+                    anc_emission_rate: Decimal256::zero(),
+                    max_borrow_factor: Decimal256::one(),
+                    stable_decimals: None,
+                    aterra_name: None,
+                    aterra_symbol: None,
+                    market_id: None,
+                    additional_denoms: None,
+                },
+                &[initial_deposit],
+            )
+            .unwrap();
+
+        let liquidation = app
+            .instantiate(
+                OWNER,
+                liquidation_code,
+                "liquidation",
+                &LiquidationInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    oracle_contract: oracle.to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    safe_ratio: Decimal256::percent(80),
+                    bid_fee: Decimal256::zero(),
+                    max_premium_rate: Decimal256::percent(30),
+                    liquidation_threshold: Uint256::from(500u64),
+                    price_timeframe: 60u64,
+                },
+                &[],
+            )
+            .unwrap();
+
+        let overseer = app
+            .instantiate(
+                OWNER,
+                overseer_code,
+                "overseer",
+                &OverseerInstantiateMsg {
+                    owner_addr: OWNER.to_string(),
+                    oracle_contract: oracle.to_string(),
+                    market_contract: market.to_string(),
+                    liquidation_contract: liquidation.to_string(),
+                    collector_contract: COLLECTOR.to_string(),
+                    stable_denom: STABLE_DENOM.to_string(),
+                    epoch_period: 86400u64,
+                    threshold_deposit_rate: Decimal256::permille(3),
+                    target_deposit_rate: Decimal256::permille(5),
+                    buffer_distribution_factor: Decimal256::percent(20),
+                    anc_purchase_factor: Decimal256::percent(20),
+                    price_timeframe: 60u64,
+                    market_denom: None,
+                },
+                &[],
+            )
+            .unwrap();
+
+        app.execute(
+            OWNER,
+            &market,
+            &MarketExecuteMsg::RegisterContracts {
+                overseer_contract: overseer.to_string(),
+                interest_model: interest_model.to_string(),
+                distribution_model: distribution_model.to_string(),
+                collector_contract: COLLECTOR.to_string(),
+                distributor_contract: DISTRIBUTOR.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let market_config: MarketConfigResponse =
+            app.query_wasm(&market, &MarketQueryMsg::Config {}).unwrap();
+        let aterra = Addr::unchecked(market_config.aterra_contract);
+
+        let collateral_token = app
+            .instantiate(
+                OWNER,
+                token_code,
+                "bluna",
+                &TokenInstantiateMsg {
+                    name: "bonded luna".to_string(),
+                    symbol: "BLUNA".to_string(),
+                    decimals: 6u8,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: OWNER.to_string(),
+                        cap: None,
+                    }),
+                },
+                &[],
+            )
+            .unwrap();
+
+        let custody = app
+            .instantiate(
+                OWNER,
+                custody_code,
+                "custody_bluna",
+                &CustodyInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    collateral_token: collateral_token.to_string(),
+                    overseer_contract: overseer.to_string(),
+                    market_contract: market.to_string(),
+                    liquidation_contract: liquidation.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+
+        app.execute(
+            OWNER,
+            &overseer,
+            &OverseerExecuteMsg::Whitelist {
+                name: "bonded luna".to_string(),
+                symbol: "BLUNA".to_string(),
+                collateral_token: collateral_token.to_string(),
+                custody_contract: custody.to_string(),
+                max_ltv: Decimal256::percent(50),
+                max_collateral: None,
+                unlock_delay: None,
+                unlock_delay_threshold: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let native_custody = app
+            .instantiate(
+                OWNER,
+                native_custody_code,
+                "custody_luna",
+                &NativeCustodyInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    collateral_denom: COLLATERAL_DENOM.to_string(),
+                    overseer_contract: overseer.to_string(),
+                    market_contract: market.to_string(),
+                    liquidation_contract: liquidation.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+
+        app.execute(
+            OWNER,
+            &overseer,
+            &OverseerExecuteMsg::Whitelist {
+                name: "luna".to_string(),
+                symbol: "LUNA".to_string(),
+                collateral_token: COLLATERAL_DENOM.to_string(),
+                custody_contract: native_custody.to_string(),
+                max_ltv: Decimal256::percent(50),
+                max_collateral: None,
+                unlock_delay: None,
+                unlock_delay_threshold: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        for asset in [collateral_token.to_string(), COLLATERAL_DENOM.to_string()] {
+            app.execute(
+                OWNER,
+                &oracle,
+                &OracleExecuteMsg::RegisterFeeder {
+                    asset,
+                    feeder: OWNER.to_string(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        let suite = Suite {
+            app,
+            market,
+            aterra,
+            overseer,
+            custody,
+            collateral_token,
+            native_custody,
+            oracle,
+            liquidation,
+            interest_model,
+            distribution_model,
+        };
+        suite.feed_price(Decimal256::one()).unwrap();
+        suite
+    }
+
+    pub fn stable(amount: u128) -> Vec<Coin> {
+        vec![Coin {
+            denom: STABLE_DENOM.to_string(),
+            amount: Uint128::from(amount),
+        }]
+    }
+
+    pub fn stable_balance(&self, addr: &str) -> Uint128 {
+        self.app.native_balance(addr, STABLE_DENOM)
+    }
+
+    pub fn token_balance(&self, token: &Addr, addr: &str) -> Uint128 {
+        let res: BalanceResponse = self
+            .app
+            .query_wasm(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    }
+
+    /// Price of both collaterals in the stable denom
+    pub fn feed_price(&self, price: Decimal256) -> AppResult<Vec<Event>> {
+        self.app.execute(
+            OWNER,
+            &self.oracle,
+            &OracleExecuteMsg::FeedPrice {
+                prices: vec![
+                    (self.collateral_token.to_string(), price),
+                    (COLLATERAL_DENOM.to_string(), price),
+                ],
+            },
+            &[],
+        )
+    }
+
+    pub fn mint_collateral(&self, recipient: &str, amount: u128) {
+        self.app
+            .execute(
+                OWNER,
+                &self.collateral_token,
+                &Cw20ExecuteMsg::Mint {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::from(amount),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn deposit_stable(&self, depositor: &str, amount: u128) -> AppResult<Vec<Event>> {
+        self.app.execute(
+            depositor,
+            &self.market,
+            &MarketExecuteMsg::DepositStable {
+                recipient: None,
+                deadline: None,
+            },
+            &Suite::stable(amount),
+        )
+    }
+
+    pub fn redeem_stable(&self, depositor: &str, aterra_amount: u128) -> AppResult<Vec<Event>> {
+        self.app.execute(
+            depositor,
+            &self.aterra,
+            &Cw20ExecuteMsg::Send {
+                contract: self.market.to_string(),
+                amount: Uint128::from(aterra_amount),
+                msg: to_binary(&MarketCw20HookMsg::RedeemStable {
+                    deadline: None,
+                    target_stable: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    }
+
+    /// Deposit collateral into the custody and lock it in the overseer
+    pub fn provide_collateral(&self, borrower: &str, amount: u128) -> AppResult<Vec<Event>> {
+        let mut events = self.app.execute(
+            borrower,
+            &self.collateral_token,
+            &Cw20ExecuteMsg::Send {
+                contract: self.custody.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {}).unwrap(),
+            },
+            &[],
+        )?;
+        events.extend(self.app.execute(
+            borrower,
+            &self.overseer,
+            &OverseerExecuteMsg::LockCollateral {
+                collaterals: vec![(self.collateral_token.to_string(), Uint256::from(amount))],
+            },
+            &[],
+        )?);
+        Ok(events)
+    }
+
+    /// Deposit native collateral coins and lock them in the overseer
+    pub fn provide_native_collateral(&self, borrower: &str, amount: u128) -> AppResult<Vec<Event>> {
+        let mut events = self.app.execute(
+            borrower,
+            &self.native_custody,
+            &NativeCustodyExecuteMsg::DepositCollateral {},
+            &[Coin {
+                denom: COLLATERAL_DENOM.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )?;
+        events.extend(self.app.execute(
+            borrower,
+            &self.overseer,
+            &OverseerExecuteMsg::LockCollateral {
+                collaterals: vec![(COLLATERAL_DENOM.to_string(), Uint256::from(amount))],
+            },
+            &[],
+        )?);
+        Ok(events)
+    }
+
+    pub fn borrow_stable(&self, borrower: &str, amount: u128) -> AppResult<Vec<Event>> {
+        self.app.execute(
+            borrower,
+            &self.market,
+            &MarketExecuteMsg::BorrowStable {
+                borrow_amount: Uint256::from(amount),
+                to: None,
+                deadline: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn submit_bid(
+        &self,
+        bidder: &str,
+        amount: u128,
+        premium_rate: Decimal256,
+    ) -> AppResult<Vec<Event>> {
+        self.submit_bid_on(bidder, self.collateral_token.as_str(), amount, premium_rate)
+    }
+
+    pub fn submit_bid_on(
+        &self,
+        bidder: &str,
+        collateral_token: &str,
+        amount: u128,
+        premium_rate: Decimal256,
+    ) -> AppResult<Vec<Event>> {
+        self.app.execute(
+            bidder,
+            &self.liquidation,
+            &LiquidationExecuteMsg::SubmitBid {
+                collateral_token: collateral_token.to_string(),
+                premium_rate,
+            },
+            &Suite::stable(amount),
+        )
+    }
+
+    pub fn liquidate(&self, liquidator: &str, borrower: &str) -> AppResult<Vec<Event>> {
+        self.app.execute(
+            liquidator,
+            &self.overseer,
+            &OverseerExecuteMsg::LiquidateCollateral {
+                borrower: borrower.to_string(),
+            },
+            &[],
+        )
+    }
+
+    pub fn loan_amount(&self, borrower: &str) -> Uint256 {
+        let res: BorrowerInfoResponse = self
+            .app
+            .query_wasm(
+                &self.market,
+                &MarketQueryMsg::BorrowerInfo {
+                    borrower: borrower.to_string(),
+                    block_height: Some(self.app.block_info().height),
+                },
+            )
+            .unwrap();
+        res.loan_amount
+    }
+}
+
+impl Default for Suite {
+    fn default() -> Self {
+        Suite::new()
+    }
+}
@@ -0,0 +1,165 @@
+//! Bare CW20 token standing in for aterra and the collateral tokens.
+//! Only the calls the money market makes are supported.
+
+use cosmwasm_std::{
+    attr, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128, WasmMsg,
+};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read};
+use cw20::{
+    BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse,
+    TokenInfoResponse,
+};
+use serde::{Deserialize, Serialize};
+use terraswap::token::InstantiateMsg;
+
+static KEY_TOKEN_INFO: &[u8] = b"token_info";
+static PREFIX_BALANCE: &[u8] = b"balance";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct TokenInfo {
+    name: String,
+    symbol: String,
+    decimals: u8,
+    total_supply: Uint128,
+    minter: Option<String>,
+}
+
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let mut total_supply = Uint128::zero();
+    for coin in msg.initial_balances {
+        add_balance(deps.storage, &coin.address, coin.amount)?;
+        total_supply += coin.amount;
+    }
+
+    singleton(deps.storage, KEY_TOKEN_INFO).save(&TokenInfo {
+        name: msg.name,
+        symbol: msg.symbol,
+        decimals: msg.decimals,
+        total_supply,
+        minter: msg.mint.map(|m| m.minter),
+    })?;
+
+    Ok(Response::default())
+}
+
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: Cw20ExecuteMsg,
+) -> StdResult<Response> {
+    let sender = info.sender.to_string();
+    match msg {
+        Cw20ExecuteMsg::Transfer { recipient, amount } => {
+            sub_balance(deps.storage, &sender, amount)?;
+            add_balance(deps.storage, &recipient, amount)?;
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "transfer"),
+                attr("from", sender),
+                attr("to", recipient),
+                attr("amount", amount),
+            ]))
+        }
+        Cw20ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => {
+            sub_balance(deps.storage, &sender, amount)?;
+            add_balance(deps.storage, &contract, amount)?;
+            Ok(Response::new()
+                .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract.clone(),
+                    funds: vec![],
+                    msg: Cw20ReceiveMsg {
+                        sender: sender.clone(),
+                        amount,
+                        msg,
+                    }
+                    .into_binary()?,
+                }))
+                .add_attributes(vec![
+                    attr("action", "send"),
+                    attr("from", sender),
+                    attr("to", contract),
+                    attr("amount", amount),
+                ]))
+        }
+        Cw20ExecuteMsg::Mint { recipient, amount } => {
+            let mut token_info: TokenInfo = singleton_read(deps.storage, KEY_TOKEN_INFO).load()?;
+            if token_info.minter.as_ref() != Some(&sender) {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            token_info.total_supply += amount;
+            singleton(deps.storage, KEY_TOKEN_INFO).save(&token_info)?;
+            add_balance(deps.storage, &recipient, amount)?;
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "mint"),
+                attr("to", recipient),
+                attr("amount", amount),
+            ]))
+        }
+        Cw20ExecuteMsg::Burn { amount } => {
+            let mut token_info: TokenInfo = singleton_read(deps.storage, KEY_TOKEN_INFO).load()?;
+            token_info.total_supply = token_info.total_supply.checked_sub(amount)?;
+            singleton(deps.storage, KEY_TOKEN_INFO).save(&token_info)?;
+            sub_balance(deps.storage, &sender, amount)?;
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "burn"),
+                attr("from", sender),
+                attr("amount", amount),
+            ]))
+        }
+        _ => Err(StdError::generic_err("unsupported token message")),
+    }
+}
+
+pub fn query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw20QueryMsg::Balance { address } => to_binary(&BalanceResponse {
+            balance: read_balance(deps.storage, &address),
+        }),
+        Cw20QueryMsg::TokenInfo {} => {
+            let token_info: TokenInfo = singleton_read(deps.storage, KEY_TOKEN_INFO).load()?;
+            to_binary(&TokenInfoResponse {
+                name: token_info.name,
+                symbol: token_info.symbol,
+                decimals: token_info.decimals,
+                total_supply: token_info.total_supply,
+            })
+        }
+        Cw20QueryMsg::Minter {} => {
+            let token_info: TokenInfo = singleton_read(deps.storage, KEY_TOKEN_INFO).load()?;
+            to_binary(
+                &token_info
+                    .minter
+                    .map(|minter| MinterResponse { minter, cap: None }),
+            )
+        }
+        _ => Err(StdError::generic_err("unsupported token query")),
+    }
+}
+
+fn read_balance(storage: &dyn Storage, address: &str) -> Uint128 {
+    bucket_read(storage, PREFIX_BALANCE)
+        .may_load(address.as_bytes())
+        .unwrap_or_default()
+        .unwrap_or_default()
+}
+
+fn add_balance(storage: &mut dyn Storage, address: &str, amount: Uint128) -> StdResult<()> {
+    let balance = read_balance(storage, address) + amount;
+    bucket(storage, PREFIX_BALANCE).save(address.as_bytes(), &balance)
+}
+
+fn sub_balance(storage: &mut dyn Storage, address: &str, amount: Uint128) -> StdResult<()> {
+    let balance = read_balance(storage, address).checked_sub(amount)?;
+    bucket(storage, PREFIX_BALANCE).save(address.as_bytes(), &balance)
+}
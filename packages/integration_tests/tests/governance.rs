@@ -0,0 +1,218 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{to_binary, Addr, Uint128};
+use cw20::{Cw20Coin, Cw20ExecuteMsg};
+use terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+
+use moneymarket::gov::{
+    Cw20HookMsg as GovCw20HookMsg, ExecuteMsg as GovExecuteMsg,
+    InstantiateMsg as GovInstantiateMsg, PollExecuteMsg, VoteOption,
+};
+use moneymarket::overseer::{
+    ExecuteMsg as OverseerExecuteMsg, QueryMsg as OverseerQueryMsg, WhitelistResponse,
+};
+use moneymarket_integration_tests::app::ContractCode;
+use moneymarket_integration_tests::suite::{Suite, OWNER};
+use moneymarket_integration_tests::token;
+
+const VOTER: &str = "voter";
+const VOTING_PERIOD: u64 = 10;
+const TIMELOCK_PERIOD: u64 = 5;
+
+fn max_ltv(suite: &Suite) -> Decimal256 {
+    let res: WhitelistResponse = suite
+        .app
+        .query_wasm(
+            &suite.overseer,
+            &OverseerQueryMsg::Whitelist {
+                collateral_token: Some(suite.collateral_token.to_string()),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    res.elems[0].max_ltv
+}
+
+#[test]
+fn poll_updates_overseer_whitelist() {
+    let mut suite = Suite::new();
+
+    let token_code = suite.app.store_code(ContractCode::new(
+        token::instantiate,
+        token::execute,
+        token::query,
+    ));
+    let gov_code = suite.app.store_code(ContractCode::new(
+        moneymarket_gov::contract::instantiate,
+        moneymarket_gov::contract::execute,
+        moneymarket_gov::contract::query,
+    ));
+
+    let anc = suite
+        .app
+        .instantiate(
+            OWNER,
+            token_code,
+            "anc",
+            &TokenInstantiateMsg {
+                name: "anchor token".to_string(),
+                symbol: "ANC".to_string(),
+                decimals: 6u8,
+                initial_balances: vec![Cw20Coin {
+                    address: VOTER.to_string(),
+                    amount: Uint128::from(10_000u128),
+                }],
+                mint: None,
+            },
+            &[],
+        )
+        .unwrap();
+    let gov = suite
+        .app
+        .instantiate(
+            OWNER,
+            gov_code,
+            "gov",
+            &GovInstantiateMsg {
+                governance_token: anc.to_string(),
+                quorum: Decimal256::percent(10),
+                threshold: Decimal256::percent(50),
+                voting_period: VOTING_PERIOD,
+                timelock_period: TIMELOCK_PERIOD,
+                proposal_deposit: Uint256::from(1_000u64),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // the owner hands the overseer over to gov
+    suite
+        .app
+        .execute(
+            OWNER,
+            &suite.overseer,
+            &OverseerExecuteMsg::UpdateConfig {
+                owner_addr: Some(gov.to_string()),
+                oracle_contract: None,
+                liquidation_contract: None,
+                threshold_deposit_rate: None,
+                target_deposit_rate: None,
+                buffer_distribution_factor: None,
+                anc_purchase_factor: None,
+                epoch_period: None,
+                price_timeframe: None,
+                max_total_collateral_value: None,
+                keeper_reward: None,
+                liquidate_on_stale_price: None,
+                escrow_max_ltv: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let update_whitelist = OverseerExecuteMsg::UpdateWhitelist {
+        collateral_token: suite.collateral_token.to_string(),
+        custody_contract: None,
+        max_ltv: Some(Decimal256::percent(40)),
+        max_collateral: None,
+        unlock_delay: None,
+        unlock_delay_threshold: None,
+    };
+    let err = suite
+        .app
+        .execute(OWNER, &suite.overseer, &update_whitelist, &[])
+        .unwrap_err();
+    assert!(err.contains("Unauthorized"), "{}", err);
+
+    send(
+        &suite,
+        &anc,
+        &gov,
+        5_000,
+        &GovCw20HookMsg::StakeVotingTokens {},
+    );
+    send(
+        &suite,
+        &anc,
+        &gov,
+        1_000,
+        &GovCw20HookMsg::CreatePoll {
+            title: "lower max ltv".to_string(),
+            description: "lower the collateral max ltv to 40%".to_string(),
+            execute_msgs: Some(vec![PollExecuteMsg {
+                order: 1u64,
+                contract: suite.overseer.to_string(),
+                msg: to_binary(&update_whitelist).unwrap(),
+            }]),
+        },
+    );
+    suite
+        .app
+        .execute(
+            VOTER,
+            &gov,
+            &GovExecuteMsg::CastVote {
+                poll_id: 1u64,
+                vote: VoteOption::Yes,
+                amount: Uint256::from(5_000u64),
+            },
+            &[],
+        )
+        .unwrap();
+
+    for _ in 0..=VOTING_PERIOD {
+        suite.app.next_block(6);
+    }
+    suite
+        .app
+        .execute(VOTER, &gov, &GovExecuteMsg::EndPoll { poll_id: 1u64 }, &[])
+        .unwrap();
+
+    // the deposit went back to the creator
+    assert_eq!(
+        suite.token_balance(&anc, VOTER),
+        Uint128::from(10_000u128 - 5_000u128)
+    );
+
+    let err = suite
+        .app
+        .execute(
+            VOTER,
+            &gov,
+            &GovExecuteMsg::ExecutePoll { poll_id: 1u64 },
+            &[],
+        )
+        .unwrap_err();
+    assert!(err.contains("Timelock period has not expired"), "{}", err);
+    assert_eq!(max_ltv(&suite), Decimal256::percent(50));
+
+    for _ in 0..TIMELOCK_PERIOD {
+        suite.app.next_block(6);
+    }
+    suite
+        .app
+        .execute(
+            VOTER,
+            &gov,
+            &GovExecuteMsg::ExecutePoll { poll_id: 1u64 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(max_ltv(&suite), Decimal256::percent(40));
+}
+
+fn send(suite: &Suite, token: &Addr, contract: &Addr, amount: u128, msg: &GovCw20HookMsg) {
+    suite
+        .app
+        .execute(
+            VOTER,
+            token,
+            &Cw20ExecuteMsg::Send {
+                contract: contract.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(msg).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+}
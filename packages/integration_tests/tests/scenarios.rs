@@ -0,0 +1,494 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{to_binary, Addr, Coin, Uint128};
+use cw20::Cw20ExecuteMsg;
+
+use moneymarket::liquidation::ExecuteMsg as LiquidationExecuteMsg;
+use moneymarket::market::{
+    ConfigResponse as MarketConfigResponse, Cw20HookMsg as MarketCw20HookMsg,
+    ExecuteMsg as MarketExecuteMsg, MarketStateResponse, MarketsResponse,
+    QueryMsg as MarketQueryMsg,
+};
+use moneymarket::overseer::{
+    BufferStateResponse, CollateralsResponse, ExecuteMsg as OverseerExecuteMsg,
+    QueryMsg as OverseerQueryMsg,
+};
+use moneymarket_integration_tests::app::event_attribute;
+use moneymarket_integration_tests::suite::{Suite, COLLATERAL_DENOM, OWNER};
+
+const DEPOSITOR: &str = "depositor";
+const BORROWER: &str = "borrower";
+const LIQUIDATOR: &str = "liquidator";
+
+#[test]
+fn deposit_borrow_liquidate_redeem() {
+    let suite = Suite::new();
+
+    // deposit mints aterra at the initial exchange rate of one
+    suite.app.mint_native(DEPOSITOR, &Suite::stable(10_000_000));
+    suite.deposit_stable(DEPOSITOR, 10_000_000).unwrap();
+    assert_eq!(
+        suite.token_balance(&suite.aterra, DEPOSITOR),
+        Uint128::from(10_000_000u128)
+    );
+
+    // 1,000,000 collateral at a price of one allows a 500,000 loan
+    suite.mint_collateral(BORROWER, 1_000_000);
+    suite.provide_collateral(BORROWER, 1_000_000).unwrap();
+    assert_eq!(
+        suite.token_balance(&suite.collateral_token, suite.custody.as_str()),
+        Uint128::from(1_000_000u128)
+    );
+
+    let err = suite.borrow_stable(BORROWER, 500_001).unwrap_err();
+    assert!(err.contains("greater than borrow limit"), "{}", err);
+    suite.borrow_stable(BORROWER, 400_000).unwrap();
+    assert_eq!(suite.stable_balance(BORROWER), Uint128::from(400_000u128));
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::from(400_000u64));
+
+    // the loan is safe at the current price
+    suite.app.mint_native(LIQUIDATOR, &Suite::stable(1_000_000));
+    suite
+        .submit_bid(LIQUIDATOR, 1_000_000, Decimal256::percent(5))
+        .unwrap();
+    let err = suite.liquidate(LIQUIDATOR, BORROWER).unwrap_err();
+    assert!(
+        err.contains("Cannot liquidate safely collateralized loan"),
+        "{}",
+        err
+    );
+
+    // a price drop puts the borrow limit at 300,000
+    suite.app.next_block(6);
+    suite.feed_price(Decimal256::percent(60)).unwrap();
+
+    let market_balance = suite.stable_balance(suite.market.as_str());
+    let events = suite.liquidate(LIQUIDATOR, BORROWER).unwrap();
+    let liquidated =
+        event_attribute(&events, &suite.custody, "amount").expect("custody liquidation event");
+    let liquidated = Uint128::from(liquidated.parse::<u128>().unwrap());
+    let repaid = event_attribute(&events, &suite.market, "repay_amount")
+        .expect("market repay event")
+        .parse::<u128>()
+        .unwrap();
+
+    // the liquidation contract sizes the sale assuming the maximum
+    // 30% premium: (400,000 - 240,000) / (420,000 - 240,000) of the collateral
+    assert_eq!(liquidated, Uint128::from(888_888u128));
+    assert_eq!(
+        suite.token_balance(&suite.collateral_token, LIQUIDATOR),
+        liquidated
+    );
+    assert_eq!(
+        suite.token_balance(&suite.collateral_token, suite.custody.as_str()),
+        Uint128::from(111_112u128)
+    );
+
+    // the liquidator bid at a 5% premium, so the proceeds of
+    // 888,888 * 0.6 * 0.95 (rounded down) repay the whole loan and the excess
+    // goes back to the borrower
+    assert_eq!(repaid, 400_000u128);
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::zero());
+    assert_eq!(
+        suite.stable_balance(suite.market.as_str()),
+        market_balance + Uint128::from(repaid)
+    );
+    assert_eq!(
+        suite.stable_balance(BORROWER),
+        Uint128::from(400_000u128 + 106_665u128)
+    );
+    assert_eq!(
+        suite.stable_balance(suite.liquidation.as_str()),
+        Uint128::from(1_000_000u128 - 506_665u128)
+    );
+
+    // nothing is left to liquidate
+    let err = suite.liquidate(LIQUIDATOR, BORROWER).unwrap_err();
+    assert!(
+        err.contains("Cannot liquidate safely collateralized loan"),
+        "{}",
+        err
+    );
+
+    // depositors redeem what is left unborrowed in full
+    suite.redeem_stable(DEPOSITOR, 5_000_000).unwrap();
+    assert_eq!(
+        suite.stable_balance(DEPOSITOR),
+        Uint128::from(5_000_000u128)
+    );
+    assert_eq!(
+        suite.token_balance(&suite.aterra, DEPOSITOR),
+        Uint128::from(5_000_000u128)
+    );
+}
+
+#[test]
+fn failed_liquidation_leaves_no_state_behind() {
+    let suite = Suite::new();
+
+    suite.mint_collateral(BORROWER, 1_000_000);
+    suite.provide_collateral(BORROWER, 1_000_000).unwrap();
+    suite.borrow_stable(BORROWER, 400_000).unwrap();
+
+    // the bid cannot pay for the collateral being sold
+    suite.app.mint_native(LIQUIDATOR, &Suite::stable(100_000));
+    suite
+        .submit_bid(LIQUIDATOR, 100_000, Decimal256::percent(5))
+        .unwrap();
+
+    suite.app.next_block(6);
+    suite.feed_price(Decimal256::percent(60)).unwrap();
+
+    // the overseer and the custody both update their books
+    // before the liquidation contract fails the bid
+    let err = suite.liquidate(LIQUIDATOR, BORROWER).unwrap_err();
+    assert!(!err.is_empty());
+
+    let collaterals: CollateralsResponse = suite
+        .app
+        .query_wasm(
+            &suite.overseer,
+            &OverseerQueryMsg::Collaterals {
+                borrower: BORROWER.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        collaterals.collaterals,
+        vec![(
+            suite.collateral_token.to_string(),
+            Uint256::from(1_000_000u64)
+        )]
+    );
+    assert_eq!(
+        suite.token_balance(&suite.collateral_token, suite.custody.as_str()),
+        Uint128::from(1_000_000u128)
+    );
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::from(400_000u64));
+    assert_eq!(
+        suite.stable_balance(suite.liquidation.as_str()),
+        Uint128::from(100_000u128)
+    );
+}
+
+#[test]
+fn liquidate_on_stale_price() {
+    let suite = Suite::new();
+
+    suite.app.mint_native(DEPOSITOR, &Suite::stable(10_000_000));
+    suite.deposit_stable(DEPOSITOR, 10_000_000).unwrap();
+    suite.mint_collateral(BORROWER, 1_000_000);
+    suite.provide_collateral(BORROWER, 1_000_000).unwrap();
+    suite.borrow_stable(BORROWER, 400_000).unwrap();
+
+    suite.app.mint_native(LIQUIDATOR, &Suite::stable(1_000_000));
+    suite
+        .submit_bid(LIQUIDATOR, 1_000_000, Decimal256::percent(5))
+        .unwrap();
+
+    // the last price puts the borrow limit at 300,000,
+    // then the feeder stops past the 60 second price timeframe
+    suite.app.next_block(6);
+    suite.feed_price(Decimal256::percent(60)).unwrap();
+    suite.app.next_block(61);
+
+    let err = suite.liquidate(LIQUIDATOR, BORROWER).unwrap_err();
+    assert!(err.contains("Price is too old"), "{}", err);
+
+    // the overseer accepts the stale price, but the
+    // liquidation contract still rejects it when executing the bid
+    suite
+        .app
+        .execute(
+            OWNER,
+            &suite.overseer,
+            &OverseerExecuteMsg::UpdateConfig {
+                owner_addr: None,
+                oracle_contract: None,
+                liquidation_contract: None,
+                threshold_deposit_rate: None,
+                target_deposit_rate: None,
+                buffer_distribution_factor: None,
+                anc_purchase_factor: None,
+                epoch_period: None,
+                price_timeframe: None,
+                max_total_collateral_value: None,
+                keeper_reward: None,
+                liquidate_on_stale_price: Some(true),
+                escrow_max_ltv: None,
+            },
+            &[],
+        )
+        .unwrap();
+    let err = suite.liquidate(LIQUIDATOR, BORROWER).unwrap_err();
+    assert!(err.contains("Price is too old"), "{}", err);
+
+    suite
+        .app
+        .execute(
+            OWNER,
+            &suite.liquidation,
+            &LiquidationExecuteMsg::UpdateConfig {
+                owner: None,
+                oracle_contract: None,
+                stable_denom: None,
+                safe_ratio: None,
+                bid_fee: None,
+                max_premium_rate: None,
+                liquidation_threshold: None,
+                price_timeframe: None,
+                close_factor: None,
+                full_liquidation_ratio: None,
+                liquidate_on_stale_price: Some(true),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // the collateral is sold at the last price and the proceeds repay the loan
+    let events = suite.liquidate(LIQUIDATOR, BORROWER).unwrap();
+    let liquidated = event_attribute(&events, &suite.custody, "amount")
+        .expect("custody liquidation event")
+        .parse::<u128>()
+        .unwrap();
+    let repaid = event_attribute(&events, &suite.market, "repay_amount")
+        .expect("market repay event")
+        .parse::<u128>()
+        .unwrap();
+    assert_eq!(liquidated, 888_888u128);
+    assert_eq!(repaid, 400_000u128);
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::zero());
+    assert_eq!(
+        suite.token_balance(&suite.collateral_token, LIQUIDATOR),
+        Uint128::from(888_888u128)
+    );
+
+    // borrowing still requires a fresh price
+    let err = suite.borrow_stable(BORROWER, 1_000).unwrap_err();
+    assert!(err.contains("Price is too old"), "{}", err);
+}
+
+#[test]
+fn liquidation_bid_fee_funds_reserve() {
+    let suite = Suite::new();
+
+    suite
+        .app
+        .execute(
+            OWNER,
+            &suite.liquidation,
+            &LiquidationExecuteMsg::UpdateConfig {
+                owner: None,
+                oracle_contract: None,
+                stable_denom: None,
+                safe_ratio: None,
+                bid_fee: Some(Decimal256::percent(1)),
+                max_premium_rate: None,
+                liquidation_threshold: None,
+                price_timeframe: None,
+                close_factor: None,
+                full_liquidation_ratio: None,
+                liquidate_on_stale_price: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    suite.app.mint_native(DEPOSITOR, &Suite::stable(10_000_000));
+    suite.deposit_stable(DEPOSITOR, 10_000_000).unwrap();
+    suite.mint_collateral(BORROWER, 1_000_000);
+    suite.provide_collateral(BORROWER, 1_000_000).unwrap();
+    suite.borrow_stable(BORROWER, 400_000).unwrap();
+
+    suite.app.mint_native(LIQUIDATOR, &Suite::stable(1_000_000));
+    suite
+        .submit_bid(LIQUIDATOR, 1_000_000, Decimal256::percent(5))
+        .unwrap();
+    suite.app.next_block(6);
+    suite.feed_price(Decimal256::percent(60)).unwrap();
+    suite.liquidate(LIQUIDATOR, BORROWER).unwrap();
+
+    // the bid fee is paid to the overseer and counted in the interest buffer
+    let fee = suite.stable_balance(suite.overseer.as_str());
+    assert_eq!(fee, Uint128::from(5_187u128));
+    let buffer: BufferStateResponse = suite
+        .app
+        .query_wasm(
+            &suite.overseer,
+            &OverseerQueryMsg::BufferState { limit: None },
+        )
+        .unwrap();
+    assert_eq!(buffer.interest_buffer, Uint256::from(fee));
+    assert_eq!(buffer.accrued_buffer, Uint256::from(fee));
+}
+
+#[test]
+fn liquidate_native_collateral() {
+    let suite = Suite::new();
+
+    suite.app.mint_native(DEPOSITOR, &Suite::stable(10_000_000));
+    suite.deposit_stable(DEPOSITOR, 10_000_000).unwrap();
+
+    // half of the 500,000 borrow limit comes from native coins
+    suite.mint_collateral(BORROWER, 500_000);
+    suite.provide_collateral(BORROWER, 500_000).unwrap();
+    suite.app.mint_native(
+        BORROWER,
+        &[Coin {
+            denom: COLLATERAL_DENOM.to_string(),
+            amount: Uint128::from(500_000u128),
+        }],
+    );
+    suite.provide_native_collateral(BORROWER, 500_000).unwrap();
+    suite.borrow_stable(BORROWER, 400_000).unwrap();
+
+    suite.app.mint_native(LIQUIDATOR, &Suite::stable(2_000_000));
+    suite
+        .submit_bid(LIQUIDATOR, 1_000_000, Decimal256::percent(5))
+        .unwrap();
+    suite
+        .submit_bid_on(
+            LIQUIDATOR,
+            COLLATERAL_DENOM,
+            1_000_000,
+            Decimal256::percent(5),
+        )
+        .unwrap();
+
+    suite.app.next_block(6);
+    suite.feed_price(Decimal256::percent(60)).unwrap();
+
+    let events = suite.liquidate(LIQUIDATOR, BORROWER).unwrap();
+    let repaid = event_attribute(&events, &suite.market, "repay_amount")
+        .expect("market repay event")
+        .parse::<u128>()
+        .unwrap();
+
+    // both custodies sell their share to the liquidator's bids
+    let native_liquidated =
+        event_attribute(&events, &suite.native_custody, "amount").expect("native liquidation");
+    let native_liquidated = Uint128::from(native_liquidated.parse::<u128>().unwrap());
+    assert_eq!(native_liquidated, Uint128::from(444_444u128));
+    assert_eq!(
+        suite.app.native_balance(LIQUIDATOR, COLLATERAL_DENOM),
+        native_liquidated
+    );
+    assert_eq!(
+        suite
+            .app
+            .native_balance(suite.native_custody.as_str(), COLLATERAL_DENOM),
+        Uint128::from(55_556u128)
+    );
+    assert_eq!(
+        suite.token_balance(&suite.collateral_token, LIQUIDATOR),
+        Uint128::from(444_444u128)
+    );
+
+    assert_eq!(repaid, 400_000u128);
+    assert_eq!(suite.loan_amount(BORROWER), Uint256::zero());
+}
+
+#[test]
+fn deposit_and_redeem_in_added_market() {
+    let suite = Suite::new();
+    let config: MarketConfigResponse = suite
+        .app
+        .query_wasm(&suite.market, &MarketQueryMsg::Config {})
+        .unwrap();
+
+    let krw = |amount: u128| {
+        vec![Coin {
+            denom: "ukrw".to_string(),
+            amount: Uint128::from(amount),
+        }]
+    };
+    suite.app.mint_native(OWNER, &krw(1_000_000));
+    suite
+        .app
+        .execute(
+            OWNER,
+            &suite.market,
+            &MarketExecuteMsg::AddMarket {
+                denom: "ukrw".to_string(),
+                aterra_code_id: config.aterra_code_id,
+            },
+            &krw(1_000_000),
+        )
+        .unwrap();
+
+    let markets: MarketsResponse = suite
+        .app
+        .query_wasm(&suite.market, &MarketQueryMsg::Markets {})
+        .unwrap();
+    assert_eq!(markets.markets.len(), 1);
+    assert_eq!(markets.markets[0].denom, "ukrw");
+    let aterra_krw = Addr::unchecked(markets.markets[0].aterra_contract.clone());
+    assert_ne!(aterra_krw, suite.aterra);
+
+    suite.app.mint_native(DEPOSITOR, &krw(5_000_000));
+    suite
+        .app
+        .execute(
+            DEPOSITOR,
+            &suite.market,
+            &MarketExecuteMsg::Market {
+                denom: "ukrw".to_string(),
+                msg: Box::new(MarketExecuteMsg::DepositStable {
+                    recipient: None,
+                    deadline: None,
+                }),
+            },
+            &krw(5_000_000),
+        )
+        .unwrap();
+    assert_eq!(
+        suite.token_balance(&aterra_krw, DEPOSITOR),
+        Uint128::from(5_000_000u128)
+    );
+    assert_eq!(
+        suite.token_balance(&suite.aterra, DEPOSITOR),
+        Uint128::zero()
+    );
+
+    // the primary market does not see the deposit
+    let state: MarketStateResponse = suite
+        .app
+        .query_wasm(
+            &suite.market,
+            &MarketQueryMsg::MarketState { block_height: None },
+        )
+        .unwrap();
+    let krw_state: MarketStateResponse = suite
+        .app
+        .query_wasm(
+            &suite.market,
+            &MarketQueryMsg::Market {
+                denom: "ukrw".to_string(),
+                msg: Box::new(MarketQueryMsg::MarketState { block_height: None }),
+            },
+        )
+        .unwrap();
+    assert_eq!(state.total_deposits, Uint256::from(1_000_000u64));
+    assert_eq!(krw_state.total_deposits, Uint256::from(6_000_000u64));
+
+    suite
+        .app
+        .execute(
+            DEPOSITOR,
+            &aterra_krw,
+            &Cw20ExecuteMsg::Send {
+                contract: suite.market.to_string(),
+                amount: Uint128::from(5_000_000u128),
+                msg: to_binary(&MarketCw20HookMsg::RedeemStable {
+                    deadline: None,
+                    target_stable: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        suite.app.native_balance(DEPOSITOR, "ukrw"),
+        Uint128::from(5_000_000u128)
+    );
+}
@@ -0,0 +1,53 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    pub terraswap_factory: String,
+    /// Protocol token the collected coins are swapped to
+    pub anchor_token: String,
+    /// Recipients of the swapped protocol tokens, such as the
+    /// distributor and the gov staking contract, with their
+    /// share of every sweep; the weights must sum to one
+    pub reward_weights: Vec<(String, Decimal256)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    ////////////////////
+    /// Owner operations
+    ////////////////////
+
+    /// Update config
+    UpdateConfig {
+        owner: Option<String>,
+        reward_weights: Option<Vec<(String, Decimal256)>>,
+    },
+
+    ////////////////////
+    /// User operations
+    ////////////////////
+
+    /// Swap the whole balance of the given denom to the protocol
+    /// token on terraswap and distribute it by reward weight
+    Sweep { denom: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub terraswap_factory: String,
+    pub anchor_token: String,
+    pub reward_weights: Vec<(String, Decimal256)>,
+}
@@ -0,0 +1,29 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub use crate::custody::{BorrowerResponse, BorrowersResponse, Cw20HookMsg, ExecuteMsg, QueryMsg};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    /// owner address
+    pub owner: String,
+    /// CW20 collateral token address
+    pub collateral_token: String,
+    /// overseer contract address
+    pub overseer_contract: String,
+    /// market contract address
+    pub market_contract: String,
+    /// liquidation contract address
+    pub liquidation_contract: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub collateral_token: String,
+    pub overseer_contract: String,
+    pub market_contract: String,
+    pub liquidation_contract: String,
+}
@@ -0,0 +1,71 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Uint256;
+
+pub use crate::custody::{BorrowerResponse, BorrowersResponse, QueryMsg};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    /// owner address
+    pub owner: String,
+    /// Native collateral denom
+    pub collateral_denom: String,
+    /// overseer contract address
+    pub overseer_contract: String,
+    /// market contract address
+    pub market_contract: String,
+    /// liquidation contract address
+    pub liquidation_contract: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    ////////////////////
+    /// Overseer operations
+    ////////////////////
+
+    /// Update config
+    UpdateConfig {
+        owner: Option<String>,
+        liquidation_contract: Option<String>,
+    },
+    /// Make specified amount of tokens unspendable
+    LockCollateral { borrower: String, amount: Uint256 },
+    /// Make specified amount of collateral tokens spendable
+    UnlockCollateral { borrower: String, amount: Uint256 },
+    /// Native collaterals have no rewards; accepted so the
+    /// overseer can treat every custody alike
+    DistributeRewards {},
+
+    /// Send the liquidated coins to the liquidation
+    /// contract to be sold to the liquidator's bid
+    LiquidateCollateral {
+        liquidator: String,
+        borrower: String,
+        amount: Uint256,
+    },
+
+    ////////////////////
+    /// User operations
+    ////////////////////
+
+    /// Deposit the collateral coins sent with the message
+    DepositCollateral {},
+    /// Withdraw spendable collateral coins.
+    /// If the amount is not given,
+    /// return all spendable collateral
+    WithdrawCollateral { amount: Option<Uint256> },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub collateral_denom: String,
+    pub overseer_contract: String,
+    pub market_contract: String,
+    pub liquidation_contract: String,
+}
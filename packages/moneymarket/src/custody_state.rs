@@ -0,0 +1,151 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::custody::BorrowerResponse;
+use cosmwasm_bignumber::Uint256;
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
+use cosmwasm_storage::{Bucket, ReadonlyBucket};
+
+const PREFIX_BORROWER: &[u8] = b"borrower";
+
+/// Collateral deposited by a borrower; the part that is
+/// not spendable is locked by the overseer against a loan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerInfo {
+    pub balance: Uint256,
+    pub spendable: Uint256,
+}
+
+/// Rejected collateral transitions, carrying the amount
+/// that was available for the operation
+#[derive(Clone, Debug, PartialEq)]
+pub enum CollateralError {
+    LiquidationAmountExceedsLocked(Uint256),
+    LockAmountExceedsSpendable(Uint256),
+    UnlockAmountExceedsLocked(Uint256),
+    WithdrawAmountExceedsSpendable(Uint256),
+}
+
+impl BorrowerInfo {
+    pub fn locked(&self) -> Uint256 {
+        self.balance - self.spendable
+    }
+
+    pub fn deposit(&mut self, amount: Uint256) {
+        self.balance += amount;
+        self.spendable += amount;
+    }
+
+    /// Withdraw `amount` or the whole spendable balance,
+    /// returning the withdrawn amount
+    pub fn withdraw(&mut self, amount: Option<Uint256>) -> Result<Uint256, CollateralError> {
+        let amount = amount.unwrap_or(self.spendable);
+        if self.spendable < amount {
+            return Err(CollateralError::WithdrawAmountExceedsSpendable(
+                self.spendable,
+            ));
+        }
+
+        self.balance = self.balance - amount;
+        self.spendable = self.spendable - amount;
+        Ok(amount)
+    }
+
+    pub fn lock(&mut self, amount: Uint256) -> Result<(), CollateralError> {
+        if amount > self.spendable {
+            return Err(CollateralError::LockAmountExceedsSpendable(self.spendable));
+        }
+
+        self.spendable = self.spendable - amount;
+        Ok(())
+    }
+
+    pub fn unlock(&mut self, amount: Uint256) -> Result<(), CollateralError> {
+        let locked_amount = self.locked();
+        if amount > locked_amount {
+            return Err(CollateralError::UnlockAmountExceedsLocked(locked_amount));
+        }
+
+        self.spendable += amount;
+        Ok(())
+    }
+
+    /// Seize locked collateral
+    pub fn liquidate(&mut self, amount: Uint256) -> Result<(), CollateralError> {
+        let locked_amount = self.locked();
+        if amount > locked_amount {
+            return Err(CollateralError::LiquidationAmountExceedsLocked(
+                locked_amount,
+            ));
+        }
+
+        self.balance = self.balance - amount;
+        Ok(())
+    }
+}
+
+pub fn store_borrower_info(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    borrower_info: &BorrowerInfo,
+) -> StdResult<()> {
+    let mut borrower_bucket: Bucket<BorrowerInfo> = Bucket::new(storage, PREFIX_BORROWER);
+    borrower_bucket.save(borrower.as_slice(), borrower_info)?;
+
+    Ok(())
+}
+
+pub fn remove_borrower_info(storage: &mut dyn Storage, borrower: &CanonicalAddr) {
+    let mut borrower_bucket: Bucket<BorrowerInfo> = Bucket::new(storage, PREFIX_BORROWER);
+    borrower_bucket.remove(borrower.as_slice());
+}
+
+pub fn read_borrower_info(storage: &dyn Storage, borrower: &CanonicalAddr) -> BorrowerInfo {
+    let borrower_bucket: ReadonlyBucket<BorrowerInfo> =
+        ReadonlyBucket::new(storage, PREFIX_BORROWER);
+    match borrower_bucket.load(borrower.as_slice()) {
+        Ok(v) => v,
+        _ => BorrowerInfo {
+            balance: Uint256::zero(),
+            spendable: Uint256::zero(),
+        },
+    }
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_borrowers(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BorrowerResponse>> {
+    let position_bucket: ReadonlyBucket<BorrowerInfo> =
+        ReadonlyBucket::new(deps.storage, PREFIX_BORROWER);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    position_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let borrower: CanonicalAddr = CanonicalAddr::from(k);
+            Ok(BorrowerResponse {
+                borrower: deps.api.addr_humanize(&borrower)?.to_string(),
+                balance: v.balance,
+                spendable: v.spendable,
+            })
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
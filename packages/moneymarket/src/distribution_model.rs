@@ -21,6 +21,19 @@ pub enum ExecuteMsg {
         emission_floor: Option<Decimal256>,
         increment_multiplier: Option<Decimal256>,
         decrement_multiplier: Option<Decimal256>,
+        overseer_contract: Option<String>,
+    },
+
+    ////////////////////
+    /// Overseer operations
+    ////////////////////
+
+    /// Adjust the stored emission rate by the gap
+    /// between the deposit rate and its target
+    UpdateEmissionRate {
+        deposit_rate: Decimal256,
+        target_deposit_rate: Decimal256,
+        threshold_deposit_rate: Decimal256,
     },
 }
 
@@ -34,6 +47,12 @@ pub enum QueryMsg {
         threshold_deposit_rate: Decimal256,
         current_emission_rate: Decimal256,
     },
+    /// Emission rate kept by `UpdateEmissionRate`
+    EmissionRate {},
+    EmissionRateHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -44,6 +63,7 @@ pub struct ConfigResponse {
     pub emission_floor: Decimal256,
     pub increment_multiplier: Decimal256,
     pub decrement_multiplier: Decimal256,
+    pub overseer_contract: Option<String>,
 }
 
 // We define a custom struct for each query response
@@ -51,3 +71,17 @@ pub struct ConfigResponse {
 pub struct AncEmissionRateResponse {
     pub emission_rate: Decimal256,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionRateResponse {
+    pub emission_rate: Decimal256,
+    /// Block height of the update, 0 before the first one
+    pub last_updated: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionRateHistoryResponse {
+    pub history: Vec<EmissionRateResponse>,
+}
@@ -0,0 +1,74 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Uint256;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    /// Protocol token held and spent by the distributor
+    pub anchor_token: String,
+    /// Contracts allowed to spend, e.g. the market and the overseer
+    pub whitelist: Vec<String>,
+    /// Number of blocks a spend limit applies to
+    pub epoch_period: u64,
+    /// Maximum amount spent by all distributors in one epoch
+    pub max_spend_per_epoch: Uint256,
+    /// Minimum amount of a single spend
+    pub min_spend_amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    ////////////////////
+    /// Owner operations
+    ////////////////////
+
+    /// Update config
+    UpdateConfig {
+        owner: Option<String>,
+        epoch_period: Option<u64>,
+        max_spend_per_epoch: Option<Uint256>,
+        min_spend_amount: Option<Uint256>,
+    },
+    /// Allow a contract to spend
+    AddDistributor { distributor: String },
+    /// Revoke the spending right of a contract
+    RemoveDistributor { distributor: String },
+
+    ////////////////////
+    /// Distributor operations
+    ////////////////////
+
+    /// Send protocol tokens to the recipient, within
+    /// the spend limits of the current epoch
+    Spend { recipient: String, amount: Uint256 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    State {},
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub anchor_token: String,
+    pub whitelist: Vec<String>,
+    pub epoch_period: u64,
+    pub max_spend_per_epoch: Uint256,
+    pub min_spend_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    /// Height at which the current epoch started
+    pub epoch_start_height: u64,
+    /// Amount spent since the current epoch started
+    pub epoch_spent_amount: Uint256,
+}
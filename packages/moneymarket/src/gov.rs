@@ -0,0 +1,207 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::Binary;
+use cw20::Cw20ReceiveMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// CW20 token staked to vote
+    pub governance_token: String,
+    /// Minimum share of the staked tokens that must vote
+    /// for a poll to be valid
+    pub quorum: Decimal256,
+    /// Share of the yes votes above which a poll passes
+    pub threshold: Decimal256,
+    /// Number of blocks a poll is open for votes
+    pub voting_period: u64,
+    /// Number of blocks a passed poll waits before execution
+    pub timelock_period: u64,
+    /// Tokens locked by the poll creator, returned
+    /// once the poll reaches quorum
+    pub proposal_deposit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+
+    /// Update config; only executable through a poll
+    UpdateConfig {
+        quorum: Option<Decimal256>,
+        threshold: Option<Decimal256>,
+        voting_period: Option<u64>,
+        timelock_period: Option<u64>,
+        proposal_deposit: Option<Uint256>,
+    },
+    /// Vote on an in-progress poll with staked tokens;
+    /// the tokens stay locked until the poll ends
+    CastVote {
+        poll_id: u64,
+        vote: VoteOption,
+        amount: Uint256,
+    },
+    /// Unstake tokens not locked in in-progress polls.
+    /// If the amount is not given, withdraw all of them
+    WithdrawVotingTokens {
+        amount: Option<Uint256>,
+    },
+    /// Tally a poll once its voting period is over
+    EndPoll {
+        poll_id: u64,
+    },
+    /// Execute the messages of a passed poll once its
+    /// timelock period is over
+    ExecutePoll {
+        poll_id: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Stake the sent tokens to gain voting power
+    StakeVotingTokens {},
+    /// Create a poll with the sent tokens as its deposit
+    CreatePoll {
+        title: String,
+        description: String,
+        execute_msgs: Option<Vec<PollExecuteMsg>>,
+    },
+}
+
+/// Message the gov contract sends to `contract` once the poll
+/// passed, e.g. an `UpdateConfig` or `UpdateWhitelist` of the
+/// market or overseer; messages run in ascending `order`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollExecuteMsg {
+    pub order: u64,
+    pub contract: String,
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteOption {
+    Yes,
+    No,
+}
+
+impl fmt::Display for VoteOption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VoteOption::Yes => write!(f, "yes"),
+            VoteOption::No => write!(f, "no"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    InProgress,
+    Passed,
+    Rejected,
+    Executed,
+}
+
+impl fmt::Display for PollStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PollStatus::InProgress => write!(f, "in_progress"),
+            PollStatus::Passed => write!(f, "passed"),
+            PollStatus::Rejected => write!(f, "rejected"),
+            PollStatus::Executed => write!(f, "executed"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    State {},
+    Staker {
+        address: String,
+    },
+    Poll {
+        poll_id: u64,
+    },
+    Polls {
+        filter: Option<PollStatus>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Voters {
+        poll_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub governance_token: String,
+    pub quorum: Decimal256,
+    pub threshold: Decimal256,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub proposal_deposit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub poll_count: u64,
+    pub total_staked: Uint256,
+    pub total_deposit: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterInfo {
+    pub vote: VoteOption,
+    pub balance: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerResponse {
+    pub balance: Uint256,
+    /// Votes on polls that are still in progress
+    pub locked_balance: Vec<(u64, VoterInfo)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollResponse {
+    pub id: u64,
+    pub creator: String,
+    pub status: PollStatus,
+    pub end_height: u64,
+    pub title: String,
+    pub description: String,
+    pub deposit_amount: Uint256,
+    pub execute_msgs: Option<Vec<PollExecuteMsg>>,
+    pub yes_votes: Uint256,
+    pub no_votes: Uint256,
+    /// Total staked amount the quorum was computed against
+    pub total_balance_at_end_poll: Option<Uint256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollsResponse {
+    pub polls: Vec<PollResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotersResponseElem {
+    pub voter: String,
+    pub vote: VoteOption,
+    pub balance: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotersResponse {
+    pub voters: Vec<VotersResponseElem>,
+}
@@ -7,7 +7,14 @@ use cosmwasm_bignumber::{Decimal256, Uint256};
 pub struct InstantiateMsg {
     pub owner: String,
     pub base_rate: Decimal256,
+    /// Rate added per unit of utilization, up to `optimal_utilization`
     pub interest_multiplier: Decimal256,
+    /// Utilization above which the rate grows by `slope_2`;
+    /// a single slope is used when not set
+    pub optimal_utilization: Option<Decimal256>,
+    /// Rate added per unit of utilization above `optimal_utilization`,
+    /// defaults to `interest_multiplier`
+    pub slope_2: Option<Decimal256>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -17,6 +24,8 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         base_rate: Option<Decimal256>,
         interest_multiplier: Option<Decimal256>,
+        optimal_utilization: Option<Decimal256>,
+        slope_2: Option<Decimal256>,
     },
 }
 
@@ -28,6 +37,12 @@ pub enum QueryMsg {
         market_balance: Uint256,
         total_liabilities: Decimal256,
         total_reserves: Decimal256,
+        /// Market context, left out of the message when not set
+        /// so models built before it was added can still parse it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stable_denom: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        utilization: Option<Decimal256>,
     },
 }
 
@@ -37,6 +52,8 @@ pub struct ConfigResponse {
     pub owner: String,
     pub base_rate: Decimal256,
     pub interest_multiplier: Decimal256,
+    pub optimal_utilization: Option<Decimal256>,
+    pub slope_2: Option<Decimal256>,
 }
 
 // We define a custom struct for each query response
@@ -1,14 +1,24 @@
+pub mod airdrop;
+pub mod collector;
 pub mod common;
+pub mod community;
 pub mod custody;
+pub mod custody_cw20;
+pub mod custody_native;
+pub mod custody_state;
 pub mod distribution_model;
+pub mod distributor;
+pub mod gov;
 pub mod interest_model;
 pub mod liquidation;
 pub mod liquidation_queue;
 pub mod market;
+pub mod math;
 pub mod oracle;
 pub mod overseer;
 pub mod querier;
 pub mod tokens;
+pub mod vesting;
 
 #[cfg(test)]
 mod mock_querier;
@@ -30,6 +30,7 @@ pub struct InstantiateMsg {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     UpdateConfig {
@@ -41,6 +42,9 @@ pub enum ExecuteMsg {
         max_premium_rate: Option<Decimal256>,
         liquidation_threshold: Option<Uint256>,
         price_timeframe: Option<u64>,
+        close_factor: Option<Decimal256>,
+        full_liquidation_ratio: Option<Decimal256>,
+        liquidate_on_stale_price: Option<bool>,
     },
     SubmitBid {
         collateral_token: String,
@@ -50,6 +54,13 @@ pub enum ExecuteMsg {
         collateral_token: String,
         amount: Option<Uint256>,
     },
+    /// Custody interface to liquidate the native
+    /// collateral coins sent along with the message
+    ExecuteBid {
+        liquidator: String,
+        fee_address: Option<String>,
+        repay_address: Option<String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -99,12 +110,24 @@ pub struct ConfigResponse {
     pub max_premium_rate: Decimal256,
     pub liquidation_threshold: Uint256,
     pub price_timeframe: u64,
+    /// Max portion of the loan repaid by a single liquidation
+    pub close_factor: Option<Decimal256>,
+    /// borrow_amount / borrow_limit from which the
+    /// close factor no longer applies
+    pub full_liquidation_ratio: Option<Decimal256>,
+    /// Bids are executed at the last known price when the oracle
+    /// price is older than price_timeframe
+    pub liquidate_on_stale_price: bool,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LiquidationAmountResponse {
     pub collaterals: TokensHuman,
+    /// Stable amount repaid by liquidating the collaterals,
+    /// zero when left out by the liquidation queue
+    #[serde(default)]
+    pub max_repay_amount: Uint256,
 }
 
 // We define a custom struct for each query response
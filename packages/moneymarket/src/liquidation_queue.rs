@@ -46,6 +46,7 @@ pub enum ExecuteMsg {
         price_timeframe: Option<u64>,
         waiting_period: Option<u64>,
         overseer: Option<String>,
+        liquidate_on_stale_price: Option<bool>,
     },
     /// Owner operation to whitelist a new collateral
     WhitelistCollateral {
@@ -137,6 +138,9 @@ pub struct ConfigResponse {
     pub price_timeframe: u64,
     pub waiting_period: u64,
     pub overseer: String,
+    /// Liquidations are executed at the last known price when the
+    /// oracle price is older than price_timeframe
+    pub liquidate_on_stale_price: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
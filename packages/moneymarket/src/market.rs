@@ -2,7 +2,11 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::Binary;
 use cw20::Cw20ReceiveMsg;
+use std::fmt;
+
+use crate::tokens::TokensHuman;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -11,16 +15,40 @@ pub struct InstantiateMsg {
     pub owner_addr: String,
     /// stable coin denom used to borrow & repay
     pub stable_denom: String,
+    /// CW20 stablecoin used as the base asset instead of the native
+    /// `stable_denom`, which then only names the asset
+    pub stable_token: Option<String>,
     /// Anchor token code ID used to instantiate
     pub aterra_code_id: u64,
     /// Anchor token distribution speed
     pub anc_emission_rate: Decimal256,
     /// Maximum allowed borrow rate over deposited stable balance
     pub max_borrow_factor: Decimal256,
+    /// Decimals of the stable denom, aterra follows it (default 6)
+    pub stable_decimals: Option<u8>,
+    /// Overrides the aterra token name derived from the stable denom
+    pub aterra_name: Option<String>,
+    /// Overrides the aterra token symbol derived from the stable denom
+    pub aterra_symbol: Option<String>,
+    /// Attached to every response of this market, so indexers can
+    /// tell apart several markets deployed on the same chain
+    pub market_id: Option<String>,
+    /// Native denoms of additional markets hosted by this contract,
+    /// each needs the initial deposit of its denom attached
+    pub additional_denoms: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {
+    /// Code id of the deployed aterra token; required when migrating
+    /// a market instantiated before the contract version was stored
+    pub aterra_code_id: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
 
@@ -44,10 +72,89 @@ pub enum ExecuteMsg {
 
     /// Update config values
     UpdateConfig {
-        owner_addr: Option<String>,
         max_borrow_factor: Option<Decimal256>,
         interest_model: Option<String>,
         distribution_model: Option<String>,
+        /// Zero removes the accrual cap
+        max_accrual_blocks: Option<u64>,
+        min_borrow_rate: Option<Decimal256>,
+        insurance_fund: Option<String>,
+        /// Stop sending reserves to the insurance fund
+        remove_insurance_fund: Option<bool>,
+        insurance_split: Option<Decimal256>,
+        flash_loan_fee: Option<Decimal256>,
+        /// Zero removes the market borrow cap
+        max_borrow_amount: Option<Uint256>,
+        /// Zero removes the per account borrow cap
+        per_account_borrow_cap: Option<Uint256>,
+        guardian: Option<String>,
+        /// Leave pausing to the owner only
+        remove_guardian: Option<bool>,
+        /// Nominal seconds per block to accrue interest on block time,
+        /// zero switches back to accruing on block height
+        block_time: Option<u64>,
+        min_borrow_amount: Option<Uint256>,
+        dust_threshold: Option<Uint256>,
+        overseer_contract: Option<String>,
+        /// Allow the overseer change while loans are outstanding
+        force: Option<bool>,
+    },
+
+    /// Propose a new owner, who has to claim the ownership
+    /// before the block height passes `expires_at`
+    ProposeNewOwner {
+        owner: String,
+        expires_at: u64,
+    },
+
+    /// Accept a pending ownership proposal
+    ClaimOwnership {},
+
+    /// Set the ANC emission rate for borrowers; executable by
+    /// the owner or the distributor contract. Rewards up to this
+    /// block are accrued with the previous rate
+    UpdateEmissionRate {
+        emission_rate: Decimal256,
+    },
+
+    /// Set the ANC emission rate for aterra stakers; executable by
+    /// the owner or the distributor contract
+    UpdateStakingEmissionRate {
+        emission_rate: Decimal256,
+    },
+
+    /// Disable the given operations; executable by
+    /// the guardian or the owner
+    Pause {
+        operations: Vec<MarketOperation>,
+    },
+
+    /// Enable the given operations again; owner only
+    Unpause {
+        operations: Vec<MarketOperation>,
+    },
+
+    /// Record the aterra code id intended for a future token migration
+    UpdateAterraCodeId {
+        code_id: u64,
+    },
+
+    /// Update the addresses allowed to borrow,
+    /// the list is only enforced while enabled
+    UpdateBorrowWhitelist {
+        enabled: Option<bool>,
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
+    /// Host a market of another native stable denom, with its own
+    /// state, liabilities and aterra token. The initial deposit of
+    /// the denom has to be attached; the market shares the wired
+    /// contracts of the primary one except the overseer, which is
+    /// registered through a wrapped `UpdateConfig`
+    AddMarket {
+        denom: String,
+        aterra_code_id: u64,
     },
 
     ////////////////////
@@ -69,33 +176,202 @@ pub enum ExecuteMsg {
         distributed_interest: Uint256,
     },
 
+    /// Withdraw accrued reserves; only the owner or the
+    /// collector contract can execute this. Withdraws all
+    /// available reserves to the sender when not specified,
+    /// minus the insurance fund share
+    WithdrawReserves {
+        amount: Option<Uint256>,
+        recipient: Option<String>,
+    },
+
+    /// Write off the liability of a borrower without collaterals or escrow left;
+    /// only the owner or the overseer can execute this. The loss is
+    /// covered by the reserves first, then shared by the depositors
+    WriteOffBadDebt {
+        borrower: String,
+    },
+
+    /// Check the outstanding flash loan was returned with its fee,
+    /// only executable by the market itself after the loan callback
+    AssertFlashLoanRepaid {},
+
+    /// Borrow on behalf of `borrower` after their collaterals were locked,
+    /// only executable by the market itself
+    BorrowStableFor {
+        borrower: String,
+        borrow_amount: Uint256,
+    },
+
+    /// Repay the loan of an underwater `borrower` with their escrowed
+    /// aterra, releasing what is left; only executable by the overseer
+    RepayStableFromEscrow {
+        borrower: String,
+    },
+
     ////////////////////
     /// User operations
     ////////////////////
-    /// Deposit stable asset to get interest
-    DepositStable {},
+    /// Deposit stable asset to get interest.
+    /// Minted aterra goes to `recipient` when given,
+    /// otherwise to the sender.
+    /// Rejected once the block height passes `deadline`
+    DepositStable {
+        recipient: Option<String>,
+        deadline: Option<u64>,
+    },
 
-    /// Borrow stable asset with collaterals in overseer contract
+    /// Borrow stable asset with collaterals in overseer contract.
+    /// Rejected once the block height passes `deadline`
     BorrowStable {
         borrow_amount: Uint256,
         to: Option<String>,
+        deadline: Option<u64>,
     },
 
-    /// Repay stable asset to decrease liability
-    RepayStable {},
+    /// Lock collaterals in the overseer and borrow stable asset
+    /// against them within a single transaction
+    BorrowStableWithCollateral {
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+        borrow_amount: Uint256,
+    },
+
+    /// Deposit the sent stable asset, keep the minted aterra in escrow
+    /// as additional collateral of the sender and borrow against it
+    /// within a single transaction
+    DepositAndBorrow {
+        borrow_amount: Uint256,
+    },
+
+    /// Release escrowed aterra to the sender, as long as the loan
+    /// stays within the borrow limit; releases all when not specified
+    WithdrawEscrow {
+        amount: Option<Uint256>,
+    },
+
+    /// Repay stable asset to decrease liability.
+    /// Rejected once the block height passes `deadline`
+    RepayStable {
+        deadline: Option<u64>,
+    },
+
+    /// Repay stable asset to decrease the liability of `borrower`,
+    /// refunding any excess to the sender.
+    /// Rejected once the block height passes `deadline`
+    RepayStableFor {
+        borrower: String,
+        deadline: Option<u64>,
+    },
+
+    /// Liquidate an underwater borrower through the overseer, selling
+    /// at most `max_repay` worth of collaterals to settle the loan.
+    /// The liquidation premium goes to the sender
+    ExecuteLiquidation {
+        borrower: String,
+        max_repay: Option<Uint256>,
+    },
+
+    /// Repay the whole liability including accrued interest,
+    /// refunding any excess
+    ClosePosition {},
 
     /// Claim distributed ANC rewards
     ClaimRewards {
         to: Option<String>,
     },
+
+    /// Withdraw bonded aterra, claiming nothing
+    Unbond {
+        amount: Uint256,
+    },
+
+    /// Claim ANC rewards accrued by bonded aterra
+    ClaimStakingRewards {
+        to: Option<String>,
+    },
+
+    /// Add the sent stable asset to the reserves without minting aterra
+    FundReserves {},
+
+    /// Lend stable asset to `recipient` within a single transaction,
+    /// executing `msg` on it with the funds attached. The loan plus
+    /// the configured fee must be back in the market once it returns
+    FlashLoan {
+        amount: Uint256,
+        msg: Binary,
+        recipient: String,
+    },
+
+    /// Execute `msg` on the market of `denom`; the primary
+    /// stable denom addresses the primary market
+    Market {
+        denom: String,
+        msg: Box<ExecuteMsg>,
+    },
+}
+
+/// User operations that can be paused independently
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketOperation {
+    Borrow,
+    Deposit,
+    Redeem,
+    Repay,
+    /// Liquidations and the repayments settling them
+    Liquidate,
+}
+
+impl ExecuteMsg {
+    /// Address the message to the market of `denom` when given,
+    /// or to the primary market
+    pub fn for_market(self, denom: Option<String>) -> Self {
+        match denom {
+            Some(denom) => ExecuteMsg::Market {
+                denom,
+                msg: Box::new(self),
+            },
+            None => self,
+        }
+    }
+}
+
+impl fmt::Display for MarketOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarketOperation::Borrow => write!(f, "borrow"),
+            MarketOperation::Deposit => write!(f, "deposit"),
+            MarketOperation::Redeem => write!(f, "redeem"),
+            MarketOperation::Repay => write!(f, "repay"),
+            MarketOperation::Liquidate => write!(f, "liquidate"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     /// Return stable coins to a user
-    /// according to exchange rate
-    RedeemStable {},
+    /// according to exchange rate; with a `target_stable`
+    /// amount, only the aterra needed to redeem it is
+    /// burned and the rest is returned
+    RedeemStable {
+        deadline: Option<u64>,
+        target_stable: Option<Uint256>,
+    },
+    /// Deposit the sent stable token, for markets of a CW20 stablecoin
+    DepositStable { recipient: Option<String> },
+    /// Repay the sender's loan with the sent stable token,
+    /// for markets of a CW20 stablecoin
+    RepayStable {},
+    /// Repay the sender's loan with the sent aterra,
+    /// valued at the current exchange rate
+    RepayFromCollateral {},
+    /// Bond the sent aterra to accrue ANC rewards
+    Bond {},
+    /// Add the sent stable token to the reserves,
+    /// for markets of a CW20 stablecoin
+    FundReserves {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -105,6 +381,8 @@ pub enum QueryMsg {
     State {
         block_height: Option<u64>,
     },
+    /// Stable asset added to the reserves by external funders
+    ReservesFunded {},
     EpochState {
         block_height: Option<u64>,
         distributed_interest: Option<Uint256>,
@@ -113,10 +391,134 @@ pub enum QueryMsg {
         borrower: String,
         block_height: Option<u64>,
     },
+    /// Liabilities with interest applied up to the given block height
     BorrowerInfos {
         start_after: Option<String>,
         limit: Option<u32>,
+        block_height: Option<u64>,
+    },
+    /// Share of the lendable stable that is currently borrowed,
+    /// with interest applied up to the given block height
+    Utilization {
+        block_height: Option<u64>,
+    },
+    /// Addresses of the connected contracts, usable before
+    /// the market is fully wired up
+    WiredContracts {},
+    IsWhitelisted {
+        borrower: String,
+    },
+    /// Stored interest index of a borrower next to
+    /// the global one at the current block
+    BorrowerIndex {
+        borrower: String,
+    },
+    /// Aterra balance of a depositor and its stable value
+    /// at the exchange rate of the given block height
+    DepositValue {
+        depositor: String,
+        block_height: u64,
+    },
+    /// Stable value owed to all depositors at the exchange
+    /// rate of the given block height
+    TotalDeposits {
+        block_height: u64,
+    },
+    /// Borrow limit needed to borrow `desired_borrow` on top of
+    /// the loan, with interest applied up to the given block height
+    RequiredBorrowLimit {
+        borrower: String,
+        desired_borrow: Uint256,
+        block_height: u64,
+    },
+    /// Loan and borrow limit of a borrower in one call
+    BorrowerPosition {
+        borrower: String,
+        block_height: Option<u64>,
+    },
+    /// Loan, borrow limit and collaterals of a borrower joined
+    /// with the overseer, along with the resulting LTV and health
+    BorrowerHealth {
+        borrower: String,
+        block_height: Option<u64>,
     },
+    /// Ownership transfer waiting to be claimed, if any
+    PendingOwner {},
+    /// Deposits, liabilities, reserves, utilization and borrow rate
+    /// in one call, with interest applied up to the given block height
+    MarketState {
+        block_height: Option<u64>,
+    },
+    /// Aterra minted for depositing `amount` at the current exchange rate
+    SimulateDeposit {
+        amount: Uint256,
+    },
+    /// Stable returned, after tax, for redeeming `burn_amount`
+    /// aterra at the current exchange rate
+    SimulateRedeem {
+        burn_amount: Uint256,
+    },
+    /// Liabilities ordered by their last recorded loan amount, with
+    /// interest applied up to the given block height;
+    /// borrowers without a loan are omitted
+    LiabilitiesByAmount {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        descending: Option<bool>,
+        block_height: Option<u64>,
+    },
+    StakingState {
+        block_height: Option<u64>,
+    },
+    StakerInfo {
+        staker: String,
+        block_height: Option<u64>,
+    },
+    StakerInfos {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Amounts written off as bad debt per borrower
+    BadDebts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Global interest index of the latest checkpoint
+    /// at or before the given block height
+    InterestIndexAt {
+        height: u64,
+    },
+    /// Loan amount of a borrower as of the latest checkpoint
+    /// at or before the given block height
+    LoanAmountAt {
+        borrower: String,
+        height: u64,
+    },
+    /// Aterra held in escrow as additional collateral of a borrower
+    Escrow {
+        borrower: String,
+    },
+    /// Additional markets hosted by this contract
+    Markets {},
+    /// Run `msg` against the market of `denom`
+    Market {
+        denom: String,
+        msg: Box<QueryMsg>,
+    },
+}
+
+impl QueryMsg {
+    /// Address the query to the market of `denom` when given,
+    /// or to the primary market
+    pub fn for_market(self, denom: Option<String>) -> Self {
+        match denom {
+            Some(denom) => QueryMsg::Market {
+                denom,
+                msg: Box::new(self),
+            },
+            None => self,
+        }
+    }
 }
 
 // We define a custom struct for each query response
@@ -130,7 +532,31 @@ pub struct ConfigResponse {
     pub collector_contract: String,
     pub distributor_contract: String,
     pub stable_denom: String,
+    pub stable_token: Option<String>,
     pub max_borrow_factor: Decimal256,
+    pub max_accrual_blocks: Option<u64>,
+    pub min_borrow_rate: Decimal256,
+    pub insurance_fund: Option<String>,
+    pub insurance_split: Decimal256,
+    pub flash_loan_fee: Decimal256,
+    pub max_borrow_amount: Option<Uint256>,
+    pub per_account_borrow_cap: Option<Uint256>,
+    pub guardian: Option<String>,
+    pub paused_operations: Vec<MarketOperation>,
+    pub block_time: Option<u64>,
+    pub min_borrow_amount: Uint256,
+    pub dust_threshold: Uint256,
+    pub borrow_whitelist_enabled: bool,
+    pub aterra_name: String,
+    pub aterra_symbol: String,
+    pub aterra_code_id: u64,
+    pub market_id: Option<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReservesFundedResponse {
+    pub reserves_funded: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -154,6 +580,67 @@ pub struct EpochStateResponse {
     pub aterra_supply: Uint256,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UtilizationResponse {
+    pub utilization: Decimal256,
+    pub total_liabilities: Uint256,
+    pub available: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketStateResponse {
+    pub total_deposits: Uint256,
+    pub total_liabilities: Decimal256,
+    pub total_reserves: Decimal256,
+    pub aterra_supply: Uint256,
+    pub exchange_rate: Decimal256,
+    pub utilization: Decimal256,
+    pub borrow_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateDepositResponse {
+    pub mint_amount: Uint256,
+    pub exchange_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateRedeemResponse {
+    pub redeem_amount: Uint256,
+    pub exchange_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WiredContractsResponse {
+    pub anchor_token_registered: bool,
+    pub overseer_registered: bool,
+    pub aterra_contract: Option<String>,
+    pub overseer_contract: Option<String>,
+    pub interest_model: Option<String>,
+    pub distribution_model: Option<String>,
+    pub collector_contract: Option<String>,
+    pub distributor_contract: Option<String>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub owner: Option<String>,
+    pub expires_at: Option<u64>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsWhitelistedResponse {
+    pub borrower: String,
+    pub is_whitelisted: bool,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowerInfoResponse {
@@ -164,8 +651,143 @@ pub struct BorrowerInfoResponse {
     pub pending_rewards: Decimal256,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerIndexResponse {
+    pub borrower: String,
+    pub stored_index: Decimal256,
+    pub global_index: Decimal256,
+    pub block_height: u64,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BorrowerInfosResponse {
     pub borrower_infos: Vec<BorrowerInfoResponse>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadDebtResponse {
+    pub borrower: String,
+    pub written_off_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BadDebtsResponse {
+    pub bad_debts: Vec<BadDebtResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositValueResponse {
+    pub depositor: String,
+    pub aterra_balance: Uint256,
+    pub stable_value: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalDepositsResponse {
+    pub aterra_supply: Uint256,
+    pub total_deposit_value: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RequiredBorrowLimitResponse {
+    pub borrower: String,
+    pub required_limit: Uint256,
+    pub current_limit: Uint256,
+    pub shortfall: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerHealthResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub borrow_limit: Uint256,
+    pub collaterals: TokensHuman,
+    /// Loan amount over the borrow limit; `None` without a borrow limit
+    pub ltv: Option<Decimal256>,
+    /// Borrow limit over the loan amount, liquidatable below 1;
+    /// `None` without a loan
+    pub health_factor: Option<Decimal256>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerPositionResponse {
+    pub borrower: String,
+    pub loan_amount: Uint256,
+    pub principal: Uint256,
+    pub interest_index: Decimal256,
+    pub borrow_limit: Uint256,
+    pub borrowable: Uint256,
+    pub is_over_limit: bool,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingStateResponse {
+    pub total_bond_amount: Uint256,
+    pub global_reward_index: Decimal256,
+    pub last_reward_updated: u64,
+    pub emission_rate: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerInfoResponse {
+    pub staker: String,
+    pub reward_index: Decimal256,
+    pub bond_amount: Uint256,
+    pub pending_rewards: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakerInfosResponse {
+    pub staker_infos: Vec<StakerInfoResponse>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InterestIndexAtResponse {
+    pub height: u64,
+    pub checkpoint_height: u64,
+    pub global_interest_index: Decimal256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanAmountAtResponse {
+    pub borrower: String,
+    pub height: u64,
+    pub checkpoint_height: u64,
+    pub loan_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EscrowResponse {
+    pub borrower: String,
+    pub escrow_amount: Uint256,
+    /// Stable value of the escrowed aterra at the current exchange rate
+    pub escrow_value: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketsResponseElem {
+    pub denom: String,
+    pub aterra_contract: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MarketsResponse {
+    pub markets: Vec<MarketsResponseElem>,
+}
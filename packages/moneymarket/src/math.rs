@@ -0,0 +1,32 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{StdError, StdResult};
+
+/// Add an amount to a liability without panicking on overflow
+pub fn checked_add_liability(liability: Decimal256, amount: Decimal256) -> StdResult<Decimal256> {
+    let (res, overflow) = liability.0.overflowing_add(amount.0);
+    if overflow {
+        return Err(StdError::generic_err("Liability overflow"));
+    }
+
+    Ok(Decimal256(res))
+}
+
+/// Subtract an amount from a liability without panicking on underflow
+pub fn checked_sub_liability(liability: Decimal256, amount: Decimal256) -> StdResult<Decimal256> {
+    let (res, overflow) = liability.0.overflowing_sub(amount.0);
+    if overflow {
+        return Err(StdError::generic_err("Liability underflow"));
+    }
+
+    Ok(Decimal256(res))
+}
+
+/// Multiply a liability by a rate without panicking on overflow
+pub fn checked_mul_liability(liability: Decimal256, rate: Decimal256) -> StdResult<Decimal256> {
+    let (res, overflow) = liability.0.overflowing_mul(rate.0);
+    if overflow {
+        return Err(StdError::generic_err("Liability overflow"));
+    }
+
+    Ok(Decimal256(res / Decimal256::DECIMAL_FRACTIONAL))
+}
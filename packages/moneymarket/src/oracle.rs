@@ -19,6 +19,15 @@ pub enum ExecuteMsg {
         asset: String,
         feeder: String,
     },
+    /// Price the asset by the median of its feeders' submissions;
+    /// it is updated once `quorum` of them are no older than
+    /// `validity_window` seconds
+    RegisterFeeders {
+        asset: String,
+        feeders: Vec<String>,
+        quorum: u32,
+        validity_window: u64,
+    },
     FeedPrice {
         prices: Vec<(String, Decimal256)>, // (asset, price)
     },
@@ -31,6 +40,12 @@ pub enum QueryMsg {
     Feeder {
         asset: String,
     },
+    Feeders {
+        asset: String,
+    },
+    Submissions {
+        asset: String,
+    },
     Price {
         base: String,
         quote: String,
@@ -39,6 +54,12 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Time-weighted average price of the asset,
+    /// in the base asset, over the last `window_seconds`
+    TwapPrice {
+        asset: String,
+        window_seconds: u64,
+    },
 }
 
 // We define a custom struct for each query response
@@ -55,6 +76,30 @@ pub struct FeederResponse {
     pub feeder: String,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeedersResponse {
+    pub asset: String,
+    pub feeders: Vec<String>,
+    pub quorum: u32,
+    pub validity_window: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubmissionsResponseElem {
+    pub feeder: String,
+    pub price: Decimal256,
+    pub last_updated_time: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubmissionsResponse {
+    pub asset: String,
+    pub submissions: Vec<SubmissionsResponseElem>,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PriceResponse {
@@ -76,3 +121,11 @@ pub struct PricesResponseElem {
 pub struct PricesResponse {
     pub prices: Vec<PricesResponseElem>,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TwapPriceResponse {
+    pub asset: String,
+    pub price: Decimal256,
+    pub window_seconds: u64,
+}
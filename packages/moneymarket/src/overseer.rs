@@ -35,10 +35,14 @@ pub struct InstantiateMsg {
     pub anc_purchase_factor: Decimal256,
     /// Valid oracle price timeframe
     pub price_timeframe: u64,
+    /// Denom of the market served by this overseer when the market
+    /// contract hosts several; the primary market is used otherwise
+    pub market_denom: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     ////////////////////
     /// Owner operations
@@ -55,48 +59,92 @@ pub enum ExecuteMsg {
         anc_purchase_factor: Option<Decimal256>,
         epoch_period: Option<u64>,
         price_timeframe: Option<u64>,
+        /// Zero removes the cap
+        max_total_collateral_value: Option<Uint256>,
+        keeper_reward: Option<Uint256>,
+        liquidate_on_stale_price: Option<bool>,
+        escrow_max_ltv: Option<Decimal256>,
     },
 
     /// Create new custody contract for the given collateral token
     Whitelist {
-        name: String,             // bAsset name
-        symbol: String,           // bAsset symbol
-        collateral_token: String, // bAsset token contract
-        custody_contract: String, // bAsset custody contract
-        max_ltv: Decimal256,      // Loan To Value ratio
+        name: String,                            // bAsset name
+        symbol: String,                          // bAsset symbol
+        collateral_token: String,                // bAsset token contract
+        custody_contract: String,                // bAsset custody contract
+        max_ltv: Decimal256,                     // Loan To Value ratio
+        max_collateral: Option<Uint256>,         // Max amount locked by all borrowers
+        unlock_delay: Option<u64>,               // Blocks before delayed unlocks can be claimed
+        unlock_delay_threshold: Option<Uint256>, // Unlocks above this amount are delayed
     },
-    /// Update registered whitelist info
+    /// Update registered whitelist info; zero removes
+    /// the collateral cap, the unlock delay and its threshold
     UpdateWhitelist {
-        collateral_token: String,         // bAsset token contract
-        custody_contract: Option<String>, // bAsset custody contract
-        max_ltv: Option<Decimal256>,      // Loan To Value ratio
+        collateral_token: String,                // bAsset token contract
+        custody_contract: Option<String>,        // bAsset custody contract
+        max_ltv: Option<Decimal256>,             // Loan To Value ratio
+        max_collateral: Option<Uint256>,         // Max amount locked by all borrowers
+        unlock_delay: Option<u64>,               // Blocks before delayed unlocks can be claimed
+        unlock_delay_threshold: Option<Uint256>, // Unlocks above this amount are delayed
     },
 
     /// Claims all staking rewards from the bAsset contracts
     /// and also do a epoch basis updates
-    /// 1. Distribute interest buffers to depositors
-    /// 2. Invoke [Custody] DistributeRewards
-    /// 3. Update epoch state
+    /// 1. Pay the keeper reward to the caller from the interest buffer
+    /// 2. Distribute interest buffers to depositors
+    /// 3. Invoke [Custody] DistributeRewards
+    /// 4. Update epoch state
+    ///
+    /// Executable by anyone once an epoch has passed
     ExecuteEpochOperations {},
     UpdateEpochState {
         interest_buffer: Uint256,
         distributed_interest: Uint256,
     },
 
+    ////////////////////
+    /// Market operations
+    ////////////////////
+    /// Lock collaterals on behalf of `borrower`,
+    /// only executable by the market contract
+    LockCollateralFor {
+        borrower: String,
+        collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    },
+    /// Liquidate collaterals of `borrower` on behalf of `liquidator`,
+    /// selling at most `max_repay` worth of collaterals at oracle prices.
+    /// Only executable by the market contract
+    LiquidateCollateralFor {
+        liquidator: String,
+        borrower: String,
+        max_repay: Option<Uint256>,
+    },
+
     ////////////////////
     /// User operations
     ////////////////////
     LockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Unlock collaterals; amounts above the unlock delay threshold
+    /// of a collateral stay locked as pending unlocks until claimed
     UnlockCollateral {
         collaterals: TokensHuman, // <(Collateral Token, Amount)>
     },
+    /// Release the pending unlocks whose delay has passed,
+    /// as long as the loan stays within the borrow limit
+    ClaimUnlocked {},
 
     /////////////////////////////
     /// Permissionless operations
     /////////////////////////////
     LiquidateCollateral { borrower: String },
+    /// Deposit the sent stable coins to the interest buffer;
+    /// custody contracts send the collateral rewards through it
+    FundReserve {},
+    /// Deposit the liquidation bid fees received since `prev_balance`
+    /// to the interest buffer; only executable by the overseer itself
+    FundReserveFromLiquidation { prev_balance: Uint256 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -119,6 +167,18 @@ pub enum QueryMsg {
     BorrowLimit {
         borrower: String,
         block_time: Option<u64>,
+        /// Stable value of the aterra the borrower keeps in escrow;
+        /// queried from the market when not given
+        escrow_value: Option<Uint256>,
+    },
+    /// Interest buffer balance and its changes
+    /// over the last `limit` epochs, newest first
+    BufferState {
+        limit: Option<u32>,
+    },
+    /// Delayed unlocks of a borrower waiting to be claimed
+    PendingUnlocks {
+        borrower: String,
     },
 }
 
@@ -137,6 +197,16 @@ pub struct ConfigResponse {
     pub stable_denom: String,
     pub epoch_period: u64,
     pub price_timeframe: u64,
+    /// Max stable value of the collaterals locked by all borrowers
+    pub max_total_collateral_value: Option<Uint256>,
+    /// Paid from the interest buffer to the caller of epoch operations
+    pub keeper_reward: Uint256,
+    /// Whether liquidations proceed on prices older than `price_timeframe`
+    pub liquidate_on_stale_price: bool,
+    /// Loan To Value ratio of the aterra escrowed in the market
+    /// by deposit-and-borrow; escrows do not count while zero
+    pub escrow_max_ltv: Decimal256,
+    pub market_denom: Option<String>,
 }
 
 // We define a custom struct for each query response
@@ -147,6 +217,10 @@ pub struct WhitelistResponseElem {
     pub max_ltv: Decimal256,
     pub custody_contract: String,
     pub collateral_token: String,
+    pub max_collateral: Option<Uint256>,
+    pub unlock_delay: Option<u64>,
+    pub unlock_delay_threshold: Option<Uint256>,
+    pub total_collateral: Uint256,
 }
 
 // We define a custom struct for each query response
@@ -160,6 +234,10 @@ pub struct WhitelistResponse {
 pub struct CollateralsResponse {
     pub borrower: String,
     pub collaterals: TokensHuman, // <(Collateral Token, Amount)>
+    /// Oracle price of each collateral, in the same order
+    pub collateral_prices: Vec<Decimal256>,
+    /// Stable value of each collateral at its oracle price
+    pub collateral_values: Vec<Uint256>,
 }
 
 // We define a custom struct for each query response
@@ -173,3 +251,43 @@ pub struct BorrowLimitResponse {
     pub borrower: String,
     pub borrow_limit: Uint256,
 }
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BufferStateResponse {
+    /// Stable coins held for the protocol, excluding transfers that
+    /// did not go through `FundReserve` or a liquidation bid fee
+    pub interest_buffer: Uint256,
+    /// Deposited since the last epoch operations
+    pub accrued_buffer: Uint256,
+    pub history: Vec<BufferHistoryElem>,
+}
+
+/// Interest buffer changes of one epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BufferHistoryElem {
+    pub height: u64,
+    /// Deposited during the previous epoch
+    pub accrued_buffer: Uint256,
+    pub anc_purchase_amount: Uint256,
+    pub keeper_reward: Uint256,
+    /// Sent to the market, before tax
+    pub distributed_interest: Uint256,
+    /// Balance left once the epoch payouts are sent
+    pub interest_buffer: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUnlockResponse {
+    pub collateral_token: String,
+    pub amount: Uint256,
+    /// Block height from which the unlock can be claimed
+    pub release_height: u64,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUnlocksResponse {
+    pub borrower: String,
+    pub pending_unlocks: Vec<PendingUnlockResponse>,
+}
@@ -6,10 +6,17 @@ use cosmwasm_std::{
     to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Deps, QueryRequest,
     StdError, StdResult, Uint128, WasmQuery,
 };
-use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use std::collections::HashMap;
 use terra_cosmwasm::TerraQuerier;
 
-use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg};
+use crate::liquidation::{LiquidationAmountResponse, QueryMsg as LiquidationQueryMsg};
+use crate::market::{
+    BorrowerInfoResponse, EpochStateResponse, EscrowResponse, QueryMsg as MarketQueryMsg,
+};
+use crate::oracle::{PriceResponse, QueryMsg as OracleQueryMsg, TwapPriceResponse};
+use crate::overseer::{BorrowLimitResponse, CollateralsResponse, QueryMsg as OverseerQueryMsg};
+use crate::tokens::TokensHuman;
 
 pub fn query_all_balances(deps: Deps, account_addr: Addr) -> StdResult<Vec<Coin>> {
     // load price form the oracle
@@ -44,6 +51,7 @@ pub fn query_token_balance(
                 address: account_addr.to_string(),
             })?,
         }))
+        .map(|res: Cw20BalanceResponse| res.balance)
         .unwrap_or_else(|_| Uint128::zero());
 
     Ok(balance.into())
@@ -73,22 +81,60 @@ pub fn query_tax_rate(deps: Deps) -> StdResult<Decimal256> {
 }
 
 pub fn compute_tax(deps: Deps, coin: &Coin) -> StdResult<Uint256> {
-    let terra_querier = TerraQuerier::new(&deps.querier);
-    let tax_rate = Decimal256::from((terra_querier.query_tax_rate()?).rate);
-    let tax_cap = Uint256::from((terra_querier.query_tax_cap(coin.denom.to_string())?).cap);
-    let amount = Uint256::from(coin.amount);
-    Ok(std::cmp::min(
-        amount * Decimal256::one() - amount / (Decimal256::one() + tax_rate),
-        tax_cap,
-    ))
+    TaxCache::default().compute_tax(deps, coin)
 }
 
 pub fn deduct_tax(deps: Deps, coin: Coin) -> StdResult<Coin> {
-    let tax_amount = compute_tax(deps, &coin)?;
-    Ok(Coin {
-        denom: coin.denom,
-        amount: (Uint256::from(coin.amount) - tax_amount).into(),
-    })
+    TaxCache::default().deduct_tax(deps, coin)
+}
+
+/// Tax rate and per denom tax caps, queried once and reused
+/// for every coin paid out within the same message
+#[derive(Clone, Debug, Default)]
+pub struct TaxCache {
+    rate: Option<Decimal256>,
+    caps: HashMap<String, Uint256>,
+}
+
+impl TaxCache {
+    pub fn tax_rate(&mut self, deps: Deps) -> StdResult<Decimal256> {
+        if let Some(rate) = self.rate {
+            return Ok(rate);
+        }
+
+        let rate = query_tax_rate(deps)?;
+        self.rate = Some(rate);
+        Ok(rate)
+    }
+
+    pub fn tax_cap(&mut self, deps: Deps, denom: &str) -> StdResult<Uint256> {
+        if let Some(cap) = self.caps.get(denom) {
+            return Ok(*cap);
+        }
+
+        let terra_querier = TerraQuerier::new(&deps.querier);
+        let cap = Uint256::from(terra_querier.query_tax_cap(denom.to_string())?.cap);
+        self.caps.insert(denom.to_string(), cap);
+        Ok(cap)
+    }
+
+    pub fn compute_tax(&mut self, deps: Deps, coin: &Coin) -> StdResult<Uint256> {
+        let tax_rate = self.tax_rate(deps)?;
+        let tax_cap = self.tax_cap(deps, &coin.denom)?;
+        let amount = Uint256::from(coin.amount);
+        Ok(std::cmp::min(
+            amount * Decimal256::one() - amount / (Decimal256::one() + tax_rate),
+            tax_cap,
+        ))
+    }
+
+    pub fn deduct_tax(&mut self, deps: Deps, coin: Coin) -> StdResult<Coin> {
+        let tax_amount = self.compute_tax(deps, &coin)?;
+        Ok(Coin {
+            denom: coin.denom,
+            amount: (Uint256::from(coin.amount) - tax_amount).into(),
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -121,3 +167,152 @@ pub fn query_price(
 
     Ok(oracle_price)
 }
+
+pub fn query_twap_price(
+    deps: Deps,
+    oracle_addr: Addr,
+    asset: String,
+    window_seconds: u64,
+) -> StdResult<TwapPriceResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: oracle_addr.to_string(),
+        msg: to_binary(&OracleQueryMsg::TwapPrice {
+            asset,
+            window_seconds,
+        })?,
+    }))
+}
+
+pub fn query_epoch_state(
+    deps: Deps,
+    market_addr: Addr,
+    market_denom: Option<String>,
+    block_height: u64,
+    distributed_interest: Option<Uint256>,
+) -> StdResult<EpochStateResponse> {
+    let epoch_state: EpochStateResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: market_addr.to_string(),
+            msg: to_binary(
+                &MarketQueryMsg::EpochState {
+                    block_height: Some(block_height),
+                    distributed_interest,
+                }
+                .for_market(market_denom),
+            )?,
+        }))?;
+
+    Ok(epoch_state)
+}
+
+/// Query borrow amount from the market contract
+pub fn query_borrower_info(
+    deps: Deps,
+    market_addr: Addr,
+    market_denom: Option<String>,
+    borrower: Addr,
+    block_height: u64,
+) -> StdResult<BorrowerInfoResponse> {
+    let borrower_amount: BorrowerInfoResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: market_addr.to_string(),
+            msg: to_binary(
+                &MarketQueryMsg::BorrowerInfo {
+                    borrower: borrower.to_string(),
+                    block_height: Some(block_height),
+                }
+                .for_market(market_denom),
+            )?,
+        }))?;
+
+    Ok(borrower_amount)
+}
+
+/// Query the loan amount, with interest applied
+/// up to the given block height, from the market contract
+pub fn query_loan_amount(
+    deps: Deps,
+    market_addr: Addr,
+    market_denom: Option<String>,
+    borrower: Addr,
+    block_height: u64,
+) -> StdResult<Uint256> {
+    Ok(query_borrower_info(deps, market_addr, market_denom, borrower, block_height)?.loan_amount)
+}
+
+/// Query the aterra a borrower keeps in escrow in the market contract
+pub fn query_escrow(
+    deps: Deps,
+    market_addr: Addr,
+    market_denom: Option<String>,
+    borrower: Addr,
+) -> StdResult<EscrowResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: market_addr.to_string(),
+        msg: to_binary(
+            &MarketQueryMsg::Escrow {
+                borrower: borrower.to_string(),
+            }
+            .for_market(market_denom),
+        )?,
+    }))
+}
+
+#[allow(clippy::ptr_arg)]
+pub fn query_liquidation_amount(
+    deps: Deps,
+    liquidation_contract: Addr,
+    borrow_amount: Uint256,
+    borrow_limit: Uint256,
+    collaterals: &TokensHuman,
+    collateral_prices: Vec<Decimal256>,
+) -> StdResult<LiquidationAmountResponse> {
+    let liquidation_amount_res: LiquidationAmountResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: liquidation_contract.to_string(),
+            msg: to_binary(&LiquidationQueryMsg::LiquidationAmount {
+                borrow_amount,
+                borrow_limit,
+                collaterals: collaterals.clone(),
+                collateral_prices,
+            })?,
+        }))?;
+
+    Ok(liquidation_amount_res)
+}
+
+pub fn query_borrow_limit(
+    deps: Deps,
+    overseer_addr: Addr,
+    borrower: Addr,
+    block_time: Option<u64>,
+    escrow_value: Option<Uint256>,
+) -> StdResult<BorrowLimitResponse> {
+    let borrow_limit: BorrowLimitResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer_addr.to_string(),
+            msg: to_binary(&OverseerQueryMsg::BorrowLimit {
+                borrower: borrower.to_string(),
+                block_time,
+                escrow_value,
+            })?,
+        }))?;
+
+    Ok(borrow_limit)
+}
+
+pub fn query_collaterals(
+    deps: Deps,
+    overseer_addr: Addr,
+    borrower: Addr,
+) -> StdResult<CollateralsResponse> {
+    let collaterals: CollateralsResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: overseer_addr.to_string(),
+            msg: to_binary(&OverseerQueryMsg::Collaterals {
+                borrower: borrower.to_string(),
+            })?,
+        }))?;
+
+    Ok(collaterals)
+}
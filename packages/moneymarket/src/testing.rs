@@ -1,10 +1,14 @@
+use crate::math::{checked_add_liability, checked_mul_liability, checked_sub_liability};
 use crate::mock_querier::mock_dependencies;
 use crate::oracle::PriceResponse;
-use crate::querier::{compute_tax, deduct_tax, query_price, query_tax_rate, TimeConstraints};
+use crate::querier::{
+    compute_tax, deduct_tax, query_price, query_tax_rate, TaxCache, TimeConstraints,
+};
 use crate::tokens::{Tokens, TokensHuman, TokensMath, TokensToRaw};
 
 use cosmwasm_bignumber::{Decimal256, Uint256};
 use cosmwasm_std::{Addr, Api, CanonicalAddr, Coin, Decimal, StdError, Uint128};
+use proptest::prelude::*;
 
 #[test]
 fn tax_rate_querier() {
@@ -67,6 +71,46 @@ fn test_deduct_tax() {
     );
 }
 
+#[test]
+fn test_tax_cache() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+            (&"ukrw".to_string(), &Uint128::from(100u128)),
+        ],
+    );
+
+    let mut tax_cache = TaxCache::default();
+    for coin in [
+        Coin::new(10000000000u128, "uusd"),
+        Coin::new(50000000u128, "uusd"),
+        Coin::new(50000000u128, "ukrw"),
+    ] {
+        assert_eq!(
+            tax_cache.deduct_tax(deps.as_ref(), coin.clone()).unwrap(),
+            deduct_tax(deps.as_ref(), coin).unwrap()
+        );
+    }
+
+    // the cached parameters are reused once queried
+    deps.querier.with_tax(Decimal::percent(2), &[]);
+    assert_eq!(
+        tax_cache.tax_rate(deps.as_ref()).unwrap(),
+        Decimal256::percent(1)
+    );
+    assert_eq!(
+        tax_cache.tax_cap(deps.as_ref(), "ukrw").unwrap(),
+        Uint256::from(100u64)
+    );
+    assert_eq!(
+        tax_cache.tax_cap(deps.as_ref(), "uluna").unwrap(),
+        Uint256::zero()
+    );
+}
+
 #[test]
 fn oracle_price_querier() {
     let mut deps = mock_dependencies(&[]);
@@ -300,3 +344,51 @@ fn token_math_invalid_token_2() {
 
     let _ = tokens_1_raw.sub(tokens_2_raw);
 }
+
+fn raw_decimal(raw: u128) -> Decimal256 {
+    Decimal256(Uint256::from(raw).0)
+}
+
+proptest! {
+    #[test]
+    fn liability_multiplication_rounds_down(liability in any::<u128>(), rate in any::<u64>()) {
+        let liability = raw_decimal(liability);
+        let rate = raw_decimal(rate as u128);
+
+        let res = checked_mul_liability(liability, rate).unwrap();
+        let product = liability.0 * rate.0;
+        prop_assert!(res.0 * Decimal256::DECIMAL_FRACTIONAL <= product);
+        prop_assert!(product < (res.0 + Uint256::one().0) * Decimal256::DECIMAL_FRACTIONAL);
+    }
+
+    #[test]
+    fn liability_add_sub_round_trip(liability in any::<u128>(), amount in any::<u128>()) {
+        let liability = raw_decimal(liability);
+        let amount = raw_decimal(amount);
+
+        let res = checked_add_liability(liability, amount).unwrap();
+        prop_assert_eq!(checked_sub_liability(res, amount).unwrap(), liability);
+    }
+
+    #[test]
+    fn liability_underflow_errors(liability in any::<u128>(), amount in any::<u128>()) {
+        let res = checked_sub_liability(raw_decimal(liability), raw_decimal(amount));
+        prop_assert_eq!(res.is_err(), amount > liability);
+    }
+}
+
+#[test]
+fn liability_overflow_errors() {
+    // (2^128 - 1)^2 = 2^256 - 2^129 + 1 leaves less than 2^129 of headroom
+    let max_squared = Decimal256(Uint256::from(u128::MAX).0 * Uint256::from(u128::MAX).0);
+    let overflowing = raw_decimal(u128::MAX) * Decimal256::from_uint256(4u64);
+
+    assert_eq!(
+        checked_add_liability(max_squared, overflowing),
+        Err(StdError::generic_err("Liability overflow"))
+    );
+    assert_eq!(
+        checked_mul_liability(max_squared, Decimal256::from_uint256(2u64)),
+        Err(StdError::generic_err("Liability overflow"))
+    );
+}
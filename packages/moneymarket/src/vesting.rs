@@ -0,0 +1,88 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Uint256;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    /// Protocol token locked in the vesting accounts
+    pub anchor_token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    ////////////////////
+    /// Owner operations
+    ////////////////////
+
+    /// Update config
+    UpdateConfig { owner: Option<String> },
+    /// Register new vesting accounts; the contract must
+    /// hold enough protocol tokens to cover the schedules
+    RegisterVestingAccounts {
+        vesting_accounts: Vec<VestingAccount>,
+    },
+
+    ////////////////////
+    /// User operations
+    ////////////////////
+
+    /// Claim the vested tokens that are not claimed yet
+    Claim {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingAccount {
+    pub address: String,
+    pub schedule: VestingSchedule,
+}
+
+/// Tokens unlock every `unlock_period` seconds between `start_time`
+/// and `end_time`; nothing can be claimed before `cliff_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingSchedule {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub cliff_time: u64,
+    pub unlock_period: u64,
+    pub amount: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    VestingAccount {
+        address: String,
+    },
+    VestingAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub anchor_token: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingAccountResponse {
+    pub address: String,
+    pub schedule: VestingSchedule,
+    /// Amount vested at the current block time
+    pub vested_amount: Uint256,
+    pub claimed_amount: Uint256,
+    pub claimable_amount: Uint256,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingAccountsResponse {
+    pub vesting_accounts: Vec<VestingAccountResponse>,
+}